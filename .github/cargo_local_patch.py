@@ -99,6 +99,7 @@ if __name__ == "__main__":
         "risc0-zkp": "risc0/risc0/zkp",
         "risc0-zkvm": "risc0/risc0/zkvm",
         "risc0-zkvm-platform": "risc0/risc0/zkvm/platform",
+        "risc0-zkvm-verify": "risc0/risc0/zkvm/verify",
         "risc0-ethereum-contracts": "risc0-ethereum/contracts",
         "risc0-build-ethereum": "risc0-ethereum/build",
         "risc0-forge-ffi": "risc0-ethereum/ffi",
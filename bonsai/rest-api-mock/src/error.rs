@@ -26,6 +26,8 @@ use tokio::task::JoinError;
 pub(crate) enum Error {
     #[error("ImageIdExists")]
     ImageIdExists,
+    #[error("Unknown multipart upload or part")]
+    UnknownUpload,
     #[error("Bincode error")]
     Bincode(#[from] bincode::Error),
     #[error("Hex decode error")]
@@ -52,6 +54,7 @@ impl Error {
     pub fn status_code(&self) -> StatusCode {
         match self {
             Error::ImageIdExists { .. } => StatusCode::NO_CONTENT,
+            Error::UnknownUpload => StatusCode::NOT_FOUND,
             Error::Poisoned
             | Error::Bincode { .. }
             | Error::Unspecified { .. }
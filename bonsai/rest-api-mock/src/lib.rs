@@ -32,9 +32,9 @@ use tracing::{info, Level};
 use crate::{
     prover::{Prover, ProverHandle},
     routes::{
-        create_session, create_snark, get_image_upload, get_input_upload, get_receipt,
-        get_receipt_upload, put_image_upload, put_input_upload, put_receipt, session_status,
-        snark_status,
+        complete_image_multipart, create_session, create_snark, get_image_upload, get_input_upload,
+        get_receipt, get_receipt_upload, put_image_part, put_image_upload, put_input_upload,
+        put_receipt, session_status, snark_status, start_image_multipart,
     },
     state::BonsaiState,
 };
@@ -43,6 +43,15 @@ fn app(state: Arc<RwLock<BonsaiState>>, prover_handle: ProverHandle) -> Router {
     Router::new()
         .route("/images/upload/:image_id", get(get_image_upload))
         .route("/images/:image_id", put(put_image_upload))
+        .route(
+            "/images/upload/:image_id/multipart",
+            post(start_image_multipart),
+        )
+        .route(
+            "/images/upload/:image_id/complete",
+            post(complete_image_multipart),
+        )
+        .route("/images/parts/:upload_id/:part", put(put_image_part))
         .route("/inputs/upload", get(get_input_upload))
         .route("/inputs/:input_id", put(put_input_upload))
         .route("/sessions/create", post(create_session))
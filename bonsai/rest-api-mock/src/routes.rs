@@ -17,11 +17,13 @@ use std::vec;
 use axum::{
     body::Bytes,
     extract::{Path, State},
+    http::header,
+    response::IntoResponse,
     Extension, Json,
 };
 use bonsai_sdk::alpha::responses::{
-    CreateSessRes, ImgUploadRes, ProofReq, SessionStatusRes, SnarkReceipt, SnarkReq,
-    SnarkStatusRes, UploadRes,
+    CreateSessRes, ImgUploadRes, MultipartCompleteReq, MultipartUploadReq, MultipartUploadRes,
+    ProofReq, SessionStatusRes, SnarkReceipt, SnarkReq, SnarkStatusRes, UploadRes,
 };
 use risc0_zkvm::{Groth16Seal, Receipt};
 use tracing::info;
@@ -55,6 +57,58 @@ pub(crate) async fn put_image_upload(
     Ok(())
 }
 
+pub(crate) async fn start_image_multipart(
+    State(s): State<AppState>,
+    Path(image_id): Path<String>,
+    Json(request): Json<MultipartUploadReq>,
+) -> Result<Json<MultipartUploadRes>, Error> {
+    let state = &mut s.write()?;
+    if state.get_image(&image_id).is_some() {
+        return Err(Error::ImageIdExists);
+    }
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    state.start_image_parts(upload_id.clone());
+    Ok(Json(MultipartUploadRes {
+        urls: (1..=request.parts)
+            .map(|part| format!("{}/images/parts/{upload_id}/{part}", state.local_url))
+            .collect(),
+        upload_id,
+    }))
+}
+
+pub(crate) async fn put_image_part(
+    State(s): State<AppState>,
+    Path((upload_id, part)): Path<(String, usize)>,
+    body: Bytes,
+) -> Result<impl IntoResponse, Error> {
+    s.write()?
+        .put_image_part(&upload_id, part, body.to_vec())
+        .ok_or(Error::UnknownUpload)?;
+    Ok([(header::ETAG, format!("\"{upload_id}-{part}\""))])
+}
+
+pub(crate) async fn complete_image_multipart(
+    State(s): State<AppState>,
+    Path(image_id): Path<String>,
+    Json(request): Json<MultipartCompleteReq>,
+) -> Result<(), Error> {
+    let state = &mut s.write()?;
+    let mut parts = state
+        .take_image_parts(&request.upload_id)
+        .ok_or(Error::UnknownUpload)?;
+    let mut image = vec![];
+    for part in request.parts {
+        image.extend(
+            parts
+                .remove(&part.part_number)
+                .ok_or(Error::UnknownUpload)?,
+        );
+    }
+    state.put_image(image_id.clone(), image);
+    info!("ImageID {image_id} uploaded in parts");
+    Ok(())
+}
+
 pub(crate) async fn get_input_upload(State(s): State<AppState>) -> Result<Json<UploadRes>, Error> {
     let state = &s.read()?;
     let input_id = uuid::Uuid::new_v4();
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::{Arc, RwLock},
 };
 
@@ -24,6 +24,8 @@ pub(crate) struct BonsaiState {
     pub(crate) local_url: String,
     // ImageID - MemoryImage
     pub(crate) images: HashMap<String, Vec<u8>>,
+    // Multipart upload ID - parts of the image
+    pub(crate) image_parts: HashMap<String, BTreeMap<usize, Vec<u8>>>,
     // InputID - input
     pub(crate) inputs: HashMap<String, Vec<u8>>,
     // SessionID - Status
@@ -37,6 +39,7 @@ impl BonsaiState {
         Self {
             local_url,
             images: HashMap::new(),
+            image_parts: HashMap::new(),
             inputs: HashMap::new(),
             sessions: HashMap::new(),
             receipts: HashMap::new(),
@@ -48,6 +51,21 @@ impl BonsaiState {
     pub(crate) fn get_image(&self, image_id: impl AsRef<str>) -> Option<Vec<u8>> {
         self.images.get(image_id.as_ref()).cloned()
     }
+    pub(crate) fn start_image_parts(&mut self, upload_id: String) {
+        self.image_parts.insert(upload_id, BTreeMap::new());
+    }
+    pub(crate) fn put_image_part(
+        &mut self,
+        upload_id: &str,
+        part: usize,
+        data: Vec<u8>,
+    ) -> Option<()> {
+        self.image_parts.get_mut(upload_id)?.insert(part, data);
+        Some(())
+    }
+    pub(crate) fn take_image_parts(&mut self, upload_id: &str) -> Option<BTreeMap<usize, Vec<u8>>> {
+        self.image_parts.remove(upload_id)
+    }
     pub(crate) fn put_input(&mut self, input_id: String, input: Vec<u8>) -> Option<Vec<u8>> {
         self.inputs.insert(input_id, input)
     }
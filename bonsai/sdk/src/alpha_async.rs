@@ -14,7 +14,7 @@
 
 use crate::alpha::{
     responses::{Quotas, SessionStatusRes, SnarkStatusRes},
-    Client, SdkErr, SessionId, SnarkId,
+    Client, ExpectedReceipt, HttpOptions, PollOptions, SdkErr, SessionId, SnarkId, UploadProgress,
 };
 
 /// Construct a Bonsai SDK Client from env var
@@ -38,6 +38,21 @@ pub async fn get_client_from_parts(
         .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
 }
 
+/// Construct a Bonsai SDK Client from url + api key strings, with the given
+/// proxy and TLS settings
+pub async fn get_client_from_parts_with_options(
+    url: String,
+    api_key: String,
+    risc0_version: &'static str,
+    options: HttpOptions,
+) -> Result<Client, SdkErr> {
+    tokio::task::spawn_blocking(move || {
+        Client::from_parts_with_options(url, api_key, risc0_version, &options)
+    })
+    .await
+    .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+}
+
 /// Upload a input buffer to the /inputs/ route
 pub async fn upload_input(bonsai_client: Client, buf: Vec<u8>) -> Result<String, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.upload_input(buf))
@@ -69,6 +84,23 @@ pub async fn upload_img(
         .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
 }
 
+/// Upload a image buffer to the /images/ route in parts, reporting progress
+/// after each part
+///
+/// The boolean return indicates if the image already exists in bonsai
+pub async fn upload_img_with_progress(
+    bonsai_client: Client,
+    image_id: String,
+    image: Vec<u8>,
+    progress: impl FnMut(UploadProgress) + Send + 'static,
+) -> Result<bool, SdkErr> {
+    tokio::task::spawn_blocking(move || {
+        bonsai_client.upload_img_with_progress(&image_id, &image, progress)
+    })
+    .await
+    .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+}
+
 /// Create a new proof request Session
 ///
 /// Supply the image_id and input_id created from uploading those files in
@@ -94,6 +126,17 @@ pub async fn session_status(
         .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
 }
 
+/// Poll the status of a Session until it is no longer `RUNNING`
+pub async fn wait_for_session(
+    bonsai_client: Client,
+    session: SessionId,
+    opts: PollOptions,
+) -> Result<SessionStatusRes, SdkErr> {
+    tokio::task::spawn_blocking(move || bonsai_client.wait_for_session(&session, &opts))
+        .await
+        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+}
+
 /// Fetches the zkvm guest logs for a session
 ///
 /// After the Execution phase of proving is completed, you can use this method
@@ -134,6 +177,19 @@ pub async fn download(bonsai_client: Client, url: String) -> Result<Vec<u8>, Sdk
         .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
 }
 
+/// Download a receipt from a given url and verify it before returning it
+///
+/// Requires a [crate::alpha::ReceiptVerifier] to be configured on the client.
+pub async fn download_receipt_verified(
+    bonsai_client: Client,
+    url: String,
+    expected: ExpectedReceipt,
+) -> Result<Vec<u8>, SdkErr> {
+    tokio::task::spawn_blocking(move || bonsai_client.download_receipt_verified(&url, &expected))
+        .await
+        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+}
+
 /// Fetches your current users quotas
 ///
 /// Returns the [Quotas] structure with relevant data on cycle budget, quotas etc.
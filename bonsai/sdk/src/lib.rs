@@ -137,6 +137,8 @@ pub mod alpha;
 /// Bonsai Alpha SDK async
 pub mod alpha_async;
 
+pub use alpha::SdkErr as Error;
+
 /// HTTP header key for the API key
 pub const API_KEY_HEADER: &str = "x-api-key";
 /// HTTP header for the risc0 version string
@@ -145,3 +147,11 @@ pub const VERSION_HEADER: &str = "x-risc0-version";
 pub const API_URL_ENVVAR: &str = "BONSAI_API_URL";
 /// Environment variable name for the API key
 pub const API_KEY_ENVVAR: &str = "BONSAI_API_KEY";
+/// Environment variable name for the URL of a proxy for all requests
+pub const PROXY_ENVVAR: &str = "BONSAI_PROXY";
+/// Environment variable name for the path to a PEM bundle of additional
+/// trusted root certificates
+pub const CA_BUNDLE_ENVVAR: &str = "BONSAI_CA_BUNDLE";
+/// Environment variable name for the path to a PEM file holding the client
+/// certificate chain and private key used for mutual TLS
+pub const CLIENT_CERT_ENVVAR: &str = "BONSAI_CLIENT_CERT";
@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risc0_zkvm::Receipt;
+use risc0_zkvm::{sha::Digest, Receipt};
 use risc0_zkvm_receipts::{FIB_ID, FIB_RECEIPT};
+use risc0_zkvm_verify::verify_receipt;
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -24,3 +25,13 @@ fn test_verify() {
     let receipt: Receipt = bincode::deserialize(FIB_RECEIPT).unwrap();
     receipt.verify(FIB_ID).unwrap();
 }
+
+// `test_verify_receipt` exercises the `wasm-bindgen` export used by browser apps.
+#[wasm_bindgen_test]
+fn test_verify_receipt() {
+    let receipt: Receipt = bincode::deserialize(FIB_RECEIPT).unwrap();
+    let journal = verify_receipt(FIB_RECEIPT, Digest::from(FIB_ID).as_bytes())
+        .ok()
+        .expect("receipt should verify");
+    assert_eq!(journal, receipt.journal.bytes);
+}
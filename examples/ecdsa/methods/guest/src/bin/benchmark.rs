@@ -104,8 +104,48 @@ fn benchmark_group() {
     });
 }
 
+/// Benchmark the same field and group operations implemented by risc0-bigint2.
+fn benchmark_bigint2() {
+    use risc0_bigint2::secp256k1;
+
+    println!();
+    println!("risc0-bigint2 operations:");
+    let x = black_box(
+        secp256k1::FieldElement::from_be_bytes(&hex!(
+            "EC08EAC2CBCEFE58E61038DCA45BA2B4A56BDF05A3595EBEE1BCFC488889C1CF"
+        ))
+        .unwrap(),
+    );
+    let y = black_box(
+        secp256k1::FieldElement::from_be_bytes(&hex!(
+            "9FC3E90D2FAD03C8669F437A26374FA694CA76A7913C5E016322EBAA5C7616C5"
+        ))
+        .unwrap(),
+    );
+    let k = black_box(
+        secp256k1::Scalar::from_be_bytes(&hex!(
+            "2a3f714fcddea4984f228c4d1dbd41a79b470b1546c68f6bb268a04aa0394bac"
+        ))
+        .unwrap(),
+    );
+
+    bench("field add", || x + y);
+    bench("field mul", || x * y);
+    bench("field invert", || x.invert().unwrap());
+    bench("lincomb", || {
+        secp256k1::ProjectivePoint::lincomb(
+            &secp256k1::ProjectivePoint::GENERATOR,
+            &k,
+            &secp256k1::ProjectivePoint::GENERATOR,
+            &k,
+        )
+        .to_affine()
+    });
+}
+
 fn main() {
     benchmark_field();
     benchmark_scalar();
     benchmark_group();
+    benchmark_bigint2();
 }
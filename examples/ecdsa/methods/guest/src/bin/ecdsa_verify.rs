@@ -12,17 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use k256::{
-    ecdsa::{signature::Verifier, Signature, VerifyingKey},
-    EncodedPoint,
-};
+use k256::{ecdsa::Signature, EncodedPoint};
+use risc0_bigint2::ecdsa;
 use risc0_zkvm::guest::env;
 
 fn main() {
     // Decode the verifying key, message, and signature from the inputs.
     let (encoded_verifying_key, message, signature): (EncodedPoint, Vec<u8>, Signature) =
         env::read();
-    let verifying_key = VerifyingKey::from_encoded_point(&encoded_verifying_key).unwrap();
+    let verifying_key = ecdsa::VerifyingKey::from_sec1_bytes(encoded_verifying_key.as_bytes())
+        .expect("invalid verifying key");
+    let signature = ecdsa::Signature::from_slice(signature.to_bytes().as_slice())
+        .expect("invalid signature encoding");
 
     // Verify the signature, panicking if verification fails.
     verifying_key
@@ -0,0 +1,251 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ECDSA signature verification over secp256k1.
+//!
+//! The API mirrors `k256::ecdsa`: signatures must be normalized to the low-S
+//! form, and messages are hashed with SHA-256 before verification.
+
+use core::{cmp::Ordering, fmt};
+
+use sha2::{Digest as _, Sha256};
+
+use crate::{
+    field,
+    secp256k1::{AffinePoint, FieldElement, ProjectivePoint, Scalar},
+    U256,
+};
+
+// Half the group order, rounded down. Signatures with S above this are
+// rejected, as in k256.
+const ORDER_HALF: U256 = [
+    0x681b20a0, 0xdfe92f46, 0x57a4501d, 0x5d576e73, 0xffffffff, 0xffffffff, 0xffffffff, 0x7fffffff,
+];
+
+/// An opaque error from decoding a key or signature, or from verification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Error;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("signature error")
+    }
+}
+
+/// An ECDSA signature, as a pair of non-zero scalars `(r, s)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: Scalar,
+    s: Scalar,
+}
+
+impl Signature {
+    /// Create a signature from the big-endian encodings of `r` and `s`.
+    pub fn from_scalars(r: [u8; 32], s: [u8; 32]) -> Result<Self, Error> {
+        let r = Scalar::from_be_bytes(&r).ok_or(Error)?;
+        let s = Scalar::from_be_bytes(&s).ok_or(Error)?;
+        if r.is_zero() || s.is_zero() {
+            return Err(Error);
+        }
+        Ok(Self { r, s })
+    }
+
+    /// Decode the 64-byte `r || s` encoding.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 64 {
+            return Err(Error);
+        }
+        let (r, s) = bytes.split_at(32);
+        Self::from_scalars(r.try_into().unwrap(), s.try_into().unwrap())
+    }
+
+    /// Encode as `r || s`.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        out[..32].copy_from_slice(&self.r.to_be_bytes());
+        out[32..].copy_from_slice(&self.s.to_be_bytes());
+        out
+    }
+
+    /// The `r` component.
+    pub fn r(&self) -> &Scalar {
+        &self.r
+    }
+
+    /// The `s` component.
+    pub fn s(&self) -> &Scalar {
+        &self.s
+    }
+
+    fn s_is_high(&self) -> bool {
+        field::cmp(self.s.as_u256(), &ORDER_HALF) == Ordering::Greater
+    }
+
+    /// Return the low-S form of this signature if `s` is high, or `None` if
+    /// it is already normalized.
+    pub fn normalize_s(&self) -> Option<Self> {
+        self.s_is_high().then_some(Self {
+            r: self.r,
+            s: -self.s,
+        })
+    }
+}
+
+/// A secp256k1 public key for verifying ECDSA signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyingKey {
+    point: AffinePoint,
+}
+
+impl VerifyingKey {
+    /// Create a key from a curve point, which must not be the identity.
+    pub fn from_affine(point: AffinePoint) -> Result<Self, Error> {
+        if point.is_identity() {
+            return Err(Error);
+        }
+        Ok(Self { point })
+    }
+
+    /// Decode a SEC1 encoded point, either compressed (33 bytes) or
+    /// uncompressed (65 bytes).
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let coordinate = |bytes: &[u8]| FieldElement::from_be_bytes(bytes.try_into().unwrap());
+        let point = match (bytes.first(), bytes.len()) {
+            (Some(0x02 | 0x03), 33) => {
+                let x = coordinate(&bytes[1..]).ok_or(Error)?;
+                AffinePoint::decompress(x, bytes[0] == 0x03)
+            }
+            (Some(0x04), 65) => {
+                let x = coordinate(&bytes[1..33]).ok_or(Error)?;
+                let y = coordinate(&bytes[33..]).ok_or(Error)?;
+                AffinePoint::new(x, y)
+            }
+            _ => None,
+        };
+        Self::from_affine(point.ok_or(Error)?)
+    }
+
+    /// The public key as a curve point.
+    pub fn as_affine(&self) -> &AffinePoint {
+        &self.point
+    }
+
+    /// Verify a signature over the SHA-256 hash of `msg`.
+    pub fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        self.verify_prehash(&Sha256::digest(msg), signature)
+    }
+
+    /// Verify a signature over a message hash.
+    ///
+    /// As in k256, hashes longer than 32 bytes are truncated, shorter ones
+    /// are zero-extended on the left, and hashes shorter than 16 bytes are
+    /// rejected.
+    pub fn verify_prehash(&self, prehash: &[u8], signature: &Signature) -> Result<(), Error> {
+        if prehash.len() < 16 || signature.s_is_high() {
+            return Err(Error);
+        }
+        let mut bytes = [0u8; 32];
+        let len = prehash.len().min(32);
+        bytes[32 - len..].copy_from_slice(&prehash[..len]);
+        let z = Scalar::reduce(&field::from_be_bytes(&bytes));
+
+        let s_inv = signature.s.invert().ok_or(Error)?;
+        let u1 = z * s_inv;
+        let u2 = signature.r * s_inv;
+        let point = ProjectivePoint::lincomb(
+            &ProjectivePoint::GENERATOR,
+            &u1,
+            &self.point.to_projective(),
+            &u2,
+        )
+        .to_affine();
+        if point.is_identity() {
+            return Err(Error);
+        }
+
+        if Scalar::reduce(point.x().as_u256()) == signature.r {
+            Ok(())
+        } else {
+            Err(Error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::{
+        ecdsa::{signature::Signer, SigningKey},
+        elliptic_curve::sec1::ToEncodedPoint,
+    };
+
+    use super::*;
+    use crate::{field::Modulus, secp256k1::ScalarModulus};
+
+    fn sign(msg: &[u8]) -> (SigningKey, Signature) {
+        let signing_key = SigningKey::from_bytes(&[0x11; 32].into()).unwrap();
+        let signature: k256::ecdsa::Signature = signing_key.sign(msg);
+        let signature = Signature::from_slice(signature.to_bytes().as_slice()).unwrap();
+        (signing_key, signature)
+    }
+
+    #[test]
+    fn order_half() {
+        let mut doubled = ORDER_HALF;
+        for i in (1..doubled.len()).rev() {
+            doubled[i] = (doubled[i] << 1) | (doubled[i - 1] >> 31);
+        }
+        doubled[0] = (doubled[0] << 1) | 1;
+        assert_eq!(doubled, ScalarModulus::MODULUS);
+    }
+
+    #[test]
+    fn verify() {
+        let (signing_key, signature) = sign(b"hello");
+        for compress in [false, true] {
+            let encoded = signing_key.verifying_key().to_encoded_point(compress);
+            let key = VerifyingKey::from_sec1_bytes(encoded.as_bytes()).unwrap();
+            key.verify(b"hello", &signature).unwrap();
+            assert_eq!(key.verify(b"hellp", &signature), Err(Error));
+        }
+    }
+
+    #[test]
+    fn reject_high_s() {
+        let (signing_key, signature) = sign(b"hello");
+        let encoded = signing_key.verifying_key().to_encoded_point(true);
+        let key = VerifyingKey::from_sec1_bytes(encoded.as_bytes()).unwrap();
+
+        assert_eq!(signature.normalize_s(), None);
+        let high = Signature::from_scalars(signature.r.to_be_bytes(), (-signature.s).to_be_bytes())
+            .unwrap();
+        assert_eq!(key.verify(b"hello", &high), Err(Error));
+        assert_eq!(high.normalize_s(), Some(signature));
+    }
+
+    #[test]
+    fn reject_malformed() {
+        let g = AffinePoint::GENERATOR;
+        let mut bytes = [0u8; 65];
+        bytes[0] = 0x04;
+        bytes[1..33].copy_from_slice(&g.x().to_be_bytes());
+        bytes[33..].copy_from_slice(&g.x().to_be_bytes());
+        assert_eq!(VerifyingKey::from_sec1_bytes(&bytes), Err(Error));
+        assert_eq!(VerifyingKey::from_sec1_bytes(&bytes[..33]), Err(Error));
+        assert_eq!(VerifyingKey::from_sec1_bytes(&[]), Err(Error));
+
+        assert_eq!(Signature::from_slice(&[0u8; 64]), Err(Error));
+        assert_eq!(Signature::from_slice(&[0xff; 64]), Err(Error));
+        assert_eq!(Signature::from_slice(&[1u8; 63]), Err(Error));
+    }
+}
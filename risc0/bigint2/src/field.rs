@@ -0,0 +1,401 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Modular arithmetic on [U256] values.
+//!
+//! The free functions operate on raw [U256] values and an explicit modulus.
+//! [Residue] wraps a value that is known to be reduced modulo a fixed
+//! [Modulus], and is what the curve implementation is built on.
+
+use core::{
+    cmp::Ordering,
+    fmt,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use crate::{U256, WIDTH_WORDS};
+
+/// The integer zero.
+pub const ZERO: U256 = [0; WIDTH_WORDS];
+
+/// The integer one.
+pub const ONE: U256 = {
+    let mut one = ZERO;
+    one[0] = 1;
+    one
+};
+
+/// Decode a big-endian byte string.
+pub fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut out = ZERO;
+    for (i, chunk) in bytes.rchunks_exact(4).enumerate() {
+        out[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    out
+}
+
+/// Encode as a big-endian byte string.
+pub fn to_be_bytes(x: &U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, chunk) in out.rchunks_exact_mut(4).enumerate() {
+        chunk.copy_from_slice(&x[i].to_be_bytes());
+    }
+    out
+}
+
+/// Compare two integers.
+pub fn cmp(x: &U256, y: &U256) -> Ordering {
+    x.iter().rev().cmp(y.iter().rev())
+}
+
+/// Returns true if `x` is zero.
+pub fn is_zero(x: &U256) -> bool {
+    x.iter().all(|word| *word == 0)
+}
+
+fn add_words(x: &U256, y: &U256) -> (U256, bool) {
+    let mut out = ZERO;
+    let mut carry = false;
+    for i in 0..WIDTH_WORDS {
+        let (sum, c1) = x[i].overflowing_add(y[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        out[i] = sum;
+        carry = c1 | c2;
+    }
+    (out, carry)
+}
+
+fn sub_words(x: &U256, y: &U256) -> (U256, bool) {
+    let mut out = ZERO;
+    let mut borrow = false;
+    for i in 0..WIDTH_WORDS {
+        let (diff, b1) = x[i].overflowing_sub(y[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u32);
+        out[i] = diff;
+        borrow = b1 | b2;
+    }
+    (out, borrow)
+}
+
+/// Compute `(x + y) mod modulus`. Both inputs must be less than `modulus`.
+pub fn modadd(x: &U256, y: &U256, modulus: &U256) -> U256 {
+    let (sum, carry) = add_words(x, y);
+    if carry || cmp(&sum, modulus) != Ordering::Less {
+        sub_words(&sum, modulus).0
+    } else {
+        sum
+    }
+}
+
+/// Compute `(x - y) mod modulus`. Both inputs must be less than `modulus`.
+pub fn modsub(x: &U256, y: &U256, modulus: &U256) -> U256 {
+    let (diff, borrow) = sub_words(x, y);
+    if borrow {
+        add_words(&diff, modulus).0
+    } else {
+        diff
+    }
+}
+
+/// Compute `(x * y) mod modulus`, where `modulus` is greater than one.
+///
+/// At least one of the inputs must be less than `modulus`. In the zkVM this
+/// is a single call to the BigInt accelerator.
+pub fn modmul(x: &U256, y: &U256, modulus: &U256) -> U256 {
+    #[cfg(target_os = "zkvm")]
+    {
+        use risc0_zkvm_platform::syscall::{bigint, sys_bigint};
+
+        let mut out = ZERO;
+        unsafe { sys_bigint(&mut out, bigint::OP_MULTIPLY, x, y, modulus) };
+        check_reduced(out, modulus)
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    {
+        check_reduced(soft_modmul(x, y, modulus), modulus)
+    }
+}
+
+// The BigInt circuit proves `x * y = q * modulus + out`, but does not constrain
+// `out` to be less than `modulus`. Without this check a dishonest prover could
+// return an unreduced value and break the invariants [Residue] relies on.
+fn check_reduced(out: U256, modulus: &U256) -> U256 {
+    assert!(
+        cmp(&out, modulus) == Ordering::Less,
+        "BigInt result is not reduced"
+    );
+    out
+}
+
+// Schoolbook multiplication followed by binary long division. This is slow,
+// but it is only used to test guest code on the host.
+#[cfg(not(target_os = "zkvm"))]
+fn soft_modmul(x: &U256, y: &U256, modulus: &U256) -> U256 {
+    let mut wide = [0u32; 2 * WIDTH_WORDS];
+    for i in 0..WIDTH_WORDS {
+        let mut carry = 0u64;
+        for j in 0..WIDTH_WORDS {
+            let t = wide[i + j] as u64 + x[i] as u64 * y[j] as u64 + carry;
+            wide[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        wide[i + WIDTH_WORDS] = carry as u32;
+    }
+
+    let mut rem = ZERO;
+    for bit in (0..wide.len() * 32).rev() {
+        let overflow = rem[WIDTH_WORDS - 1] >> 31 == 1;
+        for k in (1..WIDTH_WORDS).rev() {
+            rem[k] = (rem[k] << 1) | (rem[k - 1] >> 31);
+        }
+        rem[0] = (rem[0] << 1) | ((wide[bit / 32] >> (bit % 32)) & 1);
+        if overflow || cmp(&rem, modulus) != Ordering::Less {
+            rem = sub_words(&rem, modulus).0;
+        }
+    }
+    rem
+}
+
+/// Compute `base^exp mod modulus` by square-and-multiply.
+///
+/// `base` must be less than `modulus`.
+pub fn modexp(base: &U256, exp: &U256, modulus: &U256) -> U256 {
+    let mut acc = modmul(&ONE, &ONE, modulus);
+    let bits = exp
+        .iter()
+        .rposition(|word| *word != 0)
+        .map_or(0, |i| (i + 1) * 32 - exp[i].leading_zeros() as usize);
+    for i in (0..bits).rev() {
+        acc = modmul(&acc, &acc, modulus);
+        if (exp[i / 32] >> (i % 32)) & 1 == 1 {
+            acc = modmul(&acc, base, modulus);
+        }
+    }
+    acc
+}
+
+/// A fixed modulus for [Residue].
+pub trait Modulus: Copy + 'static {
+    /// The modulus, which must be greater than one.
+    const MODULUS: U256;
+}
+
+/// An integer reduced modulo `M`.
+pub struct Residue<M: Modulus> {
+    value: U256,
+    _modulus: PhantomData<M>,
+}
+
+impl<M: Modulus> Residue<M> {
+    /// The residue zero.
+    pub const ZERO: Self = Self::new_unchecked(ZERO);
+
+    /// The residue one.
+    pub const ONE: Self = Self::new_unchecked(ONE);
+
+    pub(crate) const fn new_unchecked(value: U256) -> Self {
+        Self {
+            value,
+            _modulus: PhantomData,
+        }
+    }
+
+    /// Create a residue from `value`, or return `None` if it is not less than
+    /// the modulus.
+    pub fn new(value: U256) -> Option<Self> {
+        (cmp(&value, &M::MODULUS) == Ordering::Less).then_some(Self::new_unchecked(value))
+    }
+
+    /// Create a residue from `value`, reducing it modulo the modulus.
+    pub fn reduce(value: &U256) -> Self {
+        Self::new_unchecked(modmul(value, &ONE, &M::MODULUS))
+    }
+
+    /// Decode a big-endian byte string, or return `None` if it is not less
+    /// than the modulus.
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        Self::new(from_be_bytes(bytes))
+    }
+
+    /// Encode as a big-endian byte string.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        to_be_bytes(&self.value)
+    }
+
+    /// The reduced value.
+    pub fn as_u256(&self) -> &U256 {
+        &self.value
+    }
+
+    /// Returns true if this is zero.
+    pub fn is_zero(&self) -> bool {
+        is_zero(&self.value)
+    }
+
+    /// Returns true if the reduced value is odd.
+    pub fn is_odd(&self) -> bool {
+        self.value[0] & 1 == 1
+    }
+
+    /// Compute `self * self`.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Compute `self + self`.
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// Raise this to the power `exp`.
+    pub fn pow(&self, exp: &U256) -> Self {
+        Self::new_unchecked(modexp(&self.value, exp, &M::MODULUS))
+    }
+
+    /// Compute the multiplicative inverse, or return `None` for zero.
+    ///
+    /// This uses Fermat's little theorem, so the modulus must be prime.
+    pub fn invert(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let (exp, _) = sub_words(&M::MODULUS, &[2, 0, 0, 0, 0, 0, 0, 0]);
+        Some(self.pow(&exp))
+    }
+}
+
+impl<M: Modulus> Clone for Residue<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: Modulus> Copy for Residue<M> {}
+
+impl<M: Modulus> PartialEq for Residue<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<M: Modulus> Eq for Residue<M> {}
+
+impl<M: Modulus> fmt::Debug for Residue<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.to_be_bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<M: Modulus> Add for Residue<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new_unchecked(modadd(&self.value, &rhs.value, &M::MODULUS))
+    }
+}
+
+impl<M: Modulus> Sub for Residue<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new_unchecked(modsub(&self.value, &rhs.value, &M::MODULUS))
+    }
+}
+
+impl<M: Modulus> Mul for Residue<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new_unchecked(modmul(&self.value, &rhs.value, &M::MODULUS))
+    }
+}
+
+impl<M: Modulus> Neg for Residue<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    struct Small;
+
+    impl Modulus for Small {
+        const MODULUS: U256 = [1_000_003, 0, 0, 0, 0, 0, 0, 0];
+    }
+
+    fn small(x: u32) -> Residue<Small> {
+        Residue::reduce(&[x, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let x = from_be_bytes(&bytes);
+        assert_eq!(x[0], 0x1c1d1e1f);
+        assert_eq!(x[WIDTH_WORDS - 1], 0x00010203);
+        assert_eq!(to_be_bytes(&x), bytes);
+    }
+
+    #[test]
+    fn modmul_wide() {
+        // (2^256 - 1)^2 mod (2^256 - 189), where 2^256 - 1 = 188 mod m.
+        let max = [u32::MAX; WIDTH_WORDS];
+        let mut modulus = max;
+        modulus[0] -= 188;
+        let expected = [188 * 188, 0, 0, 0, 0, 0, 0, 0];
+        let (reduced, _) = sub_words(&max, &modulus);
+        assert_eq!(modmul(&reduced, &reduced, &modulus), expected);
+        assert_eq!(modmul(&max, &ONE, &modulus), [188, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn reduced_result() {
+        let out = [5, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(check_reduced(out, &Small::MODULUS), out);
+    }
+
+    #[test]
+    #[should_panic(expected = "BigInt result is not reduced")]
+    fn unreduced_result() {
+        check_reduced([1_000_008, 0, 0, 0, 0, 0, 0, 0], &Small::MODULUS);
+    }
+
+    #[test]
+    fn residue_ops() {
+        assert_eq!(small(1_000_002) + small(5), small(4));
+        assert_eq!(small(3) - small(5), small(1_000_001));
+        assert_eq!(-small(1), small(1_000_002));
+        assert_eq!(small(1_000) * small(1_001), small(1_001_000 % 1_000_003));
+        assert_eq!(small(2).pow(&[20, 0, 0, 0, 0, 0, 0, 0]), small(1 << 20));
+        for x in [1, 2, 12345, 1_000_002] {
+            assert_eq!(small(x) * small(x).invert().unwrap(), Residue::ONE);
+        }
+        assert!(Residue::<Small>::ZERO.invert().is_none());
+        assert!(Residue::<Small>::new(Small::MODULUS).is_none());
+    }
+}
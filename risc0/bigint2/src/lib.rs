@@ -0,0 +1,29 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+#![no_std]
+#![deny(missing_docs)]
+
+pub mod ecdsa;
+pub mod field;
+pub mod secp256k1;
+
+/// Width of the integers handled by this crate, in 32-bit words.
+pub const WIDTH_WORDS: usize = risc0_zkvm_platform::syscall::bigint::WIDTH_WORDS;
+
+/// A 256-bit unsigned integer, as little-endian 32-bit words.
+///
+/// This is the layout used by the BigInt accelerator.
+pub type U256 = [u32; WIDTH_WORDS];
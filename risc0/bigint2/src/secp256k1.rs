@@ -0,0 +1,359 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The secp256k1 elliptic curve, `y^2 = x^3 + 7` over the integers modulo
+//! [FieldModulus::MODULUS].
+//!
+//! Points are kept in Jacobian coordinates during computation so that only a
+//! single field inversion is needed, when converting back to affine form.
+
+use core::ops::{Add, Mul, Neg};
+
+use crate::{
+    field::{Modulus, Residue},
+    U256,
+};
+
+/// The modulus of the secp256k1 base field.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldModulus;
+
+impl Modulus for FieldModulus {
+    const MODULUS: U256 = [
+        0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+        0xffffffff,
+    ];
+}
+
+/// The order of the secp256k1 group, which is the modulus of the scalar field.
+#[derive(Clone, Copy, Debug)]
+pub struct ScalarModulus;
+
+impl Modulus for ScalarModulus {
+    const MODULUS: U256 = [
+        0xd0364141, 0xbfd25e8c, 0xaf48a03b, 0xbaaedce6, 0xfffffffe, 0xffffffff, 0xffffffff,
+        0xffffffff,
+    ];
+}
+
+/// An element of the secp256k1 base field.
+pub type FieldElement = Residue<FieldModulus>;
+
+/// An element of the secp256k1 scalar field.
+pub type Scalar = Residue<ScalarModulus>;
+
+// (p + 1) / 4, the exponent for square roots since p = 3 mod 4.
+const SQRT_EXP: U256 = [
+    0xbfffff0c, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0x3fffffff,
+];
+
+const CURVE_B: FieldElement = FieldElement::new_unchecked([7, 0, 0, 0, 0, 0, 0, 0]);
+
+fn curve_rhs(x: &FieldElement) -> FieldElement {
+    x.square() * *x + CURVE_B
+}
+
+/// A point on the curve in affine coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AffinePoint {
+    x: FieldElement,
+    y: FieldElement,
+    infinity: bool,
+}
+
+impl AffinePoint {
+    /// The point at infinity.
+    pub const IDENTITY: Self = Self {
+        x: FieldElement::ZERO,
+        y: FieldElement::ZERO,
+        infinity: true,
+    };
+
+    /// The standard generator of the group.
+    pub const GENERATOR: Self = Self {
+        x: FieldElement::new_unchecked([
+            0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb, 0xce870b07, 0x55a06295, 0xf9dcbbac,
+            0x79be667e,
+        ]),
+        y: FieldElement::new_unchecked([
+            0xfb10d4b8, 0x9c47d08f, 0xa6855419, 0xfd17b448, 0x0e1108a8, 0x5da4fbfc, 0x26a3c465,
+            0x483ada77,
+        ]),
+        infinity: false,
+    };
+
+    /// Create a point from its coordinates, or return `None` if it is not on
+    /// the curve.
+    pub fn new(x: FieldElement, y: FieldElement) -> Option<Self> {
+        (y.square() == curve_rhs(&x)).then_some(Self {
+            x,
+            y,
+            infinity: false,
+        })
+    }
+
+    /// Create the point with the given `x` coordinate and `y` parity, or
+    /// return `None` if there is no such point.
+    pub fn decompress(x: FieldElement, y_is_odd: bool) -> Option<Self> {
+        let rhs = curve_rhs(&x);
+        let y = rhs.pow(&SQRT_EXP);
+        if y.square() != rhs {
+            return None;
+        }
+        let y = if y.is_odd() == y_is_odd { y } else { -y };
+        Some(Self {
+            x,
+            y,
+            infinity: false,
+        })
+    }
+
+    /// Returns true if this is the point at infinity.
+    pub fn is_identity(&self) -> bool {
+        self.infinity
+    }
+
+    /// The `x` coordinate, which is zero for the point at infinity.
+    pub fn x(&self) -> &FieldElement {
+        &self.x
+    }
+
+    /// The `y` coordinate, which is zero for the point at infinity.
+    pub fn y(&self) -> &FieldElement {
+        &self.y
+    }
+
+    /// Convert to Jacobian coordinates.
+    pub fn to_projective(&self) -> ProjectivePoint {
+        if self.infinity {
+            return ProjectivePoint::IDENTITY;
+        }
+        ProjectivePoint {
+            x: self.x,
+            y: self.y,
+            z: FieldElement::ONE,
+        }
+    }
+}
+
+/// A point on the curve in Jacobian coordinates.
+///
+/// `(X, Y, Z)` represents the affine point `(X / Z^2, Y / Z^3)`, and any point
+/// with `Z = 0` is the point at infinity.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectivePoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl ProjectivePoint {
+    /// The point at infinity.
+    pub const IDENTITY: Self = Self {
+        x: FieldElement::ONE,
+        y: FieldElement::ONE,
+        z: FieldElement::ZERO,
+    };
+
+    /// The standard generator of the group.
+    pub const GENERATOR: Self = Self {
+        x: AffinePoint::GENERATOR.x,
+        y: AffinePoint::GENERATOR.y,
+        z: FieldElement::ONE,
+    };
+
+    /// Returns true if this is the point at infinity.
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Convert to affine coordinates. This costs one field inversion.
+    pub fn to_affine(&self) -> AffinePoint {
+        let Some(z_inv) = self.z.invert() else {
+            return AffinePoint::IDENTITY;
+        };
+        let z_inv2 = z_inv.square();
+        AffinePoint {
+            x: self.x * z_inv2,
+            y: self.y * z_inv2 * z_inv,
+            infinity: false,
+        }
+    }
+
+    /// Compute `2 * self`.
+    pub fn double(&self) -> Self {
+        // dbl-2009-l from the Explicit-Formulas Database, for a = 0.
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = ((self.x + b).square() - a - c).double();
+        let e = a.double() + a;
+        let f = e.square();
+        let x = f - d.double();
+        let c8 = c.double().double().double();
+        let y = e * (d - x) - c8;
+        let z = (self.y * self.z).double();
+        Self { x, y, z }
+    }
+
+    fn add_point(&self, other: &Self) -> Self {
+        if self.is_identity() {
+            return *other;
+        }
+        if other.is_identity() {
+            return *self;
+        }
+
+        // add-2007-bl from the Explicit-Formulas Database.
+        let z1z1 = self.z.square();
+        let z2z2 = other.z.square();
+        let u1 = self.x * z2z2;
+        let u2 = other.x * z1z1;
+        let s1 = self.y * other.z * z2z2;
+        let s2 = other.y * self.z * z1z1;
+        let h = u2 - u1;
+        let r = (s2 - s1).double();
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double()
+            } else {
+                Self::IDENTITY
+            };
+        }
+        let i = h.double().square();
+        let j = h * i;
+        let v = u1 * i;
+        let x = r.square() - j - v.double();
+        let y = r * (v - x) - (s1 * j).double();
+        let z = ((self.z + other.z).square() - z1z1 - z2z2) * h;
+        Self { x, y, z }
+    }
+
+    /// Compute `k * p + l * q` with a single chain of doublings.
+    pub fn lincomb(p: &Self, k: &Scalar, q: &Self, l: &Scalar) -> Self {
+        let pq = *p + *q;
+        let (k, l) = (k.as_u256(), l.as_u256());
+        let bit = |x: &U256, i: usize| (x[i / 32] >> (i % 32)) & 1 == 1;
+
+        let mut acc = Self::IDENTITY;
+        for i in (0..256).rev() {
+            let doubled = acc.double();
+            acc = match (bit(k, i), bit(l, i)) {
+                (true, true) => doubled + pq,
+                (true, false) => doubled + *p,
+                (false, true) => doubled + *q,
+                (false, false) => doubled,
+            };
+        }
+        acc
+    }
+}
+
+impl Add for ProjectivePoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.add_point(&rhs)
+    }
+}
+
+impl Neg for ProjectivePoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+}
+
+impl Mul<Scalar> for ProjectivePoint {
+    type Output = Self;
+
+    fn mul(self, k: Scalar) -> Self {
+        Self::lincomb(&self, &k, &Self::IDENTITY, &Scalar::ZERO)
+    }
+}
+
+impl PartialEq for ProjectivePoint {
+    fn eq(&self, other: &Self) -> bool {
+        if self.is_identity() || other.is_identity() {
+            return self.is_identity() == other.is_identity();
+        }
+        let z1z1 = self.z.square();
+        let z2z2 = other.z.square();
+        self.x * z2z2 == other.x * z1z1 && self.y * other.z * z2z2 == other.y * self.z * z1z1
+    }
+}
+
+impl Eq for ProjectivePoint {}
+
+#[cfg(test)]
+mod tests {
+    use k256::elliptic_curve::{sec1::ToEncodedPoint, PrimeField};
+
+    use super::*;
+
+    fn scalar(k: u32) -> Scalar {
+        Scalar::reduce(&[k, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    #[test]
+    fn generator_on_curve() {
+        let g = AffinePoint::GENERATOR;
+        assert_eq!(AffinePoint::new(g.x, g.y), Some(g));
+        assert_eq!(AffinePoint::decompress(g.x, g.y.is_odd()), Some(g));
+        assert_eq!(AffinePoint::decompress(g.x, !g.y.is_odd()).unwrap().y, -g.y);
+        assert!(AffinePoint::new(g.x, g.x).is_none());
+    }
+
+    #[test]
+    fn group_law() {
+        let g = ProjectivePoint::GENERATOR;
+        assert_eq!(g + g, g.double());
+        assert_eq!(g + -g, ProjectivePoint::IDENTITY);
+        assert_eq!(g * scalar(3), g.double() + g);
+        assert_eq!(g * -Scalar::ONE, -g);
+        assert_eq!(g * Scalar::ZERO, ProjectivePoint::IDENTITY);
+        assert!((g * Scalar::ZERO).to_affine().is_identity());
+    }
+
+    #[test]
+    fn matches_k256() {
+        for k in [1u32, 2, 7, 0xdeadbeef] {
+            let expected = (k256::ProjectivePoint::GENERATOR * k256::Scalar::from(k as u64))
+                .to_affine()
+                .to_encoded_point(false);
+            let actual = (ProjectivePoint::GENERATOR * scalar(k)).to_affine();
+            assert_eq!(expected.x().unwrap().as_slice(), actual.x().to_be_bytes());
+            assert_eq!(expected.y().unwrap().as_slice(), actual.y().to_be_bytes());
+        }
+
+        let k = k256::Scalar::from_repr([0x42; 32].into()).unwrap();
+        let l = k256::Scalar::from_repr([0x17; 32].into()).unwrap();
+        let q = k256::ProjectivePoint::GENERATOR * l;
+        let expected = (k256::ProjectivePoint::GENERATOR * k + q * k)
+            .to_affine()
+            .to_encoded_point(false);
+
+        let k = Scalar::from_be_bytes(&[0x42; 32]).unwrap();
+        let l = Scalar::from_be_bytes(&[0x17; 32]).unwrap();
+        let g = ProjectivePoint::GENERATOR;
+        let actual = ProjectivePoint::lincomb(&g, &k, &(g * l), &k).to_affine();
+        assert_eq!(expected.x().unwrap().as_slice(), actual.x().to_be_bytes());
+        assert_eq!(expected.y().unwrap().as_slice(), actual.y().to_be_bytes());
+    }
+}
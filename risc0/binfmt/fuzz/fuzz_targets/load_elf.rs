@@ -0,0 +1,26 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use risc0_binfmt::{MemoryImage, Program};
+use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE};
+
+// Loading an arbitrary ELF must either succeed or return an error, and a
+// successfully loaded program must produce a memory image.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(program) = Program::load_elf(data, GUEST_MAX_MEM as u32) {
+        MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+    }
+});
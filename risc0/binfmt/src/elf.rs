@@ -14,12 +14,136 @@
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, Result};
 use elf::{endian::LittleEndian, file::Class, ElfBytes};
 use risc0_zkvm_platform::WORD_SIZE;
 
+/// The maximum number of program headers accepted by [Program::load_elf].
+pub const MAX_PROGRAM_HEADERS: usize = 256;
+
+/// An error returned by [Program::load_elf] for a malformed or unsupported
+/// ELF.
+///
+/// [Program::load_elf] returns an [anyhow::Error]; use
+/// [anyhow::Error::downcast_ref] to recover the [ElfError].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ElfError {
+    /// The input could not be parsed as an ELF.
+    Parse(String),
+
+    /// The ELF is not a 32-bit ELF.
+    NotElf32,
+
+    /// The ELF is not for RISC-V.
+    InvalidMachine,
+
+    /// The ELF is not an executable.
+    NotExecutable,
+
+    /// The ELF has no program headers.
+    MissingSegments,
+
+    /// The ELF has more than [MAX_PROGRAM_HEADERS] program headers.
+    TooManySegments(usize),
+
+    /// A header field does not fit in 32 bits.
+    FieldTooLarge(&'static str),
+
+    /// The entry point is out of range or unaligned.
+    InvalidEntry(u32),
+
+    /// The entry point is not inside any loadable segment.
+    EntryNotLoaded(u32),
+
+    /// The file size of a segment is larger than the guest memory.
+    InvalidFileSize(u32),
+
+    /// The memory size of a segment is larger than the guest memory.
+    InvalidMemSize(u32),
+
+    /// The file size of a segment is larger than its memory size.
+    FileSizeExceedsMemSize {
+        /// The size of the segment in the file.
+        file_size: u32,
+        /// The size of the segment in memory.
+        mem_size: u32,
+    },
+
+    /// The virtual address of a segment is not word aligned.
+    UnalignedSegment(u32),
+
+    /// A segment extends past the end of guest memory.
+    AddressOutOfRange {
+        /// The virtual address of the segment.
+        vaddr: u32,
+        /// The maximum address for guest programs.
+        max_mem: u32,
+    },
+
+    /// The file contents of a segment are outside of the input.
+    SegmentOutOfBounds {
+        /// The offset of the segment in the file.
+        offset: u32,
+        /// The size of the segment in the file.
+        file_size: u32,
+    },
+
+    /// Two segments are loaded at overlapping addresses.
+    OverlappingSegments {
+        /// The virtual address of the segment loaded first.
+        first: u32,
+        /// The virtual address of the overlapping segment.
+        second: u32,
+    },
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "Elf parse error: {err}"),
+            Self::NotElf32 => write!(f, "Not a 32-bit ELF"),
+            Self::InvalidMachine => write!(f, "Invalid machine type, must be RISC-V"),
+            Self::NotExecutable => write!(f, "Invalid ELF type, must be executable"),
+            Self::MissingSegments => write!(f, "Missing segment table"),
+            Self::TooManySegments(count) => write!(f, "Too many program headers: {count}"),
+            Self::FieldTooLarge(field) => write!(f, "{field} was larger than 32 bits"),
+            Self::InvalidEntry(entry) => write!(f, "Invalid entrypoint: 0x{entry:08x}"),
+            Self::EntryNotLoaded(entry) => {
+                write!(f, "Entrypoint 0x{entry:08x} is not in a loadable segment")
+            }
+            Self::InvalidFileSize(size) => write!(f, "Invalid segment file_size: 0x{size:08x}"),
+            Self::InvalidMemSize(size) => write!(f, "Invalid segment mem_size: 0x{size:08x}"),
+            Self::FileSizeExceedsMemSize {
+                file_size,
+                mem_size,
+            } => write!(
+                f,
+                "Segment file_size 0x{file_size:08x} exceeds mem_size 0x{mem_size:08x}"
+            ),
+            Self::UnalignedSegment(vaddr) => write!(f, "vaddr {vaddr:08x} is unaligned"),
+            Self::AddressOutOfRange { vaddr, max_mem } => write!(
+                f,
+                "Segment [0x{vaddr:08x}] exceeds maximum address for guest programs [0x{max_mem:08x}]"
+            ),
+            Self::SegmentOutOfBounds { offset, file_size } => write!(
+                f,
+                "Invalid segment offset: 0x{offset:08x} + 0x{file_size:08x} is past the end of the file"
+            ),
+            Self::OverlappingSegments { first, second } => write!(
+                f,
+                "Segment at 0x{second:08x} overlaps segment at 0x{first:08x}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ElfError {}
+
 /// A RISC Zero program
 pub struct Program {
     /// The entrypoint of the program
@@ -31,78 +155,247 @@ pub struct Program {
 
 impl Program {
     /// Initialize a RISC Zero Program from an appropriate ELF file
+    ///
+    /// The ELF is validated before it is loaded, so untrusted input results in
+    /// an [ElfError] rather than a panic or an unbounded allocation.
     pub fn load_elf(input: &[u8], max_mem: u32) -> Result<Program> {
         let mut image: BTreeMap<u32, u32> = BTreeMap::new();
         let elf = ElfBytes::<LittleEndian>::minimal_parse(input)
-            .map_err(|err| anyhow!("Elf parse error: {err}"))?;
+            .map_err(|err| anyhow!(ElfError::Parse(alloc::format!("{err}"))))?;
         if elf.ehdr.class != Class::ELF32 {
-            bail!("Not a 32-bit ELF");
+            bail!(ElfError::NotElf32);
         }
         if elf.ehdr.e_machine != elf::abi::EM_RISCV {
-            bail!("Invalid machine type, must be RISC-V");
+            bail!(ElfError::InvalidMachine);
         }
         if elf.ehdr.e_type != elf::abi::ET_EXEC {
-            bail!("Invalid ELF type, must be executable");
+            bail!(ElfError::NotExecutable);
         }
-        let entry: u32 = elf
-            .ehdr
-            .e_entry
-            .try_into()
-            .map_err(|err| anyhow!("e_entry was larger than 32 bits. {err}"))?;
+        let entry = to_u32(elf.ehdr.e_entry, "e_entry")?;
         if entry >= max_mem || entry % WORD_SIZE as u32 != 0 {
-            bail!("Invalid entrypoint");
+            bail!(ElfError::InvalidEntry(entry));
         }
-        let segments = elf.segments().ok_or(anyhow!("Missing segment table"))?;
-        if segments.len() > 256 {
-            bail!("Too many program headers");
+        let segments = elf
+            .segments()
+            .ok_or_else(|| anyhow!(ElfError::MissingSegments))?;
+        if segments.len() > MAX_PROGRAM_HEADERS {
+            bail!(ElfError::TooManySegments(segments.len()));
         }
+        // Address ranges of the segments loaded so far, as (start, end).
+        let mut loaded: Vec<(u32, u32)> = Vec::new();
         for segment in segments.iter().filter(|x| x.p_type == elf::abi::PT_LOAD) {
-            let file_size: u32 = segment
-                .p_filesz
-                .try_into()
-                .map_err(|err| anyhow!("filesize was larger than 32 bits. {err}"))?;
+            let file_size = to_u32(segment.p_filesz, "filesize")?;
             if file_size >= max_mem {
-                bail!("Invalid segment file_size");
+                bail!(ElfError::InvalidFileSize(file_size));
             }
-            let mem_size: u32 = segment
-                .p_memsz
-                .try_into()
-                .map_err(|err| anyhow!("mem_size was larger than 32 bits {err}"))?;
+            let mem_size = to_u32(segment.p_memsz, "mem_size")?;
             if mem_size >= max_mem {
-                bail!("Invalid segment mem_size");
+                bail!(ElfError::InvalidMemSize(mem_size));
             }
-            let vaddr: u32 = segment
-                .p_vaddr
-                .try_into()
-                .map_err(|err| anyhow!("vaddr is larger than 32 bits. {err}"))?;
+            if file_size > mem_size {
+                bail!(ElfError::FileSizeExceedsMemSize {
+                    file_size,
+                    mem_size
+                });
+            }
+            let vaddr = to_u32(segment.p_vaddr, "vaddr")?;
             if vaddr % WORD_SIZE as u32 != 0 {
-                bail!("vaddr {vaddr:08x} is unaligned");
+                bail!(ElfError::UnalignedSegment(vaddr));
+            }
+            // Segments are loaded a word at a time, so round the end up.
+            let end = vaddr as u64 + (mem_size as u64).next_multiple_of(WORD_SIZE as u64);
+            if mem_size > 0 && end > max_mem as u64 {
+                bail!(ElfError::AddressOutOfRange { vaddr, max_mem });
+            }
+            let end = end as u32;
+            let offset = to_u32(segment.p_offset, "offset")?;
+            if file_size > 0 && offset as u64 + file_size as u64 > input.len() as u64 {
+                bail!(ElfError::SegmentOutOfBounds { offset, file_size });
+            }
+            if let Some(&(first, _)) = loaded
+                .iter()
+                .find(|&&(start, stop)| vaddr < stop && start < end)
+            {
+                bail!(ElfError::OverlappingSegments {
+                    first,
+                    second: vaddr
+                });
             }
-            let offset: u32 = segment
-                .p_offset
-                .try_into()
-                .map_err(|err| anyhow!("offset is larger than 32 bits. {err}"))?;
+            loaded.push((vaddr, end));
+
+            let data = match file_size {
+                0 => &[][..],
+                _ => &input[offset as usize..][..file_size as usize],
+            };
             for i in (0..mem_size).step_by(WORD_SIZE) {
-                let addr = vaddr.checked_add(i).context("Invalid segment vaddr")?;
-                if addr >= max_mem {
-                    bail!("Address [0x{addr:08x}] exceeds maximum address for guest programs [0x{max_mem:08x}]");
-                }
-                if i >= file_size {
-                    // Past the file size, all zeros.
-                    image.insert(addr, 0);
-                } else {
-                    let mut word = 0;
-                    // Don't read past the end of the file.
-                    let len = core::cmp::min(file_size - i, WORD_SIZE as u32);
-                    for j in 0..len {
-                        let offset = (offset + i + j) as usize;
-                        let byte = input.get(offset).context("Invalid segment offset")?;
-                        word |= (*byte as u32) << (j * 8);
-                    }
-                    image.insert(addr, word);
+                let addr = vaddr + i;
+                let mut word = [0u8; WORD_SIZE];
+                // Past the file size, all zeros.
+                if i < file_size {
+                    let bytes = &data[i as usize..data.len().min((i as usize) + WORD_SIZE)];
+                    word[..bytes.len()].copy_from_slice(bytes);
                 }
+                image.insert(addr, u32::from_le_bytes(word));
             }
         }
+        if !loaded
+            .iter()
+            .any(|&(start, end)| (start..end).contains(&entry))
+        {
+            bail!(ElfError::EntryNotLoaded(entry));
+        }
         Ok(Program { entry, image })
     }
 }
+
+fn to_u32(value: u64, field: &'static str) -> Result<u32> {
+    value
+        .try_into()
+        .map_err(|_| anyhow!(ElfError::FieldTooLarge(field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ElfError, Program};
+
+    const MAX_MEM: u32 = 0x1000_0000;
+
+    struct Segment {
+        vaddr: u32,
+        data: Vec<u8>,
+        mem_size: u32,
+    }
+
+    fn segment(vaddr: u32, data: &[u8], mem_size: u32) -> Segment {
+        Segment {
+            vaddr,
+            data: data.to_vec(),
+            mem_size,
+        }
+    }
+
+    // Build a minimal little-endian RISC-V ELF32 executable.
+    fn build_elf(entry: u32, segments: &[Segment]) -> Vec<u8> {
+        const EHDR_SIZE: u32 = 52;
+        const PHDR_SIZE: u32 = 32;
+        let mut out = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        out.resize(16, 0);
+        out.extend_from_slice(&2u16.to_le_bytes()); // e_type: ET_EXEC
+        out.extend_from_slice(&243u16.to_le_bytes()); // e_machine: EM_RISCV
+        out.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        out.extend_from_slice(&entry.to_le_bytes());
+        out.extend_from_slice(&EHDR_SIZE.to_le_bytes()); // e_phoff
+        out.extend_from_slice(&0u32.to_le_bytes()); // e_shoff
+        out.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        out.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes());
+        out.extend_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        out.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+        out.extend_from_slice(&40u16.to_le_bytes()); // e_shentsize
+        out.extend_from_slice(&0u16.to_le_bytes()); // e_shnum
+        out.extend_from_slice(&0u16.to_le_bytes()); // e_shstrndx
+
+        let mut offset = EHDR_SIZE + PHDR_SIZE * segments.len() as u32;
+        for segment in segments {
+            let len = segment.data.len() as u32;
+            for field in [
+                1, // p_type: PT_LOAD
+                offset,
+                segment.vaddr,
+                segment.vaddr,
+                len,
+                segment.mem_size,
+                5, // p_flags: R + X
+                4,
+            ] {
+                out.extend_from_slice(&field.to_le_bytes());
+            }
+            offset += len;
+        }
+        for segment in segments {
+            out.extend_from_slice(&segment.data);
+        }
+        out
+    }
+
+    fn load_err(elf: &[u8]) -> ElfError {
+        let err = Program::load_elf(elf, MAX_MEM).err().unwrap();
+        err.downcast_ref::<ElfError>().unwrap().clone()
+    }
+
+    #[test]
+    fn load() {
+        let elf = build_elf(
+            0x1000,
+            &[
+                segment(0x1000, &[1, 2, 3, 4, 5, 6], 12),
+                segment(0x2000, &[7, 8, 9, 10], 4),
+            ],
+        );
+        let program = Program::load_elf(&elf, MAX_MEM).unwrap();
+        assert_eq!(program.entry, 0x1000);
+        assert_eq!(
+            program.image.into_iter().collect::<Vec<_>>(),
+            vec![
+                (0x1000, 0x04030201),
+                (0x1004, 0x00000605),
+                (0x1008, 0),
+                (0x2000, 0x0a090807),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_segments() {
+        let elf = build_elf(
+            0x1000,
+            &[segment(0x1000, &[0; 8], 8), segment(0x1004, &[0; 4], 4)],
+        );
+        assert_eq!(
+            load_err(&elf),
+            ElfError::OverlappingSegments {
+                first: 0x1000,
+                second: 0x1004
+            }
+        );
+    }
+
+    #[test]
+    fn entry_not_loaded() {
+        let elf = build_elf(0x3000, &[segment(0x1000, &[0; 4], 4)]);
+        assert_eq!(load_err(&elf), ElfError::EntryNotLoaded(0x3000));
+        let elf = build_elf(MAX_MEM, &[segment(0x1000, &[0; 4], 4)]);
+        assert_eq!(load_err(&elf), ElfError::InvalidEntry(MAX_MEM));
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        let elf = build_elf(0x1000, &[segment(0x1000, &[0; 8], 4)]);
+        assert_eq!(
+            load_err(&elf),
+            ElfError::FileSizeExceedsMemSize {
+                file_size: 8,
+                mem_size: 4
+            }
+        );
+        let elf = build_elf(0x1000, &[segment(0x1000, &[], MAX_MEM)]);
+        assert_eq!(load_err(&elf), ElfError::InvalidMemSize(MAX_MEM));
+        let elf = build_elf(0x1000, &[segment(MAX_MEM - 4, &[], 8)]);
+        assert_eq!(
+            load_err(&elf),
+            ElfError::AddressOutOfRange {
+                vaddr: MAX_MEM - 4,
+                max_mem: MAX_MEM
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_segment() {
+        let mut elf = build_elf(0x1000, &[segment(0x1000, &[0; 8], 8)]);
+        elf.truncate(elf.len() - 4);
+        assert!(matches!(
+            load_err(&elf),
+            ElfError::SegmentOutOfBounds { file_size: 8, .. }
+        ));
+    }
+}
@@ -53,6 +53,13 @@ pub enum ExitCode {
     /// NOTE: The current version of the RISC Zero zkVM will never exit with an exit code of SessionLimit.
     /// This is because the system cannot currently prove that the session limit as been reached.
     SessionLimit,
+
+    /// This indicates that the host stopped execution early at a system split,
+    /// e.g. from a segment callback installed as a watchdog.
+    ///
+    /// An aborted session has no output and cannot be proven as a whole, but
+    /// the segments executed before the abort are complete.
+    Aborted,
 }
 
 impl ExitCode {
@@ -62,6 +69,7 @@ impl ExitCode {
             ExitCode::Paused(user_exit) => (1, user_exit),
             ExitCode::SystemSplit => (2, 0),
             ExitCode::SessionLimit => (2, 2),
+            ExitCode::Aborted => (2, 3),
         }
     }
 
@@ -78,7 +86,7 @@ impl ExitCode {
     pub fn expects_output(&self) -> bool {
         match self {
             ExitCode::Halted(_) | ExitCode::Paused(_) => true,
-            ExitCode::SystemSplit | ExitCode::SessionLimit => false,
+            ExitCode::SystemSplit | ExitCode::SessionLimit | ExitCode::Aborted => false,
         }
     }
 
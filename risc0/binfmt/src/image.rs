@@ -15,6 +15,7 @@
 extern crate alloc;
 
 use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::fmt;
 
 use anyhow::{ensure, Result};
 use risc0_zkp::core::{
@@ -27,7 +28,40 @@ use risc0_zkvm_platform::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{elf::Program, Digestible, SystemState};
+use crate::{
+    elf::{ElfError, Program},
+    Digestible, SystemState,
+};
+
+/// An error returned when constructing a [MemoryImage].
+///
+/// [MemoryImage::with_max_mem] returns an [anyhow::Error]; use
+/// [anyhow::Error::downcast_ref] to recover the [ImageError].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImageError {
+    /// The requested guest memory size is larger than the circuit supports.
+    MaxMemTooLarge {
+        /// The requested guest memory size.
+        max_mem: u32,
+        /// The largest guest memory size supported by the circuit.
+        limit: u32,
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxMemTooLarge { max_mem, limit } => write!(
+                f,
+                "Guest memory size 0x{max_mem:08x} exceeds the circuit limit of 0x{limit:08x}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImageError {}
 
 /// An image of a zkVM guest's memory
 ///
@@ -46,6 +80,26 @@ pub struct MemoryImage {
     pub pc: u32,
 }
 
+/// The pages that differ between two [MemoryImage]s.
+///
+/// Produced by [MemoryImage::diff] and consumed by [MemoryImage::apply_delta].
+/// Consecutive images of an execution share most of their pages, so storing a
+/// delta is usually much smaller than storing the full image.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageDelta {
+    /// Page size of the images being compared.
+    pub page_size: u32,
+
+    /// Pages that were added or changed, by page index.
+    pub pages: BTreeMap<u32, Vec<u8>>,
+
+    /// Indices of pages present in the base image but not in the target.
+    pub removed: Vec<u32>,
+
+    /// Program counter of the target image.
+    pub pc: u32,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct PersistentPageTableInfo {
     page_size: u32,
@@ -170,6 +224,26 @@ impl MemoryImage {
     /// execution not yet begun), and with the page table Merkle tree
     /// constructed.
     pub fn new(program: &Program, page_size: u32) -> Result<Self> {
+        Self::with_max_mem(program, page_size, GUEST_MAX_MEM as u32)
+    }
+
+    /// Construct the initial memory image for `program`, checking that the
+    /// program is loaded entirely below `max_mem`.
+    ///
+    /// This only checks where the program is placed. It does not stop the
+    /// guest from using memory above `max_mem` once it runs; use
+    /// `ExecutorEnvBuilder::memory_limit` in `risc0-zkvm` to bound that.
+    /// `max_mem` cannot be larger than [GUEST_MAX_MEM]: the system region and
+    /// the page table sit directly above guest memory at addresses fixed by
+    /// the circuit.
+    pub fn with_max_mem(program: &Program, page_size: u32, max_mem: u32) -> Result<Self> {
+        if max_mem as usize > GUEST_MAX_MEM {
+            anyhow::bail!(ImageError::MaxMemTooLarge {
+                max_mem,
+                limit: GUEST_MAX_MEM as u32,
+            });
+        }
+
         // Compute the page table hashes except for the very last root hash.
         let info = PageTableInfo::new(PAGE_TABLE.start() as u32, page_size)?;
         let mut img = Self {
@@ -180,8 +254,11 @@ impl MemoryImage {
 
         // Load the ELF into the memory image.
         for (&addr, &data) in program.image.iter() {
-            if addr as usize >= GUEST_MAX_MEM {
-                anyhow::bail!("Invalid Elf Program, address outside GUEST_MAX_MEM");
+            if addr >= max_mem {
+                anyhow::bail!(ElfError::AddressOutOfRange {
+                    vaddr: addr,
+                    max_mem,
+                });
             }
             img.store_region_in_page(addr, &data.to_le_bytes());
         }
@@ -190,6 +267,44 @@ impl MemoryImage {
         Ok(img)
     }
 
+    /// Compute the [ImageDelta] that turns this image into `other`.
+    pub fn diff(&self, other: &MemoryImage) -> ImageDelta {
+        let pages = other
+            .pages
+            .iter()
+            .filter(|(idx, page)| self.pages.get(idx) != Some(page))
+            .map(|(&idx, page)| (idx, page.clone()))
+            .collect();
+        let removed = self
+            .pages
+            .keys()
+            .filter(|idx| !other.pages.contains_key(idx))
+            .copied()
+            .collect();
+        ImageDelta {
+            page_size: other.info.page_size,
+            pages,
+            removed,
+            pc: other.pc,
+        }
+    }
+
+    /// Apply a delta produced by [MemoryImage::diff] on this image.
+    pub fn apply_delta(&mut self, delta: ImageDelta) -> Result<()> {
+        ensure!(
+            delta.page_size == self.info.page_size,
+            "delta page size {} does not match image page size {}",
+            delta.page_size,
+            self.info.page_size
+        );
+        for idx in delta.removed.iter() {
+            self.pages.remove(idx);
+        }
+        self.pages.extend(delta.pages);
+        self.pc = delta.pc;
+        Ok(())
+    }
+
     /// Load a page specified by page_idx. If no page is found, a zero page is
     /// returned.
     pub fn load_page(&self, page_idx: u32) -> Vec<u8> {
@@ -239,9 +354,41 @@ impl MemoryImage {
 
     /// Calculate and update the image merkle tree within this image.
     pub fn hash_pages(&mut self) {
+        #[cfg(feature = "parallel")]
+        self.hash_pages_parallel();
+        #[cfg(not(feature = "parallel"))]
         self.hash_pages_iter(0..self.info.num_pages)
     }
 
+    /// Calculate and update the image merkle tree, hashing pages on the rayon
+    /// thread pool.
+    ///
+    /// Pages are hashed in waves. The entry of each page lives at a higher
+    /// page index than the page itself, so every page in `start..end`, where
+    /// `end` is the page holding the entry of `start`, only depends on entries
+    /// written by earlier waves.
+    #[cfg(feature = "parallel")]
+    fn hash_pages_parallel(&mut self) {
+        use rayon::prelude::*;
+
+        let mut start = 0;
+        while start < self.info.num_pages {
+            let end = self
+                .info
+                .get_page_index(self.info.get_page_entry_addr(start))
+                .clamp(start + 1, self.info.num_pages);
+            let digests: Vec<Digest> = (start..end)
+                .into_par_iter()
+                .map(|page_idx| self.hash_page(page_idx))
+                .collect();
+            for (page_idx, digest) in (start..end).zip(digests) {
+                let entry_addr = self.info.get_page_entry_addr(page_idx);
+                self.store_region_in_page(entry_addr, digest.as_bytes());
+            }
+            start = end;
+        }
+    }
+
     /// Calculate and update the image merkle tree within this image based on
     /// the supplied page indices.
     pub fn hash_pages_iter<I: Iterator<Item = u32>>(&mut self, iter: I) {
@@ -317,6 +464,24 @@ impl MemoryImage {
         compute_image_id(&self.compute_root_hash(), self.pc)
     }
 
+    /// Compute and return the ImageID of the specified ELF binary, reusing the
+    /// result of an earlier call with the same ELF in this process.
+    ///
+    /// The cache is keyed by the SHA-256 digest of the ELF.
+    #[cfg(feature = "std")]
+    pub fn compute_id_cached(elf: &[u8]) -> Result<Digest> {
+        static CACHE: std::sync::Mutex<BTreeMap<Digest, Digest>> =
+            std::sync::Mutex::new(BTreeMap::new());
+
+        let elf_digest = *Impl::hash_bytes(elf);
+        if let Some(image_id) = CACHE.lock().unwrap().get(&elf_digest) {
+            return Ok(*image_id);
+        }
+        let image_id = crate::compute_image_id(elf)?;
+        CACHE.lock().unwrap().insert(elf_digest, image_id);
+        Ok(image_id)
+    }
+
     /// Return the [SystemState] for this image.
     pub fn get_system_state(&self) -> SystemState {
         SystemState {
@@ -346,7 +511,11 @@ mod tests {
     };
     use test_log::test;
 
-    use crate::{elf::Program, image::PageTableInfo, MemoryImage};
+    use crate::{
+        elf::{ElfError, Program},
+        image::{ImageError, PageTableInfo},
+        MemoryImage,
+    };
 
     fn page_table_size(max_mem: u32, page_size: u32) -> u32 {
         PageTableInfo::new(max_mem, page_size)
@@ -371,6 +540,94 @@ mod tests {
         image.check(image.info.root_page_addr).unwrap();
     }
 
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn hash_pages_parallel() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+
+        // Change a page so that the page table entries above it are stale.
+        let mut modified = image.clone();
+        modified.store_region_in_page(program.entry, &[0xff; 4]);
+
+        let mut parallel = modified.clone();
+        parallel.hash_pages_parallel();
+        let mut sequential = modified;
+        sequential.hash_pages_iter(0..image.info.num_pages);
+        assert_eq!(parallel.pages, sequential.pages);
+        assert_ne!(parallel.compute_id(), image.compute_id());
+    }
+
+    #[test]
+    fn compute_id_cached() {
+        let image_id = crate::compute_image_id(MULTI_TEST_ELF).unwrap();
+        assert_eq!(
+            MemoryImage::compute_id_cached(MULTI_TEST_ELF).unwrap(),
+            image_id
+        );
+        assert_eq!(
+            MemoryImage::compute_id_cached(MULTI_TEST_ELF).unwrap(),
+            image_id
+        );
+    }
+
+    #[test]
+    fn image_delta() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let base = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+
+        let mut target = base.clone();
+        target.store_region_in_page(STACK_TOP - 4, &[1, 2, 3, 4]);
+        let removed = *target.pages.keys().next_back().unwrap();
+        target.pages.remove(&removed);
+        target.pc += 4;
+
+        let delta = base.diff(&target);
+        assert_eq!(delta.removed, vec![removed]);
+        assert_eq!(delta.pages.len(), 1);
+        assert!(delta.pages.len() < target.pages.len());
+
+        let mut image = base.clone();
+        image.apply_delta(delta).unwrap();
+        assert_eq!(image.pages, target.pages);
+        assert_eq!(image.pc, target.pc);
+
+        assert!(base.diff(&base).pages.is_empty());
+    }
+
+    #[test]
+    fn max_mem() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+        let limited = MemoryImage::with_max_mem(&program, PAGE_SIZE, GUEST_MAX_MEM as u32).unwrap();
+        assert_eq!(image.compute_id(), limited.compute_id());
+
+        let err = MemoryImage::with_max_mem(&program, PAGE_SIZE, GUEST_MAX_MEM as u32 + PAGE_SIZE)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast::<ImageError>().unwrap(),
+            ImageError::MaxMemTooLarge {
+                max_mem: GUEST_MAX_MEM as u32 + PAGE_SIZE,
+                limit: GUEST_MAX_MEM as u32,
+            }
+        );
+
+        let err = MemoryImage::with_max_mem(&program, PAGE_SIZE, TEXT_START)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast::<ElfError>().unwrap(),
+            ElfError::AddressOutOfRange {
+                max_mem: TEXT_START,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn page_table_info() {
         const PAGE_SIZE_1K: u32 = 1024;
@@ -24,9 +24,9 @@ mod image;
 mod sys_state;
 
 #[cfg(not(target_os = "zkvm"))]
-pub use self::image::{MemoryImage, PageTableInfo};
+pub use self::image::{ImageDelta, ImageError, MemoryImage, PageTableInfo};
 pub use crate::{
-    elf::Program,
+    elf::{ElfError, Program, MAX_PROGRAM_HEADERS},
     exit_code::{ExitCode, InvalidExitCodeError},
     hash::{tagged_list, tagged_list_cons, tagged_struct, Digestible},
     sys_state::{read_sha_halfs, write_sha_halfs, DecodeError, SystemState},
@@ -0,0 +1,293 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supply chain metadata for guest ELFs built with [docker_build](crate::docker_build).
+//!
+//! For each ELF, two files are written next to it:
+//!
+//! * `<elf>.cdx.json`, a CycloneDX SBOM listing the packages in the guest's
+//!   dependency graph.
+//! * `<elf>.provenance.json`, an in-toto statement recording the builder,
+//!   the builder image, a digest of the source tree, and the image ID.
+//!
+//! If `RISC0_ATTESTATION_KEY` names an Ed25519 PKCS#8 key file, the provenance
+//! is also signed, and the signature is written to `<elf>.provenance.json.sig`.
+//! Signing requires the `sign-attestations` feature.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_metadata::{Metadata, Package};
+use risc0_zkp::core::hash::sha::{cpu::Impl, Sha256};
+use serde_json::{json, Value};
+
+use crate::get_env_var;
+
+/// Directories excluded from the source digest, matching the docker ignore
+/// file used for the build context.
+const IGNORED_DIRS: &[&str] = &[".git", "node_modules", "target", "tmp"];
+
+/// Inputs of a guest build that are recorded in its provenance.
+pub(crate) struct BuildInfo<'a> {
+    meta: &'a Metadata,
+    root_pkg: &'a Package,
+    src_dir: &'a Path,
+    manifest_path: &'a Path,
+    features: &'a [String],
+    builder_image: &'a str,
+    builder_digest: String,
+    source_digest: String,
+}
+
+impl<'a> BuildInfo<'a> {
+    /// Collect the build inputs, hashing the source tree and looking up the
+    /// builder image once for all ELFs of the package.
+    pub fn new(
+        meta: &'a Metadata,
+        root_pkg: &'a Package,
+        src_dir: &'a Path,
+        manifest_path: &'a Path,
+        features: &'a [String],
+        builder_image: &'a str,
+    ) -> Result<Self> {
+        Ok(Self {
+            meta,
+            root_pkg,
+            src_dir,
+            manifest_path,
+            features,
+            builder_image,
+            builder_digest: docker_image_digest(builder_image)?,
+            source_digest: source_digest(src_dir)?,
+        })
+    }
+
+    /// Write the SBOM and provenance for the ELF at `elf_path`.
+    ///
+    /// `name` is the path of the ELF relative to the source directory.
+    pub fn write_attestations(&self, elf_path: &Path, name: &str, image_id: &str) -> Result<()> {
+        let elf_digest = Impl::hash_bytes(&fs::read(elf_path)?).to_string();
+
+        let sbom = self.sbom(name, &elf_digest, image_id);
+        fs::write(
+            with_suffix(elf_path, ".cdx.json"),
+            serde_json::to_vec_pretty(&sbom)?,
+        )?;
+
+        let provenance =
+            serde_json::to_vec_pretty(&self.provenance(name, &elf_digest, image_id)?)?;
+        let provenance_path = with_suffix(elf_path, ".provenance.json");
+        fs::write(&provenance_path, &provenance)?;
+
+        let key_path = get_env_var("RISC0_ATTESTATION_KEY");
+        if !key_path.is_empty() {
+            let signature = sign(Path::new(&key_path), &provenance)?;
+            fs::write(
+                with_suffix(&provenance_path, ".sig"),
+                serde_json::to_vec_pretty(&signature)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn sbom(&self, name: &str, elf_digest: &str, image_id: &str) -> Value {
+        let components: Vec<_> = self
+            .meta
+            .packages
+            .iter()
+            .filter(|pkg| pkg.id != self.root_pkg.id)
+            .map(|pkg| {
+                let mut component = json!({
+                    "type": "library",
+                    "bom-ref": pkg.id.repr,
+                    "name": pkg.name,
+                    "version": pkg.version.to_string(),
+                    "purl": purl(pkg),
+                });
+                if let Some(license) = &pkg.license {
+                    component["licenses"] = json!([{ "expression": license }]);
+                }
+                component
+            })
+            .collect();
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "tools": [{
+                    "vendor": "RISC Zero",
+                    "name": "risc0-build",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+                "component": {
+                    "type": "application",
+                    "bom-ref": self.root_pkg.id.repr,
+                    "name": name,
+                    "version": self.root_pkg.version.to_string(),
+                    "purl": purl(self.root_pkg),
+                    "hashes": [{ "alg": "SHA-256", "content": elf_digest }],
+                    "properties": [{ "name": "risc0:image_id", "value": image_id }],
+                },
+            },
+            "components": components,
+        })
+    }
+
+    fn provenance(&self, name: &str, elf_digest: &str, image_id: &str) -> Result<Value> {
+        Ok(json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [{
+                "name": name,
+                "digest": { "sha256": elf_digest, "risc0ImageId": image_id },
+            }],
+            "predicateType": "https://slsa.dev/provenance/v1",
+            "predicate": {
+                "buildDefinition": {
+                    "buildType": "https://risczero.com/risc0-build/docker/v1",
+                    "externalParameters": {
+                        "manifestPath": self.manifest_path.strip_prefix(self.src_dir)?,
+                        "features": self.features,
+                    },
+                    "resolvedDependencies": [
+                        {
+                            "uri": format!("docker://{}", self.builder_image),
+                            "digest": { "sha256": self.builder_digest },
+                        },
+                        {
+                            "name": "source",
+                            "digest": { "sha256": self.source_digest },
+                        },
+                    ],
+                },
+                "runDetails": {
+                    "builder": {
+                        "id": "https://github.com/risc0/risc0/tree/main/risc0/build",
+                        "version": { "risc0-build": env!("CARGO_PKG_VERSION") },
+                    },
+                },
+            },
+        }))
+    }
+}
+
+fn purl(pkg: &Package) -> String {
+    format!("pkg:cargo/{}@{}", pkg.name, pkg.version)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// Return the digest of the docker image used for the build, which pins the
+/// Rust toolchain and the rest of the build environment.
+fn docker_image_digest(image: &str) -> Result<String> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", image])
+        .output()
+        .context("docker failed to execute")?;
+    if !output.status.success() {
+        bail!("`docker image inspect {image}` failed");
+    }
+    let id = String::from_utf8(output.stdout)?;
+    Ok(id.trim().trim_start_matches("sha256:").to_string())
+}
+
+/// Compute a digest of the source tree used as the docker build context.
+///
+/// This is the SHA-256 of a `sha256sum`-style listing of every file, sorted by
+/// path.
+fn source_digest(src_dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_files(src_dir, &mut files)?;
+    files.sort();
+
+    let mut listing = String::new();
+    for path in files {
+        let digest = Impl::hash_bytes(&fs::read(&path)?);
+        let rel_path = path.strip_prefix(src_dir)?;
+        listing.push_str(&format!("{digest}  {}\n", rel_path.display()));
+    }
+    Ok(Impl::hash_bytes(listing.as_bytes()).to_string())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !IGNORED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                collect_files(&entry.path(), files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sign-attestations")]
+fn sign(key_path: &Path, message: &[u8]) -> Result<Value> {
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    let key =
+        fs::read(key_path).with_context(|| format!("failed to read {}", key_path.display()))?;
+    let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&key)
+        .map_err(|err| anyhow::anyhow!("invalid Ed25519 key {}: {err}", key_path.display()))?;
+    Ok(json!({
+        "algorithm": "ed25519",
+        "publicKey": hex(pair.public_key().as_ref()),
+        "signature": hex(pair.sign(message).as_ref()),
+    }))
+}
+
+#[cfg(not(feature = "sign-attestations"))]
+fn sign(_key_path: &Path, _message: &[u8]) -> Result<Value> {
+    bail!("RISC0_ATTESTATION_KEY is set, but risc0-build was built without the `sign-attestations` feature")
+}
+
+#[cfg(feature = "sign-attestations")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::source_digest;
+
+    #[test]
+    fn source_digest_ignores_build_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        let digest = source_digest(dir.path()).unwrap();
+
+        fs::create_dir_all(dir.path().join("target/release")).unwrap();
+        fs::write(dir.path().join("target/release/guest"), "elf").unwrap();
+        assert_eq!(source_digest(dir.path()).unwrap(), digest);
+
+        fs::write(dir.path().join("src/main.rs"), "fn main() { todo!() }").unwrap();
+        assert_ne!(source_digest(dir.path()).unwrap(), digest);
+    }
+}
@@ -12,19 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs, path::Path, process::Command};
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path, process::Command};
 
 use anyhow::{bail, Context, Result};
 use cargo_metadata::MetadataCommand;
 use docker_generate::DockerFile;
 use risc0_binfmt::{MemoryImage, Program};
+use risc0_zkp::core::digest::Digest;
 use risc0_zkvm_platform::{
     memory::{GUEST_MAX_MEM, TEXT_START},
     PAGE_SIZE,
 };
 use tempfile::tempdir;
 
-use crate::get_env_var;
+use crate::{attestation::BuildInfo, get_env_var};
 
 const DOCKER_IGNORE: &str = r#"
 **/Dockerfile
@@ -34,6 +35,9 @@ const DOCKER_IGNORE: &str = r#"
 **/tmp
 "#;
 
+/// The docker image used to build guests, which pins the Rust toolchain.
+const BUILDER_IMAGE: &str = "risczero/risc0-guest-builder:v2024-04-22.0";
+
 /// The target directory for the ELF binaries.
 pub const TARGET_DIR: &str = "target/riscv-guest/riscv32im-risc0-zkvm-elf/docker";
 
@@ -50,6 +54,23 @@ pub fn docker_build(
     manifest_path: &Path,
     src_dir: &Path,
     features: &[String],
+) -> Result<BuildStatus> {
+    docker_build_verified(manifest_path, src_dir, features, &BTreeMap::new())
+}
+
+/// Build the package in the manifest path using a docker environment, and
+/// check the image IDs of the resulting ELFs.
+///
+/// The guest is compiled in [BUILDER_IMAGE] with `--locked`, so the same
+/// sources produce the same ELFs on any machine. `expected_image_ids` maps
+/// binary target names to the image ID each must have; targets not listed
+/// are not checked. The build fails if any listed target is missing or has
+/// a different image ID, which lets CI catch non-reproducible changes.
+pub fn docker_build_verified(
+    manifest_path: &Path,
+    src_dir: &Path,
+    features: &[String],
+    expected_image_ids: &BTreeMap<String, Digest>,
 ) -> Result<BuildStatus> {
     if !get_env_var("RISC0_SKIP_BUILD").is_empty() {
         eprintln!("Skipping build because RISC0_SKIP_BUILD is set");
@@ -93,17 +114,58 @@ pub fn docker_build(
     }
     println!("ELFs ready at:");
 
+    let build_info = BuildInfo::new(
+        &meta,
+        root_pkg,
+        &src_dir,
+        &manifest_path,
+        features,
+        BUILDER_IMAGE,
+    )?;
     let target_dir = src_dir.join(TARGET_DIR);
+    let mut image_ids = BTreeMap::new();
     for target in root_pkg.targets.iter().filter(|t| t.is_bin()) {
         let elf_path = target_dir.join(&pkg_name).join(&target.name);
         let image_id = compute_image_id(&elf_path)?;
         let rel_elf_path = Path::new(TARGET_DIR).join(&pkg_name).join(&target.name);
+        build_info
+            .write_attestations(
+                &elf_path,
+                &rel_elf_path.to_string_lossy(),
+                &image_id.to_string(),
+            )
+            .context("failed to write SBOM and provenance")?;
         println!("ImageID: {} - {:?}", image_id, rel_elf_path);
+        image_ids.insert(target.name.clone(), image_id);
     }
+    check_image_ids(&image_ids, expected_image_ids)?;
 
     Ok(BuildStatus::Success)
 }
 
+/// Compare built image IDs against the expected ones, reporting every
+/// mismatch at once.
+fn check_image_ids(
+    actual: &BTreeMap<String, Digest>,
+    expected: &BTreeMap<String, Digest>,
+) -> Result<()> {
+    let mut mismatches = String::new();
+    for (name, expected_id) in expected {
+        match actual.get(name) {
+            Some(actual_id) if actual_id == expected_id => {}
+            Some(actual_id) => writeln!(
+                mismatches,
+                "  {name}: expected {expected_id}, built {actual_id}"
+            )?,
+            None => writeln!(mismatches, "  {name}: expected {expected_id}, not built")?,
+        }
+    }
+    if !mismatches.is_empty() {
+        bail!("guest build is not reproducible, image IDs differ:\n{mismatches}");
+    }
+    Ok(())
+}
+
 /// Create the dockerfile.
 ///
 /// Overwrites if a dockerfile already exists.
@@ -145,7 +207,7 @@ fn create_dockerfile(
     .join(" ");
 
     let build = DockerFile::new()
-        .from_alias("build", "risczero/risc0-guest-builder:v2024-04-22.0")
+        .from_alias("build", BUILDER_IMAGE)
         .workdir("/src")
         .copy(".", ".")
         .env(manifest_env)
@@ -208,21 +270,23 @@ fn check_cargo_lock(manifest_path: &Path) -> Result<()> {
 }
 
 /// Compute the image ID for a given ELF.
-fn compute_image_id(elf_path: &Path) -> Result<String> {
+fn compute_image_id(elf_path: &Path) -> Result<Digest> {
     let elf = fs::read(elf_path)?;
     let program = Program::load_elf(&elf, GUEST_MAX_MEM as u32).context("unable to load elf")?;
     let image =
         MemoryImage::new(&program, PAGE_SIZE as u32).context("unable to create memory image")?;
-    Ok(image.compute_id().to_string())
+    Ok(image.compute_id())
 }
 
 // requires Docker to be installed
 #[cfg(feature = "docker")]
 #[cfg(test)]
 mod test {
-    use std::path::Path;
+    use std::{collections::BTreeMap, path::Path};
+
+    use risc0_zkp::core::digest::Digest;
 
-    use super::{docker_build, TARGET_DIR};
+    use super::{check_image_ids, docker_build, TARGET_DIR};
 
     const SRC_DIR: &str = "../..";
 
@@ -237,7 +301,26 @@ mod test {
         let target_dir = src_dir.join(TARGET_DIR);
         let elf_path = target_dir.join(bin_path);
         let actual = super::compute_image_id(&elf_path).unwrap();
-        assert_eq!(expected, actual);
+        assert_eq!(expected, actual.to_string());
+    }
+
+    #[test]
+    fn check_image_ids_reports_mismatches() {
+        let a = Digest::from([1u32; 8]);
+        let b = Digest::from([2u32; 8]);
+        let actual = BTreeMap::from([("one".to_string(), a), ("two".to_string(), b)]);
+
+        check_image_ids(&actual, &BTreeMap::new()).unwrap();
+        check_image_ids(&actual, &BTreeMap::from([("one".to_string(), a)])).unwrap();
+
+        let err = check_image_ids(
+            &actual,
+            &BTreeMap::from([("two".to_string(), a), ("three".to_string(), a)]),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains(&format!("two: expected {a}, built {b}")));
+        assert!(err.contains(&format!("three: expected {a}, not built")));
     }
 
     // Test build reproducibility for risc0_zkvm_methods_guest.
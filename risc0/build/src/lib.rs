@@ -16,11 +16,13 @@
 #![deny(missing_docs)]
 #![deny(rustdoc::broken_intra_doc_links)]
 
+mod attestation;
 mod docker;
+pub mod solidity;
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     default::Default,
     env,
     fs::{self, File},
@@ -32,11 +34,11 @@ use std::{
 use anyhow::{Context, Result};
 use cargo_metadata::{Message, MetadataCommand, Package};
 use risc0_binfmt::compute_image_id;
-use risc0_zkp::core::digest::DIGEST_WORDS;
+use risc0_zkp::core::digest::{Digest, DIGEST_WORDS};
 use risc0_zkvm_platform::memory;
 use serde::Deserialize;
 
-pub use docker::{docker_build, BuildStatus, TARGET_DIR};
+pub use docker::{docker_build, docker_build_verified, BuildStatus, TARGET_DIR};
 
 const RUSTUP_TOOLCHAIN_NAME: &str = "risc0";
 
@@ -198,9 +200,25 @@ fn is_debug() -> bool {
     get_env_var("RISC0_BUILD_DEBUG") == "1"
 }
 
+/// Returns the directory name cargo uses for artifacts built with the given
+/// profile.
+fn profile_dir(profile: Option<&str>) -> &str {
+    match profile {
+        None if is_debug() => "debug",
+        None => "release",
+        Some("dev") | Some("test") => "debug",
+        Some("bench") => "release",
+        Some(profile) => profile,
+    }
+}
+
 /// Returns all methods associated with the given guest crate.
-fn guest_methods(pkg: &Package, target_dir: impl AsRef<Path>) -> Vec<GuestListEntry> {
-    let profile = if is_debug() { "debug" } else { "release" };
+fn guest_methods(
+    pkg: &Package,
+    target_dir: impl AsRef<Path>,
+    profile: Option<&str>,
+) -> Vec<GuestListEntry> {
+    let profile = profile_dir(profile);
     pkg.targets
         .iter()
         .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
@@ -266,15 +284,65 @@ fn sanitized_cmd(tool: &str) -> Command {
 /// Creates a std::process::Command to execute the given cargo
 /// command in an environment suitable for targeting the zkvm guest.
 pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
-    let rustc = sanitized_cmd("rustup")
+    guest_cargo_command(subcmd, rust_flags, None)
+}
+
+/// Returns the rustc of the newest toolchain installed by `cargo risczero
+/// install`, if any.
+fn installed_rustc() -> Option<PathBuf> {
+    let rustc_exe = if cfg!(target_os = "windows") {
+        "rustc.exe"
+    } else {
+        "rustc"
+    };
+    let toolchains = risc0_data().ok()?.join("toolchains");
+    fs::read_dir(toolchains)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let version = toolchain_version(&entry.file_name().to_string_lossy())?;
+            Some((version, entry.path().join("bin").join(rustc_exe)))
+        })
+        .filter(|(_, rustc)| rustc.is_file())
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, rustc)| rustc)
+}
+
+/// Parses the version of a toolchain from the name of its directory.
+///
+/// `cargo risczero install` names directories `rust_{target}_{tag}`, with tags
+/// such as `v2024-01-31.1`. The version is the numeric components of the tag,
+/// so that e.g. `v1.10` is newer than `v1.9`.
+fn toolchain_version(dir_name: &str) -> Option<Vec<u64>> {
+    let (_, tag) = dir_name.strip_prefix("rust_")?.rsplit_once('_')?;
+    let version: Vec<u64> = tag
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    (!version.is_empty()).then_some(version)
+}
+
+/// Returns the rustc of the risc0 toolchain.
+///
+/// The toolchain linked to rustup is preferred. If none is linked, the newest
+/// toolchain installed by `cargo risczero install` is used directly.
+fn risc0_rustc() -> Option<PathBuf> {
+    let linked = sanitized_cmd("rustup")
         .args(["+risc0", "which", "rustc"])
         .output()
-        .expect("rustup failed to find risc0 toolchain")
-        .stdout;
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    match linked {
+        Some(rustc) => Some(rustc.trim().into()),
+        None => installed_rustc(),
+    }
+}
 
-    let rustc = String::from_utf8(rustc).unwrap();
-    let rustc = rustc.trim();
-    println!("Using rustc: {rustc}");
+fn guest_cargo_command(subcmd: &str, rust_flags: &[&str], stack_size: Option<u32>) -> Command {
+    let rustc = risc0_rustc().expect("failed to find risc0 toolchain");
+    println!("Using rustc: {}", rustc.display());
 
     let mut cmd = sanitized_cmd("cargo");
     let mut args = vec![subcmd, "--target", "riscv32im-risc0-zkvm-elf"];
@@ -294,6 +362,14 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
 
     println!("Building guest package: cargo {}", args.join(" "));
 
+    // The platform crate reads the stack size at compile time, so it must
+    // agree with the text start passed to the linker below.
+    let text_start = memory::text_start(stack_size.unwrap_or(memory::DEFAULT_STACK_SIZE));
+    match stack_size {
+        Some(stack_size) => cmd.env("RISC0_GUEST_STACK_SIZE", stack_size.to_string()),
+        None => cmd.env_remove("RISC0_GUEST_STACK_SIZE"),
+    };
+
     let rustflags_envvar = [
         rust_flags,
         &[
@@ -306,7 +382,7 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
             // https://ftp.gnu.org/old-gnu/Manuals/ld-2.9.1/html_mono/ld.html#SEC3
             // for details.
             "-C",
-            &format!("link-arg=-Ttext=0x{:08X}", memory::TEXT_START),
+            &format!("link-arg=-Ttext=0x{:08X}", text_start),
             // Apparently not having an entry point is only a linker warning(!), so
             // error out in this case.
             "-C",
@@ -423,11 +499,14 @@ fn build_guest_package<P>(
 
     fs::create_dir_all(target_dir.as_ref()).unwrap();
 
-    let mut cmd = if let Some(lib) = runtime_lib {
-        cargo_command("build", &["-C", &format!("link_arg={}", lib)])
-    } else {
-        cargo_command("build", &[])
-    };
+    let link_arg = runtime_lib.map(|lib| format!("link_arg={}", lib));
+    let mut rust_flags: Vec<&str> = Vec::new();
+    if let Some(link_arg) = &link_arg {
+        rust_flags.extend(["-C", link_arg]);
+    }
+    rust_flags.extend(guest_opts.rustc_flags.iter().map(String::as_str));
+
+    let mut cmd = guest_cargo_command("build", &rust_flags, guest_opts.stack_size);
 
     let features_str = guest_opts.features.join(",");
     if !features_str.is_empty() {
@@ -441,8 +520,14 @@ fn build_guest_package<P>(
         target_dir.as_ref().to_str().unwrap(),
     ]);
 
-    if !is_debug() {
-        cmd.args(["--release"]);
+    match &guest_opts.profile {
+        Some(profile) => {
+            cmd.args(["--profile", profile]);
+        }
+        None if !is_debug() => {
+            cmd.args(["--release"]);
+        }
+        None => {}
     }
 
     let mut child = cmd
@@ -487,45 +572,129 @@ fn build_guest_package<P>(
 }
 
 fn detect_toolchain(name: &str) {
-    let result = Command::new("rustup")
+    let linked = match Command::new("rustup")
         .args(["toolchain", "list", "--verbose"])
         .stderr(Stdio::inherit())
         .output()
-        .unwrap();
-    if !result.status.success() {
-        eprintln!("Failed to run: 'rustup toolchain list --verbose'");
-        std::process::exit(result.status.code().unwrap());
-    }
-
-    let stdout = String::from_utf8(result.stdout).unwrap();
-    if !stdout.lines().any(|line| line.trim().starts_with(name)) {
-        eprintln!("The 'risc0' toolchain could not be found.");
-        eprintln!("To install the risc0 toolchain, use cargo-risczero.");
-        eprintln!("For example:");
-        eprintln!("  cargo binstall cargo-risczero");
-        eprintln!("  cargo risczero install");
-        std::process::exit(-1);
+    {
+        Ok(result) if result.status.success() => String::from_utf8_lossy(&result.stdout)
+            .lines()
+            .any(|line| line.trim().starts_with(name)),
+        _ => {
+            eprintln!("Failed to run: 'rustup toolchain list --verbose'");
+            false
+        }
+    };
+    if linked {
+        return;
+    }
+
+    if let Some(rustc) = installed_rustc() {
+        eprintln!(
+            "The '{name}' toolchain is not linked to rustup, using {}.",
+            rustc.display()
+        );
+        eprintln!("Run 'cargo risczero install --use <version>' to select a toolchain.");
+        return;
     }
+
+    eprintln!("The 'risc0' toolchain could not be found.");
+    eprintln!("To install the risc0 toolchain, use cargo-risczero.");
+    eprintln!("For example:");
+    eprintln!("  cargo binstall cargo-risczero");
+    eprintln!("  cargo risczero install");
+    std::process::exit(-1);
 }
 
 /// Options for configuring a docker build environment.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct DockerOptions {
     /// Specify the root directory for docker builds.
     ///
     /// The current working directory is used if `None` is specified.
     pub root_dir: Option<PathBuf>,
+
+    /// Image IDs the built guest binaries must have, keyed by binary name.
+    ///
+    /// The build fails if any of them differ. See [docker_build_verified].
+    pub expected_image_ids: BTreeMap<String, Digest>,
 }
 
 /// Options defining how to embed a guest package in
 /// [`embed_methods_with_options`].
+///
+/// This struct is `#[non_exhaustive]` so that new options can be added
+/// without breaking callers: start from [GuestOptions::default] and use the
+/// `with_*` methods.
 #[derive(Default)]
+#[non_exhaustive]
 pub struct GuestOptions {
     /// Features for cargo to build the guest with.
     pub features: Vec<String>,
 
     /// Use a docker environment for building.
     pub use_docker: Option<DockerOptions>,
+
+    /// Size of the guest stack in bytes, instead of
+    /// [memory::DEFAULT_STACK_SIZE].
+    ///
+    /// The program is linked directly above the stack, so this moves the
+    /// start of the text section. The guest can read the value back from
+    /// `risc0_zkvm_platform::memory::STACK_SIZE`. Not supported together
+    /// with `use_docker`.
+    pub stack_size: Option<u32>,
+
+    /// Cargo profile to build the guest with, e.g. `"release"` or a custom
+    /// profile defined in the guest workspace.
+    ///
+    /// When `None`, the guest is built with `--release` unless
+    /// `RISC0_BUILD_DEBUG=1` is set. Not supported together with
+    /// `use_docker`.
+    pub profile: Option<String>,
+
+    /// Additional flags passed to rustc when building the guest.
+    ///
+    /// These are prepended to the flags risc0-build always sets, so they
+    /// cannot override the linker layout or panic strategy. Not supported
+    /// together with `use_docker`.
+    pub rustc_flags: Vec<String>,
+}
+
+impl GuestOptions {
+    /// Set the features for cargo to build the guest with.
+    pub fn with_features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Build the guest in a docker environment.
+    pub fn with_docker(mut self, docker_opts: DockerOptions) -> Self {
+        self.use_docker = Some(docker_opts);
+        self
+    }
+
+    /// Set the size of the guest stack in bytes. See [GuestOptions::stack_size].
+    pub fn with_stack_size(mut self, stack_size: u32) -> Self {
+        self.stack_size = Some(stack_size);
+        self
+    }
+
+    /// Set the cargo profile to build the guest with. See
+    /// [GuestOptions::profile].
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Set additional flags passed to rustc when building the guest. See
+    /// [GuestOptions::rustc_flags].
+    pub fn with_rustc_flags(
+        mut self,
+        rustc_flags: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.rustc_flags = rustc_flags.into_iter().map(Into::into).collect();
+        self
+    }
 }
 
 fn get_guest_dir() -> PathBuf {
@@ -581,19 +750,30 @@ pub fn embed_methods_with_options(
             .unwrap_or_default();
 
         let methods = if let Some(docker_opts) = guest_opts.use_docker {
+            assert!(
+                guest_opts.stack_size.is_none(),
+                "stack_size is not supported when building {} with docker",
+                guest_pkg.name
+            );
+            assert!(
+                guest_opts.profile.is_none() && guest_opts.rustc_flags.is_empty(),
+                "profile and rustc_flags are not supported when building {} with docker",
+                guest_pkg.name
+            );
             let src_dir = docker_opts
                 .root_dir
                 .unwrap_or_else(|| std::env::current_dir().unwrap());
-            docker_build(
+            docker_build_verified(
                 guest_pkg.manifest_path.as_std_path(),
                 &src_dir,
                 &guest_opts.features,
+                &docker_opts.expected_image_ids,
             )
             .unwrap();
             guest_methods_docker(&guest_pkg, &guest_dir)
         } else {
             build_guest_package(&guest_pkg, &guest_dir, &guest_opts, None);
-            guest_methods(&guest_pkg, &guest_dir)
+            guest_methods(&guest_pkg, &guest_dir, guest_opts.profile.as_deref())
         };
 
         for method in methods {
@@ -649,3 +829,19 @@ pub fn embed_methods_with_options(
 pub fn embed_methods() -> Vec<GuestListEntry> {
     embed_methods_with_options(HashMap::new())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::toolchain_version;
+
+    #[test]
+    fn toolchain_version_orders_numerically() {
+        let version =
+            |tag: &str| toolchain_version(&format!("rust_x86_64-unknown-linux-gnu_{tag}"));
+        assert_eq!(version("v2024-01-31.1"), Some(vec![2024, 1, 31, 1]));
+        assert!(version("v1.10.0") > version("v1.9.0"));
+        assert!(version("v2024-02-08.1") > version("v2024-01-31.10"));
+        assert_eq!(version("latest"), None);
+        assert_eq!(toolchain_version("c_x86_64-unknown-linux-gnu_v1.0"), None);
+    }
+}
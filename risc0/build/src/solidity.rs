@@ -0,0 +1,519 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generate Solidity decoders for journals committed with `env::commit`.
+//!
+//! Journals are encoded with the zkVM's word-oriented serde format, which
+//! differs from the Solidity ABI. This module emits a Solidity library with a
+//! struct matching each described Rust type, and `decode` functions that read
+//! that format, so that contracts consuming a journal stay consistent with the
+//! guest that committed it.
+//!
+//! ```
+//! use risc0_build::{solidity::SolidityLibrary, solidity_struct};
+//!
+//! let source = SolidityLibrary::new("JournalLib")
+//!     .with_struct(solidity_struct!(Journal {
+//!         count: u32,
+//!         total: u64,
+//!         digest: [u32; 8],
+//!         name: String,
+//!     }))
+//!     .generate();
+//! assert!(source.contains("function decodeJournal(bytes memory journal)"));
+//! ```
+//!
+//! [receipt_claim_library] emits a reference library that recomputes the
+//! digest of a `ReceiptClaim`, so that contracts can check a claim against the
+//! image ID and journal they expect rather than trusting a digest assembled
+//! off-chain.
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::Result;
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::sha::{cpu::Impl, Sha256},
+};
+
+/// A Solidity type, as decoded from the zkVM serde format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolType {
+    /// `bool`, encoded as a single word.
+    Bool,
+    /// `uintN` for N in 8, 16, 32 and 64.
+    Uint(u16),
+    /// `intN` for N in 8, 16, 32 and 64.
+    Int(u16),
+    /// `string`, encoded as a length followed by padded UTF-8 bytes.
+    String,
+    /// `T[]`, encoded as a length followed by the elements.
+    Array(Box<SolType>),
+    /// `T[N]`, encoded as the elements.
+    FixedArray(Box<SolType>, usize),
+    /// A struct described by a [SolStruct] in the same library.
+    Struct(String),
+}
+
+impl SolType {
+    fn name(&self) -> String {
+        match self {
+            SolType::Bool => "bool".to_string(),
+            SolType::Uint(bits) => format!("uint{bits}"),
+            SolType::Int(bits) => format!("int{bits}"),
+            SolType::String => "string".to_string(),
+            SolType::Array(elem) => format!("{}[]", elem.name()),
+            SolType::FixedArray(elem, len) => format!("{}[{len}]", elem.name()),
+            SolType::Struct(name) => name.clone(),
+        }
+    }
+
+    fn reader(&self) -> String {
+        match self {
+            SolType::Struct(name) => format!("decode{name}"),
+            SolType::Bool => "_readBool".to_string(),
+            SolType::Uint(bits) => format!("_readUint{bits}"),
+            SolType::Int(bits) => format!("_readInt{bits}"),
+            SolType::String => "_readString".to_string(),
+            SolType::Array(_) | SolType::FixedArray(..) => {
+                unreachable!("arrays are decoded inline")
+            }
+        }
+    }
+}
+
+/// A Rust type with a known Solidity counterpart.
+///
+/// Implement this for structs described with [solidity_struct!] so that they
+/// can be nested in other structs.
+pub trait SolidityType {
+    /// The Solidity type that this type decodes to.
+    fn sol_type() -> SolType;
+}
+
+macro_rules! impl_sol_type {
+    ($($ty:ty => $sol:expr),* $(,)?) => {
+        $(impl SolidityType for $ty {
+            fn sol_type() -> SolType {
+                $sol
+            }
+        })*
+    };
+}
+
+impl_sol_type! {
+    bool => SolType::Bool,
+    u8 => SolType::Uint(8),
+    u16 => SolType::Uint(16),
+    u32 => SolType::Uint(32),
+    u64 => SolType::Uint(64),
+    i8 => SolType::Int(8),
+    i16 => SolType::Int(16),
+    i32 => SolType::Int(32),
+    i64 => SolType::Int(64),
+    String => SolType::String,
+}
+
+impl<T: SolidityType> SolidityType for Vec<T> {
+    fn sol_type() -> SolType {
+        SolType::Array(Box::new(T::sol_type()))
+    }
+}
+
+impl<T: SolidityType, const N: usize> SolidityType for [T; N] {
+    fn sol_type() -> SolType {
+        SolType::FixedArray(Box::new(T::sol_type()), N)
+    }
+}
+
+/// A struct to generate in a [SolidityLibrary].
+///
+/// Fields must be listed in declaration order, as serde encodes them in that
+/// order. See [solidity_struct!] for building one from Rust field types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolStruct {
+    name: String,
+    fields: Vec<(String, SolType)>,
+}
+
+impl SolStruct {
+    /// Start describing a struct named `name`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Append a field with the Solidity counterpart of `T`.
+    pub fn field<T: SolidityType>(self, name: &str) -> Self {
+        self.field_type(name, T::sol_type())
+    }
+
+    /// Append a field with an explicit Solidity type.
+    pub fn field_type(mut self, name: &str, ty: SolType) -> Self {
+        self.fields.push((name.to_string(), ty));
+        self
+    }
+}
+
+/// Describe a Rust struct as a [SolStruct].
+///
+/// The field list mirrors the Rust struct definition, and each field type must
+/// implement [SolidityType].
+#[macro_export]
+macro_rules! solidity_struct {
+    ($name:ident { $($field:ident : $ty:ty),* $(,)? }) => {
+        $crate::solidity::SolStruct::new(stringify!($name))
+            $(.field::<$ty>(stringify!($field)))*
+    };
+}
+
+/// A Solidity library of journal structs and their decoders.
+#[derive(Clone, Debug)]
+pub struct SolidityLibrary {
+    name: String,
+    license: String,
+    structs: Vec<SolStruct>,
+}
+
+impl SolidityLibrary {
+    /// Start a library named `name`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            license: "UNLICENSED".to_string(),
+            structs: Vec::new(),
+        }
+    }
+
+    /// Set the SPDX license identifier of the generated file.
+    pub fn license(mut self, spdx: &str) -> Self {
+        self.license = spdx.to_string();
+        self
+    }
+
+    /// Add a struct. Nested structs must be added as well.
+    pub fn with_struct(mut self, item: SolStruct) -> Self {
+        self.structs.push(item);
+        self
+    }
+
+    /// Generate the Solidity source of this library.
+    pub fn generate(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "// SPDX-License-Identifier: {}", self.license).unwrap();
+        writeln!(
+            out,
+            "// This file is generated by risc0-build. Do not edit."
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "pragma solidity ^0.8.9;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "library {} {{", self.name).unwrap();
+        for item in self.structs.iter() {
+            write_struct(&mut out, item);
+        }
+        out.push_str(HELPERS);
+        out.push_str("}\n");
+        out
+    }
+
+    /// Generate the Solidity source and write it to `path`.
+    ///
+    /// The file is left untouched if its contents would not change.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let source = self.generate();
+        if fs::read_to_string(path).ok().as_deref() != Some(source.as_str()) {
+            fs::write(path, source)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_struct(out: &mut String, item: &SolStruct) {
+    let name = &item.name;
+    writeln!(out, "    struct {name} {{").unwrap();
+    for (field, ty) in item.fields.iter() {
+        writeln!(out, "        {} {field};", ty.name()).unwrap();
+    }
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(
+        out,
+        "    function decode{name}(bytes memory journal) internal pure returns ({name} memory value) {{"
+    )
+    .unwrap();
+    writeln!(out, "        uint256 offset;").unwrap();
+    writeln!(out, "        (value, offset) = decode{name}(journal, 0);").unwrap();
+    writeln!(
+        out,
+        "        require(offset == journal.length, \"trailing journal data\");"
+    )
+    .unwrap();
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(
+        out,
+        "    function decode{name}(bytes memory data, uint256 offset)\n        internal\n        pure\n        returns ({name} memory value, uint256)\n    {{"
+    )
+    .unwrap();
+    for (field, ty) in item.fields.iter() {
+        write_decode(out, ty, &format!("value.{field}"), 0);
+    }
+    writeln!(out, "        return (value, offset);").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+}
+
+fn write_decode(out: &mut String, ty: &SolType, target: &str, depth: usize) {
+    let indent = "    ".repeat(depth + 2);
+    let idx = format!("i{depth}");
+    match ty {
+        SolType::Array(elem) => {
+            writeln!(out, "{indent}{{").unwrap();
+            writeln!(out, "{indent}    uint32 len;").unwrap();
+            writeln!(
+                out,
+                "{indent}    (len, offset) = _readUint32(data, offset);"
+            )
+            .unwrap();
+            writeln!(out, "{indent}    {target} = new {}[](len);", elem.name()).unwrap();
+            writeln!(
+                out,
+                "{indent}    for (uint256 {idx} = 0; {idx} < len; {idx}++) {{"
+            )
+            .unwrap();
+            write_decode(out, elem, &format!("{target}[{idx}]"), depth + 2);
+            writeln!(out, "{indent}    }}").unwrap();
+            writeln!(out, "{indent}}}").unwrap();
+        }
+        SolType::FixedArray(elem, len) => {
+            writeln!(
+                out,
+                "{indent}for (uint256 {idx} = 0; {idx} < {len}; {idx}++) {{"
+            )
+            .unwrap();
+            write_decode(out, elem, &format!("{target}[{idx}]"), depth + 1);
+            writeln!(out, "{indent}}}").unwrap();
+        }
+        ty => {
+            writeln!(
+                out,
+                "{indent}({target}, offset) = {}(data, offset);",
+                ty.reader()
+            )
+            .unwrap();
+        }
+    }
+}
+
+const HELPERS: &str = r#"    function _readWord(bytes memory data, uint256 offset) private pure returns (uint32, uint256) {
+        require(offset + 4 <= data.length, "journal too short");
+        uint32 word = uint32(uint8(data[offset])) | (uint32(uint8(data[offset + 1])) << 8)
+            | (uint32(uint8(data[offset + 2])) << 16) | (uint32(uint8(data[offset + 3])) << 24);
+        return (word, offset + 4);
+    }
+
+    function _readBool(bytes memory data, uint256 offset) private pure returns (bool, uint256) {
+        (uint32 word, uint256 next) = _readWord(data, offset);
+        require(word <= 1, "invalid bool");
+        return (word == 1, next);
+    }
+
+    function _readUint8(bytes memory data, uint256 offset) private pure returns (uint8, uint256) {
+        (uint32 word, uint256 next) = _readWord(data, offset);
+        require(word <= type(uint8).max, "invalid uint8");
+        return (uint8(word), next);
+    }
+
+    function _readUint16(bytes memory data, uint256 offset) private pure returns (uint16, uint256) {
+        (uint32 word, uint256 next) = _readWord(data, offset);
+        require(word <= type(uint16).max, "invalid uint16");
+        return (uint16(word), next);
+    }
+
+    function _readUint32(bytes memory data, uint256 offset) private pure returns (uint32, uint256) {
+        return _readWord(data, offset);
+    }
+
+    function _readUint64(bytes memory data, uint256 offset) private pure returns (uint64, uint256) {
+        (uint32 lo, uint256 mid) = _readWord(data, offset);
+        (uint32 hi, uint256 next) = _readWord(data, mid);
+        return (uint64(lo) | (uint64(hi) << 32), next);
+    }
+
+    function _readInt8(bytes memory data, uint256 offset) private pure returns (int8, uint256) {
+        (int32 value, uint256 next) = _readInt32(data, offset);
+        require(value >= type(int8).min && value <= type(int8).max, "invalid int8");
+        return (int8(value), next);
+    }
+
+    function _readInt16(bytes memory data, uint256 offset) private pure returns (int16, uint256) {
+        (int32 value, uint256 next) = _readInt32(data, offset);
+        require(value >= type(int16).min && value <= type(int16).max, "invalid int16");
+        return (int16(value), next);
+    }
+
+    function _readInt32(bytes memory data, uint256 offset) private pure returns (int32, uint256) {
+        (uint32 word, uint256 next) = _readWord(data, offset);
+        return (int32(word), next);
+    }
+
+    function _readInt64(bytes memory data, uint256 offset) private pure returns (int64, uint256) {
+        (uint64 value, uint256 next) = _readUint64(data, offset);
+        return (int64(value), next);
+    }
+
+    function _readString(bytes memory data, uint256 offset) private pure returns (string memory, uint256) {
+        (uint32 len, uint256 start) = _readWord(data, offset);
+        uint256 padded = (uint256(len) + 3) & ~uint256(3);
+        require(start + padded <= data.length, "journal too short");
+        bytes memory value = new bytes(len);
+        for (uint256 i = 0; i < len; i++) {
+            value[i] = data[start + i];
+        }
+        return (string(value), start + padded);
+    }
+"#;
+
+/// Generate a Solidity library named `name` that computes `ReceiptClaim`
+/// digests.
+///
+/// The library mirrors `ReceiptClaimPreimage` in `risc0-zkvm`: `digest` hashes
+/// the same tagged struct encoding as `ReceiptClaim::digest`, and
+/// `outputDigest` computes the digest of an `Output` from the journal digest
+/// and the assumptions digest.
+pub fn receipt_claim_library(name: &str, license: &str) -> String {
+    RECEIPT_CLAIM_LIB
+        .replace("{license}", license)
+        .replace("{name}", name)
+        .replace("{claim_tag}", &tag_digest_hex("risc0.ReceiptClaim"))
+        .replace("{output_tag}", &tag_digest_hex("risc0.Output"))
+}
+
+fn tag_digest_hex(tag: &str) -> String {
+    let digest: Digest = *Impl::hash_bytes(tag.as_bytes());
+    digest
+        .as_bytes()
+        .iter()
+        .fold(String::new(), |mut out, byte| {
+            write!(out, "{byte:02x}").unwrap();
+            out
+        })
+}
+
+const RECEIPT_CLAIM_LIB: &str = r#"// SPDX-License-Identifier: {license}
+// This file is generated by risc0-build. Do not edit.
+
+pragma solidity ^0.8.9;
+
+library {name} {
+    /// sha256("risc0.ReceiptClaim")
+    bytes32 internal constant RECEIPT_CLAIM_TAG = hex"{claim_tag}";
+    /// sha256("risc0.Output")
+    bytes32 internal constant OUTPUT_TAG = hex"{output_tag}";
+
+    /// The fields of the ReceiptClaim preimage, with each nested struct given by its digest.
+    struct ReceiptClaim {
+        bytes32 input;
+        bytes32 pre;
+        bytes32 post;
+        bytes32 output;
+        uint32 sysExit;
+        uint32 userExit;
+    }
+
+    /// Compute the digest of a ReceiptClaim.
+    function digest(ReceiptClaim memory claim) internal pure returns (bytes32) {
+        return sha256(
+            abi.encodePacked(
+                RECEIPT_CLAIM_TAG,
+                claim.input,
+                claim.pre,
+                claim.post,
+                claim.output,
+                _reverseUint32(claim.sysExit),
+                _reverseUint32(claim.userExit),
+                uint16(4) << 8
+            )
+        );
+    }
+
+    /// Compute the digest of an Output from the journal and assumptions digests.
+    function outputDigest(bytes32 journalDigest, bytes32 assumptionsDigest) internal pure returns (bytes32) {
+        return sha256(abi.encodePacked(OUTPUT_TAG, journalDigest, assumptionsDigest, uint16(2) << 8));
+    }
+
+    /// Convert a uint32 to little-endian, as the preimage encodes words.
+    function _reverseUint32(uint32 x) private pure returns (uint32) {
+        x = ((x & 0xFF00FF00) >> 8) | ((x & 0x00FF00FF) << 8);
+        return (x >> 16) | (x << 16);
+    }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::{receipt_claim_library, SolType, SolidityLibrary, SolidityType};
+
+    struct Inner;
+
+    impl SolidityType for Inner {
+        fn sol_type() -> SolType {
+            SolType::Struct("Inner".to_string())
+        }
+    }
+
+    #[test]
+    fn rust_types() {
+        assert_eq!(<Vec<u8>>::sol_type().name(), "uint8[]");
+        assert_eq!(<[u32; 8]>::sol_type().name(), "uint32[8]");
+        assert_eq!(<Vec<[i64; 2]>>::sol_type().name(), "int64[2][]");
+        assert_eq!(<Vec<Inner>>::sol_type().name(), "Inner[]");
+    }
+
+    #[test]
+    fn generate() {
+        let source = SolidityLibrary::new("JournalLib")
+            .license("Apache-2.0")
+            .with_struct(crate::solidity_struct!(Inner { flag: bool }))
+            .with_struct(crate::solidity_struct!(Journal {
+                count: u32,
+                inner: Inner,
+                items: Vec<Inner>,
+            }))
+            .generate();
+
+        assert!(source.starts_with("// SPDX-License-Identifier: Apache-2.0\n"));
+        assert!(source.contains("library JournalLib {"));
+        assert!(source.contains("    struct Journal {\n        uint32 count;\n        Inner inner;\n        Inner[] items;\n    }"));
+        assert!(source.contains("(value.inner, offset) = decodeInner(data, offset);"));
+        assert!(source.contains("value.items = new Inner[](len);"));
+        assert!(source.contains("(value.items[i0], offset) = decodeInner(data, offset);"));
+    }
+
+    #[test]
+    fn receipt_claim() {
+        let source = receipt_claim_library("ReceiptClaimLib", "Apache-2.0");
+        assert!(source.starts_with("// SPDX-License-Identifier: Apache-2.0\n"));
+        assert!(source.contains("library ReceiptClaimLib {"));
+        assert!(!source.contains("{claim_tag}") && !source.contains("{output_tag}"));
+
+        let claim_tag = super::tag_digest_hex("risc0.ReceiptClaim");
+        assert_eq!(claim_tag.len(), 64);
+        assert!(source.contains(&format!("RECEIPT_CLAIM_TAG = hex\"{claim_tag}\";")));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#pragma once
+
+// This file is generated by cbindgen from src/lib.rs. Do not edit it by hand.
+
+#include <stddef.h>
+#include <stdint.h>
+
+/// All functions returning `int32_t` return 0 on success and -1 on failure.
+/// On failure, `risc0_last_error` describes the error.
+///
+/// `risc0_execute`, `risc0_prove` and `risc0_compute_image_id` are only
+/// available when the library is built with the `prove` feature, which is
+/// enabled by default. Define `RISC0_VERIFY_ONLY` before including this header
+/// when linking a library built with `--no-default-features`.
+#ifndef RISC0_VERIFY_ONLY
+#define RISC0_PROVE
+#endif
+
+/// A byte buffer allocated by this library.
+///
+/// Buffers returned by the API must be released with [risc0_buffer_free].
+typedef struct Risc0Buffer {
+  /// Pointer to the start of the data.
+  uint8_t *ptr;
+  /// Length of the data in bytes.
+  size_t len;
+} Risc0Buffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+#if defined(RISC0_PROVE)
+/// Execute a guest without proving it, returning its journal.
+///
+/// `input` is written to the guest's stdin as-is, so it must already be
+/// encoded in the format the guest reads. On success, `journal_out` is set to
+/// a buffer that must be released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `elf` and `input` must be valid for reads of `elf_len` and `input_len`
+/// bytes. `journal_out` must be valid for writes.
+int32_t risc0_execute(const uint8_t *elf,
+                      size_t elf_len,
+                      const uint8_t *input,
+                      size_t input_len,
+                      struct Risc0Buffer *journal_out);
+#endif
+
+#if defined(RISC0_PROVE)
+/// Prove a guest, returning its receipt encoded with bincode.
+///
+/// `input` is handled as in [risc0_execute]. On success, `receipt_out` is set
+/// to a buffer that must be released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `elf` and `input` must be valid for reads of `elf_len` and `input_len`
+/// bytes. `receipt_out` must be valid for writes.
+int32_t risc0_prove(const uint8_t *elf,
+                    size_t elf_len,
+                    const uint8_t *input,
+                    size_t input_len,
+                    struct Risc0Buffer *receipt_out);
+#endif
+
+/// Verify a bincode-encoded receipt against a 32-byte image ID.
+///
+/// # Safety
+///
+/// `receipt` must be valid for reads of `receipt_len` bytes and `image_id`
+/// must be valid for reads of 32 bytes.
+int32_t risc0_verify(const uint8_t *receipt, size_t receipt_len, const uint8_t *image_id);
+
+/// Extract the journal of a bincode-encoded receipt, without verifying it.
+///
+/// The journal is only trustworthy once [risc0_verify] has succeeded on the
+/// same receipt. On success, `journal_out` is set to a buffer that must be
+/// released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `receipt` must be valid for reads of `receipt_len` bytes and
+/// `journal_out` must be valid for writes.
+int32_t risc0_receipt_journal(const uint8_t *receipt,
+                              size_t receipt_len,
+                              struct Risc0Buffer *journal_out);
+
+#if defined(RISC0_PROVE)
+/// Compute the image ID of a guest ELF, writing 32 bytes to `image_id_out`.
+///
+/// # Safety
+///
+/// `elf` must be valid for reads of `elf_len` bytes and `image_id_out` must
+/// be valid for writes of 32 bytes.
+int32_t risc0_compute_image_id(const uint8_t *elf, size_t elf_len, uint8_t *image_id_out);
+#endif
+
+/// Release a buffer returned by this library.
+///
+/// # Safety
+///
+/// `buf` must have been returned by this library and not already freed.
+void risc0_buffer_free(struct Risc0Buffer buf);
+
+/// Return the message of the last error on the calling thread, or null if no
+/// error has occurred.
+///
+/// The returned string is owned by the library and remains valid until the
+/// next failing call on the same thread.
+const char *risc0_last_error(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
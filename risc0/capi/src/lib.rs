@@ -0,0 +1,387 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! C API for the RISC Zero zkVM.
+//!
+//! This crate builds `librisc0`, as both a static and a shared library,
+//! exposing entry points to execute guests, prove them and verify receipts.
+//! The matching header `include/risc0.h` is generated from this file with
+//! cbindgen. See the crate README for building it without network access.
+//!
+//! Executing and proving require the default `prove` feature. Building with
+//! `--no-default-features` links only the verifier, exposing [risc0_verify]
+//! and [risc0_receipt_journal].
+//!
+//! All functions return `0` on success and `-1` on failure. On failure, a
+//! description of the error can be retrieved with [risc0_last_error]. Panics
+//! are caught at the API boundary and reported the same way.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use anyhow::{anyhow, Result};
+#[cfg(feature = "prove")]
+use risc0_zkvm::{Executor, ExecutorEnv, LocalProver, Prover};
+use risc0_zkvm_verify::{
+    sha::{Digest, DIGEST_BYTES},
+    Receipt,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A byte buffer allocated by this library.
+///
+/// Buffers returned by the API must be released with [risc0_buffer_free].
+#[repr(C)]
+pub struct Risc0Buffer {
+    /// Pointer to the start of the data.
+    pub ptr: *mut u8,
+    /// Length of the data in bytes.
+    pub len: usize,
+}
+
+impl Risc0Buffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let data = Box::into_raw(data.into_boxed_slice());
+        Self {
+            ptr: data.cast(),
+            len: data.len(),
+        }
+    }
+}
+
+/// Run `f`, translating errors and panics into a status code and recording
+/// the error message for [risc0_last_error].
+fn ffi_call(f: impl FnOnce() -> Result<()>) -> i32 {
+    let err = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return 0,
+        Ok(Err(err)) => format!("{err:#}"),
+        Err(panic) => match panic.downcast_ref::<&str>() {
+            Some(msg) => format!("panic: {msg}"),
+            None => match panic.downcast_ref::<String>() {
+                Some(msg) => format!("panic: {msg}"),
+                None => "panic".to_string(),
+            },
+        },
+    };
+    let err = CString::new(err.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(err));
+    -1
+}
+
+/// Borrow a byte slice from a pointer and length passed in by the caller.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, or `len` must be zero.
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(anyhow!("null pointer with non-zero length"));
+    }
+    Ok(slice::from_raw_parts(ptr, len))
+}
+
+/// Write `buf` to an output pointer passed in by the caller.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write_out(out: *mut Risc0Buffer, buf: Vec<u8>) -> Result<()> {
+    if out.is_null() {
+        return Err(anyhow!("null output buffer"));
+    }
+    out.write(Risc0Buffer::from_vec(buf));
+    Ok(())
+}
+
+/// Decode a bincode-encoded receipt passed in by the caller.
+///
+/// # Safety
+///
+/// `receipt` must be valid for reads of `receipt_len` bytes.
+unsafe fn decode_receipt(receipt: *const u8, receipt_len: usize) -> Result<Receipt> {
+    bincode::deserialize(bytes(receipt, receipt_len)?)
+        .map_err(|err| anyhow!("invalid receipt encoding: {err}"))
+}
+
+#[cfg(feature = "prove")]
+fn build_env(input: &[u8]) -> Result<ExecutorEnv<'static>> {
+    ExecutorEnv::builder().write_slice(input).build()
+}
+
+/// Execute a guest without proving it, returning its journal.
+///
+/// `input` is written to the guest's stdin as-is, so it must already be
+/// encoded in the format the guest reads. On success, `journal_out` is set to
+/// a buffer that must be released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `elf` and `input` must be valid for reads of `elf_len` and `input_len`
+/// bytes. `journal_out` must be valid for writes.
+#[cfg(feature = "prove")]
+#[no_mangle]
+pub unsafe extern "C" fn risc0_execute(
+    elf: *const u8,
+    elf_len: usize,
+    input: *const u8,
+    input_len: usize,
+    journal_out: *mut Risc0Buffer,
+) -> i32 {
+    ffi_call(|| {
+        let elf = bytes(elf, elf_len)?;
+        let env = build_env(bytes(input, input_len)?)?;
+        let session = LocalProver::new("local").execute(env, elf)?;
+        write_out(journal_out, session.journal.bytes)
+    })
+}
+
+/// Prove a guest, returning its receipt encoded with bincode.
+///
+/// `input` is handled as in [risc0_execute]. On success, `receipt_out` is set
+/// to a buffer that must be released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `elf` and `input` must be valid for reads of `elf_len` and `input_len`
+/// bytes. `receipt_out` must be valid for writes.
+#[cfg(feature = "prove")]
+#[no_mangle]
+pub unsafe extern "C" fn risc0_prove(
+    elf: *const u8,
+    elf_len: usize,
+    input: *const u8,
+    input_len: usize,
+    receipt_out: *mut Risc0Buffer,
+) -> i32 {
+    ffi_call(|| {
+        let elf = bytes(elf, elf_len)?;
+        let env = build_env(bytes(input, input_len)?)?;
+        let receipt = LocalProver::new("local").prove(env, elf)?.receipt;
+        write_out(receipt_out, bincode::serialize(&receipt)?)
+    })
+}
+
+/// Verify a bincode-encoded receipt against a 32-byte image ID.
+///
+/// # Safety
+///
+/// `receipt` must be valid for reads of `receipt_len` bytes and `image_id`
+/// must be valid for reads of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_verify(
+    receipt: *const u8,
+    receipt_len: usize,
+    image_id: *const u8,
+) -> i32 {
+    ffi_call(|| {
+        let receipt = decode_receipt(receipt, receipt_len)?;
+        let image_id = Digest::try_from(bytes(image_id, DIGEST_BYTES)?)
+            .map_err(|_| anyhow!("invalid image ID"))?;
+        receipt.verify(image_id)?;
+        Ok(())
+    })
+}
+
+/// Extract the journal of a bincode-encoded receipt, without verifying it.
+///
+/// The journal is only trustworthy once [risc0_verify] has succeeded on the
+/// same receipt. On success, `journal_out` is set to a buffer that must be
+/// released with [risc0_buffer_free].
+///
+/// # Safety
+///
+/// `receipt` must be valid for reads of `receipt_len` bytes and
+/// `journal_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_receipt_journal(
+    receipt: *const u8,
+    receipt_len: usize,
+    journal_out: *mut Risc0Buffer,
+) -> i32 {
+    ffi_call(|| {
+        let journal = decode_receipt(receipt, receipt_len)?.journal.bytes;
+        write_out(journal_out, journal)
+    })
+}
+
+/// Compute the image ID of a guest ELF, writing 32 bytes to `image_id_out`.
+///
+/// # Safety
+///
+/// `elf` must be valid for reads of `elf_len` bytes and `image_id_out` must
+/// be valid for writes of 32 bytes.
+#[cfg(feature = "prove")]
+#[no_mangle]
+pub unsafe extern "C" fn risc0_compute_image_id(
+    elf: *const u8,
+    elf_len: usize,
+    image_id_out: *mut u8,
+) -> i32 {
+    ffi_call(|| {
+        if image_id_out.is_null() {
+            return Err(anyhow!("null output buffer"));
+        }
+        let image_id = risc0_zkvm::compute_image_id(bytes(elf, elf_len)?)?;
+        ptr::copy_nonoverlapping(image_id.as_bytes().as_ptr(), image_id_out, DIGEST_BYTES);
+        Ok(())
+    })
+}
+
+/// Release a buffer returned by this library.
+///
+/// # Safety
+///
+/// `buf` must have been returned by this library and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn risc0_buffer_free(buf: Risc0Buffer) {
+    if !buf.ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buf.ptr, buf.len,
+        )));
+    }
+}
+
+/// Return the message of the last error on the calling thread, or null if no
+/// error has occurred.
+///
+/// The returned string is owned by the library and remains valid until the
+/// next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn risc0_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |err| err.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, ptr};
+
+    use super::*;
+
+    fn empty() -> Risc0Buffer {
+        Risc0Buffer {
+            ptr: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    #[cfg(feature = "prove")]
+    #[test]
+    fn execute_and_verify() {
+        use risc0_zkvm::serde::to_vec;
+        use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+        let spec = MultiTestSpec::Echo {
+            bytes: b"hello".to_vec(),
+        };
+        let input: Vec<u8> = to_vec(&spec)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+
+        let mut image_id = [0u8; DIGEST_BYTES];
+        unsafe {
+            assert_eq!(
+                risc0_compute_image_id(
+                    MULTI_TEST_ELF.as_ptr(),
+                    MULTI_TEST_ELF.len(),
+                    image_id.as_mut_ptr()
+                ),
+                0
+            );
+        }
+        assert_eq!(Digest::from(MULTI_TEST_ID), Digest::from(image_id));
+
+        let mut journal = empty();
+        unsafe {
+            assert_eq!(
+                risc0_execute(
+                    MULTI_TEST_ELF.as_ptr(),
+                    MULTI_TEST_ELF.len(),
+                    input.as_ptr(),
+                    input.len(),
+                    &mut journal,
+                ),
+                0
+            );
+            assert_eq!(slice::from_raw_parts(journal.ptr, journal.len), b"hello");
+            risc0_buffer_free(journal);
+        }
+
+        let mut receipt = empty();
+        unsafe {
+            assert_eq!(
+                risc0_prove(
+                    MULTI_TEST_ELF.as_ptr(),
+                    MULTI_TEST_ELF.len(),
+                    input.as_ptr(),
+                    input.len(),
+                    &mut receipt,
+                ),
+                0
+            );
+            assert_eq!(risc0_verify(receipt.ptr, receipt.len, image_id.as_ptr()), 0);
+
+            let mut journal = empty();
+            assert_eq!(
+                risc0_receipt_journal(receipt.ptr, receipt.len, &mut journal),
+                0
+            );
+            assert_eq!(slice::from_raw_parts(journal.ptr, journal.len), b"hello");
+            risc0_buffer_free(journal);
+
+            let wrong_id = [0u8; DIGEST_BYTES];
+            assert_eq!(
+                risc0_verify(receipt.ptr, receipt.len, wrong_id.as_ptr()),
+                -1
+            );
+            assert!(!risc0_last_error().is_null());
+            risc0_buffer_free(receipt);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut journal = empty();
+        let status = unsafe { risc0_receipt_journal(ptr::null(), 1, &mut journal) };
+        assert_eq!(status, -1);
+        let err = unsafe { CStr::from_ptr(risc0_last_error()) };
+        assert_eq!(err.to_str().unwrap(), "null pointer with non-zero length");
+        assert!(journal.ptr.is_null());
+
+        let receipt = [0xffu8; 4];
+        let image_id = [0u8; DIGEST_BYTES];
+        let status = unsafe { risc0_verify(receipt.as_ptr(), receipt.len(), image_id.as_ptr()) };
+        assert_eq!(status, -1);
+        let err = unsafe { CStr::from_ptr(risc0_last_error()) };
+        assert!(err
+            .to_str()
+            .unwrap()
+            .starts_with("invalid receipt encoding"));
+    }
+}
@@ -12,14 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use hex::FromHex;
 use risc0_build::BuildStatus;
+use risc0_zkvm::sha::Digest;
 
 /// `cargo risczero build`
 ///
+/// Each ELF is written with a CycloneDX SBOM and a provenance statement next to
+/// it. Set `RISC0_ATTESTATION_KEY` to the path of an Ed25519 PKCS#8 key to sign
+/// the provenance.
+///
 /// NOTE: Requires Docker to be installed and running.
 #[derive(Parser)]
 pub struct BuildGuest {
@@ -32,15 +41,37 @@ pub struct BuildGuest {
     /// Feature flags passed to cargo.
     #[arg(long, value_delimiter = ',')]
     pub features: Vec<String>,
+
+    /// Expected image ID of a guest binary, as `<name>=<hex image ID>`.
+    ///
+    /// May be given multiple times. The build fails if any built binary has a
+    /// different image ID.
+    #[arg(long = "expect-image-id", value_parser = parse_image_id)]
+    pub expected_image_ids: Vec<(String, Digest)>,
 }
 
 impl BuildGuest {
     pub fn run(&self) -> Result<()> {
-        build(&self.manifest_path, &self.features)?;
+        let src_dir = std::env::current_dir().unwrap();
+        let expected_image_ids: BTreeMap<_, _> = self.expected_image_ids.iter().cloned().collect();
+        risc0_build::docker_build_verified(
+            &self.manifest_path,
+            &src_dir,
+            &self.features,
+            &expected_image_ids,
+        )?;
         Ok(())
     }
 }
 
+fn parse_image_id(arg: &str) -> Result<(String, Digest)> {
+    let (name, image_id) = arg
+        .split_once('=')
+        .context("expected <name>=<hex image ID>")?;
+    let image_id = Digest::from_hex(image_id).context("invalid image ID")?;
+    Ok((name.to_string(), image_id))
+}
+
 pub(crate) fn build(manifest_path: &Path, features: &[String]) -> Result<BuildStatus> {
     let src_dir = std::env::current_dir().unwrap();
     risc0_build::docker_build(manifest_path, &src_dir, features)
@@ -41,8 +41,26 @@ use risc0_build::risc0_data;
 /// `cargo risczero install`
 #[derive(Parser)]
 pub struct Install {
+    /// The toolchain release to install. Defaults to the latest release.
     #[arg(long)]
     version: Option<String>,
+
+    /// Download the toolchain again even if it is already installed.
+    ///
+    /// Use this to repair a broken installation.
+    #[arg(long)]
+    force: bool,
+
+    /// List the toolchains that have been downloaded and exit.
+    #[arg(long, conflicts_with_all = ["version", "force"])]
+    list: bool,
+
+    /// Activate a rust toolchain release that has already been downloaded,
+    /// without accessing the network.
+    ///
+    /// Accepts a release tag or a name printed by `--list`.
+    #[arg(long = "use", value_name = "VERSION", conflicts_with_all = ["version", "force", "list"])]
+    use_version: Option<String>,
 }
 
 /// Release returned by Github API.
@@ -69,6 +87,13 @@ impl Install {
         let _lock = flock(&lockfile_path);
 
         let toolchain_dir = root_dir.join("toolchains");
+        if self.list {
+            return list_toolchains(&toolchain_dir);
+        }
+        if let Some(version) = &self.use_version {
+            return use_toolchain(&toolchain_dir, version);
+        }
+
         let (rust_chain, cpp_chain) = self.install_prebuilt_toolchain(&toolchain_dir)?;
 
         eprintln!(
@@ -190,6 +215,14 @@ impl Install {
         let toolchain_dir =
             toolchains_root_dir.join(format!("{}_{target}_{}", repo.language(), tag_name));
         if toolchain_dir.is_dir() {
+            if !self.force && repo.is_complete(&toolchain_dir, target) {
+                eprintln!(
+                    "{} toolchain {tag_name} is already installed at {}",
+                    repo.language(),
+                    toolchain_dir.display()
+                );
+                return Ok(toolchain_dir);
+            }
             eprintln!(
                 "Toolchain path {} already exists - deleting existing files!",
                 toolchain_dir.display()
@@ -275,6 +308,63 @@ impl Install {
     }
 }
 
+/// Print the downloaded toolchains, marking the one linked to rustup.
+fn list_toolchains(toolchain_dir: &Path) -> Result<()> {
+    let active = RustupToolchain::find_by_name(RUSTUP_TOOLCHAIN_NAME)?.map(|chain| chain.path);
+
+    let mut names = Vec::new();
+    if toolchain_dir.is_dir() {
+        for entry in std::fs::read_dir(toolchain_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.sort();
+
+    if names.is_empty() {
+        eprintln!("No toolchains installed. Run 'cargo risczero install' to install one.");
+    }
+    for name in names {
+        let path = toolchain_dir.join(&name);
+        let marker = if active.as_deref() == Some(path.as_path()) {
+            " (active)"
+        } else {
+            ""
+        };
+        println!("{name}{marker}");
+    }
+
+    Ok(())
+}
+
+/// Link a downloaded rust toolchain to rustup.
+fn use_toolchain(toolchain_dir: &Path, version: &str) -> Result<()> {
+    let target = guess_host_target()
+        .context("The risc0 toolchain is not available for download on this platform")?;
+    let repo = ToolchainRepo::Rust;
+    let name = if version.starts_with(&format!("{}_", repo.language())) {
+        version.to_string()
+    } else {
+        format!("{}_{target}_{version}", repo.language())
+    };
+
+    let path = toolchain_dir.join(&name);
+    if !repo.is_complete(&path, target) {
+        bail!(
+            "Toolchain {version} is not installed. Run 'cargo risczero install --list' to \
+            see installed toolchains or 'cargo risczero install --version {version}' to \
+            download it."
+        );
+    }
+
+    RustupToolchain::link(RUSTUP_TOOLCHAIN_NAME, &path)?;
+    eprintln!("The risc0 toolchain now uses {name}.");
+
+    Ok(())
+}
+
 /// Try to get the host target triple.
 ///
 /// Only checks for targets that have pre-built toolchains.
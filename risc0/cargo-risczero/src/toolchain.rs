@@ -56,6 +56,22 @@ impl ToolchainRepo {
             },
         }
     }
+
+    /// Returns true if `dir` contains a fully extracted toolchain for `target`.
+    pub fn is_complete(&self, dir: &Path, target: &str) -> bool {
+        match self {
+            Self::Rust => {
+                #[cfg(not(target_os = "windows"))]
+                let rustc_exe = "rustc";
+                #[cfg(target_os = "windows")]
+                let rustc_exe = "rustc.exe";
+
+                dir.join("bin").join(rustc_exe).is_file()
+                    && dir.join(format!("lib/rustlib/{target}/bin")).is_dir()
+            }
+            Self::Cpp => CppToolchain::get_subdir(dir).is_ok(),
+        }
+    }
 }
 
 /// Branch to use in the custom Rust repo.
@@ -78,7 +94,7 @@ impl RustupToolchain {
     /// Verify if the toolchain is present in rustup.
     ///
     /// Returns the path to the toolchain.
-    fn find_by_name(name: &str) -> Result<Option<Self>, anyhow::Error> {
+    pub fn find_by_name(name: &str) -> Result<Option<Self>, anyhow::Error> {
         let out = Command::new("rustup")
             .args(["toolchain", "list", "--verbose"])
             .capture_stdout()?;
@@ -23,8 +23,8 @@ use risc0_zkp::{
     },
     hal::{
         cuda::{
-            BufferImpl as CudaBuffer, CudaHal, CudaHash, CudaHashPoseidon, CudaHashPoseidon2,
-            CudaHashSha256,
+            synchronize_kernels, with_kernel_stream, BufferImpl as CudaBuffer, CudaHal, CudaHash,
+            CudaHashPoseidon, CudaHashPoseidon2, CudaHashSha256,
         },
         Buffer, CircuitHal,
     },
@@ -94,6 +94,9 @@ impl<'a, CH: CudaHash> CircuitHal<CudaHal<CH>> for CudaCircuitHal<CH> {
                 .try_into()
                 .unwrap();
 
+        // The global is written synchronously and may still be read by the
+        // previous launch.
+        synchronize_kernels();
         let mix_pows_name = std::ffi::CString::new("poly_mix").unwrap();
         self.module
             .get_global(&mix_pows_name)
@@ -101,11 +104,9 @@ impl<'a, CH: CudaHash> CircuitHal<CudaHal<CH>> for CudaCircuitHal<CH> {
             .copy_from(poly_mix_pows)
             .unwrap();
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
-
         let kernel = self.module.get_function("eval_check").unwrap();
         let params = self.hal.compute_simple_params(domain);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 check.as_device_ptr(),
                 code.as_device_ptr(),
@@ -118,8 +119,7 @@ impl<'a, CH: CudaHash> CircuitHal<CudaHal<CH>> for CudaCircuitHal<CH> {
                 size.as_device_ptr()
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 }
 
@@ -15,7 +15,14 @@
 #[cfg(test)]
 mod tests;
 
-use std::{array, cell::RefCell, collections::BTreeSet, mem, rc::Rc};
+use std::{
+    array,
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    mem,
+    ops::ControlFlow,
+    rc::Rc,
+};
 
 use anyhow::{bail, ensure, Result};
 use crypto_bigint::{CheckedMul as _, Encoding as _, NonZero, U256, U512};
@@ -38,6 +45,7 @@ use risc0_zkvm_platform::{
     },
     PAGE_SIZE, WORD_SIZE,
 };
+use serde::{Deserialize, Serialize};
 use sha2::digest::generic_array::GenericArray;
 
 use super::{
@@ -55,6 +63,8 @@ use crate::{
     trace::{TraceCallback, TraceEvent},
 };
 
+pub use super::pager::PAGE_CYCLES;
+
 pub const DEFAULT_SEGMENT_LIMIT_PO2: usize = 20;
 
 /// A host-side implementation of a system call.
@@ -91,6 +101,23 @@ pub trait SyscallContext {
     /// Returns the current cycle count.
     // TODO(breaking change): use `u64`
     fn get_cycle(&self) -> usize;
+
+    /// Returns the number of instruction cycles executed in the current
+    /// segment.
+    ///
+    /// The default implementation returns 0, for contexts that do not track
+    /// segments.
+    fn get_segment_cycle(&self) -> usize {
+        0
+    }
+
+    /// Returns the program counter of the instruction being executed.
+    ///
+    /// The default implementation returns 0, for contexts that do not track
+    /// the program counter.
+    fn get_pc(&self) -> u32 {
+        0
+    }
 }
 
 pub struct ExecutorResult {
@@ -102,6 +129,74 @@ pub struct ExecutorResult {
     pub pre_state: SystemState,
     pub post_state: SystemState,
     pub output_digest: Option<Digest>,
+    pub guest_pages: usize,
+}
+
+/// The error returned when the guest touches more memory pages than allowed by
+/// [Executor::with_memory_limit].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryLimitExceeded {
+    /// The maximum number of guest pages the session may touch.
+    pub limit: usize,
+
+    /// The number of guest pages touched when execution stopped.
+    pub pages: usize,
+}
+
+impl std::fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Memory limit exceeded: guest touched {} pages, limit is {}",
+            self.pages, self.limit
+        )
+    }
+}
+
+impl std::error::Error for MemoryLimitExceeded {}
+
+/// How far a session got before execution stopped at a segment split.
+///
+/// Passing this to [Executor::with_progress], together with the post image of
+/// the stopped run, continues the session as if it had not stopped.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExecutorProgress {
+    /// The number of segments produced so far.
+    pub segments: usize,
+
+    /// The number of user cycles executed so far.
+    pub user_cycles: u64,
+
+    /// The number of total cycles executed so far.
+    pub total_cycles: u64,
+
+    /// The system state at the start of the session.
+    pub initial_state: SystemState,
+
+    /// The host response to a syscall made by the instruction that caused the
+    /// split. The instruction is replayed in the next segment, so the response
+    /// must be replayed with it instead of calling the host again.
+    pub pending_syscall: Option<SyscallRecord>,
+
+    /// The guest pages touched so far.
+    #[serde(default)]
+    pub guest_pages: BTreeSet<u32>,
+}
+
+/// The state of the guest at some point during execution.
+pub struct ExecutorSnapshot {
+    /// The program counter.
+    pub pc: u32,
+
+    /// The number of user cycles executed so far.
+    pub cycle: u64,
+
+    /// The general purpose registers.
+    pub registers: [u32; REG_MAX],
+
+    /// The guest memory pages touched in the current segment, keyed by page
+    /// index.
+    pub pages: BTreeMap<u32, Vec<u8>>,
 }
 
 #[derive(Default)]
@@ -138,6 +233,9 @@ pub struct Executor<'a, 'b, S: Syscall> {
     pending: PendingState,
     trace: Vec<Rc<RefCell<dyn TraceCallback + 'b>>>,
     cycles: SessionCycles,
+    progress: Option<ExecutorProgress>,
+    split_points: Vec<u64>,
+    memory_limit: Option<usize>,
 }
 
 impl PendingState {
@@ -178,14 +276,64 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             },
             trace,
             cycles: SessionCycles::default(),
+            progress: None,
+            split_points: Vec::new(),
+            memory_limit: None,
         }
     }
 
+    /// Continue a session that an earlier [Executor] stopped at a segment
+    /// split. The image passed to [Executor::new] must be the post image of
+    /// that run.
+    pub fn with_progress(mut self, progress: ExecutorProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// End segments early so that they stop before these user cycle counts, in
+    /// addition to splitting at the segment limit. Points must be sorted in
+    /// ascending order.
+    pub fn with_split_points(mut self, split_points: Vec<u64>) -> Self {
+        self.split_points = split_points;
+        self
+    }
+
+    /// Stop with a [MemoryLimitExceeded] error if the session touches more than
+    /// `max_pages` distinct pages of guest memory.
+    pub fn with_memory_limit(mut self, max_pages: usize) -> Self {
+        self.memory_limit = Some(max_pages);
+        self
+    }
+
+    /// Returns the progress of the session if the last call to
+    /// [Executor::run_until] stopped at a segment split.
+    pub fn take_progress(&mut self) -> Option<ExecutorProgress> {
+        self.progress.take()
+    }
+
     pub fn run<F: FnMut(Segment) -> Result<()>>(
         &mut self,
         segment_po2: usize,
         max_cycles: Option<u64>,
         mut callback: F,
+    ) -> Result<ExecutorResult> {
+        self.run_until(segment_po2, max_cycles, |segment| {
+            callback(segment)?;
+            Ok(ControlFlow::Continue(()))
+        })
+    }
+
+    /// Run the guest, allowing `callback` to stop execution early.
+    ///
+    /// If `callback` returns [ControlFlow::Break] for a segment ending in a
+    /// system split, execution stops at that split and the result has an exit
+    /// code of [ExitCode::Aborted]. The value returned for the final segment is
+    /// ignored.
+    pub fn run_until<F: FnMut(Segment) -> Result<ControlFlow<()>>>(
+        &mut self,
+        segment_po2: usize,
+        max_cycles: Option<u64>,
+        mut callback: F,
     ) -> Result<ExecutorResult> {
         // at least one HaltCycle needs to appear in the body
         const MIN_HALT_CYCLES: usize = 1;
@@ -199,8 +347,19 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         self.reset();
 
         let mut emu = Emulator::new();
-        let mut segments = 0;
-        let initial_state = self.pager.image.get_system_state();
+        let (mut segments, initial_state) = match self.progress.take() {
+            Some(progress) => {
+                self.cycles.user = progress.user_cycles.try_into()?;
+                self.cycles.total = progress.total_cycles.try_into()?;
+                self.pending.syscall = progress.pending_syscall;
+                self.pager.guest_pages = progress.guest_pages;
+                (progress.segments, progress.initial_state)
+            }
+            None => (0, self.pager.image.get_system_state()),
+        };
+        let mut next_split = self
+            .split_points
+            .partition_point(|&at| at <= self.cycles.user as u64);
 
         loop {
             if self.exit_code.is_some() {
@@ -215,8 +374,20 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
 
             emu.step(self)?;
 
+            if let Some(limit) = self.memory_limit {
+                let pages = self.pager.guest_pages.len();
+                if pages > limit {
+                    return Err(MemoryLimitExceeded { limit, pages }.into());
+                }
+            }
+
             let segment_cycles = self.insn_cycles + self.pager.cycles + self.pending.cycles;
-            if segment_cycles < segment_limit {
+            let planned_split = self.insn_cycles > 0
+                && self
+                    .split_points
+                    .get(next_split)
+                    .is_some_and(|&at| ((self.cycles.user + self.pending.cycles) as u64) > at);
+            if segment_cycles < segment_limit && !planned_split {
                 self.advance()?;
             } else if self.insn_cycles == 0 {
                 bail!(
@@ -226,7 +397,16 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             } else {
                 self.pager.undo();
                 let used_cycles = self.insn_cycles + self.pager.cycles + RESERVED_CYCLES;
-                let waste = (1 << segment_po2) - used_cycles;
+                // A planned split may end a segment well below the limit, so
+                // only pad it to the next power of two.
+                let po2 = if planned_split {
+                    log2_ceil(used_cycles.next_power_of_two()).min(segment_po2)
+                } else {
+                    segment_po2
+                };
+                let reached = (self.cycles.user + self.pending.cycles) as u64;
+                next_split = self.split_points.partition_point(|&at| at < reached);
+                let waste = (1 << po2) - used_cycles;
                 tracing::debug!(
                     "split: {} + {} + {RESERVED_CYCLES} = {used_cycles}, waste: {waste}, pending: {:?}",
                     self.insn_cycles,
@@ -236,26 +416,50 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
 
                 // split
                 let (pre_state, partial_image, post_state) = self.pager.commit(self.pc);
-                callback(Segment {
+                let split_state = post_state.clone();
+                let flow = callback(Segment {
                     partial_image,
                     pre_state,
                     post_state,
                     syscalls: mem::take(&mut self.syscalls),
                     insn_cycles: self.insn_cycles,
-                    po2: segment_po2,
+                    po2,
                     exit_code: ExitCode::SystemSplit,
                     index: segments,
                     input_digest: self.input_digest,
                     output_digest: self.output_digest,
                 })?;
                 segments += 1;
-                self.cycles.total += 1 << segment_po2;
+                self.cycles.total += 1 << po2;
                 self.pager.clear();
                 self.insn_cycles = 0;
 
                 // replay the current instruction in a new segment
                 self.pending.pc = self.pc;
                 self.pending.cycles = 0;
+
+                if flow.is_break() {
+                    tracing::debug!("aborted after segment {}", segments - 1);
+                    self.progress = Some(ExecutorProgress {
+                        segments,
+                        user_cycles: self.cycles.user.try_into()?,
+                        total_cycles: self.cycles.total.try_into()?,
+                        initial_state: initial_state.clone(),
+                        pending_syscall: self.pending.syscall.clone(),
+                        guest_pages: self.pager.guest_pages.clone(),
+                    });
+                    return Ok(ExecutorResult {
+                        segments,
+                        exit_code: ExitCode::Aborted,
+                        post_image: self.pager.image.clone(),
+                        user_cycles: self.cycles.user.try_into()?,
+                        total_cycles: self.cycles.total.try_into()?,
+                        pre_state: initial_state,
+                        post_state: split_state,
+                        output_digest: self.output_digest,
+                        guest_pages: self.pager.guest_pages.len(),
+                    });
+                }
             }
         }
 
@@ -264,7 +468,8 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         let po2 = log2_ceil(segment_cycles.next_power_of_two());
         let exit_code = self.exit_code.unwrap();
 
-        callback(Segment {
+        // execution is already complete, so there is nothing left to abort
+        let _ = callback(Segment {
             partial_image,
             pre_state: pre_state.clone(),
             post_state: post_state.clone(),
@@ -297,6 +502,31 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             pre_state: initial_state,
             post_state,
             output_digest: self.output_digest,
+            guest_pages: self.pager.guest_pages.len(),
+        })
+    }
+
+    /// Capture the registers and touched pages of the guest.
+    ///
+    /// This is intended for post-mortem analysis after [Executor::run] fails,
+    /// in which case the snapshot reflects the state at the faulting
+    /// instruction.
+    pub fn snapshot(&self) -> Result<ExecutorSnapshot> {
+        let mut registers = [0; REG_MAX];
+        for (idx, reg) in registers.iter_mut().enumerate() {
+            *reg = self.pager.peek(SYSTEM_START + idx)?;
+        }
+        let pages = self
+            .pager
+            .touched_pages()
+            .filter(|(page_idx, _)| is_guest_memory(page_idx * PAGE_SIZE as u32))
+            .map(|(page_idx, page)| (page_idx, page.to_vec()))
+            .collect();
+        Ok(ExecutorSnapshot {
+            pc: self.pc.0,
+            cycle: self.cycles.user.try_into()?,
+            registers,
+            pages,
         })
     }
 
@@ -313,6 +543,24 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             for event in &self.pending.events {
                 trace.borrow_mut().trace_callback(event.clone()).unwrap();
             }
+
+            for (page_idx, cycles, dirty) in self.pager.pending_pages() {
+                let cycle = self.cycles.user.try_into()?;
+                let cycles = cycles.try_into()?;
+                trace.borrow_mut().trace_callback(if dirty {
+                    TraceEvent::PageOut {
+                        cycle,
+                        page_idx,
+                        cycles,
+                    }
+                } else {
+                    TraceEvent::PageIn {
+                        cycle,
+                        page_idx,
+                        cycles,
+                    }
+                })?;
+            }
         }
 
         self.pc = self.pending.pc;
@@ -640,7 +888,27 @@ impl<'a, 'b, S: Syscall> EmuContext for Executor<'a, 'b, S> {
         unimplemented!()
     }
 
-    fn trap(&self, cause: TrapCause) -> Result<bool> {
+    fn trap(&mut self, cause: TrapCause) -> Result<bool> {
+        let fault = match cause {
+            TrapCause::LoadAccessFault(addr) => Some((addr, false)),
+            TrapCause::StoreAccessFault(addr) => Some((addr, true)),
+            _ => None,
+        };
+        if let Some((addr, store)) = fault {
+            // The instruction never completes, so report the fault directly
+            // rather than queueing it with the pending events.
+            for trace in &self.trace {
+                trace
+                    .borrow_mut()
+                    .trace_callback(TraceEvent::MemoryAccessFault {
+                        cycle: self.cycles.user.try_into()?,
+                        pc: self.pc.0,
+                        addr: addr.0,
+                        store,
+                    })?;
+            }
+        }
+
         let msg = format!("Trap: {cause:08x?}, pc: {:?}", self.pc);
         tracing::info!("{msg}");
         bail!("{msg}");
@@ -716,6 +984,14 @@ impl<'a, 'b, S: Syscall> SyscallContext for Executor<'a, 'b, S> {
         self.cycles.user
     }
 
+    fn get_segment_cycle(&self) -> usize {
+        self.insn_cycles
+    }
+
+    fn get_pc(&self) -> u32 {
+        self.pc.0
+    }
+
     fn peek_register(&mut self, idx: usize) -> Result<u32> {
         if idx >= REG_MAX {
             bail!("invalid register: x{idx}");
@@ -20,7 +20,7 @@ use std::{
 use anyhow::Result;
 use risc0_binfmt::{MemoryImage, SystemState};
 use risc0_zkp::core::hash::sha::BLOCK_BYTES;
-use risc0_zkvm_platform::{PAGE_SIZE, WORD_SIZE};
+use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE, WORD_SIZE};
 
 use super::addr::{ByteAddr, WordAddr};
 
@@ -37,6 +37,9 @@ const fn cycles_per_page(blocks_per_page: usize) -> usize {
     1 + SHA_INIT + (SHA_LOAD + SHA_MAIN) * blocks_per_page
 }
 
+/// The number of cycles needed to page in or page out a single page.
+pub const PAGE_CYCLES: usize = cycles_per_page(BLOCKS_PER_PAGE);
+
 struct Page(Vec<u8>);
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -64,6 +67,10 @@ pub struct PagedMemory {
     page_states: BTreeMap<u32, PageState>,
     pub cycles: usize,
     pending_actions: Vec<Action>,
+    /// The guest pages loaded since this [PagedMemory] was created. Unlike the
+    /// page states, this is not reset by [PagedMemory::clear], so it covers
+    /// every segment.
+    pub guest_pages: BTreeSet<u32>,
 }
 
 impl WordAddr {
@@ -80,6 +87,7 @@ impl PagedMemory {
             page_states: BTreeMap::new(),
             cycles: 0,
             pending_actions: Vec::new(),
+            guest_pages: BTreeSet::new(),
         }
     }
 
@@ -196,6 +204,18 @@ impl PagedMemory {
         }
     }
 
+    /// Returns the pages paged in or dirtied by the current step, as
+    /// `(page_idx, cycles, dirty)`.
+    pub fn pending_pages(&self) -> impl Iterator<Item = (u32, usize, bool)> + '_ {
+        self.pending_actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::PageRead(page_idx, cycles) => Some((*page_idx, *cycles, false)),
+                Action::PageWrite(page_idx, cycles, _) => Some((*page_idx, *cycles, true)),
+                Action::Store(..) => None,
+            })
+    }
+
     pub fn commit_step(&mut self) {
         self.pending_actions.clear();
     }
@@ -207,6 +227,14 @@ impl PagedMemory {
         self.cycles = 0;
     }
 
+    /// Returns the pages loaded since the last [PagedMemory::clear], including
+    /// any changes made to them.
+    pub fn touched_pages(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        self.page_cache
+            .iter()
+            .map(|(page_idx, page)| (*page_idx, page.0.as_slice()))
+    }
+
     pub fn get_faults(&self) -> PageFaults {
         let mut faults = PageFaults::default();
         for (page_idx, page_state) in &self.page_states {
@@ -222,6 +250,9 @@ impl PagedMemory {
         tracing::trace!("load_page: 0x{page_idx:05x}");
         let page = self.image.load_page(page_idx);
         self.page_cache.insert(page_idx, Page(page));
+        if (page_idx as usize) < GUEST_MAX_MEM / PAGE_SIZE {
+            self.guest_pages.insert(page_idx);
+        }
         self.update(page_idx, PageState::Loaded);
         self.page_changed(page_idx, PageState::Loaded);
     }
@@ -254,7 +285,7 @@ impl PagedMemory {
             let num_root_entries = info.num_root_entries as usize;
             cycles_per_page(num_root_entries / 2)
         } else {
-            cycles_per_page(BLOCKS_PER_PAGE)
+            PAGE_CYCLES
         };
 
         tracing::trace!("page_changed(0x{page_idx:05x}, {state:?}) <= {page_cycles}");
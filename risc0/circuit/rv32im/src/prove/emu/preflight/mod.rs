@@ -734,7 +734,7 @@ impl EmuContext for Preflight {
         unimplemented!()
     }
 
-    fn trap(&self, cause: TrapCause) -> Result<bool> {
+    fn trap(&mut self, cause: TrapCause) -> Result<bool> {
         bail!("Trap: {cause:08x?}");
     }
 
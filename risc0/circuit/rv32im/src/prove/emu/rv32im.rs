@@ -25,7 +25,7 @@ pub trait EmuContext {
     fn mret(&self) -> Result<bool>;
 
     // Handle a trap
-    fn trap(&self, cause: TrapCause) -> Result<bool>;
+    fn trap(&mut self, cause: TrapCause) -> Result<bool>;
 
     // Callback when instructions are decoded
     fn on_insn_decoded(&self, kind: &Instruction, decoded: &DecodedInstruction);
@@ -79,9 +79,9 @@ pub enum TrapCause {
     IllegalInstruction(u32),
     Breakpoint,
     LoadAddressMisaligned,
-    LoadAccessFault,
+    LoadAccessFault(ByteAddr),
     StoreAddressMisaligned(ByteAddr),
-    StoreAccessFault,
+    StoreAccessFault(ByteAddr),
     EnvironmentCallFromUserMode,
 }
 
@@ -357,6 +357,14 @@ impl Emulator {
         }
     }
 
+    /// Returns the kind of the instruction encoded by `word`.
+    pub fn decode_kind(&self, word: u32) -> InsnKind {
+        if word & 0x03 != 0x03 {
+            return InsnKind::INVALID;
+        }
+        self.table.lookup(&DecodedInstruction::new(word)).kind
+    }
+
     pub fn step<C: EmuContext>(&mut self, ctx: &mut C) -> Result<()> {
         let pc = ctx.get_pc();
 
@@ -521,7 +529,7 @@ impl Emulator {
         let _rs2 = ctx.load_register(decoded.rs2 as usize)?;
         let addr = ByteAddr(rs1.wrapping_add(decoded.imm_i()));
         if !ctx.check_data_load(addr) {
-            return ctx.trap(TrapCause::LoadAccessFault);
+            return ctx.trap(TrapCause::LoadAccessFault(addr));
         }
         let data = ctx.load_memory(addr.waddr())?;
         let shift = 8 * (addr.0 & 3);
@@ -574,7 +582,7 @@ impl Emulator {
         let addr = ByteAddr(rs1.wrapping_add(decoded.imm_s()));
         let shift = 8 * (addr.0 & 3);
         if !ctx.check_data_store(addr) {
-            return ctx.trap(TrapCause::StoreAccessFault);
+            return ctx.trap(TrapCause::StoreAccessFault(addr));
         }
         let mut data = ctx.load_memory(addr.waddr())?;
         match kind {
@@ -16,9 +16,9 @@ use anyhow::Result;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkp::{
     core::{digest::Digest, hash::sha::Sha256HashSuite},
-    field::baby_bear::BabyBearElem,
+    field::{baby_bear::BabyBearElem, Elem},
     hal::{cpu::CpuHal, Hal},
-    verify::VerificationError,
+    verify::{MerkleTree, VerificationError},
 };
 use risc0_zkvm_platform::PAGE_SIZE;
 use test_log::test;
@@ -108,6 +108,50 @@ fn basic() {
     risc0_zkp::verify::verify(&CIRCUIT, &suite, &seal, |x, y| checker.check_ctrl(x, y)).unwrap();
 }
 
+#[test]
+fn merkle_openings() {
+    let program = testutil::basic();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let result = execute(
+        image,
+        DEFAULT_SEGMENT_LIMIT_PO2,
+        DEFAULT_SESSION_LIMIT,
+        &NullSyscall::default(),
+        None,
+    )
+    .unwrap();
+    let segment = result.segments.first().unwrap();
+
+    let prover = get_segment_prover();
+    let seal = prover.prove_segment(&segment).unwrap();
+
+    let suite = Sha256HashSuite::new_suite();
+    let hal = CpuHal::new(suite.clone());
+    let checker = ControlCheck::new(&hal, segment.po2);
+    let openings = risc0_zkp::verify::verify_with_openings(&CIRCUIT, &suite, &seal, |x, y| {
+        checker.check_ctrl(x, y)
+    })
+    .unwrap();
+
+    let code_openings = openings
+        .iter()
+        .filter(|opening| opening.tree == MerkleTree::Code)
+        .count();
+    assert_eq!(code_openings, risc0_zkp::QUERIES);
+
+    for opening in openings.iter() {
+        opening.verify(suite.hashfn.as_ref()).unwrap();
+    }
+
+    let mut tampered = openings[0].clone();
+    tampered.values[0] += BabyBearElem::ONE;
+    assert_eq!(
+        tampered.verify(suite.hashfn.as_ref()),
+        Err(VerificationError::InvalidProof)
+    );
+}
+
 #[test]
 fn system_split() {
     let program = testutil::simple_loop();
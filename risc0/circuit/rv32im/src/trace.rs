@@ -15,12 +15,14 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 /// An event traced from the running VM.
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum TraceEvent {
     /// An instruction has started at the given program counter
     InstructionStart {
@@ -48,6 +50,40 @@ pub enum TraceEvent {
         /// Data that's been written
         region: Vec<u8>,
     },
+
+    /// A load or store instruction faulted because it accessed memory outside
+    /// of the guest address space, such as the null page or system memory
+    MemoryAccessFault {
+        /// Cycle number since startup
+        cycle: u32,
+        /// Program counter of the faulting instruction
+        pc: u32,
+        /// Address the instruction tried to access
+        addr: u32,
+        /// Whether the access was a store, rather than a load
+        store: bool,
+    },
+
+    /// A page was paged in by the instruction that started at the given cycle
+    PageIn {
+        /// Cycle number since startup
+        cycle: u32,
+        /// Index of the page that was paged in
+        page_idx: u32,
+        /// Number of paging cycles charged to the segment
+        cycles: u32,
+    },
+
+    /// A page was dirtied by the instruction that started at the given cycle,
+    /// so it will be paged out at the end of the segment
+    PageOut {
+        /// Cycle number since startup
+        cycle: u32,
+        /// Index of the page that will be paged out
+        page_idx: u32,
+        /// Number of paging cycles charged to the segment
+        cycles: u32,
+    },
 }
 
 /// A callback used to collect [TraceEvent]s.
@@ -61,6 +97,160 @@ impl<F: FnMut(TraceEvent) -> Result<()>> TraceCallback for F {
     }
 }
 
+/// The kind of a [TraceEvent], without its payload.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum TraceEventKind {
+    /// [TraceEvent::InstructionStart]
+    InstructionStart,
+    /// [TraceEvent::RegisterSet]
+    RegisterSet,
+    /// [TraceEvent::MemorySet]
+    MemorySet,
+    /// [TraceEvent::MemoryAccessFault]
+    MemoryAccessFault,
+    /// [TraceEvent::PageIn]
+    PageIn,
+    /// [TraceEvent::PageOut]
+    PageOut,
+}
+
+impl TraceEvent {
+    /// Returns the kind of this event.
+    pub fn kind(&self) -> TraceEventKind {
+        match self {
+            Self::InstructionStart { .. } => TraceEventKind::InstructionStart,
+            Self::RegisterSet { .. } => TraceEventKind::RegisterSet,
+            Self::MemorySet { .. } => TraceEventKind::MemorySet,
+            Self::MemoryAccessFault { .. } => TraceEventKind::MemoryAccessFault,
+            Self::PageIn { .. } => TraceEventKind::PageIn,
+            Self::PageOut { .. } => TraceEventKind::PageOut,
+        }
+    }
+}
+
+/// Selects the [TraceEvent]s passed on to a [TraceCallback].
+///
+/// Tracing every instruction of a large guest produces far more events than
+/// are useful when debugging one function or one buffer. A filter drops events
+/// before they reach the callback:
+///
+/// * [TraceFilter::events] keeps only events of the given kinds.
+/// * [TraceFilter::pc_range] keeps only events caused by instructions whose
+///   program counter is in the range. Register and memory writes are
+///   attributed to the instruction that started before them.
+/// * [TraceFilter::memory_range] keeps only memory writes and faults that
+///   touch the range.
+///
+/// Page events are only subject to [TraceFilter::events]. The default filter
+/// passes every event.
+#[derive(Clone, Debug, Default)]
+pub struct TraceFilter {
+    kinds: Option<Vec<TraceEventKind>>,
+    pc_range: Option<Range<u32>>,
+    memory_range: Option<Range<u32>>,
+}
+
+impl TraceFilter {
+    /// Construct a filter that passes every event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only pass events of the given kinds.
+    pub fn events(mut self, kinds: impl IntoIterator<Item = TraceEventKind>) -> Self {
+        self.kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Only pass events caused by instructions with a program counter in
+    /// `range`.
+    pub fn pc_range(mut self, range: Range<u32>) -> Self {
+        self.pc_range = Some(range);
+        self
+    }
+
+    /// Only pass memory writes and faults that touch addresses in `range`.
+    pub fn memory_range(mut self, range: Range<u32>) -> Self {
+        self.memory_range = Some(range);
+        self
+    }
+
+    /// Wrap `callback` so that it only receives the events passed by this
+    /// filter.
+    pub fn apply<C: TraceCallback>(self, callback: C) -> FilteredTrace<C> {
+        FilteredTrace {
+            filter: self,
+            callback,
+            pc: None,
+        }
+    }
+
+    fn pc_matches(&self, pc: Option<u32>) -> bool {
+        match (&self.pc_range, pc) {
+            (None, _) => true,
+            (Some(range), Some(pc)) => range.contains(&pc),
+            (Some(_), None) => false,
+        }
+    }
+
+    fn memory_matches(&self, addr: u32, len: usize) -> bool {
+        let Some(ref range) = self.memory_range else {
+            return true;
+        };
+        let end = addr.saturating_add(len.max(1) as u32);
+        addr < range.end && range.start < end
+    }
+
+    fn matches(&self, event: &TraceEvent, pc: Option<u32>) -> bool {
+        if let Some(ref kinds) = self.kinds {
+            if !kinds.contains(&event.kind()) {
+                return false;
+            }
+        }
+        match event {
+            TraceEvent::InstructionStart { .. } | TraceEvent::RegisterSet { .. } => {
+                self.pc_matches(pc)
+            }
+            TraceEvent::MemorySet { addr, region } => {
+                self.pc_matches(pc) && self.memory_matches(*addr, region.len())
+            }
+            TraceEvent::MemoryAccessFault { pc, addr, .. } => {
+                self.pc_matches(Some(*pc)) && self.memory_matches(*addr, 1)
+            }
+            TraceEvent::PageIn { .. } | TraceEvent::PageOut { .. } => true,
+        }
+    }
+}
+
+/// A [TraceCallback] that only receives the events passed by a [TraceFilter].
+///
+/// Constructed with [TraceFilter::apply].
+pub struct FilteredTrace<C> {
+    filter: TraceFilter,
+    callback: C,
+    pc: Option<u32>,
+}
+
+impl<C> FilteredTrace<C> {
+    /// Returns the wrapped callback.
+    pub fn into_inner(self) -> C {
+        self.callback
+    }
+}
+
+impl<C: TraceCallback> TraceCallback for FilteredTrace<C> {
+    fn trace_callback(&mut self, event: TraceEvent) -> Result<()> {
+        if let TraceEvent::InstructionStart { pc, .. } = event {
+            self.pc = Some(pc);
+        }
+        if self.filter.matches(&event, self.pc) {
+            self.callback.trace_callback(event)?;
+        }
+        Ok(())
+    }
+}
+
 impl core::fmt::Debug for TraceEvent {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -71,6 +261,103 @@ impl core::fmt::Debug for TraceEvent {
             Self::MemorySet { addr, region } => {
                 write!(f, "MemorySet(0x{addr:08X}, {region:#04X?})")
             }
+            Self::MemoryAccessFault {
+                cycle,
+                pc,
+                addr,
+                store,
+            } => {
+                let kind = if *store { "store" } else { "load" };
+                write!(
+                    f,
+                    "MemoryAccessFault({cycle}, 0x{pc:08X}, 0x{addr:08X}, {kind})"
+                )
+            }
+            Self::PageIn {
+                cycle,
+                page_idx,
+                cycles,
+            } => write!(f, "PageIn({cycle}, 0x{page_idx:05X}, {cycles})"),
+            Self::PageOut {
+                cycle,
+                page_idx,
+                cycles,
+            } => write!(f, "PageOut({cycle}, 0x{page_idx:05X}, {cycles})"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{TraceCallback, TraceEvent, TraceEventKind, TraceFilter};
+
+    fn events() -> Vec<TraceEvent> {
+        vec![
+            TraceEvent::InstructionStart {
+                cycle: 0,
+                pc: 0x1000,
+                insn: 0,
+            },
+            TraceEvent::MemorySet {
+                addr: 0x8000,
+                region: vec![0; 4],
+            },
+            TraceEvent::InstructionStart {
+                cycle: 1,
+                pc: 0x2000,
+                insn: 0,
+            },
+            TraceEvent::RegisterSet { idx: 5, value: 1 },
+            TraceEvent::MemorySet {
+                addr: 0x9000,
+                region: vec![0; 4],
+            },
+            TraceEvent::PageIn {
+                cycle: 1,
+                page_idx: 3,
+                cycles: 1130,
+            },
+        ]
+    }
+
+    fn run(filter: TraceFilter) -> Vec<TraceEvent> {
+        let mut seen = Vec::new();
+        let mut trace = filter.apply(|event| {
+            seen.push(event);
+            anyhow::Ok(())
+        });
+        for event in events() {
+            trace.trace_callback(event).unwrap();
+        }
+        drop(trace);
+        seen
+    }
+
+    #[test]
+    fn filter() {
+        assert_eq!(run(TraceFilter::new()), events());
+
+        let all = events();
+        assert_eq!(
+            run(TraceFilter::new().pc_range(0x2000..0x2004)),
+            vec![
+                all[2].clone(),
+                all[3].clone(),
+                all[4].clone(),
+                all[5].clone()
+            ]
+        );
+        assert_eq!(
+            run(TraceFilter::new().events([TraceEventKind::MemorySet])),
+            vec![all[1].clone(), all[4].clone()]
+        );
+        assert_eq!(
+            run(TraceFilter::new()
+                .events([TraceEventKind::MemorySet])
+                .memory_range(0x8002..0x8100)),
+            vec![all[1].clone()]
+        );
+    }
+}
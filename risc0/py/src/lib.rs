@@ -0,0 +1,151 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Python bindings for the RISC Zero zkVM.
+//!
+//! This crate builds the `risc0` Python module with [pyo3]. It exposes
+//! functions to execute and prove guests and to verify receipts, mirroring the
+//! C API in `risc0-capi`. Receipts are encoded with bincode.
+//!
+//! The GIL is released while a guest runs, so other Python threads can make
+//! progress during a long proof. Errors raised while executing or proving a
+//! guest are reported as `RuntimeError`, and invalid receipts as `ValueError`.
+
+use anyhow::{anyhow, Result};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use risc0_zkvm::{
+    sha::{Digest, DIGEST_BYTES},
+    Executor, ExecutorEnv, LocalProver, Prover, Receipt,
+};
+
+fn build_env(input: &[u8]) -> Result<ExecutorEnv<'static>> {
+    ExecutorEnv::builder().write_slice(input).build()
+}
+
+fn execute_impl(elf: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+    let session = LocalProver::new("local").execute(build_env(input)?, elf)?;
+    Ok(session.journal.bytes)
+}
+
+fn prove_impl(elf: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+    let receipt = LocalProver::new("local")
+        .prove(build_env(input)?, elf)?
+        .receipt;
+    Ok(bincode::serialize(&receipt)?)
+}
+
+fn verify_impl(receipt: &[u8], image_id: &[u8]) -> Result<Vec<u8>> {
+    let receipt: Receipt = bincode::deserialize(receipt)?;
+    let image_id = Digest::try_from(image_id).map_err(|_| {
+        anyhow!(
+            "image ID must be {DIGEST_BYTES} bytes, got {}",
+            image_id.len()
+        )
+    })?;
+    receipt.verify(image_id)?;
+    Ok(receipt.journal.bytes)
+}
+
+fn runtime_error(err: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{err:#}"))
+}
+
+/// Execute a guest without proving it, returning its journal.
+///
+/// `input` is written to the guest's stdin as-is, so it must already be
+/// encoded in the format the guest reads.
+#[pyfunction]
+fn execute(py: Python<'_>, elf: &[u8], input: &[u8]) -> PyResult<Py<PyBytes>> {
+    let journal = py
+        .allow_threads(|| execute_impl(elf, input))
+        .map_err(runtime_error)?;
+    Ok(PyBytes::new_bound(py, &journal).unbind())
+}
+
+/// Prove a guest, returning its receipt encoded with bincode.
+///
+/// `input` is handled as in `execute`.
+#[pyfunction]
+fn prove(py: Python<'_>, elf: &[u8], input: &[u8]) -> PyResult<Py<PyBytes>> {
+    let receipt = py
+        .allow_threads(|| prove_impl(elf, input))
+        .map_err(runtime_error)?;
+    Ok(PyBytes::new_bound(py, &receipt).unbind())
+}
+
+/// Verify a bincode-encoded receipt against a 32-byte image ID, returning
+/// the journal of the receipt.
+#[pyfunction]
+fn verify(py: Python<'_>, receipt: &[u8], image_id: &[u8]) -> PyResult<Py<PyBytes>> {
+    let journal = py
+        .allow_threads(|| verify_impl(receipt, image_id))
+        .map_err(|err| PyValueError::new_err(format!("{err:#}")))?;
+    Ok(PyBytes::new_bound(py, &journal).unbind())
+}
+
+/// Compute the 32-byte image ID of a guest ELF.
+#[pyfunction]
+fn compute_image_id(py: Python<'_>, elf: &[u8]) -> PyResult<Py<PyBytes>> {
+    let image_id = risc0_zkvm::compute_image_id(elf).map_err(runtime_error)?;
+    Ok(PyBytes::new_bound(py, image_id.as_bytes()).unbind())
+}
+
+#[pymodule]
+#[pyo3(name = "risc0")]
+fn risc0_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", risc0_zkvm::VERSION)?;
+    m.add_function(wrap_pyfunction!(execute, m)?)?;
+    m.add_function(wrap_pyfunction!(prove, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_image_id, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::serde::to_vec;
+    use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+    use super::*;
+
+    #[test]
+    fn prove_and_verify() {
+        let spec = MultiTestSpec::Echo {
+            bytes: b"hello".to_vec(),
+        };
+        let input: Vec<u8> = to_vec(&spec)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let image_id = Digest::from(MULTI_TEST_ID);
+
+        assert_eq!(execute_impl(MULTI_TEST_ELF, &input).unwrap(), b"hello");
+
+        let receipt = prove_impl(MULTI_TEST_ELF, &input).unwrap();
+        assert_eq!(
+            verify_impl(&receipt, image_id.as_bytes()).unwrap(),
+            b"hello"
+        );
+        assert!(verify_impl(&receipt, &[0; DIGEST_BYTES]).is_err());
+        assert_eq!(
+            verify_impl(&receipt, &[0; 4]).unwrap_err().to_string(),
+            "image ID must be 32 bytes, got 4"
+        );
+    }
+}
@@ -63,6 +63,18 @@ struct Cli {
     /// to read it.
     #[arg(long, env = "RISC0_PPROF_OUT")]
     pprof_out: Option<PathBuf>,
+
+    /// Write the profile of the guest's run as folded stacks to this file.
+    /// The output can be rendered with flamegraph tools such as
+    /// `inferno-flamegraph`.
+    #[arg(long, env = "RISC0_FOLDED_OUT")]
+    folded_out: Option<PathBuf>,
+
+    /// Wait for a GDB remote debugger to connect on this address (e.g.
+    /// `localhost:9000`) before executing the guest.
+    #[cfg(feature = "gdb-stub")]
+    #[arg(long)]
+    gdb: Option<String>,
 }
 
 #[derive(Args)]
@@ -119,6 +131,10 @@ pub fn main() {
             builder.enable_profiler(pprof_out);
         }
 
+        if let Some(folded_out) = args.folded_out.as_ref() {
+            builder.enable_folded_profiler(folded_out);
+        }
+
         builder.build().unwrap()
     };
 
@@ -133,6 +149,15 @@ pub fn main() {
         } else {
             unreachable!()
         };
+
+        #[cfg(feature = "gdb-stub")]
+        if let Some(addr) = args.gdb.as_ref() {
+            let listener = std::net::TcpListener::bind(addr).unwrap();
+            eprintln!("Waiting for GDB connection on {addr}");
+            let (stream, _) = listener.accept().unwrap();
+            exec.attach_gdb(stream).unwrap();
+        }
+
         exec.run().unwrap()
     };
 
@@ -160,10 +185,9 @@ impl Cli {
             HashFn::Sha256 => "sha-256",
             HashFn::Poseidon2 => "poseidon2",
         };
-        let opts = ProverOpts {
-            hashfn: hashfn.to_string(),
-            prove_guest_errors: self.prove_guest_errors,
-        };
+        let opts = ProverOpts::default()
+            .with_hashfn(hashfn)
+            .with_prove_guest_errors(self.prove_guest_errors);
 
         get_prover_server(&opts).unwrap()
     }
@@ -14,7 +14,7 @@
 
 use assert_cmd::Command;
 use assert_fs::{fixture::PathChild, TempDir};
-use risc0_zkvm::{serde::to_vec, Receipt};
+use risc0_zkvm::{serde::to_vec, LocalProver, Prover, ProverOpts, Receipt};
 use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_PATH};
 
 fn run_dev_mode() -> Receipt {
@@ -60,6 +60,25 @@ fn dev_mode_verify_fail() {
     });
 }
 
+#[test]
+#[cfg(not(feature = "disable-dev-mode"))]
+fn dev_mode_compress() {
+    let receipt = run_dev_mode();
+    let prover = LocalProver::new("local");
+    temp_env::with_var("RISC0_DEV_MODE", Some("1"), || {
+        let compressed = prover.compress(&ProverOpts::default(), &receipt).unwrap();
+        assert_eq!(compressed, receipt);
+        compressed
+            .verify(risc0_zkvm_methods::MULTI_TEST_ID)
+            .unwrap();
+    });
+    temp_env::with_var("RISC0_DEV_MODE", None::<&str>, || {
+        prover
+            .compress(&ProverOpts::default(), &receipt)
+            .expect_err("Expecting error");
+    });
+}
+
 #[test]
 #[should_panic(
     expected = "zkVM: Inconsistent settings -- please resolve. The RISC0_DEV_MODE environment variable is set but dev mode has been disabled by feature flag."
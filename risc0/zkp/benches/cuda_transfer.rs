@@ -0,0 +1,88 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cust::memory::{CopyDestination, DeviceBuffer};
+use risc0_core::field::baby_bear::BabyBearElem;
+use risc0_zkp::hal::{
+    cuda::{synchronize_kernels, BufferImpl, CudaHalSha256},
+    Buffer, Hal,
+};
+
+const SIZES: [usize; 3] = [1 << 20, 1 << 24, 1 << 28];
+
+fn benchmark_upload(c: &mut Criterion) {
+    let _hal = CudaHalSha256::new();
+    let mut group = c.benchmark_group("cuda_upload");
+    for size in SIZES {
+        let data = vec![0x5au8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("pageable", size), &data, |b, data| {
+            b.iter(|| DeviceBuffer::from_slice(data).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("staged", size), &data, |b, data| {
+            b.iter(|| BufferImpl::copy_from("bench", data))
+        });
+    }
+    group.finish();
+}
+
+fn benchmark_download(c: &mut Criterion) {
+    let _hal = CudaHalSha256::new();
+    let mut group = c.benchmark_group("cuda_download");
+    for size in SIZES {
+        let data = vec![0x5au8; size];
+        let device = DeviceBuffer::from_slice(&data).unwrap();
+        let buffer = BufferImpl::copy_from("bench", &data);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(BenchmarkId::new("pageable", size), |b| {
+            let mut host = vec![0u8; size];
+            b.iter(|| device.copy_to(&mut host[..]).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("staged", size), |b| {
+            b.iter(|| buffer.view(|slice| assert_eq!(slice.len(), size)))
+        });
+    }
+    group.finish();
+}
+
+fn benchmark_overlap(c: &mut Criterion) {
+    let hal = CudaHalSha256::new();
+    let mut group = c.benchmark_group("cuda_overlap");
+    for size in SIZES {
+        let elems = size / std::mem::size_of::<BabyBearElem>();
+        let output = hal.alloc_elem("output", elems);
+        let input = hal.copy_from_elem("input", &vec![BabyBearElem::new(1); elems]);
+        let data = vec![0x5au8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        // Upload while a kernel over a buffer of the same size is in flight.
+        group.bench_with_input(BenchmarkId::new("kernel+upload", size), &data, |b, data| {
+            b.iter(|| {
+                hal.eltwise_add_elem(&output, &input, &input);
+                let buffer = BufferImpl::copy_from("bench", data);
+                synchronize_kernels();
+                buffer
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_upload,
+    benchmark_download,
+    benchmark_overlap
+);
+criterion_main!(benches);
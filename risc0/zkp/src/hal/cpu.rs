@@ -687,4 +687,11 @@ mod tests {
             &["da5698be17b9b46962335799779fbeca8ce5d491c0d26243bafef9ea1837a9d8"],
         );
     }
+
+    #[test]
+    fn testsuite_benchmark() {
+        let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
+        let results = crate::hal::testsuite::benchmark(&hal, 4);
+        assert_eq!(results.len(), 6);
+    }
 }
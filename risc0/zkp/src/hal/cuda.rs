@@ -18,7 +18,7 @@ use bytemuck::Pod;
 use cust::{
     device::DeviceAttribute,
     function::{BlockSize, GridSize},
-    memory::{DevicePointer, GpuBuffer},
+    memory::{AsyncCopyDestination, DevicePointer, DeviceSlice, GpuBuffer, LockedBuffer},
     prelude::*,
 };
 use lazy_static::lazy_static;
@@ -54,6 +54,111 @@ lazy_static! {
     };
 }
 
+/// Size of each pinned buffer used to stage host/device transfers.
+const STAGING_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+thread_local! {
+    static STAGING: RefCell<Option<Staging>> = RefCell::new(None);
+    static KERNEL_STREAM: Stream = Stream::new(StreamFlags::NON_BLOCKING, None).unwrap();
+}
+
+/// Runs `f` with the stream that kernels on this thread are launched on.
+///
+/// The stream does not synchronize with the legacy default stream, so
+/// kernels queued on it keep running while [Staging] moves data for the next
+/// step. Launches return without waiting; anything that reads kernel output
+/// on the host, or hands buffers to code using other streams, must call
+/// [synchronize_kernels] first. Dropping a buffer waits for the stream too.
+pub fn with_kernel_stream<R>(f: impl FnOnce(&Stream) -> R) -> R {
+    KERNEL_STREAM.with(f)
+}
+
+/// Blocks until every kernel launched on this thread's kernel stream has
+/// finished.
+pub fn synchronize_kernels() {
+    with_kernel_stream(|stream| stream.synchronize().unwrap());
+}
+
+/// Pinned host memory used to stage copies between pageable host memory and
+/// the device.
+///
+/// Copies from pageable memory are synchronous and go through a driver-owned
+/// bounce buffer. Instead, transfers are split into chunks that alternate
+/// between two pinned buffers, each with its own stream, so that copying one
+/// chunk on the host overlaps with the DMA of the previous one. The buffers
+/// are allocated once per thread and reused.
+///
+/// The staging streams are independent of the kernel stream, so uploads into
+/// fresh allocations proceed while earlier kernels are still running. An
+/// upload returns once its data is on the device, which orders it before any
+/// kernel launched afterwards. Downloads wait for queued kernels first.
+struct Staging {
+    bufs: [LockedBuffer<u8>; 2],
+    streams: [Stream; 2],
+}
+
+impl Staging {
+    fn new() -> Self {
+        let alloc = || unsafe { LockedBuffer::uninitialized(STAGING_CHUNK_SIZE).unwrap() };
+        let stream = || Stream::new(StreamFlags::NON_BLOCKING, None).unwrap();
+        Self {
+            bufs: [alloc(), alloc()],
+            streams: [stream(), stream()],
+        }
+    }
+
+    fn with<R>(f: impl FnOnce(&mut Staging) -> R) -> R {
+        STAGING.with(|staging| f(staging.borrow_mut().get_or_insert_with(Staging::new)))
+    }
+
+    fn upload(&mut self, dst: &DeviceSlice<u8>, src: &[u8]) {
+        assert_eq!(dst.len(), src.len());
+        for (idx, chunk) in src.chunks(STAGING_CHUNK_SIZE).enumerate() {
+            let slot = idx % 2;
+            let offset = idx * STAGING_CHUNK_SIZE;
+            // Wait for the previous transfer out of this staging buffer.
+            self.streams[slot].synchronize().unwrap();
+            let staging = &mut self.bufs[slot][..chunk.len()];
+            staging.copy_from_slice(chunk);
+            let mut dst = dst.index(offset..offset + chunk.len());
+            unsafe { dst.async_copy_from(staging, &self.streams[slot]) }.unwrap();
+        }
+        self.synchronize();
+    }
+
+    fn download(&mut self, src: &DeviceSlice<u8>, dst: &mut [u8]) {
+        assert_eq!(src.len(), dst.len());
+        synchronize_kernels();
+        let mut pending: Option<(usize, &mut [u8])> = None;
+        for (idx, chunk) in dst.chunks_mut(STAGING_CHUNK_SIZE).enumerate() {
+            let slot = idx % 2;
+            let offset = idx * STAGING_CHUNK_SIZE;
+            let src = src.index(offset..offset + chunk.len());
+            let staging = &mut self.bufs[slot][..chunk.len()];
+            unsafe { src.async_copy_to(staging, &self.streams[slot]) }.unwrap();
+            // Drain the previous chunk while this one is in flight.
+            if let Some((idx, chunk)) = pending.replace((idx, chunk)) {
+                self.drain(idx, chunk);
+            }
+        }
+        if let Some((idx, chunk)) = pending {
+            self.drain(idx, chunk);
+        }
+    }
+
+    fn drain(&mut self, idx: usize, chunk: &mut [u8]) {
+        let slot = idx % 2;
+        self.streams[slot].synchronize().unwrap();
+        chunk.copy_from_slice(&self.bufs[slot][..chunk.len()]);
+    }
+
+    fn synchronize(&self) {
+        for stream in self.streams.iter() {
+            stream.synchronize().unwrap();
+        }
+    }
+}
+
 pub trait CudaHash {
     /// Create a hash implementation
     fn new(hal: &CudaHal<Self>) -> Self;
@@ -85,10 +190,9 @@ impl CudaHash for CudaHashSha256 {
     }
 
     fn hash_fold(&self, hal: &CudaHal<Self>, io: &BufferImpl<Digest>, output_size: usize) {
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("sha_fold").unwrap();
         let params = hal.compute_simple_params(output_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             // DevicePointers require that the underlying type of the pointer implements the
             // DeviceCopy trait. core::Digest does not implement this trait.
             // TODO: refactor data types to allow safer copying.
@@ -104,8 +208,7 @@ impl CudaHash for CudaHashSha256 {
                 output_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn hash_rows(
@@ -118,10 +221,9 @@ impl CudaHash for CudaHashSha256 {
         let col_size = matrix.size() / output.size();
         assert_eq!(matrix.size(), col_size * row_size);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("sha_rows").unwrap();
         let params = hal.compute_simple_params(row_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 matrix.as_device_ptr(),
@@ -129,8 +231,7 @@ impl CudaHash for CudaHashSha256 {
                 col_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn get_hash_suite(&self) -> &HashSuite<BabyBear> {
@@ -165,10 +266,9 @@ impl CudaHash for CudaHashPoseidon {
     }
 
     fn hash_fold(&self, hal: &CudaHal<Self>, io: &BufferImpl<Digest>, output_size: usize) {
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("poseidon_fold").unwrap();
         let params = hal.compute_simple_params(output_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             // DevicePointers require that the underlying type of the pointer implements the
             // DeviceCopy trait. core::Digest does not implement this trait.
             // TODO: refactor data types to allow safer copying.
@@ -188,8 +288,7 @@ impl CudaHash for CudaHashPoseidon {
                 output_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn hash_rows(
@@ -202,10 +301,9 @@ impl CudaHash for CudaHashPoseidon {
         let col_size = matrix.size() / output.size();
         assert_eq!(matrix.size(), col_size * row_size);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("poseidon_rows").unwrap();
         let params = hal.compute_simple_params(row_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 self.round_constants.as_device_ptr(),
                 self.mds.as_device_ptr(),
@@ -217,8 +315,7 @@ impl CudaHash for CudaHashPoseidon {
                 col_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn get_hash_suite(&self) -> &HashSuite<BabyBear> {
@@ -234,11 +331,9 @@ pub struct CudaHashPoseidon2 {
 
 impl CudaHash for CudaHashPoseidon2 {
     fn new(hal: &CudaHal<Self>) -> Self {
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let round_constants =
             hal.copy_from_elem("round_constants", poseidon2::consts::ROUND_CONSTANTS);
         let m_int_diag = hal.copy_from_elem("m_int_diag", poseidon2::consts::M_INT_DIAG_HZN);
-        stream.synchronize().unwrap();
         CudaHashPoseidon2 {
             suite: Poseidon2HashSuite::new_suite(),
             round_constants,
@@ -247,10 +342,9 @@ impl CudaHash for CudaHashPoseidon2 {
     }
 
     fn hash_fold(&self, hal: &CudaHal<Self>, io: &BufferImpl<Digest>, output_size: usize) {
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("poseidon2_fold").unwrap();
         let params = hal.compute_simple_params(output_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             // DevicePointers require that the underlying type of the pointer implements the
             // DeviceCopy trait. core::Digest does not implement this trait.
             // TODO: refactor data types to allow safer copying.
@@ -268,8 +362,7 @@ impl CudaHash for CudaHashPoseidon2 {
                 output_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn hash_rows(
@@ -282,10 +375,9 @@ impl CudaHash for CudaHashPoseidon2 {
         let col_size = matrix.size() / output.size();
         assert_eq!(matrix.size(), col_size * row_size);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = hal.module.get_function("poseidon2_rows").unwrap();
         let params = hal.compute_simple_params(row_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 self.round_constants.as_device_ptr(),
                 self.m_int_diag.as_device_ptr(),
@@ -295,8 +387,7 @@ impl CudaHash for CudaHashPoseidon2 {
                 col_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn get_hash_suite(&self) -> &HashSuite<BabyBear> {
@@ -333,6 +424,8 @@ impl RawBuffer {
 
 impl Drop for RawBuffer {
     fn drop(&mut self) {
+        // Kernels queued on this thread may still be using the allocation.
+        let _ = KERNEL_STREAM.try_with(|stream| stream.synchronize());
         tracing::trace!("free: {} bytes, {}", self.buf.len(), self.name);
         TRACKER.lock().unwrap().free(self.buf.len());
     }
@@ -361,9 +454,9 @@ impl<T: Pod> BufferImpl<T> {
     pub fn copy_from(name: &'static str, slice: &[T]) -> Self {
         let bytes_len = std::mem::size_of::<T>() * slice.len();
         assert!(bytes_len > 0);
-        let mut buffer = RawBuffer::new(name, bytes_len);
+        let buffer = RawBuffer::new(name, bytes_len);
         let bytes = bytemuck::cast_slice(slice);
-        buffer.buf.copy_from(bytes).unwrap();
+        Staging::with(|staging| staging.upload(&buffer.buf, bytes));
         BufferImpl {
             buffer: Rc::new(RefCell::new(buffer)),
             size: slice.len(),
@@ -406,17 +499,19 @@ impl<T: Pod> Buffer<T> for BufferImpl<T> {
 
     fn view<F: FnOnce(&[T])>(&self, f: F) {
         let buf = self.buffer.borrow_mut();
-        let host_buf = buf.buf.as_host_vec().unwrap();
+        let mut host_buf = vec![0u8; buf.buf.len()];
+        Staging::with(|staging| staging.download(&buf.buf, &mut host_buf));
         let slice = bytemuck::cast_slice(&host_buf);
         f(&slice[self.offset..]);
     }
 
     fn view_mut<F: FnOnce(&mut [T])>(&self, f: F) {
-        let mut buf = self.buffer.borrow_mut();
-        let mut host_buf = buf.buf.as_host_vec().unwrap();
+        let buf = self.buffer.borrow_mut();
+        let mut host_buf = vec![0u8; buf.buf.len()];
+        Staging::with(|staging| staging.download(&buf.buf, &mut host_buf));
         let slice = bytemuck::cast_slice_mut(&mut host_buf);
         f(&mut slice[self.offset..]);
-        buf.buf.copy_from(&host_buf).unwrap();
+        Staging::with(|staging| staging.upload(&buf.buf, &host_buf));
     }
 }
 
@@ -545,6 +640,8 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
             assert_eq!(input.size(), in_size * poly_count);
             assert_eq!(out_size, in_size * (1 << expand_bits));
             let in_bits = log2_ceil(in_size);
+            // sppark uses its own streams and waits for them before returning.
+            synchronize_kernels();
             let err = unsafe {
                 batch_expand(
                     output.as_device_ptr(),
@@ -588,6 +685,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(row_size, 1 << n_bits);
         assert!(n_bits < Self::Elem::MAX_ROU_PO2);
 
+        synchronize_kernels();
         let err = unsafe {
             batch_iNTT(
                 io.as_device_ptr(),
@@ -608,18 +706,16 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(row_size, 1 << bits);
         let io_size = io.size();
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("multi_bit_reverse").unwrap();
         let params = self.compute_simple_params(io_size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 io.as_device_ptr(),
                 bits,
                 io_size
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -638,14 +734,13 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(xs.size(), eval_count);
         assert_eq!(out.size(), eval_count);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("multi_poly_eval").unwrap();
         let threads_per_block = self.max_threads / 4;
         const BYTES_PER_WORD: u32 = 4;
         const WORDS_PER_FPEXT: u32 = 4;
         let shared_size = threads_per_block * BYTES_PER_WORD * WORDS_PER_FPEXT;
         let (grid, block) = self.compute_simple_params(out.size() * threads_per_block as usize);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<grid, block, shared_size, stream>>>(
                 out.as_device_ptr(),
                 coeffs.as_device_ptr(),
@@ -654,8 +749,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 count,
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     // #[tracing::instrument(skip_all)]
@@ -667,10 +761,9 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         size: usize,
         stride: usize,
     ) {
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("gather_sample").unwrap();
         let (grid, block) = self.compute_simple_params(size);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<grid, block, 0, stream>>>(
                 dst.as_device_ptr(),
                 src.as_device_ptr(),
@@ -679,8 +772,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 stride,
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn has_unified_memory(&self) -> bool {
@@ -692,6 +784,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         let bits = log2_ceil(io.size() / poly_count);
         assert_eq!(io.size(), poly_count * (1 << bits));
 
+        synchronize_kernels();
         let err = unsafe {
             batch_zk_shift(
                 io.as_device_ptr(),
@@ -717,10 +810,9 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         let mix_start = self.copy_from_extelem("mix_start", &[*mix_start]);
         let mix = self.copy_from_extelem("mix", &[*mix]);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("mix_poly_coeffs").unwrap();
         let params = self.compute_simple_params(count);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 input.as_device_ptr(),
@@ -731,8 +823,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 count
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -746,10 +837,9 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(output.size(), input2.size());
         let count = output.size();
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("eltwise_add_fp").unwrap();
         let params = self.compute_simple_params(count);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 input1.as_device_ptr(),
@@ -757,8 +847,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 count
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -772,10 +861,9 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(output.size(), count * Self::ExtElem::EXT_SIZE);
         assert_eq!(input.size(), count * to_add);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("eltwise_sum_fpext").unwrap();
         let params = self.compute_simple_params(output.size());
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 input.as_device_ptr(),
@@ -783,8 +871,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 count
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -796,18 +883,16 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         let count = output.size();
         assert_eq!(count, input.size());
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("eltwise_copy_fp").unwrap();
         let params = self.compute_simple_params(count);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 input.as_device_ptr(),
                 count
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -822,10 +907,9 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
         assert_eq!(input.size(), output.size() * FRI_FOLD);
         let mix = self.copy_from_extelem("mix", &[*mix]);
 
-        let stream = Stream::new(StreamFlags::DEFAULT, None).unwrap();
         let kernel = self.module.get_function("fri_fold").unwrap();
         let params = self.compute_simple_params(count);
-        unsafe {
+        with_kernel_stream(|stream| unsafe {
             launch!(kernel<<<params.0, params.1, 0, stream>>>(
                 output.as_device_ptr(),
                 input.as_device_ptr(),
@@ -833,8 +917,7 @@ impl<CH: CudaHash> Hal for CudaHal<CH> {
                 count
             ))
             .unwrap();
-        }
-        stream.synchronize().unwrap();
+        });
     }
 
     fn hash_fold(&self, io: &Self::Buffer<Digest>, input_size: usize, output_size: usize) {
@@ -862,113 +945,113 @@ mod tests {
     use test_log::test;
 
     use super::{CudaHalPoseidon, CudaHalPoseidon2, CudaHalSha256};
-    use crate::hal::testutil;
+    use crate::hal::testsuite;
 
     #[test]
     #[should_panic]
     fn check_req() {
-        testutil::check_req(CudaHalSha256::new());
+        testsuite::check_req(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn eltwise_add_elem() {
-        testutil::eltwise_add_elem(CudaHalSha256::new());
+        testsuite::eltwise_add_elem(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn eltwise_copy_elem() {
-        testutil::eltwise_copy_elem(CudaHalSha256::new());
+        testsuite::eltwise_copy_elem(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn eltwise_sum_extelem() {
-        testutil::eltwise_sum_extelem(CudaHalSha256::new());
+        testsuite::eltwise_sum_extelem(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn hash_rows_sha256() {
-        testutil::hash_rows(CudaHalSha256::new());
+        testsuite::hash_rows(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn hash_fold_sha256() {
-        testutil::hash_fold(CudaHalSha256::new());
+        testsuite::hash_fold(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn hash_rows_poseidon() {
-        testutil::hash_rows(CudaHalPoseidon::new());
+        testsuite::hash_rows(CudaHalPoseidon::new());
     }
 
     #[test]
     #[serial]
     fn hash_fold_poseidon() {
-        testutil::hash_fold(CudaHalPoseidon::new());
+        testsuite::hash_fold(CudaHalPoseidon::new());
     }
 
     #[test]
     #[serial]
     fn hash_rows_poseidon2() {
-        testutil::hash_rows(CudaHalPoseidon2::new());
+        testsuite::hash_rows(CudaHalPoseidon2::new());
     }
 
     #[test]
     #[serial]
     fn hash_fold_poseidon2() {
-        testutil::hash_fold(CudaHalPoseidon2::new());
+        testsuite::hash_fold(CudaHalPoseidon2::new());
     }
 
     #[test]
     #[serial]
     fn fri_fold() {
-        testutil::fri_fold(CudaHalSha256::new());
+        testsuite::fri_fold(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn batch_expand_into_evaluate_ntt() {
-        testutil::batch_expand_into_evaluate_ntt(CudaHalSha256::new());
+        testsuite::batch_expand_into_evaluate_ntt(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn batch_interpolate_ntt() {
-        testutil::batch_interpolate_ntt(CudaHalSha256::new());
+        testsuite::batch_interpolate_ntt(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn batch_bit_reverse() {
-        testutil::batch_bit_reverse(CudaHalSha256::new());
+        testsuite::batch_bit_reverse(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn batch_evaluate_any() {
-        testutil::batch_evaluate_any(CudaHalSha256::new());
+        testsuite::batch_evaluate_any(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn gather_sample() {
-        testutil::gather_sample(CudaHalSha256::new());
+        testsuite::gather_sample(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn zk_shift() {
-        testutil::zk_shift(CudaHalSha256::new());
+        testsuite::zk_shift(CudaHalSha256::new());
     }
 
     #[test]
     #[serial]
     fn mix_poly_coeffs() {
-        testutil::mix_poly_coeffs(CudaHalSha256::new());
+        testsuite::mix_poly_coeffs(CudaHalSha256::new());
     }
 }
@@ -828,101 +828,101 @@ mod tests {
     use test_log::test;
 
     use super::{MetalHalPoseidon, MetalHalPoseidon2, MetalHalSha256};
-    use crate::hal::testutil;
+    use crate::hal::testsuite;
 
     #[test]
     fn batch_bit_reverse() {
-        testutil::batch_bit_reverse(MetalHalSha256::new());
+        testsuite::batch_bit_reverse(MetalHalSha256::new());
     }
 
     #[test]
     fn batch_evaluate_any() {
-        testutil::batch_evaluate_any(MetalHalSha256::new());
+        testsuite::batch_evaluate_any(MetalHalSha256::new());
     }
 
     #[test]
     fn batch_expand_into_evaluate_ntt() {
-        testutil::batch_expand_into_evaluate_ntt(MetalHalSha256::new());
+        testsuite::batch_expand_into_evaluate_ntt(MetalHalSha256::new());
     }
 
     #[test]
     fn batch_interpolate_ntt() {
-        testutil::batch_interpolate_ntt(MetalHalSha256::new());
+        testsuite::batch_interpolate_ntt(MetalHalSha256::new());
     }
 
     #[test]
     #[should_panic]
     fn check_req() {
-        testutil::check_req(MetalHalSha256::new());
+        testsuite::check_req(MetalHalSha256::new());
     }
 
     #[test]
     fn eltwise_add_fp() {
-        testutil::eltwise_add_elem(MetalHalSha256::new());
+        testsuite::eltwise_add_elem(MetalHalSha256::new());
     }
 
     #[test]
     fn eltwise_copy_fp() {
-        testutil::eltwise_copy_elem(MetalHalSha256::new());
+        testsuite::eltwise_copy_elem(MetalHalSha256::new());
     }
 
     #[test]
     fn eltwise_sum_extelem() {
-        testutil::eltwise_sum_extelem(MetalHalSha256::new());
+        testsuite::eltwise_sum_extelem(MetalHalSha256::new());
     }
 
     #[test]
     fn fri_fold() {
-        testutil::fri_fold(MetalHalSha256::new());
+        testsuite::fri_fold(MetalHalSha256::new());
     }
 
     #[test]
     fn mix_poly_coeffs() {
-        testutil::mix_poly_coeffs(MetalHalSha256::new());
+        testsuite::mix_poly_coeffs(MetalHalSha256::new());
     }
 
     #[test]
     fn hash_fold_sha256() {
-        testutil::hash_fold(MetalHalSha256::new());
+        testsuite::hash_fold(MetalHalSha256::new());
     }
 
     #[test]
     fn hash_rows_sha256() {
-        testutil::hash_rows(MetalHalSha256::new());
+        testsuite::hash_rows(MetalHalSha256::new());
     }
 
     #[test]
     fn hash_fold_poseidon() {
-        testutil::hash_fold(MetalHalPoseidon::new());
+        testsuite::hash_fold(MetalHalPoseidon::new());
     }
 
     #[test]
     fn hash_rows_poseidon() {
-        testutil::hash_rows(MetalHalPoseidon::new());
+        testsuite::hash_rows(MetalHalPoseidon::new());
     }
 
     #[test]
     fn hash_fold_poseidon2() {
-        testutil::hash_fold(MetalHalPoseidon2::new());
+        testsuite::hash_fold(MetalHalPoseidon2::new());
     }
 
     #[test]
     fn hash_rows_poseidon2() {
-        testutil::hash_rows(MetalHalPoseidon2::new());
+        testsuite::hash_rows(MetalHalPoseidon2::new());
     }
 
     #[test]
     fn slice() {
-        testutil::slice(MetalHalSha256::new());
+        testsuite::slice(MetalHalSha256::new());
     }
 
     #[test]
     fn zk_shift() {
-        testutil::zk_shift(MetalHalSha256::new());
+        testsuite::zk_shift(MetalHalSha256::new());
     }
 
     #[test]
     fn gather_sample() {
-        testutil::gather_sample(MetalHalSha256::new());
+        testsuite::gather_sample(MetalHalSha256::new());
     }
 }
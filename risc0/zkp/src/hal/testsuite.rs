@@ -0,0 +1,530 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A conformance and benchmark suite for [Hal] implementations.
+//!
+//! Third-party HALs, such as ones for FPGAs or other accelerators, can use
+//! this module to check that they compute the same results as [CpuHal] before
+//! they are used to prove. Most checks run the HAL side by side with the CPU
+//! HAL through a [DualHal], which panics on the first mismatch.
+//!
+//! [conformance] runs every check and reports which ones failed. A HAL is
+//! compatible with the prover only if every check passes. [benchmark]
+//! measures the operations that dominate proving time, for comparison with
+//! the CPU HAL.
+//!
+//! ```no_run
+//! use risc0_core::field::baby_bear::BabyBear;
+//! use risc0_zkp::{
+//!     core::hash::sha::Sha256HashSuite,
+//!     hal::{cpu::CpuHal, testsuite},
+//! };
+//!
+//! let report =
+//!     testsuite::conformance(|| CpuHal::new(Sha256HashSuite::<BabyBear>::new_suite()));
+//! println!("{report}");
+//! assert!(report.passed());
+//! ```
+
+use std::{
+    fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{thread_rng, RngCore};
+use risc0_core::field::{baby_bear::BabyBearElem, Elem, ExtElem};
+
+use super::{dual::DualHal, Hal};
+use crate::{
+    core::digest::Digest,
+    hal::{cpu::CpuHal, Buffer},
+    FRI_FOLD, INV_RATE,
+};
+
+const COUNTS: [usize; 7] = [1, 9, 12, 1001, 1024, 1025, 1024 * 1024];
+
+/// The number of columns used by the checks and benchmarks.
+pub const DATA_SIZE: usize = 223;
+
+fn generate_elem<H: Hal, R: RngCore>(hal: &H, rng: &mut R, size: usize) -> H::Buffer<H::Elem> {
+    let values: Vec<H::Elem> = (0..size).map(|_| H::Elem::random(rng)).collect();
+    hal.copy_from_elem("values", &values)
+}
+
+fn generate_extelem<H: Hal, R: RngCore>(
+    hal: &H,
+    rng: &mut R,
+    size: usize,
+) -> H::Buffer<H::ExtElem> {
+    let values: Vec<H::ExtElem> = (0..size).map(|_| H::ExtElem::random(rng)).collect();
+    hal.copy_from_extelem("values", &values)
+}
+
+/// Check [Hal::batch_bit_reverse] against the CPU HAL.
+pub fn batch_bit_reverse<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let steps = 1 << 12;
+    let count = DATA_SIZE;
+    let domain = steps * INV_RATE;
+    let io_size = count * domain;
+
+    let io = generate_elem(&hal, &mut rng, io_size);
+    hal.batch_bit_reverse(&io, count);
+}
+
+/// Check [Hal::batch_evaluate_any] against the CPU HAL.
+pub fn batch_evaluate_any<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let eval_size = 865;
+    let poly_count = 223;
+    let steps = 1 << 16;
+    let coeffs_size = steps * poly_count;
+
+    let z = H::ExtElem::random(&mut rng);
+    let z_pow = z.pow(H::ExtElem::EXT_SIZE);
+
+    let coeffs = generate_elem(&hal, &mut rng, coeffs_size);
+    let which = hal.copy_from_u32("which", &vec![0; eval_size]);
+    let xs = hal.copy_from_extelem("xs", &vec![z_pow; eval_size]);
+    let out = hal.alloc_extelem("out", eval_size);
+
+    hal.batch_evaluate_any(&coeffs, poly_count as usize, &which, &xs, &out);
+}
+
+/// Check [Hal::batch_expand_into_evaluate_ntt] against the CPU HAL.
+pub fn batch_expand_into_evaluate_ntt<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let count = DATA_SIZE;
+    let expand_bits = 2;
+    let steps = 1 << 16;
+    let domain = steps * INV_RATE;
+    let input_size = count * steps;
+    let output_size = count * domain;
+
+    let input = generate_elem(&hal, &mut rng, input_size);
+    let output = hal.alloc_elem("output", output_size);
+    hal.batch_expand_into_evaluate_ntt(&output, &input, count, expand_bits);
+}
+
+/// Check [Hal::batch_interpolate_ntt] against the CPU HAL.
+pub fn batch_interpolate_ntt<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let count = DATA_SIZE;
+    let steps = 1 << 16;
+    let domain = steps * INV_RATE;
+    let io_size = count * domain;
+
+    let io = generate_elem(&hal, &mut rng, io_size);
+    hal.batch_interpolate_ntt(&io, count);
+}
+
+/// Check [Hal::gather_sample] against a reference computed on the host.
+pub fn gather_sample<H: Hal>(hal: H) {
+    let mut rng = thread_rng();
+    let rows = 1000;
+    let cols = 900;
+    let idx = 400;
+    let src_size = rows * cols;
+    let src = hal.alloc_elem("src", src_size);
+    let dst = hal.alloc_elem("dst", rows);
+    src.view_mut(|buf| {
+        for x in 0..cols {
+            for y in 0..rows {
+                let value = H::Elem::random(&mut rng);
+                buf[y * cols + x] = value;
+            }
+        }
+    });
+    hal.gather_sample(&dst, &src, idx, rows, cols);
+    src.view(|src| {
+        dst.view(|dst| {
+            for y in 0..rows {
+                assert_eq!(src[y * cols + idx], dst[y]);
+            }
+        });
+    });
+}
+
+/// Call [Hal::eltwise_add_elem] with mismatched buffer sizes.
+///
+/// A conforming HAL must panic.
+pub fn check_req<H: Hal>(hal: H) {
+    let a = hal.alloc_elem("a", 10);
+    let b = hal.alloc_elem("b", 20);
+    hal.eltwise_add_elem(&a, &b, &b);
+}
+
+/// Check [Hal::eltwise_add_elem] against a reference computed on the host.
+pub fn eltwise_add_elem<H: Hal>(hal_gpu: H) {
+    for (x, count) in COUNTS.iter().enumerate() {
+        let a = hal_gpu.alloc_elem("a", *count);
+        let b = hal_gpu.alloc_elem("b", *count);
+        let o = hal_gpu.alloc_elem("o", *count);
+        let mut golden = Vec::with_capacity(*count);
+
+        let mut rng = thread_rng();
+        a.view_mut(|a| {
+            b.view_mut(|b| {
+                assert_eq!(a.len(), b.len());
+                for i in 0..a.len() {
+                    a[i] = H::Elem::random(&mut rng);
+                    b[i] = H::Elem::random(&mut rng);
+                }
+                for i in 0..a.len() {
+                    golden.push(a[i] + b[i]);
+                }
+            });
+        });
+
+        hal_gpu.eltwise_add_elem(&o, &a, &b);
+
+        o.view(|o| {
+            for i in 0..o.len() {
+                assert_eq!(o[i], golden[i], "x: {x}, count: {count}, i: {i}");
+            }
+        });
+    }
+}
+
+/// Check [Hal::eltwise_copy_elem] against a reference computed on the host.
+pub fn eltwise_copy_elem<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    for count in COUNTS {
+        let input = generate_elem(&hal_gpu, &mut rng, count);
+        let output = hal_gpu.alloc_elem("output", count);
+        hal_gpu.eltwise_copy_elem(&output, &input);
+        output.view(|output| {
+            input.view(|input| assert_eq!(output, input));
+        });
+    }
+}
+
+/// Check [Hal::eltwise_sum_extelem] against the CPU HAL.
+pub fn eltwise_sum_extelem<H: Hal>(hal_gpu: H) {
+    const COUNT: usize = 1024 * 1024;
+
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let input = generate_extelem(&hal, &mut rng, COUNT);
+    let output = hal.alloc_elem("output", COUNT);
+    hal.eltwise_sum_extelem(&output, &input);
+}
+
+/// Check [Hal::fri_fold] against the CPU HAL.
+pub fn fri_fold<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+    for count in COUNTS {
+        let output_size = count * H::ExtElem::EXT_SIZE;
+        let input_size = output_size * FRI_FOLD;
+
+        let output = hal.alloc_elem("output", output_size);
+        let mix = H::ExtElem::random(&mut rng);
+        let input = generate_elem(&hal, &mut rng, input_size);
+        hal.fri_fold(&output, &input, &mix);
+    }
+}
+
+/// Check [Hal::mix_poly_coeffs] against the CPU HAL.
+pub fn mix_poly_coeffs<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let combo_count = 100;
+    let steps = 1 << 12;
+    let domain = steps * INV_RATE;
+    let input_size = H::CHECK_SIZE * domain;
+    let output_size = steps * (combo_count + 1);
+    let combos = vec![0; H::CHECK_SIZE];
+    let mix_start = H::ExtElem::random(&mut rng);
+    let mix = H::ExtElem::random(&mut rng);
+
+    let output = hal.alloc_extelem("output", output_size);
+    let combos = hal.copy_from_u32("combos", &combos);
+    let input = generate_elem(&hal, &mut rng, input_size);
+
+    hal.mix_poly_coeffs(
+        &output,
+        &mix_start,
+        &mix,
+        &input,
+        &combos,
+        H::CHECK_SIZE,
+        steps,
+    );
+}
+
+/// Check [Hal::hash_fold] against the CPU HAL.
+pub fn hash_fold<H: Hal>(hal_gpu: H) {
+    const INPUTS: usize = 1024;
+    const OUTPUTS: usize = INPUTS / 2;
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+    let io = hal.alloc_digest("io", INPUTS * 2);
+    io.view_mut(|g| {
+        for i in 0..INPUTS {
+            g[i + INPUTS] = Digest::from([
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+            ]);
+        }
+    });
+    hal.hash_fold(&io, INPUTS, OUTPUTS);
+}
+
+/// Check [Hal::hash_rows] against the CPU HAL.
+pub fn hash_rows<H: Hal<Elem = BabyBearElem>>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+    let rows = [1, 2, 3, 4, 10];
+    let cols = [16, 32, 64, 128];
+    for row_count in rows {
+        for col_count in cols {
+            let matrix_size = row_count * col_count;
+            let matrix = generate_elem(&hal, &mut rng, matrix_size);
+            let output = hal.alloc_digest("output", row_count);
+            hal.hash_rows(&output, &matrix);
+        }
+    }
+}
+
+/// Check [Hal::hash_rows] into a slice of a buffer against the CPU HAL.
+pub fn slice<H: Hal<Elem = BabyBearElem>>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+
+    let rows = 4096;
+    let cols = 256;
+    let matrix_size = rows * cols;
+
+    let nodes = hal.alloc_digest("nodes", rows * 2);
+    let matrix = generate_elem(&hal, &mut rng, matrix_size);
+    hal.hash_rows(&nodes.slice(rows, rows), &matrix);
+}
+
+/// Check [Hal::zk_shift] against the CPU HAL.
+pub fn zk_shift<H: Hal>(hal_gpu: H) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), Rc::new(hal_gpu));
+    let counts = [(1000, (1 << 8)), (900, (1 << 12))];
+    for (poly_count, steps) in counts {
+        let count = poly_count * steps;
+        let io = generate_elem(&hal, &mut rng, count);
+        hal.zk_shift(&io, poly_count);
+    }
+}
+
+/// The outcome of a single conformance check.
+#[derive(Clone, Debug)]
+pub struct CheckResult {
+    /// The name of the check.
+    pub name: &'static str,
+
+    /// How long the check took.
+    pub duration: Duration,
+
+    /// The panic message if the check failed.
+    pub failure: Option<String>,
+}
+
+/// The results of [conformance].
+#[derive(Clone, Debug, Default)]
+pub struct ConformanceReport {
+    /// The result of each check, in the order they were run.
+    pub results: Vec<CheckResult>,
+}
+
+impl ConformanceReport {
+    /// Returns true if every check passed.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Iterate over the checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results
+            .iter()
+            .filter(|result| result.failure.is_some())
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.results.iter() {
+            let status = if result.failure.is_some() {
+                "FAIL"
+            } else {
+                "ok"
+            };
+            writeln!(
+                f,
+                "{:<32} {status:<4} {:>10.3?}",
+                result.name, result.duration
+            )?;
+            if let Some(failure) = &result.failure {
+                writeln!(f, "    {failure}")?;
+            }
+        }
+        let failed = self.failures().count();
+        write!(f, "{} passed, {failed} failed", self.results.len() - failed)
+    }
+}
+
+fn run_check(name: &'static str, expect_panic: bool, check: impl FnOnce()) -> CheckResult {
+    tracing::info!("hal conformance: {name}");
+    let start = Instant::now();
+    let outcome = catch_unwind(AssertUnwindSafe(check));
+    let duration = start.elapsed();
+    let failure = match (outcome, expect_panic) {
+        (Ok(()), false) | (Err(_), true) => None,
+        (Ok(()), true) => Some("expected a panic".to_string()),
+        (Err(err), false) => Some(
+            err.downcast_ref::<String>()
+                .cloned()
+                .or_else(|| err.downcast_ref::<&str>().map(|msg| msg.to_string()))
+                .unwrap_or_else(|| "panicked".to_string()),
+        ),
+    };
+    CheckResult {
+        name,
+        duration,
+        failure,
+    }
+}
+
+/// Run every conformance check against HALs constructed by `make_hal`.
+///
+/// Each check gets a fresh HAL. Checks that fail by panicking are caught and
+/// recorded in the returned [ConformanceReport], so one failure does not stop
+/// the rest of the suite.
+pub fn conformance<H, F>(make_hal: F) -> ConformanceReport
+where
+    H: Hal<Elem = BabyBearElem>,
+    F: Fn() -> H,
+{
+    let checks: [(&'static str, bool, fn(H)); 15] = [
+        ("check_req", true, check_req),
+        ("eltwise_add_elem", false, eltwise_add_elem),
+        ("eltwise_copy_elem", false, eltwise_copy_elem),
+        ("eltwise_sum_extelem", false, eltwise_sum_extelem),
+        ("gather_sample", false, gather_sample),
+        ("batch_bit_reverse", false, batch_bit_reverse),
+        ("batch_evaluate_any", false, batch_evaluate_any),
+        (
+            "batch_expand_into_evaluate_ntt",
+            false,
+            batch_expand_into_evaluate_ntt,
+        ),
+        ("batch_interpolate_ntt", false, batch_interpolate_ntt),
+        ("fri_fold", false, fri_fold),
+        ("mix_poly_coeffs", false, mix_poly_coeffs),
+        ("hash_fold", false, hash_fold),
+        ("hash_rows", false, hash_rows),
+        ("slice", false, slice),
+        ("zk_shift", false, zk_shift),
+    ];
+    let results = checks
+        .into_iter()
+        .map(|(name, expect_panic, check)| {
+            let hal = make_hal();
+            run_check(name, expect_panic, move || check(hal))
+        })
+        .collect();
+    ConformanceReport { results }
+}
+
+/// The time taken by one operation in [benchmark].
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    /// The name of the [Hal] operation.
+    pub name: &'static str,
+
+    /// The time taken by the operation.
+    pub duration: Duration,
+}
+
+/// Measure the operations that dominate proving time on `hal`, for a trace
+/// with `2^po2` rows and [DATA_SIZE] columns.
+///
+/// Run this against both the HAL under test and [CpuHal] to compare them.
+pub fn benchmark<H: Hal<Elem = BabyBearElem>>(hal: &H, po2: usize) -> Vec<Measurement> {
+    let mut rng = thread_rng();
+    let steps = 1 << po2;
+    let domain = steps * INV_RATE;
+    let mut results = Vec::new();
+    let mut measure = |name: &'static str, op: &mut dyn FnMut()| {
+        let start = Instant::now();
+        op();
+        results.push(Measurement {
+            name,
+            duration: start.elapsed(),
+        });
+    };
+
+    let input = generate_elem(hal, &mut rng, DATA_SIZE * steps);
+    let expanded = hal.alloc_elem("expanded", DATA_SIZE * domain);
+    measure("batch_expand_into_evaluate_ntt", &mut || {
+        hal.batch_expand_into_evaluate_ntt(&expanded, &input, DATA_SIZE, INV_RATE.ilog2() as usize)
+    });
+    measure("batch_interpolate_ntt", &mut || {
+        hal.batch_interpolate_ntt(&expanded, DATA_SIZE)
+    });
+    measure("batch_bit_reverse", &mut || {
+        hal.batch_bit_reverse(&expanded, DATA_SIZE)
+    });
+
+    let rows = hal.alloc_digest("rows", domain);
+    let matrix = generate_elem(hal, &mut rng, DATA_SIZE * domain);
+    measure("hash_rows", &mut || hal.hash_rows(&rows, &matrix));
+
+    let nodes = hal.alloc_digest("nodes", domain * 2);
+    hal.hash_rows(&nodes.slice(domain, domain), &matrix);
+    measure("hash_fold", &mut || {
+        hal.hash_fold(&nodes, domain, domain / 2)
+    });
+
+    let fri_out = hal.alloc_elem("fri_out", domain / FRI_FOLD * H::ExtElem::EXT_SIZE);
+    let fri_in = generate_elem(hal, &mut rng, domain * H::ExtElem::EXT_SIZE);
+    let mix = H::ExtElem::random(&mut rng);
+    measure("fri_fold", &mut || hal.fri_fold(&fri_out, &fri_in, &mix));
+
+    results
+}
@@ -24,7 +24,7 @@ use crate::{
         log2_ceil,
         ntt::{bit_reverse, interpolate_ntt},
     },
-    verify::{merkle::MerkleTreeVerifier, read_iop::ReadIOP, VerificationError},
+    verify::{merkle::MerkleTreeVerifier, read_iop::ReadIOP, MerkleTree, VerificationError},
     FRI_FOLD, FRI_FOLD_PO2, FRI_MIN_DEGREE, INV_RATE, QUERIES,
 };
 
@@ -61,6 +61,7 @@ where
 {
     fn verify_query(
         &self,
+        round_idx: usize,
         round: &mut VerifyRoundInfo<'a, F>,
         iop: &mut ReadIOP<'a, F>,
         pos: &mut usize,
@@ -69,9 +70,7 @@ where
         let quot = *pos / round.domain;
         let group = *pos % round.domain;
         // Get the column data
-        let data = round
-            .merkle
-            .verify(iop, self.suite.hashfn.as_ref(), group)?;
+        let data = self.open_row(&round.merkle, MerkleTree::Fri(round_idx), iop, group)?;
         let mut data_ext: Vec<F::ExtElem> = (0..FRI_FOLD)
             .map(|i| {
                 let mut inps = Vec::with_capacity(F::ExtElem::EXT_SIZE);
@@ -140,8 +139,8 @@ where
             // Do the 'inner' verification for this index
             let mut goal = inner(iop, pos)?;
             // Verify the per-round proofs
-            for round in &mut rounds {
-                self.verify_query(round, iop, &mut pos, &mut goal)?;
+            for (round_idx, round) in rounds.iter_mut().enumerate() {
+                self.verify_query(round_idx, round, iop, &mut pos, &mut goal)?;
             }
             // Do final verification
             let x = gen.pow(pos);
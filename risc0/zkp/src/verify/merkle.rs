@@ -14,7 +14,7 @@
 
 use alloc::{boxed::Box, vec::Vec};
 
-use risc0_core::field::Field;
+use risc0_core::field::{Elem, Field};
 
 use crate::{
     core::{digest::Digest, hash::HashFn},
@@ -22,6 +22,73 @@ use crate::{
     verify::{read_iop::ReadIOP, VerificationError},
 };
 
+/// A Merkle tree committed to in a seal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleTree {
+    /// The control (code) columns of the circuit.
+    Code,
+    /// The execution trace (data) columns.
+    Data,
+    /// The accumulator columns of the permutation arguments.
+    Accum,
+    /// The evaluations of the constraint (check) polynomial.
+    Check,
+    /// The given FRI folding round, starting from zero.
+    Fri(usize),
+}
+
+/// The opening of one row of a committed Merkle tree, as queried by the
+/// verifier.
+///
+/// An opening can be checked on its own with [MerkleOpening::verify], without
+/// the rest of the seal, which makes it usable by tooling that only needs the
+/// committed data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleOpening<E> {
+    /// The tree that was opened.
+    pub tree: MerkleTree,
+
+    /// The index of the opened row.
+    pub index: usize,
+
+    /// The column values of the opened row.
+    pub values: Vec<E>,
+
+    /// The sibling hashes on the path from the leaf to the root, starting at
+    /// the leaf.
+    pub path: Vec<Digest>,
+
+    /// The root of the tree, as committed in the seal.
+    pub root: Digest,
+}
+
+impl<E: Elem> MerkleOpening<E> {
+    /// Compute the root implied by the opened values and path.
+    pub fn compute_root<F: Field<Elem = E>>(&self, hashfn: &dyn HashFn<F>) -> Digest {
+        let mut cur = *hashfn.hash_elem_slice(&self.values);
+        for (level, sibling) in self.path.iter().enumerate() {
+            cur = if (self.index >> level) & 1 == 1 {
+                *hashfn.hash_pair(sibling, &cur)
+            } else {
+                *hashfn.hash_pair(&cur, sibling)
+            };
+        }
+        cur
+    }
+
+    /// Check that the opened values and path hash to [MerkleOpening::root].
+    pub fn verify<F: Field<Elem = E>>(
+        &self,
+        hashfn: &dyn HashFn<F>,
+    ) -> Result<(), VerificationError> {
+        if self.compute_root(hashfn) == self.root {
+            Ok(())
+        } else {
+            Err(VerificationError::InvalidProof)
+        }
+    }
+}
+
 /// A struct against which we verify merkle branches, consisting of the
 /// parameters of the Merkle tree and top - the vector of hash values in the top
 /// row of the tree, above which we verify only once.
@@ -135,12 +202,34 @@ impl<'a> MerkleTreeVerifier<'a> {
         }
     }
 
+    /// Returns the hash at the given virtual index, which must be in the
+    /// top row or above it.
+    fn node(&self, idx: usize) -> &Digest {
+        if idx >= self.params.top_size {
+            &self.top[self.params.idx_to_top(idx)]
+        } else {
+            &self.rest[self.params.idx_to_rest(idx)]
+        }
+    }
+
     /// Verifies a branch provided by an IOP.
     pub fn verify<F: Field>(
+        &self,
+        iop: &mut ReadIOP<'a, F>,
+        hashfn: &dyn HashFn<F>,
+        idx: usize,
+    ) -> Result<&'a [F::Elem], VerificationError> {
+        self.verify_branch(iop, hashfn, idx, None)
+    }
+
+    /// Verifies a branch provided by an IOP, appending the sibling hashes from
+    /// the leaf up to the root to `path` if it is given.
+    pub(crate) fn verify_branch<F: Field>(
         &self,
         iop: &mut ReadIOP<'a, F>,
         hashfn: &dyn HashFn<F>,
         mut idx: usize,
+        mut path: Option<&mut Vec<Digest>>,
     ) -> Result<&'a [F::Elem], VerificationError> {
         if idx >= self.params.row_size {
             return Err(VerificationError::MerkleQueryOutOfRange {
@@ -163,6 +252,9 @@ impl<'a> MerkleTreeVerifier<'a> {
                 [other] => other,
                 _ => unreachable!(),
             };
+            if let Some(path) = path.as_mut() {
+                path.push(*other);
+            }
             // Now ascend to the parent index, and compute the hash there.
             idx /= 2;
             if low_bit == 1 {
@@ -173,15 +265,16 @@ impl<'a> MerkleTreeVerifier<'a> {
         }
         // Once we reduce to an index for which we have the hash, check that it's
         // correct.
-        let present_hash: &Digest = if idx >= self.params.top_size {
-            &self.top[self.params.idx_to_top(idx)]
-        } else {
-            &self.rest[self.params.idx_to_rest(idx)]
-        };
-        if *present_hash == *cur {
-            Ok(out)
-        } else {
-            Err(VerificationError::InvalidProof)
+        if *self.node(idx) != *cur {
+            return Err(VerificationError::InvalidProof);
+        }
+        // The rest of the path is in the part of the tree verified up front.
+        if let Some(path) = path {
+            while idx > 1 {
+                path.push(*self.node(idx ^ 1));
+                idx /= 2;
+            }
         }
+        Ok(out)
     }
 }
@@ -22,6 +22,7 @@ use alloc::{vec, vec::Vec};
 use core::{cell::RefCell, fmt, iter::zip};
 
 pub(crate) use merkle::MerkleTreeVerifier;
+pub use merkle::{MerkleOpening, MerkleTree};
 pub use read_iop::ReadIOP;
 use risc0_core::field::{Elem, ExtElem, Field, RootsOfUnity};
 
@@ -44,6 +45,7 @@ pub enum VerificationError {
     MerkleQueryOutOfRange { idx: usize, rows: usize },
     InvalidProof,
     JournalDigestMismatch,
+    JournalTooLarge { size: usize, limit: usize },
     UnexpectedExitCode,
     InvalidHashSuite,
     FaultStateMismatch,
@@ -72,6 +74,9 @@ impl fmt::Display for VerificationError {
             VerificationError::JournalDigestMismatch => {
                 write!(f, "Journal digest mismatch detected")
             }
+            VerificationError::JournalTooLarge { size, limit } => {
+                write!(f, "Journal size {size} exceeds limit {limit}")
+            }
             VerificationError::UnexpectedExitCode => write!(f, "Unexpected exit_code"),
             VerificationError::InvalidHashSuite => write!(f, "Invalid hash suite"),
             VerificationError::FaultStateMismatch => {
@@ -109,6 +114,7 @@ where
     out: Option<&'a [F::Elem]>,
     mix: Vec<F::Elem>,
     tap_cache: RefCell<Option<TapCache<F>>>,
+    openings: Option<RefCell<Vec<MerkleOpening<F::Elem>>>>,
 }
 
 impl<'a, F: Field, C> VerifyParams<F> for Verifier<'a, F, C> {}
@@ -127,9 +133,34 @@ where
             out: None,
             mix: Vec::new(),
             tap_cache: RefCell::new(None),
+            openings: None,
         }
     }
 
+    // Verify a branch of the given tree, recording the opening if requested.
+    fn open_row(
+        &self,
+        merkle: &MerkleTreeVerifier<'a>,
+        tree: MerkleTree,
+        iop: &mut ReadIOP<'a, F>,
+        idx: usize,
+    ) -> Result<&'a [F::Elem], VerificationError> {
+        let hashfn = self.suite.hashfn.as_ref();
+        let Some(openings) = &self.openings else {
+            return merkle.verify(iop, hashfn, idx);
+        };
+        let mut path = Vec::new();
+        let row = merkle.verify_branch(iop, hashfn, idx, Some(&mut path))?;
+        openings.borrow_mut().push(MerkleOpening {
+            tree,
+            index: idx,
+            values: row.to_vec(),
+            path,
+            root: *merkle.root(),
+        });
+        Ok(row)
+    }
+
     // Compute the FRI verify taps sum.
     #[allow(clippy::too_many_arguments)]
     fn fri_eval_taps(
@@ -409,11 +440,11 @@ where
             // tracing::debug!("fri_verify");
             let x = gen.pow(idx);
             let rows = [
-                accum_merkle.verify(iop, hashfn, idx)?,
-                code_merkle.verify(iop, hashfn, idx)?,
-                data_merkle.verify(iop, hashfn, idx)?,
+                self.open_row(&accum_merkle, MerkleTree::Accum, iop, idx)?,
+                self.open_row(&code_merkle, MerkleTree::Code, iop, idx)?,
+                self.open_row(&data_merkle, MerkleTree::Data, iop, idx)?,
             ];
-            let check_row = check_merkle.verify(iop, hashfn, idx)?;
+            let check_row = self.open_row(&check_merkle, MerkleTree::Check, iop, idx)?;
             let ret = self.fri_eval_taps(taps, mix, &combo_u, check_row, back_one, x, z, rows);
             Ok(ret)
         })?;
@@ -471,3 +502,25 @@ where
 {
     Verifier::<F, C>::new(circuit, suite).verify(seal, check_code)
 }
+
+/// Verify a seal like [verify], and return the opening of every Merkle tree
+/// row queried during verification.
+///
+/// Each [MerkleOpening] can then be checked on its own, against the root
+/// committed in the seal.
+pub fn verify_with_openings<F, C, CheckCode>(
+    circuit: &C,
+    suite: &HashSuite<F>,
+    seal: &[u32],
+    check_code: CheckCode,
+) -> Result<Vec<MerkleOpening<F::Elem>>, VerificationError>
+where
+    F: Field,
+    C: CircuitCoreDef<F>,
+    CheckCode: Fn(u32, &Digest) -> Result<(), VerificationError>,
+{
+    let mut verifier = Verifier::<F, C>::new(circuit, suite);
+    verifier.openings = Some(RefCell::new(Vec::new()));
+    verifier.verify(seal, check_code)?;
+    Ok(verifier.openings.unwrap().into_inner())
+}
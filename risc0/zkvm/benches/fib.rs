@@ -57,10 +57,7 @@ fn prove_segment(group: &mut BenchGroup, hashfn: &str) {
     group.bench(name, |b| {
         let iterations = 100_000;
 
-        let opts = ProverOpts {
-            hashfn: hashfn.to_string(),
-            prove_guest_errors: false,
-        };
+        let opts = ProverOpts::default().with_hashfn(hashfn);
         let prover = get_prover_server(&opts).unwrap();
         let ctx = VerifierContext::default();
 
@@ -28,36 +28,21 @@ fn main() {
 
     let docker_opts = DockerOptions {
         root_dir: Some("../../..".into()),
+        ..Default::default()
     };
 
-    let use_docker = if env::var("RISC0_USE_DOCKER").is_ok() {
-        Some(docker_opts)
-    } else {
-        None
-    };
+    let mut guest_opts = GuestOptions::default();
+    if env::var("RISC0_USE_DOCKER").is_ok() {
+        guest_opts = guest_opts.with_docker(docker_opts);
+    }
 
     let map = HashMap::from([
-        (
-            "risc0-zkvm-methods-guest",
-            GuestOptions {
-                features: vec![],
-                use_docker,
-            },
-        ),
+        ("risc0-zkvm-methods-guest", guest_opts),
         (
             "risc0-zkvm-methods-std",
-            GuestOptions {
-                features: vec!["test_feature1".to_string(), "test_feature2".to_string()],
-                use_docker: None,
-            },
-        ),
-        (
-            "risc0-zkvm-methods-cpp-crates",
-            GuestOptions {
-                features: vec![],
-                use_docker: None,
-            },
+            GuestOptions::default().with_features(["test_feature1", "test_feature2"]),
         ),
+        ("risc0-zkvm-methods-cpp-crates", GuestOptions::default()),
     ]);
 
     embed_methods_with_options(map);
@@ -23,12 +23,12 @@ use alloc::{
     alloc::{alloc_zeroed, Layout},
     format, vec,
 };
-use core::arch::asm;
+use core::{arch::asm, ops::ControlFlow};
 
 use getrandom::getrandom;
 use risc0_zkp::core::hash::sha::testutil::test_sha_impl;
 use risc0_zkvm::{
-    guest::{env, memory_barrier, sha},
+    guest::{checked, env, memory_barrier, sha, timebox::TimeBox},
     sha::{Digest, Sha256},
     ReceiptClaim,
 };
@@ -36,7 +36,10 @@ use risc0_zkvm_methods::multi_test::{MultiTestSpec, SYS_MULTI_TEST};
 use risc0_zkvm_platform::{
     fileno,
     memory::{self, SYSTEM},
-    syscall::{bigint, sys_bigint, sys_log, sys_read, sys_read_words, sys_write},
+    syscall::{
+        bigint, bigint_addmod, bigint_modexp, bigint_submod, sys_bigint, sys_log, sys_read,
+        sys_read_words, sys_write,
+    },
     PAGE_SIZE,
 };
 
@@ -101,6 +104,9 @@ fn main() {
         MultiTestSpec::Panic => {
             panic!("MultiTestSpec::Panic invoked");
         }
+        MultiTestSpec::CheckedAdd { a, b } => {
+            env::commit(&checked::add(a, b));
+        }
         MultiTestSpec::Fault => unsafe {
             asm!("sw x0, 1(x0)");
         },
@@ -112,6 +118,12 @@ fn main() {
             env::pause(exit_code);
             env::log("after");
         }
+        MultiTestSpec::PauseBetweenReads => {
+            let first: u32 = env::read();
+            env::pause(0);
+            let second: u32 = env::read();
+            env::commit(&(first, second));
+        }
         MultiTestSpec::ReadWriteMem { values } => {
             for (addr, value) in values.into_iter() {
                 if value != 0 {
@@ -209,6 +221,22 @@ fn main() {
             unsafe { sys_read_words(fd, buf.as_mut_ptr(), nwords) };
             env::commit_slice(&buf);
         }
+        MultiTestSpec::ReadFrame { max_len } => loop {
+            match env::read_frame(max_len as usize) {
+                Ok(frame) => env::commit(&frame),
+                Err(err) => {
+                    env::commit(&format!("{err}"));
+                    break;
+                }
+            }
+        },
+        MultiTestSpec::ReadChannels { reads } => {
+            for (name, nbytes) in reads {
+                let mut buf = vec![0u8; nbytes as usize];
+                env::channel(&name).read_slice(&mut buf);
+                env::commit_slice(&buf);
+            }
+        }
         MultiTestSpec::BusyLoop { cycles } => {
             let mut last_cycles = env::cycle_count();
 
@@ -237,6 +265,14 @@ fn main() {
             }
             env::commit_slice(&result);
         }
+        MultiTestSpec::BigIntOps { x, y, modulus } => {
+            let result = [
+                bigint_addmod(&x, &y, &modulus),
+                bigint_submod(&x, &y, &modulus),
+                bigint_modexp(&x, &y, &modulus),
+            ];
+            env::commit_slice(&result);
+        }
         MultiTestSpec::LibM => {
             use core::hint::black_box;
             let f = black_box(1.0_f32);
@@ -332,5 +368,44 @@ fn main() {
                 assert_eq!(*value, 0);
             }
         }
+        MultiTestSpec::CycleCounters { cycles } => {
+            let mut last_session = env::session_cycle_count();
+            while last_session < cycles {
+                let session = env::session_cycle_count();
+                assert!(session > last_session);
+                last_session = session;
+            }
+            let segment = env::segment_cycle_count();
+            env::commit(&(last_session, segment));
+        }
+        MultiTestSpec::Clock => {
+            let first = env::time();
+            assert_eq!(env::time(), first);
+            env::commit(&(first.as_nanos() as u64));
+        }
+        MultiTestSpec::ClockAcrossPause => {
+            let first = env::time();
+            env::pause(0);
+            let second = env::time();
+            env::commit(&(first.as_nanos() as u64, second.as_nanos() as u64));
+        }
+        MultiTestSpec::TimeBox { steps, work } => {
+            let mut timebox = TimeBox::new();
+            let mut value = 0u64;
+            let mut step = 0;
+            let result = timebox.run(|| {
+                if step == steps {
+                    return ControlFlow::Break(value);
+                }
+                for i in 0..work {
+                    value = value
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(i as u64 | 1);
+                }
+                step += 1;
+                ControlFlow::Continue(())
+            });
+            env::commit(&(result, timebox.pauses()));
+        }
     }
 }
@@ -36,9 +36,17 @@ pub enum MultiTestSpec {
     EventTrace,
     Profiler,
     Panic,
+    /// Add the inputs with [risc0_zkvm::guest::checked::add] and commit the
+    /// sum.
+    CheckedAdd {
+        a: u64,
+        b: u64,
+    },
     Fault,
     Halt(u8),
     PauseResume(u8),
+    /// Read a `u32`, pause, then read another `u32` and commit both.
+    PauseBetweenReads,
     ReadWriteMem {
         /// Tuples of (address, value). Zero means read the value and
         /// output it; nonzero means write that value.
@@ -72,11 +80,27 @@ pub enum MultiTestSpec {
         fd: u32,
         nwords: u32,
     },
+    /// Read length-prefixed frames from stdin until one fails, committing
+    /// each frame and then the final error message.
+    ReadFrame {
+        max_len: u32,
+    },
+    /// Read the given number of bytes from each named input channel in turn,
+    /// committing the bytes read.
+    ReadChannels {
+        reads: Vec<(String, u32)>,
+    },
     BigInt {
         x: [u32; bigint::WIDTH_WORDS],
         y: [u32; bigint::WIDTH_WORDS],
         modulus: [u32; bigint::WIDTH_WORDS],
     },
+    /// Commit addmod, submod, and modexp of the inputs, in that order.
+    BigIntOps {
+        x: [u32; bigint::WIDTH_WORDS],
+        y: [u32; bigint::WIDTH_WORDS],
+        modulus: [u32; bigint::WIDTH_WORDS],
+    },
     BusyLoop {
         /// Busy loop until the guest has run for at least this number of cycles
         cycles: u32,
@@ -90,6 +114,21 @@ pub enum MultiTestSpec {
     TooManySha,
     AlignedAlloc,
     AllocZeroed,
+    CycleCounters {
+        /// Loop until the session cycle counter reaches this value, then
+        /// commit the session and segment cycle counters.
+        cycles: u64,
+    },
+    /// Read the host clock twice and commit the time.
+    Clock,
+    /// Read the host clock, pause, read it again and commit both times.
+    ClockAcrossPause,
+    /// Run `steps` steps of `work` LCG rounds each in a `TimeBox`, then
+    /// commit the final value and the number of pauses.
+    TimeBox {
+        steps: u32,
+        work: u32,
+    },
 }
 
 declare_syscall!(pub SYS_MULTI_TEST);
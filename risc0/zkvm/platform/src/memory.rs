@@ -19,11 +19,23 @@ pub const MEM_SIZE: usize = 1 << MEM_BITS;
 pub const GUEST_MIN_MEM: usize = 0x0000_0400;
 pub const GUEST_MAX_MEM: usize = SYSTEM.start;
 
+/// Stack size used when the guest is built without an explicit one.
+pub const DEFAULT_STACK_SIZE: u32 = 0x0020_0000;
+/// Size of the guest stack in bytes, which grows down from [STACK_TOP] to
+/// [GUEST_MIN_MEM].
+///
+/// This is fixed when the guest is built, from the `RISC0_GUEST_STACK_SIZE`
+/// environment variable that `risc0-build` sets from
+/// `GuestOptions::stack_size`.
+pub const STACK_SIZE: u32 = match option_env!("RISC0_GUEST_STACK_SIZE") {
+    Some(size) => parse_stack_size(size),
+    None => DEFAULT_STACK_SIZE,
+};
 /// Top of stack; stack grows down from this location.
-pub const STACK_TOP: u32 = 0x0020_0400;
+pub const STACK_TOP: u32 = GUEST_MIN_MEM as u32 + STACK_SIZE;
 /// Program (text followed by data and then bss) gets loaded in
 /// starting at this location.  HEAP begins right afterwards.
-pub const TEXT_START: u32 = 0x0020_0800;
+pub const TEXT_START: u32 = text_start(STACK_SIZE);
 pub const SYSTEM: Region = Region::new(0x0C00_0000, mb(16));
 pub const PAGE_TABLE: Region = Region::new(0x0D00_0000, mb(16));
 pub const PRE_LOAD: Region = Region::new(0x0D70_0000, mb(9));
@@ -64,6 +76,39 @@ const fn mb(mb: usize) -> usize {
     kb(mb * 1024)
 }
 
+/// Returns the address the program is loaded at for a given stack size.
+///
+/// Panics if `stack_size` is not word aligned or leaves no room for the
+/// program below [GUEST_MAX_MEM].
+pub const fn text_start(stack_size: u32) -> u32 {
+    assert!(
+        stack_size as usize % WORD_SIZE == 0,
+        "stack size must be word aligned"
+    );
+    assert!(
+        GUEST_MIN_MEM + (stack_size as usize) + kb(1) < GUEST_MAX_MEM,
+        "stack size leaves no room for the program"
+    );
+    GUEST_MIN_MEM as u32 + stack_size + kb(1) as u32
+}
+
+const fn parse_stack_size(size: &str) -> u32 {
+    let bytes = size.as_bytes();
+    assert!(!bytes.is_empty(), "RISC0_GUEST_STACK_SIZE is empty");
+    let mut value: u32 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        assert!(
+            digit.is_ascii_digit(),
+            "RISC0_GUEST_STACK_SIZE must be a decimal number of bytes"
+        );
+        value = value * 10 + (digit - b'0') as u32;
+        i += 1;
+    }
+    value
+}
+
 /// Returns whether `addr` is within guest memory bounds.
 pub fn is_guest_memory(addr: u32) -> bool {
     GUEST_MIN_MEM <= (addr as usize) && (addr as usize) < GUEST_MAX_MEM
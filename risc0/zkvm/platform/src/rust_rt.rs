@@ -18,14 +18,11 @@
 //! * It defines an entrypoint ensuring initialization and finalization are done
 //!   properly.
 //! * It includes a panic handler.
-//! * It includes an allocator.
+//! * It includes an allocator, unless the `heap-stats` feature is enabled.
 
-use core::{
-    alloc::{GlobalAlloc, Layout},
-    panic::PanicInfo,
-};
+use core::panic::PanicInfo;
 
-use crate::syscall::{sys_alloc_aligned, sys_panic};
+use crate::syscall::sys_panic;
 
 extern crate alloc;
 
@@ -80,23 +77,30 @@ mod entrypoint {
     );
 }
 
-struct BumpPointerAlloc;
+#[cfg(not(feature = "heap-stats"))]
+mod allocator {
+    use core::alloc::{GlobalAlloc, Layout};
 
-unsafe impl GlobalAlloc for BumpPointerAlloc {
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        sys_alloc_aligned(layout.size(), layout.align())
-    }
+    use crate::syscall::sys_alloc_aligned;
 
-    unsafe fn dealloc(&self, _: *mut u8, _: Layout) {
-        // this allocator never deallocates memory
-    }
+    struct BumpPointerAlloc;
+
+    unsafe impl GlobalAlloc for BumpPointerAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            sys_alloc_aligned(layout.size(), layout.align())
+        }
 
-    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
-        // NOTE: This is safe to avoid zeroing allocated bytes, as the bump allocator does not
-        //       re-use memory and the zkVM memory is zero-initialized.
-        self.alloc(layout)
+        unsafe fn dealloc(&self, _: *mut u8, _: Layout) {
+            // this allocator never deallocates memory
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            // NOTE: This is safe to avoid zeroing allocated bytes, as the bump allocator does not
+            //       re-use memory and the zkVM memory is zero-initialized.
+            self.alloc(layout)
+        }
     }
-}
 
-#[global_allocator]
-static HEAP: BumpPointerAlloc = BumpPointerAlloc;
+    #[global_allocator]
+    static HEAP: BumpPointerAlloc = BumpPointerAlloc;
+}
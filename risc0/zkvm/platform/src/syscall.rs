@@ -128,12 +128,19 @@ macro_rules! declare_syscall {
 pub mod nr {
     declare_syscall!(pub SYS_ARGC);
     declare_syscall!(pub SYS_ARGV);
+    declare_syscall!(pub SYS_CHANNEL);
+    declare_syscall!(pub SYS_CLOCK);
     declare_syscall!(pub SYS_CYCLE_COUNT);
     declare_syscall!(pub SYS_GETENV);
+    declare_syscall!(pub SYS_HEAP_STATS);
     declare_syscall!(pub SYS_LOG);
     declare_syscall!(pub SYS_PANIC);
     declare_syscall!(pub SYS_RANDOM);
     declare_syscall!(pub SYS_READ);
+    declare_syscall!(pub SYS_SEGMENT_CYCLE_COUNT);
+    declare_syscall!(pub SYS_SEGMENT_LIMIT_PO2);
+    declare_syscall!(pub SYS_SESSION_CYCLE_COUNT);
+    declare_syscall!(pub SYS_SESSION_LIMIT);
     declare_syscall!(pub SYS_VERIFY);
     declare_syscall!(pub SYS_VERIFY_INTEGRITY);
     declare_syscall!(pub SYS_WRITE);
@@ -399,6 +406,137 @@ pub unsafe extern "C" fn sys_bigint(
     );
 }
 
+type BigIntWords = [u32; bigint::WIDTH_WORDS];
+
+const BIGINT_ONE: BigIntWords = {
+    let mut one = [0u32; bigint::WIDTH_WORDS];
+    one[0] = 1;
+    one
+};
+
+fn bigint_is_zero(x: &BigIntWords) -> bool {
+    x.iter().all(|word| *word == 0)
+}
+
+fn bigint_add(x: &BigIntWords, y: &BigIntWords) -> (BigIntWords, bool) {
+    let mut out = [0u32; bigint::WIDTH_WORDS];
+    let mut carry = false;
+    for i in 0..bigint::WIDTH_WORDS {
+        let (sum, c1) = x[i].overflowing_add(y[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        out[i] = sum;
+        carry = c1 | c2;
+    }
+    (out, carry)
+}
+
+fn bigint_sub(x: &BigIntWords, y: &BigIntWords) -> (BigIntWords, bool) {
+    let mut out = [0u32; bigint::WIDTH_WORDS];
+    let mut borrow = false;
+    for i in 0..bigint::WIDTH_WORDS {
+        let (diff, b1) = x[i].overflowing_sub(y[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u32);
+        out[i] = diff;
+        borrow = b1 | b2;
+    }
+    (out, borrow)
+}
+
+/// Multiply `x` and `y` with the BigInt accelerator.
+///
+/// The circuit does not constrain the result to be less than a non-zero
+/// `modulus`, so that is checked here; the software routines below rely on
+/// it.
+fn bigint_mulmod(x: &BigIntWords, y: &BigIntWords, modulus: &BigIntWords) -> BigIntWords {
+    let mut out = [0u32; bigint::WIDTH_WORDS];
+    unsafe { sys_bigint(&mut out, bigint::OP_MULTIPLY, x, y, modulus) };
+    assert!(
+        bigint_is_zero(modulus) || bigint_sub(&out, modulus).1,
+        "BigInt result is not reduced"
+    );
+    out
+}
+
+/// Reduce `x` modulo `modulus`, or return it unchanged if `modulus` is zero.
+fn bigint_reduce(x: &BigIntWords, modulus: &BigIntWords) -> BigIntWords {
+    if bigint_is_zero(modulus) {
+        return *x;
+    }
+    bigint_mulmod(x, &BIGINT_ONE, modulus)
+}
+
+/// Compute `(x + y) mod modulus`, or `x + y` wrapping at 2^256 if `modulus`
+/// is zero.
+///
+/// This is a software routine, not a BigInt accelerator op: the inputs are
+/// reduced with the accelerator's multiply ([sys_bigint] with
+/// [bigint::OP_MULTIPLY]) and checked to be less than the modulus, and the
+/// addition is done in software. The inputs do not need to be reduced.
+pub fn bigint_addmod(
+    x: &[u32; bigint::WIDTH_WORDS],
+    y: &[u32; bigint::WIDTH_WORDS],
+    modulus: &[u32; bigint::WIDTH_WORDS],
+) -> [u32; bigint::WIDTH_WORDS] {
+    let x = bigint_reduce(x, modulus);
+    let y = bigint_reduce(y, modulus);
+    let (mut sum, carry) = bigint_add(&x, &y);
+    if !bigint_is_zero(modulus) {
+        let (reduced, borrow) = bigint_sub(&sum, modulus);
+        if carry || !borrow {
+            sum = reduced;
+        }
+    }
+    sum
+}
+
+/// Compute `(x - y) mod modulus`, or `x - y` wrapping at 2^256 if `modulus`
+/// is zero.
+///
+/// This is a software routine, not a BigInt accelerator op: the inputs are
+/// reduced with the accelerator's multiply and checked to be less than the
+/// modulus, and the subtraction is done in software. The inputs do not need
+/// to be reduced.
+pub fn bigint_submod(
+    x: &[u32; bigint::WIDTH_WORDS],
+    y: &[u32; bigint::WIDTH_WORDS],
+    modulus: &[u32; bigint::WIDTH_WORDS],
+) -> [u32; bigint::WIDTH_WORDS] {
+    let x = bigint_reduce(x, modulus);
+    let y = bigint_reduce(y, modulus);
+    let (mut diff, borrow) = bigint_sub(&x, &y);
+    if borrow && !bigint_is_zero(modulus) {
+        diff = bigint_add(&diff, modulus).0;
+    }
+    diff
+}
+
+/// Compute `base^exp mod modulus` by square-and-multiply in software.
+///
+/// This is not a BigInt accelerator op: each modular multiplication is a call
+/// to the accelerator's multiply, so the cost grows with the bit length of
+/// `exp`. If `modulus` is zero the result is not reduced, and execution fails
+/// if it does not fit in 256 bits.
+pub fn bigint_modexp(
+    base: &[u32; bigint::WIDTH_WORDS],
+    exp: &[u32; bigint::WIDTH_WORDS],
+    modulus: &[u32; bigint::WIDTH_WORDS],
+) -> [u32; bigint::WIDTH_WORDS] {
+    let base = bigint_reduce(base, modulus);
+    let mut acc = bigint_reduce(&BIGINT_ONE, modulus);
+
+    let bits = exp
+        .iter()
+        .rposition(|word| *word != 0)
+        .map_or(0, |i| (i + 1) * 32 - exp[i].leading_zeros() as usize);
+    for i in (0..bits).rev() {
+        acc = bigint_mulmod(&acc, &acc, modulus);
+        if (exp[i / 32] >> (i % 32)) & 1 == 1 {
+            acc = bigint_mulmod(&acc, &base, modulus);
+        }
+    }
+    acc
+}
+
 /// # Safety
 ///
 /// `recv_buf` must be aligned and dereferenceable.
@@ -434,6 +572,54 @@ pub extern "C" fn sys_cycle_count() -> usize {
     a0 as usize
 }
 
+/// Returns the host-supplied time in nanoseconds since the UNIX epoch.
+///
+/// The host captures this value once, before the guest starts, so every call
+/// in a session returns the same value.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_clock() -> u64 {
+    let Return(a0, a1) = unsafe { syscall_0(nr::SYS_CLOCK, null_mut(), 0) };
+    ((a1 as u64) << 32) | a0 as u64
+}
+
+/// Returns the number of instruction cycles executed so far in the current
+/// segment.
+///
+/// The count restarts at zero in each segment. It is deterministic for a
+/// given program, input and segment limit, and does not depend on host time.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_segment_cycle_count() -> u32 {
+    let Return(a0, _) = unsafe { syscall_0(nr::SYS_SEGMENT_CYCLE_COUNT, null_mut(), 0) };
+    a0
+}
+
+/// Returns the number of instruction cycles executed so far in the session.
+///
+/// The count does not wrap at `u32::MAX`. It is deterministic for a given
+/// program and input, and does not depend on host time or on how the session
+/// is split into segments.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_session_cycle_count() -> u64 {
+    let Return(a0, a1) = unsafe { syscall_0(nr::SYS_SESSION_CYCLE_COUNT, null_mut(), 0) };
+    ((a1 as u64) << 32) | a0 as u64
+}
+
+/// Returns the log2 of the maximum number of cycles in a segment, as
+/// configured by the host.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_segment_limit_po2() -> u32 {
+    let Return(a0, _) = unsafe { syscall_0(nr::SYS_SEGMENT_LIMIT_PO2, null_mut(), 0) };
+    a0
+}
+
+/// Returns the maximum number of user cycles the host allows in the current
+/// session, or u64::MAX if the session is unlimited.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub extern "C" fn sys_session_limit() -> u64 {
+    let Return(a0, a1) = unsafe { syscall_0(nr::SYS_SESSION_LIMIT, null_mut(), 0) };
+    ((a1 as u64) << 32) | a0 as u64
+}
+
 /// Reads the given number of bytes into the given buffer, posix-style.  Returns
 /// the number of bytes actually read.  On end of file, returns 0.
 ///
@@ -632,6 +818,21 @@ pub unsafe extern "C" fn sys_getenv(
     }
 }
 
+/// Looks up the file descriptor of the named input channel registered by the
+/// host.
+///
+/// Returns the file descriptor, which can be passed to [sys_read], or
+/// u32::MAX if the host has no channel with that name.
+///
+/// # Safety
+///
+/// `name` must be dereferenceable for `name_len` bytes.
+#[cfg_attr(feature = "export-syscalls", no_mangle)]
+pub unsafe extern "C" fn sys_channel(name: *const u8, name_len: usize) -> u32 {
+    let Return(a0, _) = syscall_2(nr::SYS_CHANNEL, null_mut(), 0, name as u32, name_len as u32);
+    a0
+}
+
 /// Retrieves the count of arguments provided to program execution.
 ///
 /// NOTE: Repeated calls to sys_argc are not guaranteed to result in the same
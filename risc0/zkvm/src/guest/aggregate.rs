@@ -0,0 +1,58 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The body of a guest that aggregates the receipts of other guests.
+//!
+//! The `risc0-zkvm-aggregation` crate provides a prebuilt aggregation guest as
+//! `AGGREGATE_ELF`, with image ID `AGGREGATE_ID`. A custom aggregation guest is
+//! a single line:
+//!
+//! ```rust,ignore
+//! #![no_main]
+//! #![no_std]
+//!
+//! risc0_zkvm::entry!(main);
+//!
+//! fn main() {
+//!     risc0_zkvm::guest::aggregate::run();
+//! }
+//! ```
+//!
+//! The host proves it with `Prover::aggregate`.
+
+use alloc::vec::Vec;
+
+use crate::{
+    aggregation::{AggregatedClaim, Aggregation},
+    guest::env,
+    sha::Digest,
+};
+
+/// Verify the executions named in the input and commit an [Aggregation] of
+/// them.
+///
+/// The input is a list of image IDs, each with the journal of an execution of
+/// that guest. Each execution must have exited with `Halted(0)` or
+/// `Paused(0)`.
+pub fn run() {
+    let input: Vec<(Digest, Vec<u8>)> = env::read();
+    let claims = input
+        .iter()
+        .map(|(image_id, journal)| {
+            env::verify(*image_id, journal.as_slice()).unwrap();
+            AggregatedClaim::new(*image_id, journal)
+        })
+        .collect();
+    env::commit(&Aggregation { claims });
+}
@@ -0,0 +1,403 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checked integer arithmetic that terminates the guest on failure.
+//!
+//! Guests handling balances and other amounts should not rely on wrapping
+//! arithmetic or ad-hoc asserts. The functions in this module return the
+//! result of the operation, or panic with a stable, structured message when the
+//! operation overflows or divides by zero. The message is reported to the host
+//! as the guest's panic payload, so every failure has the same auditable form:
+//!
+//! ```text
+//! checked arithmetic failure: code=1 reason=overflow op=add type=u64
+//! ```
+//!
+//! The panic is reported at the caller of the checked function. On the host,
+//! the failure can be recovered as a typed [ArithError] with
+//! [ArithError::decode], which is how `GuestPanic::arith_error` is populated.
+//!
+//! Use the `try_*` variants to handle the failure in the guest instead.
+//!
+//! # Example
+//!
+//! ```
+//! use risc0_zkvm::guest::checked;
+//!
+//! let total = checked::add(40u64, 2);
+//! assert_eq!(total, 42);
+//! assert_eq!(
+//!     checked::try_div(1u32, 0),
+//!     Err(checked::ArithError::new(checked::Reason::DivisionByZero, checked::Op::Div, "u32"))
+//! );
+//! ```
+
+use core::fmt;
+
+/// The reason a checked operation failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// The result does not fit in the operand type.
+    Overflow,
+    /// The divisor was zero.
+    DivisionByZero,
+}
+
+impl Reason {
+    /// Returns the stable numeric code of this reason.
+    pub const fn code(self) -> u32 {
+        match self {
+            Reason::Overflow => 1,
+            Reason::DivisionByZero => 2,
+        }
+    }
+
+    /// Returns the stable name of this reason.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Reason::Overflow => "overflow",
+            Reason::DivisionByZero => "division_by_zero",
+        }
+    }
+
+    /// Returns the reason with the given numeric code, if any.
+    pub const fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(Reason::Overflow),
+            2 => Some(Reason::DivisionByZero),
+            _ => None,
+        }
+    }
+}
+
+/// The operation that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Addition.
+    Add,
+    /// Subtraction.
+    Sub,
+    /// Multiplication.
+    Mul,
+    /// Division.
+    Div,
+}
+
+impl Op {
+    /// Returns the stable name of this operation.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::Mul => "mul",
+            Op::Div => "div",
+        }
+    }
+
+    /// Returns the operation with the given name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "add" => Some(Op::Add),
+            "sub" => Some(Op::Sub),
+            "mul" => Some(Op::Mul),
+            "div" => Some(Op::Div),
+            _ => None,
+        }
+    }
+}
+
+const PREFIX: &str = "checked arithmetic failure: ";
+
+const TYPE_NAMES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// A failed checked operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArithError {
+    /// Why the operation failed.
+    pub reason: Reason,
+    /// The operation that failed.
+    pub op: Op,
+    /// The name of the operand type, e.g. `u64`.
+    pub ty: &'static str,
+}
+
+impl ArithError {
+    /// Construct a new [ArithError].
+    pub const fn new(reason: Reason, op: Op, ty: &'static str) -> Self {
+        Self { reason, op, ty }
+    }
+
+    /// Decode an [ArithError] from its encoding, i.e. its [Display](fmt::Display)
+    /// output, as found in a guest panic message.
+    ///
+    /// Returns `None` if `msg` is not a checked arithmetic failure.
+    pub fn decode(msg: &str) -> Option<Self> {
+        let mut fields = msg.strip_prefix(PREFIX)?.split(' ');
+        let mut field = |key: &str| fields.next()?.strip_prefix(key)?.strip_prefix('=');
+        let code = field("code")?.parse().ok()?;
+        let reason = Reason::from_code(code)?;
+        if field("reason")? != reason.as_str() {
+            return None;
+        }
+        let op = Op::from_name(field("op")?)?;
+        let ty = field("type")?;
+        let ty = TYPE_NAMES.iter().copied().find(|name| *name == ty)?;
+        if fields.next().is_some() {
+            return None;
+        }
+        Some(Self::new(reason, op, ty))
+    }
+}
+
+impl fmt::Display for ArithError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{PREFIX}code={} reason={} op={} type={}",
+            self.reason.code(),
+            self.reason.as_str(),
+            self.op.as_str(),
+            self.ty
+        )
+    }
+}
+
+/// Integer types supported by the functions in this module.
+pub trait CheckedInt: Copy + sealed::Sealed {
+    /// The name of the type, as reported in an [ArithError].
+    const NAME: &'static str;
+
+    #[doc(hidden)]
+    fn add(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn sub(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn mul(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn div(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn is_zero(self) -> bool;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_checked_int {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl CheckedInt for $ty {
+                const NAME: &'static str = stringify!($ty);
+
+                fn add(self, rhs: Self) -> Option<Self> {
+                    self.checked_add(rhs)
+                }
+
+                fn sub(self, rhs: Self) -> Option<Self> {
+                    self.checked_sub(rhs)
+                }
+
+                fn mul(self, rhs: Self) -> Option<Self> {
+                    self.checked_mul(rhs)
+                }
+
+                fn div(self, rhs: Self) -> Option<Self> {
+                    self.checked_div(rhs)
+                }
+
+                fn is_zero(self) -> bool {
+                    self == 0
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+fn overflow<T: CheckedInt>(op: Op) -> ArithError {
+    ArithError::new(Reason::Overflow, op, T::NAME)
+}
+
+/// Returns `a + b`, or an [ArithError] on overflow.
+pub fn try_add<T: CheckedInt>(a: T, b: T) -> Result<T, ArithError> {
+    a.add(b).ok_or_else(|| overflow::<T>(Op::Add))
+}
+
+/// Returns `a - b`, or an [ArithError] on overflow.
+pub fn try_sub<T: CheckedInt>(a: T, b: T) -> Result<T, ArithError> {
+    a.sub(b).ok_or_else(|| overflow::<T>(Op::Sub))
+}
+
+/// Returns `a * b`, or an [ArithError] on overflow.
+pub fn try_mul<T: CheckedInt>(a: T, b: T) -> Result<T, ArithError> {
+    a.mul(b).ok_or_else(|| overflow::<T>(Op::Mul))
+}
+
+/// Returns `a / b`, or an [ArithError] if `b` is zero or the division
+/// overflows (e.g. `i64::MIN / -1`).
+pub fn try_div<T: CheckedInt>(a: T, b: T) -> Result<T, ArithError> {
+    if b.is_zero() {
+        return Err(ArithError::new(Reason::DivisionByZero, Op::Div, T::NAME));
+    }
+    a.div(b).ok_or_else(|| overflow::<T>(Op::Div))
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn fail(err: ArithError) -> ! {
+    panic!("{err}")
+}
+
+/// Returns `a + b`, panicking with a structured message on overflow.
+#[track_caller]
+pub fn add<T: CheckedInt>(a: T, b: T) -> T {
+    match try_add(a, b) {
+        Ok(val) => val,
+        Err(err) => fail(err),
+    }
+}
+
+/// Returns `a - b`, panicking with a structured message on overflow.
+#[track_caller]
+pub fn sub<T: CheckedInt>(a: T, b: T) -> T {
+    match try_sub(a, b) {
+        Ok(val) => val,
+        Err(err) => fail(err),
+    }
+}
+
+/// Returns `a * b`, panicking with a structured message on overflow.
+#[track_caller]
+pub fn mul<T: CheckedInt>(a: T, b: T) -> T {
+    match try_mul(a, b) {
+        Ok(val) => val,
+        Err(err) => fail(err),
+    }
+}
+
+/// Returns `a / b`, panicking with a structured message if `b` is zero or the
+/// division overflows.
+#[track_caller]
+pub fn div<T: CheckedInt>(a: T, b: T) -> T {
+    match try_div(a, b) {
+        Ok(val) => val,
+        Err(err) => fail(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_range() {
+        assert_eq!(add(u64::MAX - 1, 1), u64::MAX);
+        assert_eq!(sub(5u32, 5), 0);
+        assert_eq!(mul(-3i64, 7), -21);
+        assert_eq!(div(u128::MAX, 2), u128::MAX >> 1);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            try_add(u8::MAX, 1),
+            Err(ArithError::new(Reason::Overflow, Op::Add, "u8"))
+        );
+        assert_eq!(
+            try_sub(0u32, 1),
+            Err(ArithError::new(Reason::Overflow, Op::Sub, "u32"))
+        );
+        assert_eq!(
+            try_div(i64::MIN, -1),
+            Err(ArithError::new(Reason::Overflow, Op::Div, "i64"))
+        );
+        assert_eq!(
+            try_div(7u64, 0),
+            Err(ArithError::new(Reason::DivisionByZero, Op::Div, "u64"))
+        );
+    }
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            ArithError::new(Reason::Overflow, Op::Mul, "u64").to_string(),
+            "checked arithmetic failure: code=1 reason=overflow op=mul type=u64"
+        );
+    }
+
+    #[test]
+    fn decode() {
+        for err in [
+            ArithError::new(Reason::Overflow, Op::Add, "u8"),
+            ArithError::new(Reason::Overflow, Op::Mul, "i128"),
+            ArithError::new(Reason::DivisionByZero, Op::Div, "usize"),
+        ] {
+            assert_eq!(ArithError::decode(&err.to_string()), Some(err));
+        }
+        assert_eq!(ArithError::decode("boom"), None);
+        assert_eq!(
+            ArithError::decode("checked arithmetic failure: code=2 reason=overflow op=add type=u8"),
+            None
+        );
+        assert_eq!(
+            ArithError::decode(
+                "checked arithmetic failure: code=1 reason=overflow op=add type=f32"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn panic_location_is_caller() {
+        use std::{cell::RefCell, panic};
+
+        thread_local! {
+            static LOCATION: RefCell<Option<(String, u32)>> = const { RefCell::new(None) };
+        }
+
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(|info| {
+            let location = info.location().map(|loc| (loc.file().into(), loc.line()));
+            LOCATION.with(|cell| *cell.borrow_mut() = location);
+        }));
+        let line = line!() + 1;
+        let result = panic::catch_unwind(|| sub(0u16, 1));
+        panic::set_hook(prev_hook);
+
+        assert!(result.is_err());
+        assert_eq!(
+            LOCATION.with(|cell| cell.take()),
+            Some((file!().into(), line))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "checked arithmetic failure: code=1 reason=overflow op=mul type=u64")]
+    fn mul_overflow_panics() {
+        mul(u64::MAX, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "code=2 reason=division_by_zero op=div type=u32")]
+    fn div_by_zero_panics() {
+        div(1u32, 0);
+    }
+}
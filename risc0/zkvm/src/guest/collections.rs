@@ -0,0 +1,435 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Deterministic collections for the guest.
+//!
+//! The standard library's `HashMap` and `HashSet` use `RandomState`, which
+//! seeds every map from `sys_random`. In the zkVM this costs a host call and
+//! makes iteration order vary between runs of the same program. The
+//! [HashMap] and [HashSet] aliases here use [ZkHasher], a fixed-seed hasher
+//! that operates on 32-bit words, so they are both cheaper and deterministic.
+//! Because the aliases use a custom hasher, construct them with
+//! `Default::default()` or `with_capacity_and_hasher` instead of `new`.
+//!
+//! For small to medium sized maps, [VecMap] and [VecSet] keep their entries
+//! sorted in a single contiguous allocation. Lookups use binary search, and a
+//! traversal touches far fewer pages than following the nodes of a
+//! [BTreeMap], which reduces paging cycles. Insertion and removal are `O(n)`,
+//! so prefer [BTreeMap] for large maps that change often.
+//!
+//! WARNING: [ZkHasher] is not resistant to collisions chosen by an adversary.
+//! If keys come from untrusted input, an attacker can make lookups slow, which
+//! increases the cycle count of the guest. Use [BTreeMap] or [VecMap] for such
+//! keys.
+
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+pub use alloc::collections::{BTreeMap, BTreeSet};
+
+/// A [HashMap](std::collections::HashMap) which uses [ZkHasher].
+#[cfg(feature = "std")]
+pub type HashMap<K, V> = std::collections::HashMap<K, V, BuildZkHasher>;
+
+/// A [HashSet](std::collections::HashSet) which uses [ZkHasher].
+#[cfg(feature = "std")]
+pub type HashSet<T> = std::collections::HashSet<T, BuildZkHasher>;
+
+/// A [BuildHasher](core::hash::BuildHasher) for [ZkHasher].
+pub type BuildZkHasher = BuildHasherDefault<ZkHasher>;
+
+const SEED: u32 = 0x9e37_79b9;
+
+/// A fast, deterministic, non-cryptographic hasher.
+///
+/// This uses the same construction as FxHash, on 32-bit words, which are
+/// native to the zkVM.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZkHasher {
+    hash: u32,
+}
+
+impl ZkHasher {
+    #[inline]
+    fn add_word(&mut self, word: u32) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for ZkHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(4);
+        for chunk in chunks.by_ref() {
+            self.add_word(u32::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut word = [0u8; 4];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add_word(u32::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_word(i as u32);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_word(i as u32);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_word(i);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_word(i as u32);
+        self.add_word((i >> 32) as u32);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        // Hash tables on 64-bit hosts take control bits from the top of the
+        // hash, so repeat the state in both halves.
+        ((self.hash as u64) << 32) | self.hash as u64
+    }
+}
+
+/// A map backed by a sorted vector.
+///
+/// See the [module documentation](self) for when to prefer this over
+/// [BTreeMap]. Iteration is in key order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VecMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> VecMap<K, V> {
+    /// Construct an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct an empty map with space for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn find<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Return a reference to the value for `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).ok().map(|idx| &self.entries[idx].1)
+    }
+
+    /// Return a mutable reference to the value for `key`.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).ok().map(|idx| &mut self.entries[idx].1)
+    }
+
+    /// Return true if the map has an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_ok()
+    }
+
+    /// Insert `value` for `key`, returning the previous value if there was
+    /// one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.find(&key) {
+            Ok(idx) => Some(core::mem::replace(&mut self.entries[idx].1, value)),
+            Err(idx) => {
+                self.entries.insert(idx, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove the entry for `key`, returning its value if there was one.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).ok().map(|idx| self.entries.remove(idx).1)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Iterate over the entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Iterate over the keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for VecMap<K, V> {
+    /// Collect entries into a map. If a key appears more than once, the last
+    /// value wins, as with [VecMap::insert].
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        // A stable sort keeps duplicates in insertion order, so keep the last.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut deduped: Vec<(K, V)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match deduped.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+        Self { entries: deduped }
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// A set backed by a sorted vector.
+///
+/// See the [module documentation](self) for when to prefer this over
+/// [BTreeSet]. Iteration is in order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VecSet<T> {
+    map: VecMap<T, ()>,
+}
+
+impl<T> Default for VecSet<T> {
+    fn default() -> Self {
+        Self {
+            map: VecMap::default(),
+        }
+    }
+}
+
+impl<T: Ord> VecSet<T> {
+    /// Construct an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct an empty set with space for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: VecMap::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return true if the set has no values.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return true if the set contains `value`.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Add `value` to the set, returning true if it was not already present.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Remove `value` from the set, returning true if it was present.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Remove all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterate over the values in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.map.keys()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for VecSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            map: iter.into_iter().map(|value| (value, ())).collect(),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for VecSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> IntoIterator for VecSet<T> {
+    type Item = T;
+    type IntoIter = core::iter::Map<alloc::vec::IntoIter<(T, ())>, fn((T, ())) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter().map(|(value, ())| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::{BuildHasher, Hash};
+
+    use super::*;
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        BuildZkHasher::default().hash_one(value)
+    }
+
+    #[test]
+    fn hasher_is_deterministic() {
+        assert_eq!(hash(&"hello"), hash(&"hello"));
+        assert_ne!(hash(&"hello"), hash(&"hellp"));
+        assert_ne!(hash(&1u64), hash(&(1u64 << 32)));
+        assert_ne!(hash(&[1u8, 2, 3]), hash(&[1u8, 2, 3, 0]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_map_order_is_stable() {
+        let keys: Vec<u32> = (0..100).map(|i| i * 7919).collect();
+        let a: HashMap<u32, u32> = keys.iter().map(|&k| (k, k)).collect();
+        let b: HashMap<u32, u32> = keys.iter().rev().map(|&k| (k, k)).collect();
+        assert_eq!(a.len(), 100);
+        let order_a: Vec<_> = a.keys().copied().collect();
+        let order_b: Vec<_> = b.keys().copied().collect();
+        assert_eq!(order_a, order_b);
+    }
+
+    #[test]
+    fn vec_map() {
+        let mut map = VecMap::new();
+        assert_eq!(map.insert(3, "c"), None);
+        assert_eq!(map.insert(1, "a"), None);
+        assert_eq!(map.insert(2, "b"), None);
+        assert_eq!(map.insert(1, "A"), Some("a"));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&1), Some(&"A"));
+        assert!(map.contains_key(&2));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(map.remove(&2), Some("b"));
+        assert_eq!(map.remove(&2), None);
+        *map.get_mut(&3).unwrap() = "C";
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            vec![(1, "A"), (3, "C")]
+        );
+
+        let map: VecMap<_, _> = [(2, 'x'), (1, 'y'), (2, 'z')].into_iter().collect();
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&1, &'y'), (&2, &'z')]);
+    }
+
+    #[test]
+    fn vec_set() {
+        let mut set: VecSet<_> = [5, 1, 3, 1].into_iter().collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.insert(4));
+        assert!(!set.insert(4));
+        assert!(set.remove(&1));
+        assert!(!set.contains(&1));
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+}
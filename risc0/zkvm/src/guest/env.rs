@@ -47,7 +47,12 @@
 //!
 //! Convenience functions to read and write to default file descriptors are
 //! provided. See [read], [write][write()], [commit] (and their `_slice`
-//! variants) for more information.
+//! variants) for more information. Inputs that come from an untrusted party
+//! can be read with [read_frame], which bounds the amount of data accepted and
+//! reports malformed input as an error instead of panicking.
+//!
+//! Hosts may also provide named input channels, which can be opened with
+//! [channel] and read independently of stdin.
 //!
 //! In order to access default file descriptors directly, see [stdin], [stdout],
 //! [stderr] and [journal]. These file descriptors are either [FdReader] or
@@ -68,14 +73,17 @@
 //! [proof composition]:https://www.risczero.com/blog/proof-composition
 //! [guest-optimization]: https://dev.risczero.com/api/zkvm/optimization#when-reading-data-as-raw-bytes-use-envread_slice
 
+use alloc::{vec, vec::Vec};
 use core::{cell::OnceCell, fmt, mem::MaybeUninit};
 
 use bytemuck::Pod;
 use risc0_zkvm_platform::{
     align_up, fileno,
     syscall::{
-        self, sys_alloc_words, sys_cycle_count, sys_halt, sys_input, sys_log, sys_pause, sys_read,
-        sys_read_words, sys_verify, sys_verify_integrity, sys_write, syscall_2, SyscallName,
+        self, sys_alloc_words, sys_channel, sys_clock, sys_cycle_count, sys_halt, sys_input,
+        sys_log, sys_pause, sys_read, sys_read_words, sys_segment_cycle_count,
+        sys_segment_limit_po2, sys_session_cycle_count, sys_session_limit, sys_verify,
+        sys_verify_integrity, sys_write, syscall_2, SyscallName,
     },
     WORD_SIZE,
 };
@@ -114,6 +122,9 @@ pub(crate) fn init() {
 }
 
 pub(crate) fn finalize(halt: bool, user_exit: u8) {
+    #[cfg(feature = "heap-stats")]
+    crate::guest::heap::report();
+
     unsafe {
         let hasher = HASHER.take();
         let journal_digest: Digest = hasher.unwrap().finalize().as_slice().try_into().unwrap();
@@ -415,6 +426,73 @@ pub fn read_slice<T: Pod>(slice: &mut [T]) {
     stdin().read_slice(slice)
 }
 
+/// Read a length-prefixed frame of bytes from the STDIN of the zkVM.
+///
+/// A frame is a little-endian `u32` byte length followed by that many bytes.
+/// On the host, it can be written with `ExecutorEnvBuilder::write_frame`.
+///
+/// Unlike [read], this function does not panic on malformed input. Frames
+/// longer than `max_len` are rejected before any buffer is allocated, so a
+/// guest processing adversarial input can handle the [FrameError] and commit
+/// a rejection result instead of failing to produce a receipt.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::guest::env;
+///
+/// match env::read_frame(1024) {
+///     Ok(frame) => env::commit(&(true, frame)),
+///     Err(_) => env::commit(&(false, Vec::<u8>::new())),
+/// }
+/// ```
+pub fn read_frame(max_len: usize) -> Result<Vec<u8>, FrameError> {
+    stdin().read_frame(max_len)
+}
+
+/// Error encountered during a call to [read_frame].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameError {
+    /// The input ended before the length prefix could be read.
+    MissingLength,
+
+    /// The length prefix exceeds the maximum accepted frame length.
+    ///
+    /// The frame payload is left unread.
+    TooLarge {
+        /// Length given by the prefix.
+        len: usize,
+        /// Maximum accepted length.
+        max_len: usize,
+    },
+
+    /// The input ended before the full frame payload was read.
+    Truncated {
+        /// Length given by the prefix.
+        len: usize,
+        /// Number of bytes actually read.
+        read: usize,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingLength => write!(f, "input ended before frame length"),
+            Self::TooLarge { len, max_len } => {
+                write!(f, "frame length {len} exceeds maximum of {max_len}")
+            }
+            Self::Truncated { len, read } => {
+                write!(f, "frame truncated: expected {len} bytes, got {read}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
 /// Serialize the given data and write it to the STDOUT of the zkVM.
 ///
 /// This is available to the host as the private output on the prover.
@@ -543,6 +621,66 @@ pub fn cycle_count() -> usize {
     sys_cycle_count()
 }
 
+/// Return the time supplied by the host, as a duration since the UNIX epoch.
+///
+/// The host decides the time with [ClockPolicy](crate::ClockPolicy), and it
+/// stays the same for the whole session. Use this for timestamps, not to
+/// measure elapsed time.
+///
+/// WARNING: The time is provided by the host and is not checked by the zkVM circuit.
+pub fn time() -> core::time::Duration {
+    core::time::Duration::from_nanos(sys_clock())
+}
+
+/// Return the number of instruction cycles executed so far in the current
+/// segment.
+///
+/// This counter resets to zero at the start of every segment, so its value
+/// depends on the segment limit chosen by the host as well as on the guest
+/// program and its inputs. It does not depend on host time.
+///
+/// WARNING: The cycle count is provided by the host and is not checked by the zkVM circuit.
+pub fn segment_cycle_count() -> u32 {
+    sys_segment_cycle_count()
+}
+
+/// Return the number of instruction cycles executed so far in the session.
+///
+/// Unlike [cycle_count], this counter does not wrap after `u32::MAX` cycles.
+/// For a given program and input the value is deterministic: it does not
+/// depend on host time or on how the session is split into segments, which
+/// makes it suitable for iteration caps and rate limiting.
+///
+/// WARNING: The cycle count is provided by the host and is not checked by the zkVM circuit.
+pub fn session_cycle_count() -> u64 {
+    sys_session_cycle_count()
+}
+
+/// Return the log2 of the maximum number of cycles in a segment.
+///
+/// The executor splits the session into segments of at most this size on its
+/// own; the value is useful for sizing work so that it fits in few segments.
+///
+/// WARNING: The limit is provided by the host and is not checked by the zkVM circuit.
+pub fn segment_limit_po2() -> u32 {
+    sys_segment_limit_po2()
+}
+
+/// Return the maximum number of instruction cycles the host allows in the
+/// current session, or `None` if the session is unlimited.
+///
+/// Execution fails once [session_cycle_count] reaches this limit. A guest can
+/// [pause] before that point and continue in a new session, whose cycle count
+/// starts again from zero; see [TimeBox](crate::guest::timebox::TimeBox).
+///
+/// WARNING: The limit is provided by the host and is not checked by the zkVM circuit.
+pub fn session_limit() -> Option<u64> {
+    match sys_session_limit() {
+        u64::MAX => None,
+        limit => Some(limit),
+    }
+}
+
 /// Print a message to the debug console.
 pub fn log(msg: &str) {
     let msg = msg.as_bytes();
@@ -573,6 +711,31 @@ pub fn stdin() -> FdReader {
     FdReader::new(fileno::STDIN)
 }
 
+/// Return a reader for the named input channel provided by the host.
+///
+/// Each channel is read independently of stdin and of other channels, so
+/// guests can consume inputs in whatever order suits them.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::guest::env::{self, Read};
+///
+/// let mut prices = [0u8; 3];
+/// env::channel("prices").read_slice(&mut prices);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the host did not provide a channel with the given name.
+pub fn channel(name: &str) -> FdReader {
+    let fd = unsafe { sys_channel(name.as_ptr(), name.len()) };
+    if fd == u32::MAX {
+        panic!("input channel not found: {name}");
+    }
+    FdReader::new(fd)
+}
+
 /// Reads and deserializes objects
 pub trait Read {
     /// Read data from the host.
@@ -623,6 +786,25 @@ impl FdReader {
 
         tot_read
     }
+
+    /// Read a length-prefixed frame of bytes. See [read_frame] for details.
+    pub fn read_frame(&mut self, max_len: usize) -> Result<Vec<u8>, FrameError> {
+        let mut len_bytes = [0u8; 4];
+        if self.read_bytes_all(&mut len_bytes) != len_bytes.len() {
+            return Err(FrameError::MissingLength);
+        }
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        if len > max_len {
+            return Err(FrameError::TooLarge { len, max_len });
+        }
+
+        let mut buf = vec![0u8; len];
+        let read = self.read_bytes_all(&mut buf);
+        if read != len {
+            return Err(FrameError::Truncated { len, read });
+        }
+        Ok(buf)
+    }
 }
 
 impl Read for FdReader {
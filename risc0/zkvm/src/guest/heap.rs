@@ -0,0 +1,162 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Heap usage tracking for the guest.
+//!
+//! When the `heap-stats` feature is enabled, this module provides the global
+//! allocator for the guest. It uses the same bump allocator as the default
+//! allocator, and additionally counts allocations. The collected [HeapUsage] is
+//! reported to the host, where it is available as the `heap` field of
+//! `SessionStats`, at the end of execution and whenever the heap grows into
+//! another page. The host uses the latter reports to find the heap in use at
+//! the end of each segment. If an allocation fails, the usage up to that point
+//! is reported before the guest panics.
+//!
+//! Note that memory is never reused by the bump allocator, so the peak heap
+//! size is the total amount of memory allocated by the guest. The peak number
+//! of live bytes indicates how much memory the guest would need with an
+//! allocator that reuses freed memory.
+
+use core::alloc::{GlobalAlloc, Layout};
+
+use risc0_zkvm_platform::{syscall::sys_alloc_aligned, PAGE_SIZE};
+
+/// Statistics about the guest's use of the heap.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeapUsage {
+    /// Number of bytes of heap used, including allocations made directly with
+    /// `sys_alloc_words` or `sys_alloc_aligned`.
+    pub peak_heap: usize,
+
+    /// Number of bytes currently allocated and not yet deallocated.
+    pub live_bytes: usize,
+
+    /// Peak number of bytes allocated and not yet deallocated.
+    pub peak_live_bytes: usize,
+
+    /// Total number of allocations.
+    pub total_allocs: usize,
+
+    /// Total number of deallocations.
+    pub total_deallocs: usize,
+}
+
+static mut STATS: HeapUsage = HeapUsage {
+    peak_heap: 0,
+    live_bytes: 0,
+    peak_live_bytes: 0,
+    total_allocs: 0,
+    total_deallocs: 0,
+};
+
+struct TrackingAlloc;
+
+unsafe impl GlobalAlloc for TrackingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: Single threaded, so nothing else can touch this while we're working.
+        let stats = unsafe { &mut STATS };
+        stats.total_allocs += 1;
+        stats.live_bytes += layout.size();
+        stats.peak_live_bytes = stats.peak_live_bytes.max(stats.live_bytes);
+
+        let start = heap_pos();
+        if alloc_fails(start, layout) {
+            // The allocation below panics, so this is the last chance to report.
+            report();
+        }
+        let ptr = sys_alloc_aligned(layout.size(), layout.align());
+        if heap_pos() / PAGE_SIZE != start / PAGE_SIZE {
+            report();
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, _: *mut u8, layout: Layout) {
+        // this allocator never deallocates memory, but the deallocation is counted
+        let stats = unsafe { &mut STATS };
+        stats.total_deallocs += 1;
+        stats.live_bytes -= layout.size();
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // NOTE: This is safe to avoid zeroing allocated bytes, as the bump allocator does not
+        //       re-use memory and the zkVM memory is zero-initialized.
+        self.alloc(layout)
+    }
+}
+
+#[cfg(target_os = "zkvm")]
+#[global_allocator]
+static HEAP: TrackingAlloc = TrackingAlloc;
+
+/// Returns the current [HeapUsage] of the guest.
+pub fn stats() -> HeapUsage {
+    // SAFETY: Single threaded, so nothing else can touch this while we're working.
+    let mut stats = unsafe { STATS };
+    stats.peak_heap = heap_pos() - heap_start();
+    stats
+}
+
+/// Returns the current position of the bump allocator.
+fn heap_pos() -> usize {
+    // A zero-sized allocation returns the current position of the heap.
+    #[cfg(target_os = "zkvm")]
+    {
+        unsafe { sys_alloc_aligned(0, risc0_zkvm_platform::WORD_SIZE) as usize }
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    0
+}
+
+/// Returns the start of the heap.
+fn heap_start() -> usize {
+    #[cfg(target_os = "zkvm")]
+    {
+        extern "C" {
+            // This symbol is defined by the loader and marks the start of the heap.
+            static _end: u8;
+        }
+        unsafe { core::ptr::addr_of!(_end) as usize }
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    0
+}
+
+/// Returns whether allocating `layout` at heap position `pos` would run into
+/// system memory, which makes `sys_alloc_aligned` panic.
+fn alloc_fails(pos: usize, layout: Layout) -> bool {
+    let align = layout.align().max(risc0_zkvm_platform::WORD_SIZE);
+    let start = (pos + align - 1) & !(align - 1);
+    start.checked_add(layout.size()).map_or(true, |end| {
+        risc0_zkvm_platform::memory::SYSTEM.start() < end
+    })
+}
+
+/// Report the [HeapUsage] to the host.
+pub(crate) fn report() {
+    use risc0_zkvm_platform::syscall::{nr::SYS_HEAP_STATS, syscall_4};
+
+    let stats = stats();
+    unsafe {
+        syscall_4(
+            SYS_HEAP_STATS,
+            core::ptr::null_mut(),
+            0,
+            stats.peak_heap as u32,
+            stats.peak_live_bytes as u32,
+            stats.total_allocs as u32,
+            stats.total_deallocs as u32,
+        );
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integer and fixed-point math routines for the guest.
+//!
+//! The zkVM has no floating point unit, so floating point operations are
+//! emulated in software and cost many cycles each. The routines in this module
+//! use only integer arithmetic, and avoid division where possible.
+//!
+//! Trigonometric functions take angles as binary angles, where a full turn is
+//! `2^32`, and return values as signed Q16.16 fixed-point numbers, where
+//! [ONE] represents `1.0`. For example, `sin(QUARTER_TURN)` returns [ONE].
+
+/// Number of fractional bits in the fixed-point values used by this module.
+pub const FRAC_BITS: u32 = 16;
+
+/// The fixed-point representation of `1.0`.
+pub const ONE: i32 = 1 << FRAC_BITS;
+
+/// A quarter turn (90 degrees) as a binary angle.
+pub const QUARTER_TURN: u32 = 1 << 30;
+
+/// A half turn (180 degrees) as a binary angle.
+pub const HALF_TURN: u32 = 1 << 31;
+
+// sin(i * pi / 128) for i in 0..=64, in Q16.16.
+const SIN_TABLE: [i32; 65] = [
+    0, 1608, 3216, 4821, 6424, 8022, 9616, 11204, 12785, 14359, 15924, 17479, 19024, 20557, 22078,
+    23586, 25080, 26558, 28020, 29466, 30893, 32303, 33692, 35062, 36410, 37736, 39040, 40320,
+    41576, 42806, 44011, 45190, 46341, 47464, 48559, 49624, 50660, 51665, 52639, 53581, 54491,
+    55368, 56212, 57022, 57798, 58538, 59244, 59914, 60547, 61145, 61705, 62228, 62714, 63162,
+    63572, 63944, 64277, 64571, 64827, 65043, 65220, 65358, 65457, 65516, 65536,
+];
+
+/// Returns the integer square root of `n`, rounded down.
+pub fn isqrt(n: u64) -> u64 {
+    let mut rem = n;
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+    while bit > n {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+/// Returns the base 2 logarithm of `n`, rounded down.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn log2_floor(n: u64) -> u32 {
+    n.ilog2()
+}
+
+/// Returns the base 2 logarithm of `n`, rounded up.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn log2_ceil(n: u64) -> u32 {
+    assert!(n != 0, "log2_ceil of zero");
+    if n == 1 {
+        0
+    } else {
+        (n - 1).ilog2() + 1
+    }
+}
+
+/// Multiplies two Q16.16 fixed-point numbers.
+pub fn mul_fixed(a: i32, b: i32) -> i32 {
+    ((a as i64 * b as i64) >> FRAC_BITS) as i32
+}
+
+/// Returns the sine of the binary angle `angle` as a Q16.16 fixed-point number.
+///
+/// The result is linearly interpolated from a 64 entry per quadrant table, and
+/// is within `8 / ONE` of the exact value.
+pub fn sin(angle: u32) -> i32 {
+    let quadrant = angle >> 30;
+    let mut phase = angle & (QUARTER_TURN - 1);
+    if quadrant & 1 == 1 {
+        phase = QUARTER_TURN - phase;
+    }
+    let value = sin_quarter(phase);
+    if quadrant & 2 == 2 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Returns the cosine of the binary angle `angle` as a Q16.16 fixed-point
+/// number.
+///
+/// See [sin] for details on accuracy.
+pub fn cos(angle: u32) -> i32 {
+    sin(angle.wrapping_add(QUARTER_TURN))
+}
+
+// Evaluates sine for a phase in [0, QUARTER_TURN].
+fn sin_quarter(phase: u32) -> i32 {
+    let idx = (phase >> 24) as usize;
+    if idx == SIN_TABLE.len() - 1 {
+        return SIN_TABLE[idx];
+    }
+    let frac = ((phase >> 8) & 0xffff) as i32;
+    let lo = SIN_TABLE[idx];
+    let hi = SIN_TABLE[idx + 1];
+    lo + (((hi - lo) * frac) >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isqrt_bounds() {
+        for n in (0..100_000u64).chain([u32::MAX as u64, u64::MAX - 1, u64::MAX]) {
+            let root = isqrt(n);
+            assert!(root * root <= n, "isqrt({n}) = {root}");
+            assert!(
+                (root + 1).checked_mul(root + 1).map_or(true, |sq| sq > n),
+                "isqrt({n}) = {root}"
+            );
+        }
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+    }
+
+    #[test]
+    fn log2() {
+        assert_eq!(log2_floor(1), 0);
+        assert_eq!(log2_ceil(1), 0);
+        assert_eq!(log2_floor(2), 1);
+        assert_eq!(log2_ceil(2), 1);
+        assert_eq!(log2_floor(5), 2);
+        assert_eq!(log2_ceil(5), 3);
+        assert_eq!(log2_floor(1 << 40), 40);
+        assert_eq!(log2_ceil(1 << 40), 40);
+        assert_eq!(log2_ceil((1 << 40) + 1), 41);
+        assert_eq!(log2_floor(u64::MAX), 63);
+        assert_eq!(log2_ceil(u64::MAX), 64);
+    }
+
+    #[test]
+    fn trig_accuracy() {
+        assert_eq!(sin(0), 0);
+        assert_eq!(sin(QUARTER_TURN), ONE);
+        assert_eq!(sin(HALF_TURN), 0);
+        assert_eq!(sin(3 * QUARTER_TURN), -ONE);
+        assert_eq!(cos(0), ONE);
+        assert_eq!(cos(HALF_TURN), -ONE);
+
+        for i in 0..4096u32 {
+            let angle = i.wrapping_mul(1_048_573);
+            let radians = angle as f64 / 4_294_967_296.0 * core::f64::consts::TAU;
+            let expected_sin = (radians.sin() * ONE as f64).round() as i32;
+            let expected_cos = (radians.cos() * ONE as f64).round() as i32;
+            assert!((sin(angle) - expected_sin).abs() <= 8, "sin({angle})");
+            assert!((cos(angle) - expected_cos).abs() <= 8, "cos({angle})");
+        }
+    }
+
+    #[test]
+    fn fixed_mul() {
+        assert_eq!(mul_fixed(ONE, ONE), ONE);
+        assert_eq!(mul_fixed(ONE / 2, -ONE * 3), -ONE * 3 / 2);
+    }
+}
@@ -79,14 +79,22 @@
 
 #![deny(missing_docs)]
 
+pub mod aggregate;
+pub mod checked;
+pub mod collections;
 pub mod env;
-pub mod sha;
+#[cfg(feature = "heap-stats")]
+pub mod heap;
+pub mod math;
+pub mod timebox;
 
 #[cfg(target_os = "zkvm")]
 use core::arch::asm;
 
 use risc0_zkvm_platform::syscall::sys_panic;
 
+pub use risc0_zkvm_verify::sha::guest as sha;
+
 pub use crate::entry;
 
 #[cfg(target_os = "zkvm")]
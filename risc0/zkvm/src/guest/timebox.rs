@@ -0,0 +1,163 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Long-running computations that pause before exhausting the session.
+//!
+//! A session fails once it exceeds the cycle limit set by the host. Guests
+//! with more work than fits in one session can [pause](crate::guest::env::pause)
+//! and let the host resume them in a new session, but choosing where to pause
+//! by hand is fragile: the right point depends on the host's limit and on the
+//! cost of each unit of work.
+//!
+//! [TimeBox] runs an iterative computation one step at a time. Before each
+//! step it checks the cycles used so far against the session limit reported
+//! by [env::session_limit], and pauses when the next step might not fit. When
+//! the host resumes the guest, the computation continues with the next step.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::ops::ControlFlow;
+//!
+//! use risc0_zkvm::guest::{env, timebox::TimeBox};
+//!
+//! let mut acc = 0u64;
+//! let mut i = 0u64;
+//! let sum = TimeBox::new().run(|| {
+//!     if i == 1_000_000 {
+//!         return ControlFlow::Break(acc);
+//!     }
+//!     acc = acc.wrapping_add(i * i);
+//!     i += 1;
+//!     ControlFlow::Continue(())
+//! });
+//! env::commit(&sum);
+//! ```
+//!
+//! On the host, run the session and resume it with
+//! `ExecutorImpl::resume` for as long as it exits with `ExitCode::Paused`.
+
+use core::ops::ControlFlow;
+
+use crate::guest::env;
+
+/// The default number of cycles kept in reserve for pausing.
+///
+/// Pausing finalizes the journal digest, which takes a few thousand cycles.
+pub const DEFAULT_MARGIN: u64 = 1 << 14;
+
+/// Runs an iterative computation, pausing before it exceeds the session
+/// cycle budget.
+#[derive(Clone, Debug)]
+pub struct TimeBox {
+    budget: Option<u64>,
+    margin: u64,
+    exit_code: u8,
+    largest_step: u64,
+    pauses: u32,
+}
+
+impl Default for TimeBox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeBox {
+    /// Create a [TimeBox] whose budget is the session limit set by the host.
+    ///
+    /// If the session is unlimited, the computation never pauses.
+    pub fn new() -> Self {
+        Self {
+            budget: None,
+            margin: DEFAULT_MARGIN,
+            exit_code: 0,
+            largest_step: 0,
+            pauses: 0,
+        }
+    }
+
+    /// Pause once the session has used this many cycles, instead of relying
+    /// on the limit set by the host.
+    ///
+    /// The budget is capped at the session limit, if there is one.
+    pub fn budget(mut self, cycles: u64) -> Self {
+        self.budget = Some(cycles);
+        self
+    }
+
+    /// Set the number of cycles kept in reserve for pausing.
+    ///
+    /// Defaults to [DEFAULT_MARGIN].
+    pub fn margin(mut self, cycles: u64) -> Self {
+        self.margin = cycles;
+        self
+    }
+
+    /// Set the exit code reported to the host on each pause.
+    ///
+    /// Defaults to 0.
+    pub fn exit_code(mut self, exit_code: u8) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Return the number of times the computation has paused.
+    pub fn pauses(&self) -> u32 {
+        self.pauses
+    }
+
+    /// Call `step` until it returns [ControlFlow::Break], pausing between
+    /// steps whenever the next step might not fit in the current session.
+    ///
+    /// The cost of a step is estimated as the most cycles any previous step
+    /// has taken, so steps should be of roughly even size and small compared
+    /// to the budget. At least one step runs in every session, so a step that
+    /// does not fit in an empty session still fails with the session limit.
+    pub fn run<R>(&mut self, mut step: impl FnMut() -> ControlFlow<R>) -> R {
+        let mut budget = self.session_budget();
+        let mut ran = false;
+        loop {
+            if ran && self.near_limit(budget) {
+                env::pause(self.exit_code);
+                self.pauses += 1;
+                // The host may resume with a different limit.
+                budget = self.session_budget();
+                ran = false;
+            }
+
+            let start = env::session_cycle_count();
+            if let ControlFlow::Break(result) = step() {
+                return result;
+            }
+            ran = true;
+            let cycles = env::session_cycle_count().saturating_sub(start);
+            self.largest_step = self.largest_step.max(cycles);
+        }
+    }
+
+    fn near_limit(&self, budget: Option<u64>) -> bool {
+        budget.is_some_and(|budget| {
+            let reserve = self.largest_step.saturating_add(self.margin);
+            env::session_cycle_count().saturating_add(reserve) >= budget
+        })
+    }
+
+    fn session_budget(&self) -> Option<u64> {
+        match (self.budget, env::session_limit()) {
+            (Some(budget), Some(limit)) => Some(budget.min(limit)),
+            (budget, limit) => budget.or(limit),
+        }
+    }
+}
@@ -395,12 +395,14 @@ impl Client {
         Ok(pb::api::ExecutorEnv {
             binary: Some(binary),
             env_vars: env.env_vars.clone(),
+            channels: env.channels.clone().into_iter().collect(),
             args: env.args.clone(),
             slice_ios: env.slice_io.borrow().inner.keys().cloned().collect(),
             read_fds: env.posix_io.borrow().read_fds.keys().cloned().collect(),
             write_fds: env.posix_io.borrow().write_fds.keys().cloned().collect(),
             segment_limit_po2: env.segment_limit_po2,
             session_limit: env.session_limit,
+            memory_limit: env.memory_limit,
             trace_events: (!env.trace.is_empty()).then_some(()),
             pprof_out: env
                 .pprof_out
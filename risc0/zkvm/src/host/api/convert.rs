@@ -25,8 +25,8 @@ use crate::{
         receipt::{decode_receipt_claim_from_seal, CompositeReceipt, InnerReceipt, SegmentReceipt},
         recursion::SuccinctReceipt,
     },
-    Assumptions, ExitCode, Journal, MaybePruned, Output, ProveInfo, ProverOpts, Receipt,
-    ReceiptClaim, SessionStats, TraceEvent,
+    Assumptions, ExitCode, HeapStats, Hotspot, InsnStats, Journal, MaybePruned, Output, ProveInfo,
+    ProverOpts, Receipt, ReceiptClaim, SessionStats, TraceEvent,
 };
 
 mod ver {
@@ -118,6 +118,48 @@ impl From<TraceEvent> for pb::api::TraceEvent {
                     },
                 )),
             },
+            TraceEvent::MemoryAccessFault {
+                cycle,
+                pc,
+                addr,
+                store,
+            } => Self {
+                kind: Some(pb::api::trace_event::Kind::MemoryAccessFault(
+                    pb::api::trace_event::MemoryAccessFault {
+                        cycle,
+                        pc,
+                        addr,
+                        store,
+                    },
+                )),
+            },
+            TraceEvent::PageIn {
+                cycle,
+                page_idx,
+                cycles,
+            } => Self {
+                kind: Some(pb::api::trace_event::Kind::PageIn(
+                    pb::api::trace_event::Page {
+                        cycle,
+                        page_idx,
+                        cycles,
+                    },
+                )),
+            },
+            TraceEvent::PageOut {
+                cycle,
+                page_idx,
+                cycles,
+            } => Self {
+                kind: Some(pb::api::trace_event::Kind::PageOut(
+                    pb::api::trace_event::Page {
+                        cycle,
+                        page_idx,
+                        cycles,
+                    },
+                )),
+            },
+            _ => Self { kind: None },
         }
     }
 }
@@ -140,6 +182,22 @@ impl TryFrom<pb::api::TraceEvent> for TraceEvent {
                 addr: event.addr,
                 region: event.region,
             },
+            pb::api::trace_event::Kind::MemoryAccessFault(event) => TraceEvent::MemoryAccessFault {
+                cycle: event.cycle,
+                pc: event.pc,
+                addr: event.addr,
+                store: event.store,
+            },
+            pb::api::trace_event::Kind::PageIn(event) => TraceEvent::PageIn {
+                cycle: event.cycle,
+                page_idx: event.page_idx,
+                cycles: event.cycles,
+            },
+            pb::api::trace_event::Kind::PageOut(event) => TraceEvent::PageOut {
+                cycle: event.cycle,
+                page_idx: event.page_idx,
+                cycles: event.cycles,
+            },
         })
     }
 }
@@ -150,6 +208,7 @@ impl From<ExitCode> for pb::base::ExitCode {
             kind: Some(match value {
                 ExitCode::SystemSplit => pb::base::exit_code::Kind::SystemSplit(()),
                 ExitCode::SessionLimit => pb::base::exit_code::Kind::SessionLimit(()),
+                ExitCode::Aborted => pb::base::exit_code::Kind::Aborted(()),
                 ExitCode::Paused(code) => pb::base::exit_code::Kind::Paused(code),
                 ExitCode::Halted(code) => pb::base::exit_code::Kind::Halted(code),
             }),
@@ -166,6 +225,7 @@ impl TryFrom<pb::base::ExitCode> for ExitCode {
             pb::base::exit_code::Kind::Paused(code) => Self::Paused(code),
             pb::base::exit_code::Kind::SystemSplit(_) => Self::SystemSplit,
             pb::base::exit_code::Kind::SessionLimit(_) => Self::SessionLimit,
+            pb::base::exit_code::Kind::Aborted(_) => Self::Aborted,
         })
     }
 }
@@ -200,6 +260,8 @@ impl From<pb::api::ProverOpts> for ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
+            dedup_segments: opts.dedup_segments,
+            checkpoint_dir: opts.checkpoint_dir.map(PathBuf::from),
         }
     }
 }
@@ -209,6 +271,10 @@ impl From<ProverOpts> for pb::api::ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
+            dedup_segments: opts.dedup_segments,
+            checkpoint_dir: opts
+                .checkpoint_dir
+                .map(|dir| dir.to_string_lossy().into_owned()),
         }
     }
 }
@@ -304,6 +370,33 @@ impl From<SessionStats> for pb::core::SessionStats {
             segments: value.segments.try_into().unwrap(),
             total_cycles: value.total_cycles,
             user_cycles: value.user_cycles,
+            heap: value.heap.map(Into::into),
+            guest_pages: value.guest_pages,
+            insns: value.insns.map(Into::into),
+        }
+    }
+}
+
+impl From<HeapStats> for pb::core::HeapStats {
+    fn from(value: HeapStats) -> Self {
+        Self {
+            peak_heap: value.peak_heap,
+            peak_live_bytes: value.peak_live_bytes,
+            total_allocs: value.total_allocs,
+            total_deallocs: value.total_deallocs,
+            segment_peaks: value.segment_peaks,
+        }
+    }
+}
+
+impl From<pb::core::HeapStats> for HeapStats {
+    fn from(value: pb::core::HeapStats) -> Self {
+        Self {
+            peak_heap: value.peak_heap,
+            peak_live_bytes: value.peak_live_bytes,
+            total_allocs: value.total_allocs,
+            total_deallocs: value.total_deallocs,
+            segment_peaks: value.segment_peaks,
         }
     }
 }
@@ -316,10 +409,49 @@ impl TryFrom<pb::core::SessionStats> for SessionStats {
             segments: value.segments.try_into()?,
             total_cycles: value.total_cycles,
             user_cycles: value.user_cycles,
+            heap: value.heap.map(Into::into),
+            guest_pages: value.guest_pages,
+            insns: value.insns.map(Into::into),
         })
     }
 }
 
+impl From<InsnStats> for pb::core::InsnStats {
+    fn from(value: InsnStats) -> Self {
+        Self {
+            opcodes: value.opcodes.into_iter().collect(),
+            cycles: value.cycles.into_iter().collect(),
+            hotspots: value
+                .hotspots
+                .into_iter()
+                .map(|hotspot| pb::core::Hotspot {
+                    pc: hotspot.pc,
+                    count: hotspot.count,
+                    symbol: hotspot.symbol,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<pb::core::InsnStats> for InsnStats {
+    fn from(value: pb::core::InsnStats) -> Self {
+        Self {
+            opcodes: value.opcodes.into_iter().collect(),
+            cycles: value.cycles.into_iter().collect(),
+            hotspots: value
+                .hotspots
+                .into_iter()
+                .map(|hotspot| Hotspot {
+                    pc: hotspot.pc,
+                    count: hotspot.count,
+                    symbol: hotspot.symbol,
+                })
+                .collect(),
+        }
+    }
+}
+
 impl From<ProveInfo> for pb::core::ProveInfo {
     fn from(value: ProveInfo) -> Self {
         Self {
@@ -28,8 +28,9 @@ use test_log::test;
 
 use super::{Asset, AssetRequest, ConnectionWrapper, Connector, TcpConnection};
 use crate::{
-    recursion::SuccinctReceipt, ApiClient, ApiServer, ExecutorEnv, InnerReceipt, ProverOpts,
-    Receipt, SegmentReceipt, SessionInfo, VerifierContext,
+    recursion::SuccinctReceipt, ApiClient, ApiServer, ExecutorEnv, ExecutorImpl,
+    ExternalProverServer, InnerReceipt, ProverOpts, ProverServer, Receipt, SegmentReceipt,
+    SessionInfo, VerifierContext,
 };
 
 struct TestClientConnector {
@@ -51,6 +52,31 @@ impl Connector for TestClientConnector {
     }
 }
 
+/// Starts a server thread for every connection, so that one client can send
+/// any number of requests.
+struct ServerPerRequestConnector {
+    listener: TcpListener,
+}
+
+impl ServerPerRequestConnector {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind("127.0.0.1:0")?,
+        })
+    }
+}
+
+impl Connector for ServerPerRequestConnector {
+    fn connect(&self) -> Result<ConnectionWrapper> {
+        let addr = self.listener.local_addr()?.to_string();
+        thread::Builder::new()
+            .name("server".into())
+            .spawn(move || ApiServer::new_tcp(addr).run().unwrap())?;
+        let (stream, _) = self.listener.accept()?;
+        Ok(ConnectionWrapper::new(Box::new(TcpConnection::new(stream))))
+    }
+}
+
 struct TestClient {
     work_dir: TempDir,
     client: ApiClient,
@@ -307,6 +333,33 @@ fn lift_resolve() {
     receipt.verify(MULTI_TEST_ID).unwrap();
 }
 
+#[test]
+fn external_prover_server() {
+    let connector = ServerPerRequestConnector::new().unwrap();
+    let client = ApiClient::with_connector(Box::new(connector));
+    let prover = ExternalProverServer::new(client, ProverOpts::default());
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::DoNothing)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let ctx = VerifierContext::default();
+    let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
+    receipt.verify(MULTI_TEST_ID).unwrap();
+
+    let succinct_receipt = prover.compress(receipt.inner.composite().unwrap()).unwrap();
+    let receipt = Receipt::new(
+        InnerReceipt::Succinct(succinct_receipt),
+        receipt.journal.bytes,
+    );
+    receipt.verify(MULTI_TEST_ID).unwrap();
+}
+
 #[test]
 #[should_panic(expected = "MultiTestSpec::Panic invoked")]
 fn guest_error_forwarding() {
@@ -0,0 +1,185 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Envelope encryption of data the host persists, such as segments and inputs.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, ensure, Result};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+
+const MAGIC: &[u8; 4] = b"R0E1";
+const WRAP_AAD: &[u8; 4] = b"R0KW";
+const KEY_LEN: usize = 32;
+
+/// Wraps and unwraps the per-blob data keys used by an [EnvelopeCipher].
+///
+/// Implementations typically delegate to a KMS or HSM, so that the key
+/// encryption key never enters the process.
+pub trait KeyProvider {
+    /// Encrypt a freshly generated data key.
+    fn wrap_key(&self, data_key: &[u8]) -> Result<Vec<u8>>;
+
+    /// Recover a data key from the output of [KeyProvider::wrap_key].
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// A [KeyProvider] holding a local 256-bit key encryption key.
+pub struct StaticKeyProvider {
+    key: [u8; KEY_LEN],
+}
+
+impl StaticKeyProvider {
+    /// Construct a [StaticKeyProvider] from a key encryption key.
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        Self { key }
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn wrap_key(&self, data_key: &[u8]) -> Result<Vec<u8>> {
+        seal(&self.key, WRAP_AAD, data_key)
+    }
+
+    fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>> {
+        open(&self.key, WRAP_AAD, wrapped)
+    }
+}
+
+/// Encrypts blobs under a fresh data key, which is stored alongside the
+/// ciphertext after being wrapped by a [KeyProvider].
+///
+/// Blobs are encrypted with ChaCha20-Poly1305, so tampering is detected when
+/// they are opened.
+///
+/// ```
+/// use risc0_zkvm::{EnvelopeCipher, StaticKeyProvider};
+///
+/// let cipher = EnvelopeCipher::new(StaticKeyProvider::new([7; 32]));
+/// let blob = cipher.seal(b"secret input").unwrap();
+/// assert_eq!(cipher.open(&blob).unwrap(), b"secret input");
+/// ```
+#[derive(Clone)]
+pub struct EnvelopeCipher {
+    provider: Arc<dyn KeyProvider + Send + Sync>,
+}
+
+impl EnvelopeCipher {
+    /// Construct an [EnvelopeCipher] that wraps data keys with `provider`.
+    pub fn new(provider: impl KeyProvider + Send + Sync + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Encrypt `plaintext` under a new data key.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut data_key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut data_key)
+            .map_err(|_| anyhow!("failed to generate data key"))?;
+        let wrapped = self.provider.wrap_key(&data_key)?;
+
+        let mut blob = MAGIC.to_vec();
+        blob.extend_from_slice(&u32::try_from(wrapped.len())?.to_le_bytes());
+        blob.extend_from_slice(&wrapped);
+        blob.extend_from_slice(&seal(&data_key, MAGIC, plaintext)?);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob produced by [EnvelopeCipher::seal].
+    pub fn open(&self, blob: &[u8]) -> Result<Vec<u8>> {
+        ensure!(
+            blob.len() >= MAGIC.len() + 4 && blob.starts_with(MAGIC),
+            "not an encrypted blob"
+        );
+        let (len, rest) = blob[MAGIC.len()..].split_at(4);
+        let len = u32::from_le_bytes(len.try_into()?) as usize;
+        ensure!(rest.len() >= len, "encrypted blob is truncated");
+        let (wrapped, sealed) = rest.split_at(len);
+        let data_key = self.provider.unwrap_key(wrapped)?;
+        open(&data_key, MAGIC, sealed)
+    }
+}
+
+fn aead_key(key: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| anyhow!("invalid key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Encrypt `plaintext`, returning the nonce followed by the ciphertext.
+fn seal(key: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate nonce"))?;
+    let mut in_out = plaintext.to_vec();
+    aead_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow!("encryption failed"))?;
+    Ok([nonce.as_slice(), &in_out].concat())
+}
+
+fn open(key: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    ensure!(sealed.len() >= NONCE_LEN, "encrypted blob is truncated");
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = aead_key(key)?
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| anyhow!("decryption failed: wrong key or corrupted blob"))?;
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvelopeCipher, StaticKeyProvider};
+
+    #[test]
+    fn round_trip() {
+        let cipher = EnvelopeCipher::new(StaticKeyProvider::new([1; 32]));
+        let blob = cipher.seal(b"hello").unwrap();
+        assert!(!blob.windows(5).any(|x| x == b"hello"));
+        assert_eq!(cipher.open(&blob).unwrap(), b"hello");
+
+        // Each blob uses a fresh data key and nonce.
+        assert_ne!(cipher.seal(b"hello").unwrap(), blob);
+    }
+
+    #[test]
+    fn wrong_key() {
+        let blob = EnvelopeCipher::new(StaticKeyProvider::new([1; 32]))
+            .seal(b"hello")
+            .unwrap();
+        let cipher = EnvelopeCipher::new(StaticKeyProvider::new([2; 32]));
+        assert!(cipher.open(&blob).is_err());
+    }
+
+    #[test]
+    fn tampered() {
+        let cipher = EnvelopeCipher::new(StaticKeyProvider::new([1; 32]));
+        let mut blob = cipher.seal(b"hello").unwrap();
+        *blob.last_mut().unwrap() ^= 1;
+        assert!(cipher.open(&blob).is_err());
+        assert!(cipher.open(&blob[..8]).is_err());
+        assert!(cipher.open(b"hello").is_err());
+    }
+}
@@ -16,31 +16,40 @@
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     io::{BufRead, BufReader, Cursor, Read, Write},
     mem,
+    ops::ControlFlow,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
+    time::SystemTime,
 };
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use bytemuck::Pod;
 use bytes::Bytes;
 use risc0_zkp::core::digest::Digest;
 use risc0_zkvm_platform::{self, fileno};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tempfile::TempDir;
 
 use crate::{
     host::client::{
+        observer::SessionObserver,
         posix_io::PosixIo,
+        replay::SessionRecord,
         slice_io::{slice_io_from_fn, SliceIo, SliceIoTable},
     },
     serde::to_vec,
-    Assumption, TraceCallback,
+    Assumption, SegmentInfo, TraceCallback, TraceFilter,
 };
 
+/// File descriptor of the first named input channel. Channels are assigned
+/// consecutive descriptors from here, well clear of the ones hosts pick for
+/// [ExecutorEnvBuilder::read_fd].
+const CHANNEL_FD_BASE: u32 = 0x4000_0000;
+
 /// A builder pattern used to construct an [ExecutorEnv].
 #[derive(Default)]
 pub struct ExecutorEnvBuilder<'a> {
@@ -64,6 +73,65 @@ pub enum SegmentPath {
     Path(PathBuf),
 }
 
+/// Where and how often the executor writes checkpoints.
+#[derive(Clone)]
+pub(crate) struct CheckpointConfig {
+    pub(crate) every: u32,
+    pub(crate) dir: PathBuf,
+}
+
+/// How many hotspots the executor reports when instruction stats are enabled
+/// without an explicit count.
+const DEFAULT_INSN_HOTSPOTS: usize = 20;
+
+/// Which instruction stats the executor collects, and where it writes them.
+#[derive(Clone)]
+pub(crate) struct InsnStatsConfig {
+    pub(crate) hotspots: usize,
+    pub(crate) csv: Option<PathBuf>,
+}
+
+impl Default for InsnStatsConfig {
+    fn default() -> Self {
+        Self {
+            hotspots: DEFAULT_INSN_HOTSPOTS,
+            csv: None,
+        }
+    }
+}
+
+/// The format of a trace written with [ExecutorEnvBuilder::trace_file].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceFormat {
+    /// JSON in the Chrome trace event format, which can be opened in Perfetto
+    /// or `chrome://tracing`.
+    Perfetto,
+}
+
+/// The time reported to the guest by `sys_clock`.
+///
+/// Whichever policy is chosen, the time is fixed for the whole session, so
+/// reading the clock does not make execution depend on how long the guest
+/// runs. The value is recorded with the other syscalls, so replaying a
+/// [SessionRecord] reproduces it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockPolicy {
+    /// Use the host time when the session starts. A session resumed from a
+    /// [crate::SuspendedState] or a checkpoint keeps the time it started with.
+    #[default]
+    SessionStart,
+
+    /// Use the given time.
+    Fixed(SystemTime),
+
+    /// Fail execution if the guest reads the clock.
+    Disabled,
+}
+
+/// A callback invoked by the executor at each segment split.
+pub(crate) type SegmentCallback<'a> = dyn FnMut(&SegmentInfo) -> ControlFlow<()> + 'a;
+
 /// The [crate::Executor] is configured from this object.
 ///
 /// The executor environment holds configuration details that inform how the
@@ -71,17 +139,36 @@ pub enum SegmentPath {
 #[derive(Default)]
 pub struct ExecutorEnv<'a> {
     pub(crate) env_vars: HashMap<String, String>,
+    pub(crate) channels: BTreeMap<String, u32>,
     pub(crate) args: Vec<String>,
+    pub(crate) clock: ClockPolicy,
     pub(crate) segment_limit_po2: Option<u32>,
     pub(crate) session_limit: Option<u64>,
+    pub(crate) memory_limit: Option<u64>,
     pub(crate) posix_io: Rc<RefCell<PosixIo<'a>>>,
     pub(crate) slice_io: Rc<RefCell<SliceIoTable<'a>>>,
     pub(crate) input: Vec<u8>,
     pub(crate) trace: Vec<Rc<RefCell<dyn TraceCallback + 'a>>>,
+    pub(crate) observers: Vec<Rc<RefCell<dyn SessionObserver + 'a>>>,
+    pub(crate) segment_callback: Option<Rc<RefCell<SegmentCallback<'a>>>>,
     pub(crate) assumptions: Rc<RefCell<Assumptions>>,
     pub(crate) segment_path: Option<SegmentPath>,
     pub(crate) pprof_out: Option<PathBuf>,
+    pub(crate) folded_out: Option<PathBuf>,
+    pub(crate) core_dump: Option<PathBuf>,
+    pub(crate) memory_audit: Option<PathBuf>,
+    pub(crate) insn_stats: Option<InsnStatsConfig>,
+    pub(crate) trace_file: Option<(PathBuf, TraceFormat)>,
+    pub(crate) checkpoint: Option<CheckpointConfig>,
     pub(crate) input_digest: Option<Digest>,
+    pub(crate) replay: Option<SessionRecord>,
+    pub(crate) record: bool,
+    #[cfg(feature = "prove")]
+    pub(crate) sub_sessions: Rc<RefCell<Vec<crate::SubSession>>>,
+    #[cfg(feature = "prove")]
+    pub(crate) segment_plan: Option<crate::SegmentPlan>,
+    #[cfg(feature = "encryption")]
+    pub(crate) cipher: Option<crate::EnvelopeCipher>,
 }
 
 impl<'a> ExecutorEnv<'a> {
@@ -113,6 +200,18 @@ impl<'a> ExecutorEnvBuilder<'a> {
     /// After calling `build`, the [ExecutorEnvBuilder] will be reset to
     /// default.
     pub fn build(&mut self) -> Result<ExecutorEnv<'a>> {
+        self.build_inner(true)
+    }
+
+    /// Like [ExecutorEnvBuilder::build], but without reading the `RISC0_*`
+    /// environment variables that select output files, so a nested execution
+    /// does not overwrite the files of the execution that runs it.
+    #[cfg(feature = "prove")]
+    pub(crate) fn build_isolated(&mut self) -> Result<ExecutorEnv<'a>> {
+        self.build_inner(false)
+    }
+
+    fn build_inner(&mut self, read_env_vars: bool) -> Result<ExecutorEnv<'a>> {
         let mut inner = mem::take(&mut self.inner);
 
         if !inner.input.is_empty() {
@@ -123,9 +222,43 @@ impl<'a> ExecutorEnvBuilder<'a> {
                 .with_read_fd(fileno::STDIN, reader);
         }
 
-        if inner.pprof_out.is_none() {
-            if let Ok(env_var) = std::env::var("RISC0_PPROF_OUT") {
-                inner.pprof_out = Some(env_var.into());
+        if read_env_vars {
+            if inner.pprof_out.is_none() {
+                if let Ok(env_var) = std::env::var("RISC0_PPROF_OUT") {
+                    inner.pprof_out = Some(env_var.into());
+                }
+            }
+
+            if inner.folded_out.is_none() {
+                if let Ok(env_var) = std::env::var("RISC0_FOLDED_OUT") {
+                    inner.folded_out = Some(env_var.into());
+                }
+            }
+
+            if inner.core_dump.is_none() {
+                if let Ok(env_var) = std::env::var("RISC0_CORE_DUMP") {
+                    inner.core_dump = Some(env_var.into());
+                }
+            }
+
+            if inner.memory_audit.is_none() {
+                if let Ok(env_var) = std::env::var("RISC0_MEMORY_AUDIT") {
+                    inner.memory_audit = Some(env_var.into());
+                }
+            }
+
+            if let Ok(env_var) = std::env::var("RISC0_INSN_STATS_CSV") {
+                let config = inner.insn_stats.get_or_insert_with(Default::default);
+                if config.csv.is_none() {
+                    config.csv = Some(env_var.into());
+                }
+            }
+        }
+
+        if let Some(checkpoint) = &inner.checkpoint {
+            ensure!(checkpoint.every > 0, "checkpoint interval must be non-zero");
+            if inner.segment_path.is_none() {
+                inner.segment_path = Some(SegmentPath::Path(checkpoint.dir.clone()));
             }
         }
 
@@ -158,6 +291,29 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set a limit on the guest memory a session may touch, specified in
+    /// number of pages.
+    ///
+    /// Execution stops with a `MemoryLimitExceeded` error once the guest has
+    /// touched more distinct pages than this, across all segments. This guards
+    /// against guests that touch large parts of the address space to inflate
+    /// paging costs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, PAGE_SIZE};
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .memory_limit(Some((64 * 1024 * 1024 / PAGE_SIZE) as u64)) // 64MB
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn memory_limit(&mut self, limit: Option<u64>) -> &mut Self {
+        self.inner.memory_limit = limit;
+        self
+    }
+
     /// Add environment variables to the guest environment.
     ///
     /// # Example
@@ -196,6 +352,27 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set the [ClockPolicy] which decides the time returned to the guest by
+    /// `risc0_zkvm::guest::env::time`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use risc0_zkvm::{ClockPolicy, ExecutorEnv};
+    ///
+    /// let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let env = ExecutorEnv::builder()
+    ///     .clock(ClockPolicy::Fixed(time))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn clock(&mut self, policy: ClockPolicy) -> &mut Self {
+        self.inner.clock = policy;
+        self
+    }
+
     /// Add an environment variable to the guest environment.
     ///
     /// # Example
@@ -271,6 +448,36 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Write a length-prefixed frame of bytes to the zkVM guest stdin.
+    ///
+    /// The frame is encoded as a little-endian `u32` length followed by the
+    /// bytes. A corresponding `env::read_frame` can be used within the guest
+    /// to read the data with a bound on its length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .write_frame(b"untrusted input")
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn write_frame(&mut self, bytes: &[u8]) -> Result<&mut Self> {
+        ensure!(
+            bytes.len() <= u32::MAX as usize,
+            "frame of {} bytes is too large",
+            bytes.len()
+        );
+        self.inner
+            .input
+            .extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        self.inner.input.extend_from_slice(bytes);
+        Ok(self)
+    }
+
     /// Add a posix-style standard input.
     pub fn stdin(&mut self, reader: impl Read + 'a) -> &mut Self {
         self.read_fd(fileno::STDIN, BufReader::new(reader))
@@ -292,6 +499,48 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add a named input channel.
+    ///
+    /// The guest opens the channel by name with `env::channel` and reads from
+    /// it independently of stdin and of other channels. Each channel is
+    /// carried over its own file descriptor, assigned in registration order,
+    /// so the guest sees the same data regardless of the order in which it
+    /// reads the channels. Adding a channel with the same name again replaces
+    /// it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let prices = [100u8, 101, 99];
+    /// let volumes = [7u8, 3, 12];
+    /// let env = ExecutorEnv::builder()
+    ///     .channel("prices", &prices[..])
+    ///     .channel("volumes", &volumes[..])
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn channel(&mut self, name: &str, reader: impl Read + 'a) -> &mut Self {
+        let next_fd = CHANNEL_FD_BASE + self.inner.channels.len() as u32;
+        let fd = *self
+            .inner
+            .channels
+            .entry(name.to_string())
+            .or_insert(next_fd);
+        self.read_fd(fd, BufReader::new(reader))
+    }
+
+    /// Set the file descriptors of named input channels whose readers are
+    /// registered separately with [Self::read_fd].
+    pub(crate) fn channel_fds(
+        &mut self,
+        channels: impl IntoIterator<Item = (String, u32)>,
+    ) -> &mut Self {
+        self.inner.channels.extend(channels);
+        self
+    }
+
     /// Add a posix-style file descriptor for writing.
     pub fn write_fd(&mut self, fd: u32, writer: impl Write + 'a) -> &mut Self {
         self.inner.posix_io.borrow_mut().with_write_fd(fd, writer);
@@ -320,6 +569,51 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Run `elf` in a nested executor whenever the guest sends on `channel`.
+    ///
+    /// The bytes sent by the guest become the stdin of the nested guest, which
+    /// runs in an isolated [ExecutorEnv] with no other I/O. The `RISC0_*`
+    /// environment variables that select output files, e.g. `RISC0_PPROF_OUT`,
+    /// only apply to the outer execution. The nested guest must halt with exit
+    /// code zero, and its journal is returned to the outer guest.
+    ///
+    /// The claim of the nested execution is added as an unresolved
+    /// [Assumption], so the outer guest can `env::verify` the returned journal
+    /// against the nested image ID. Each run is recorded in
+    /// [crate::Session::sub_sessions].
+    #[cfg(feature = "prove")]
+    pub fn sub_executor<C: AsRef<str>>(&mut self, channel: C, elf: &'a [u8]) -> &mut Self {
+        let name = channel.as_ref().to_string();
+        let assumptions = self.inner.assumptions.clone();
+        let sub_sessions = self.inner.sub_sessions.clone();
+        self.io_callback(channel, move |from_guest| {
+            let env = ExecutorEnv::builder()
+                .write_slice(&from_guest)
+                .build_isolated()?;
+            let session = crate::ExecutorImpl::from_elf(env, elf)?.run()?;
+            anyhow::ensure!(
+                session.exit_code == crate::ExitCode::Halted(0),
+                "sub-execution on channel {name} exited with {:?}",
+                session.exit_code
+            );
+            let claim = session.get_claim()?;
+            let journal = session.journal.map(|x| x.bytes).unwrap_or_default();
+            assumptions
+                .borrow_mut()
+                .cached
+                .push(Assumption::Unresolved(claim.clone().into()));
+            sub_sessions.borrow_mut().push(crate::SubSession {
+                channel: name.clone(),
+                input: from_guest.to_vec(),
+                claim,
+                journal: journal.clone(),
+                user_cycles: session.user_cycles,
+                total_cycles: session.total_cycles,
+            });
+            Ok(journal.into())
+        })
+    }
+
     /// Add an [Assumption] to the [ExecutorEnv], for use in [composition].
     ///
     /// During execution, when the guest calls `env::verify` or
@@ -346,6 +640,123 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add a callback handler for the trace messages passed by `filter`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, TraceEvent, TraceEventKind, TraceFilter};
+    ///
+    /// // Print the memory writes made by instructions in a single function.
+    /// let filter = TraceFilter::new()
+    ///     .pc_range(0x0020_1000..0x0020_1400)
+    ///     .events([TraceEventKind::MemorySet]);
+    /// let env = ExecutorEnv::builder()
+    ///     .trace_callback_filtered(filter, |event: TraceEvent| {
+    ///         println!("{event:?}");
+    ///         anyhow::Ok(())
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn trace_callback_filtered(
+        &mut self,
+        filter: TraceFilter,
+        callback: impl TraceCallback + 'a,
+    ) -> &mut Self {
+        self.trace_callback(filter.apply(callback))
+    }
+
+    /// Add a [SessionObserver] which receives structured events, such as
+    /// segment starts, syscalls, splits and the final exit, during execution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, SessionObserver, SyscallEvent};
+    ///
+    /// #[derive(Default)]
+    /// struct SyscallCounter(usize);
+    ///
+    /// impl SessionObserver for SyscallCounter {
+    ///     fn on_syscall(&mut self, _event: &SyscallEvent<'_>) {
+    ///         self.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .observer(SyscallCounter::default())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn observer(&mut self, observer: impl SessionObserver + 'a) -> &mut Self {
+        self.inner.observers.push(Rc::new(RefCell::new(observer)));
+        self
+    }
+
+    /// Add a callback which is invoked each time execution is split into a
+    /// new segment.
+    ///
+    /// The callback receives the size and cycle count of the segment that was
+    /// just completed, and can be used to report progress. Returning
+    /// [ControlFlow::Break] stops execution cleanly at the split, producing a
+    /// [crate::Session] with an exit code of [crate::ExitCode::Aborted].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::ops::ControlFlow;
+    ///
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let max_cycles = 1 << 24;
+    /// let mut total_cycles = 0;
+    /// let env = ExecutorEnv::builder()
+    ///     .segment_callback(move |info| {
+    ///         total_cycles += info.cycles as u64;
+    ///         if total_cycles > max_cycles {
+    ///             ControlFlow::Break(())
+    ///         } else {
+    ///             ControlFlow::Continue(())
+    ///         }
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn segment_callback(
+        &mut self,
+        callback: impl FnMut(&SegmentInfo) -> ControlFlow<()> + 'a,
+    ) -> &mut Self {
+        self.inner.segment_callback = Some(Rc::new(RefCell::new(callback)));
+        self
+    }
+
+    /// Add split points from a [crate::SegmentPlan], in addition to splitting
+    /// at the segment limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, ExecutorImpl, SegmentPlan};
+    /// use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+    ///
+    /// let spec = MultiTestSpec::BusyLoop { cycles: 1 << 16 };
+    /// let env = ExecutorEnv::builder().write(&spec).unwrap().build().unwrap();
+    /// let profile = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run().unwrap();
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .write(&spec)
+    ///     .unwrap()
+    ///     .segment_plan(SegmentPlan::balanced(&profile, 4).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "prove")]
+    pub fn segment_plan(&mut self, plan: crate::SegmentPlan) -> &mut Self {
+        self.inner.segment_plan = Some(plan);
+        self
+    }
+
     /// Set the path where segments will be stored.
     pub fn segment_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.inner.segment_path = Some(SegmentPath::Path(path.as_ref().to_path_buf()));
@@ -358,9 +769,169 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Enable the profiler and output results as folded stacks to the
+    /// specified path.
+    ///
+    /// Folded stacks can be rendered with flamegraph tools such as
+    /// `inferno-flamegraph` or `flamegraph.pl`. This may be combined with
+    /// [ExecutorEnvBuilder::enable_profiler] to produce both outputs.
+    pub fn enable_folded_profiler<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.inner.folded_out = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Write a core dump to the specified path if the guest panics or faults.
+    ///
+    /// The core dump contains the registers and the guest pages touched in the
+    /// final segment. It can be loaded with `GuestCore::open` for offline
+    /// inspection.
+    pub fn core_dump<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.inner.core_dump = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Record every guest load and store into protected memory, such as the
+    /// null page or system memory, and write a report to the specified path
+    /// when the session ends.
+    ///
+    /// The report is written whether or not execution succeeds, and can be
+    /// loaded with `MemoryAuditReport::open`.
+    pub fn enable_memory_audit<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.inner.memory_audit = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Count executed instructions and report them as `Session::insn_stats`.
+    ///
+    /// The stats include the number of instructions executed and user cycles
+    /// spent per opcode, and the `hotspots` most frequently executed program
+    /// counters, resolved to function names when the guest is run from its
+    /// ELF.
+    pub fn enable_insn_stats(&mut self, hotspots: usize) -> &mut Self {
+        self.inner
+            .insn_stats
+            .get_or_insert_with(Default::default)
+            .hotspots = hotspots;
+        self
+    }
+
+    /// Count executed instructions, as with
+    /// [ExecutorEnvBuilder::enable_insn_stats], and also write them as CSV to
+    /// the specified path when the session ends.
+    pub fn insn_stats_csv<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.inner
+            .insn_stats
+            .get_or_insert_with(Default::default)
+            .csv = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Write a timeline of the session to the specified path, for visual
+    /// inspection of where the guest spends its cycles.
+    ///
+    /// With [TraceFormat::Perfetto], the timeline shows which function the
+    /// guest is executing, the syscalls it makes, and the pages it pages in
+    /// and out. Instructions are only attributed to functions when the
+    /// executor is created from the guest ELF.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, TraceFormat};
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .trace_file("trace.json", TraceFormat::Perfetto)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn trace_file<P: AsRef<Path>>(&mut self, path: P, format: TraceFormat) -> &mut Self {
+        self.inner.trace_file = Some((path.as_ref().to_path_buf(), format));
+        self
+    }
+
+    /// Write a checkpoint to `dir` after every `segments` segments.
+    ///
+    /// Each checkpoint replaces the previous one. Unless
+    /// [ExecutorEnvBuilder::segment_path] is set, segments are also stored in
+    /// `dir`, so that after a crash `ExecutorImpl::from_checkpoint` can continue
+    /// the session from the last checkpoint instead of from the start.
+    pub fn checkpoint_every<P: AsRef<Path>>(&mut self, segments: u32, dir: P) -> &mut Self {
+        self.inner.checkpoint = Some(CheckpointConfig {
+            every: segments,
+            dir: dir.as_ref().to_path_buf(),
+        });
+        self
+    }
+
     /// Set the input digest.
     pub fn input_digest(&mut self, digest: Digest) -> &mut Self {
         self.inner.input_digest = Some(digest);
         self
     }
+
+    /// Encrypt data that the executor writes to disk, such as segments, with
+    /// an [crate::EnvelopeCipher] using the given key provider.
+    ///
+    /// Each blob is encrypted under its own data key, which is wrapped by
+    /// `provider` and stored alongside it. Inputs kept in a job queue can be
+    /// protected the same way with [crate::EnvelopeCipher::seal], and opened
+    /// before being passed to [ExecutorEnvBuilder::write_slice].
+    #[cfg(feature = "encryption")]
+    pub fn encrypt_at_rest(
+        &mut self,
+        provider: impl crate::KeyProvider + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.inner.cipher = Some(crate::EnvelopeCipher::new(provider));
+        self
+    }
+
+    /// Record every syscall response so that `Session::record` can return a
+    /// [SessionRecord] of the execution.
+    ///
+    /// Recording is disabled by default, since it keeps a copy of all data
+    /// passed to the guest, including its input.
+    pub fn record(&mut self, enable: bool) -> &mut Self {
+        self.inner.record = enable;
+        self
+    }
+
+    /// Replay a previously recorded session.
+    ///
+    /// Instead of invoking the host-side syscall handlers, the executor will
+    /// answer each syscall made by the guest with the response stored in the
+    /// given [SessionRecord]. Syscalls that only pass data from the guest to
+    /// the host (e.g. writes to the journal or stdout) and assumption
+    /// verification are still handled by this environment, and their results
+    /// must match the record.
+    ///
+    /// Execution fails if the guest diverges from the recorded session in any
+    /// way, such as running a different image, making a different sequence of
+    /// syscalls, or producing a different exit code, journal, or post state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, ExecutorImpl};
+    /// use risc0_zkvm_methods::HELLO_COMMIT_ELF;
+    ///
+    /// let env = ExecutorEnv::builder().record(true).build().unwrap();
+    /// let session = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+    ///     .unwrap()
+    ///     .run()
+    ///     .unwrap();
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .replay(session.record().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let replayed = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+    ///     .unwrap()
+    ///     .run()
+    ///     .unwrap();
+    /// assert_eq!(session.journal.unwrap().bytes, replayed.journal.unwrap().bytes);
+    /// ```
+    pub fn replay(&mut self, record: SessionRecord) -> &mut Self {
+        self.inner.replay = Some(record);
+        self
+    }
 }
@@ -0,0 +1,279 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Portable manifests describing an [ExecutorEnv].
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    sha::{Digest, Impl, Sha256},
+    Assumption, ClockPolicy, ExecutorEnv, ExecutorEnvBuilder, SessionRecord,
+};
+
+/// The current version of the [EnvManifest] format.
+pub const MANIFEST_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const BLOBS_DIR: &str = "blobs";
+
+/// A reference to a blob stored alongside an [EnvManifest].
+///
+/// Blobs are stored in the `blobs` directory of the bundle, named by the hex
+/// encoding of the SHA-256 digest of their contents.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlobRef {
+    /// The SHA-256 digest of the blob contents.
+    pub digest: Digest,
+
+    /// The length of the blob in bytes.
+    pub len: u64,
+}
+
+/// A portable description of an [ExecutorEnv].
+///
+/// A manifest is stored as a bundle directory containing a `manifest.json`
+/// file and a `blobs` directory holding the inputs referenced by digest. This
+/// allows an execution to be packaged up, e.g. as part of a bug report, and
+/// re-run elsewhere.
+///
+/// Host callbacks (file descriptors, named input channels, slice I/O
+/// handlers, and trace callbacks) cannot be serialized. Their file descriptor
+/// numbers and channel names are recorded so that they can be re-attached to the [ExecutorEnvBuilder]
+/// returned by [ExecutorEnv::from_manifest]. To reproduce an execution
+/// exactly, including the responses of any such callbacks, include a
+/// [SessionRecord] via [ExecutorEnvBuilder::replay].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnvManifest {
+    /// The version of the manifest format.
+    pub version: u32,
+
+    /// Environment variables available to the guest.
+    pub env_vars: BTreeMap<String, String>,
+
+    /// Arguments available to the guest.
+    pub args: Vec<String>,
+
+    /// The segment limit, specified in powers of 2 cycles.
+    pub segment_limit_po2: Option<u32>,
+
+    /// The session limit, specified in number of cycles.
+    pub session_limit: Option<u64>,
+
+    /// The memory limit, specified in number of pages.
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
+
+    /// The time reported to the guest.
+    #[serde(default)]
+    pub clock: ClockPolicy,
+
+    /// The user cycle counts of a `SegmentPlan`, if one is set.
+    #[serde(default)]
+    pub segment_plan: Option<Vec<u64>>,
+
+    /// The input digest.
+    pub input_digest: Option<Digest>,
+
+    /// The data written to the guest's stdin with
+    /// [ExecutorEnvBuilder::write] or [ExecutorEnvBuilder::write_slice].
+    pub input: Option<BlobRef>,
+
+    /// File descriptors configured for reading.
+    pub read_fds: Vec<u32>,
+
+    /// File descriptors configured for writing.
+    pub write_fds: Vec<u32>,
+
+    /// Named input channels, by the file descriptor that carries them.
+    #[serde(default)]
+    pub channels: BTreeMap<String, u32>,
+
+    /// Channels configured with a slice I/O handler.
+    pub slice_io_channels: Vec<String>,
+
+    /// Bincode encoded [Assumption]s available to the guest.
+    pub assumptions: Vec<BlobRef>,
+
+    /// A bincode encoded [SessionRecord] to replay, if any.
+    pub replay: Option<BlobRef>,
+}
+
+impl<'a> ExecutorEnv<'a> {
+    /// Write a manifest bundle describing this [ExecutorEnv] to `dir`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let env = ExecutorEnv::builder()
+    ///     .write(&42u32)
+    ///     .unwrap()
+    ///     .env_var("VAR1", "SOME_VALUE")
+    ///     .build()
+    ///     .unwrap();
+    /// env.to_manifest(dir.path()).unwrap();
+    ///
+    /// let env = ExecutorEnv::from_manifest(dir.path())
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn to_manifest<P: AsRef<Path>>(&self, dir: P) -> Result<EnvManifest> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir.join(BLOBS_DIR))?;
+
+        let input = (!self.input.is_empty())
+            .then(|| write_blob(dir, &self.input))
+            .transpose()?;
+        let assumptions = self
+            .assumptions
+            .borrow()
+            .cached
+            .iter()
+            .map(|assumption| write_blob(dir, &bincode::serialize(assumption)?))
+            .collect::<Result<_>>()?;
+        let replay = self
+            .replay
+            .as_ref()
+            .map(|record| write_blob(dir, &bincode::serialize(record)?))
+            .transpose()?;
+
+        #[cfg(feature = "prove")]
+        let segment_plan = self
+            .segment_plan
+            .as_ref()
+            .map(|plan| plan.split_points().to_vec());
+        #[cfg(not(feature = "prove"))]
+        let segment_plan = None;
+
+        let posix_io = self.posix_io.borrow();
+        let manifest = EnvManifest {
+            version: MANIFEST_VERSION,
+            env_vars: self.env_vars.clone().into_iter().collect(),
+            args: self.args.clone(),
+            segment_limit_po2: self.segment_limit_po2,
+            session_limit: self.session_limit,
+            memory_limit: self.memory_limit,
+            clock: self.clock,
+            segment_plan,
+            input_digest: self.input_digest,
+            input,
+            read_fds: posix_io.read_fds.keys().copied().collect(),
+            write_fds: posix_io.write_fds.keys().copied().collect(),
+            channels: self.channels.clone(),
+            slice_io_channels: self.slice_io.borrow().inner.keys().cloned().collect(),
+            assumptions,
+            replay,
+        };
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+
+        Ok(manifest)
+    }
+
+    /// Construct an [ExecutorEnvBuilder] from a manifest bundle in `dir`.
+    ///
+    /// The contents of every blob are checked against the digest recorded in
+    /// the manifest. Any file descriptors, named input channels and slice I/O
+    /// channels listed in the manifest, other than stdin, stdout, and stderr,
+    /// must be re-attached to the returned builder. Named input channels keep
+    /// their file descriptors when re-attached with
+    /// [ExecutorEnvBuilder::channel].
+    pub fn from_manifest<P: AsRef<Path>>(dir: P) -> Result<ExecutorEnvBuilder<'a>> {
+        let dir = dir.as_ref();
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest: EnvManifest = serde_json::from_slice(
+            &fs::read(&manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+        )?;
+        ensure!(
+            manifest.version == MANIFEST_VERSION,
+            "Unsupported manifest version: {}",
+            manifest.version
+        );
+
+        let mut builder = ExecutorEnv::builder();
+        builder
+            .env_vars(manifest.env_vars.into_iter().collect())
+            .args(&manifest.args)
+            .session_limit(manifest.session_limit)
+            .memory_limit(manifest.memory_limit)
+            .clock(manifest.clock)
+            .channel_fds(manifest.channels);
+        if let Some(po2) = manifest.segment_limit_po2 {
+            builder.segment_limit_po2(po2);
+        }
+        if let Some(split_points) = manifest.segment_plan {
+            #[cfg(feature = "prove")]
+            builder.segment_plan(crate::SegmentPlan::new(split_points));
+            #[cfg(not(feature = "prove"))]
+            bail!(
+                "Manifest has a segment plan with {} split points, which requires the `prove` feature",
+                split_points.len()
+            );
+        }
+        if let Some(digest) = manifest.input_digest {
+            builder.input_digest(digest);
+        }
+        if let Some(input) = manifest.input {
+            builder.write_slice(&read_blob(dir, &input)?);
+        }
+        for assumption in manifest.assumptions.iter() {
+            builder.add_assumption(read_decoded_blob::<Assumption>(dir, assumption)?);
+        }
+        if let Some(replay) = manifest.replay {
+            builder.replay(read_decoded_blob::<SessionRecord>(dir, &replay)?);
+        }
+
+        Ok(builder)
+    }
+}
+
+fn blob_path(dir: &Path, digest: &Digest) -> PathBuf {
+    dir.join(BLOBS_DIR).join(hex::encode(digest.as_bytes()))
+}
+
+fn write_blob(dir: &Path, bytes: &[u8]) -> Result<BlobRef> {
+    let digest = *Impl::hash_bytes(bytes);
+    fs::write(blob_path(dir, &digest), bytes)?;
+    Ok(BlobRef {
+        digest,
+        len: bytes.len() as u64,
+    })
+}
+
+fn read_blob(dir: &Path, blob: &BlobRef) -> Result<Vec<u8>> {
+    let path = blob_path(dir, &blob.digest);
+    let bytes =
+        fs::read(&path).with_context(|| format!("Failed to read blob {}", path.display()))?;
+    if bytes.len() as u64 != blob.len || *Impl::hash_bytes(&bytes) != blob.digest {
+        bail!("Blob {} does not match its digest", blob.digest);
+    }
+    Ok(bytes)
+}
+
+fn read_decoded_blob<T: DeserializeOwned>(dir: &Path, blob: &BlobRef) -> Result<T> {
+    Ok(bincode::deserialize(&read_blob(dir, blob)?)?)
+}
@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "encryption")]
+pub(crate) mod encryption;
 pub(crate) mod env;
+pub(crate) mod manifest;
+pub(crate) mod observer;
 pub(crate) mod posix_io;
 pub(crate) mod prove;
+pub(crate) mod replay;
 pub(crate) mod slice_io;
@@ -0,0 +1,101 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Structured execution events for instrumenting the executor.
+
+use risc0_binfmt::ExitCode;
+
+/// Reported when the executor starts a new segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SegmentStart {
+    /// The index of the segment within the session.
+    pub index: u32,
+
+    /// The number of user cycles executed before this segment.
+    pub user_cycles: u64,
+}
+
+/// Reported after the host handles a syscall made by the guest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SyscallEvent<'a> {
+    /// The name of the syscall, e.g. `risc0_zkvm_platform::syscall::nr::SYS_READ`.
+    pub name: &'a str,
+
+    /// The session cycle at which the syscall was made.
+    pub cycle: u64,
+
+    /// The words written into the guest's buffer by the host.
+    pub to_guest: &'a [u32],
+
+    /// The values returned in registers `a0` and `a1`.
+    pub regs: (u32, u32),
+}
+
+/// Reported when the executor splits the session at the end of a segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SplitEvent {
+    /// The index of the segment that was just completed.
+    pub index: u32,
+
+    /// The po2 of the completed segment.
+    pub po2: u32,
+
+    /// The number of user cycles in the completed segment.
+    pub cycles: u32,
+}
+
+/// Reported once when the session stops executing with an [ExitCode].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExitEvent {
+    /// The [ExitCode] of the session.
+    pub exit_code: ExitCode,
+
+    /// The number of segments in the session.
+    pub segments: u32,
+
+    /// The number of user cycles executed by the session.
+    pub user_cycles: u64,
+
+    /// The total number of cycles, including paging and padding.
+    pub total_cycles: u64,
+}
+
+/// A handler for structured events produced while executing a guest.
+///
+/// Register an observer with [crate::ExecutorEnvBuilder::observer]. Every
+/// method has an empty default implementation, so an observer only needs to
+/// implement the events it is interested in. Unlike a
+/// [crate::TraceCallback], which sees every instruction, these events are
+/// produced at most once per syscall or segment and are cheap enough to use
+/// for telemetry in production.
+pub trait SessionObserver {
+    /// Called when the executor starts a new segment.
+    fn on_segment_start(&mut self, _event: &SegmentStart) {}
+
+    /// Called after the host handles a syscall.
+    fn on_syscall(&mut self, _event: &SyscallEvent<'_>) {}
+
+    /// Called when the session is split at the end of a segment.
+    fn on_split(&mut self, _event: &SplitEvent) {}
+
+    /// Called once when the session stops executing with an [ExitCode].
+    ///
+    /// This is not called if execution fails, e.g. because the guest
+    /// panicked or a syscall handler returned an error. The error is returned
+    /// from the executor's `run` method instead.
+    fn on_exit(&mut self, _event: &ExitEvent) {}
+}
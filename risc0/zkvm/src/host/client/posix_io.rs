@@ -15,10 +15,11 @@
 use std::{
     cell::RefCell,
     collections::BTreeMap,
-    io::{stderr, stdout, BufRead, Cursor, Write},
+    io::{self, stderr, stdout, BufRead, Cursor, Read, Write},
     rc::Rc,
 };
 
+use anyhow::{anyhow, ensure, Result};
 use risc0_zkvm_platform::fileno;
 
 /// Posix-style I/O
@@ -26,6 +27,8 @@ use risc0_zkvm_platform::fileno;
 pub struct PosixIo<'a> {
     pub(crate) read_fds: BTreeMap<u32, Rc<RefCell<dyn BufRead + 'a>>>,
     pub(crate) write_fds: BTreeMap<u32, Rc<RefCell<dyn Write + 'a>>>,
+    /// The number of bytes the guest has read from each file descriptor.
+    pub(crate) read_offsets: BTreeMap<u32, u64>,
 }
 
 impl<'a> Default for PosixIo<'a> {
@@ -33,6 +36,7 @@ impl<'a> Default for PosixIo<'a> {
         let mut new = Self {
             read_fds: Default::default(),
             write_fds: Default::default(),
+            read_offsets: Default::default(),
         };
         new.with_read_fd(fileno::STDIN, Cursor::new(vec![]))
             .with_write_fd(fileno::STDOUT, stdout())
@@ -51,4 +55,23 @@ impl<'a> PosixIo<'a> {
         self.write_fds.insert(fd, Rc::new(RefCell::new(writer)));
         self
     }
+
+    /// Discard the first `offsets[fd]` bytes of each read file descriptor, so
+    /// that the guest continues reading where an earlier execution stopped.
+    pub(crate) fn skip_reads(&mut self, offsets: &BTreeMap<u32, u64>) -> Result<()> {
+        for (&fd, &len) in offsets {
+            let reader = self
+                .read_fds
+                .get(&fd)
+                .ok_or_else(|| anyhow!("Bad read file descriptor {fd}"))?;
+            let mut reader = reader.borrow_mut();
+            let skipped = io::copy(&mut Read::take(&mut *reader, len), &mut io::sink())?;
+            ensure!(
+                skipped == len,
+                "File descriptor {fd} has {skipped} bytes, expected at least {len}"
+            );
+        }
+        self.read_offsets = offsets.clone();
+        Ok(())
+    }
 }
@@ -15,12 +15,14 @@
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use bonsai_sdk::alpha::Client;
+use bonsai_sdk::alpha::{Client, ExpectedReceipt, ReceiptVerifier};
+use hex::FromHex;
+use risc0_binfmt::MemoryImage;
 
 use super::Prover;
 use crate::{
-    compute_image_id, sha::Digestible, ExecutorEnv, InnerReceipt, ProveInfo, ProverOpts, Receipt,
-    VerifierContext,
+    sha::{Digest, Digestible},
+    ExecutorEnv, InnerReceipt, ProveInfo, ProverOpts, Receipt, VerifierContext,
 };
 
 /// An implementation of a [Prover] that runs proof workloads via Bonsai.
@@ -55,7 +57,7 @@ impl Prover for BonsaiProver {
         let client = Client::from_env(crate::VERSION)?;
 
         // Compute the ImageID and upload the ELF binary
-        let image_id = compute_image_id(elf)?;
+        let image_id = MemoryImage::compute_id_cached(elf)?;
         let image_id_hex = hex::encode(image_id);
         client.upload_img(&image_id_hex, elf.to_vec())?;
 
@@ -122,6 +124,9 @@ impl Prover for BonsaiProver {
                         segments: stats.segments,
                         total_cycles: stats.total_cycles,
                         user_cycles: stats.cycles,
+                        heap: None,
+                        guest_pages: None,
+                        insns: None,
                     },
                 });
             } else {
@@ -151,3 +156,49 @@ impl Prover for BonsaiProver {
         }
     }
 }
+
+/// A [ReceiptVerifier] for receipts downloaded with the Bonsai SDK.
+///
+/// Receipts are decoded with bincode and verified against the expected image
+/// ID with the default [VerifierContext]. If a journal digest is supplied, the
+/// journal of the receipt must match it.
+///
+/// ```
+/// use bonsai_sdk::alpha::Client;
+/// use risc0_zkvm::BonsaiReceiptVerifier;
+///
+/// let client = Client::from_parts(
+///     "http://api.bonsai.xyz".to_string(),
+///     "my_secret_key".to_string(),
+///     risc0_zkvm::VERSION,
+/// )
+/// .unwrap()
+/// .with_receipt_verifier(BonsaiReceiptVerifier);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BonsaiReceiptVerifier;
+
+impl BonsaiReceiptVerifier {
+    fn verify_receipt(receipt: &[u8], expected: &ExpectedReceipt) -> Result<()> {
+        let receipt: Receipt = bincode::deserialize(receipt)?;
+        let image_id = Digest::from_hex(&expected.image_id).context("invalid image ID")?;
+        receipt.verify(image_id)?;
+        if let Some(journal_digest) = &expected.journal_digest {
+            let journal_digest =
+                Digest::from_hex(journal_digest).context("invalid journal digest")?;
+            ensure!(
+                receipt.journal.digest() == journal_digest,
+                "received unexpected journal digest: expected {}, found {}",
+                hex::encode(journal_digest),
+                hex::encode(receipt.journal.digest())
+            );
+        }
+        Ok(())
+    }
+}
+
+impl ReceiptVerifier for BonsaiReceiptVerifier {
+    fn verify(&self, receipt: &[u8], expected: &ExpectedReceipt) -> Result<(), String> {
+        Self::verify_receipt(receipt, expected).map_err(|err| format!("{err:#}"))
+    }
+}
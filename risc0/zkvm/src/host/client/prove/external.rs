@@ -15,10 +15,11 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, ensure, Result};
+use risc0_binfmt::MemoryImage;
 
 use super::{Executor, Prover, ProverOpts};
 use crate::{
-    compute_image_id, host::api::AssetRequest, sha::Digestible, ApiClient, Asset, CompositeReceipt,
+    host::api::AssetRequest, is_dev_mode, sha::Digestible, ApiClient, Asset, CompositeReceipt,
     ExecutorEnv, InnerReceipt, ProveInfo, Receipt, SegmentReceipt, SessionInfo, SuccinctReceipt,
     VerifierContext,
 };
@@ -100,7 +101,7 @@ impl Prover for ExternalProver {
     ) -> Result<ProveInfo> {
         tracing::debug!("Launching {}", &self.r0vm_path.to_string_lossy());
 
-        let image_id = compute_image_id(elf)?;
+        let image_id = MemoryImage::compute_id_cached(elf)?;
         let client = ApiClient::new_sub_process(&self.r0vm_path)?;
         let binary = Asset::Inline(elf.to_vec().into());
         let prove_info = client.prove(&env, opts, binary)?;
@@ -135,8 +136,9 @@ impl Prover for ExternalProver {
                     journal: receipt.journal.clone(),
                 })
             }
+            InnerReceipt::Fake { .. } if is_dev_mode() => Ok(receipt.clone()),
             InnerReceipt::Fake { .. } => {
-                bail!("ExternalProver cannot compress a fake receipt")
+                bail!("fake receipts can only be compressed in dev mode")
             }
         }
     }
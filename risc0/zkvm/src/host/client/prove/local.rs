@@ -16,8 +16,8 @@ use anyhow::{bail, Result};
 
 use super::{Executor, Prover, ProverOpts};
 use crate::{
-    get_prover_server, host::server::session::NullSegmentRef, ExecutorEnv, ExecutorImpl,
-    InnerReceipt, ProveInfo, Receipt, SegmentInfo, SessionInfo, VerifierContext,
+    get_prover_server, host::server::session::NullSegmentRef, is_dev_mode, ExecutorEnv,
+    ExecutorImpl, InnerReceipt, ProveInfo, Receipt, SegmentInfo, SessionInfo, VerifierContext,
 };
 
 /// A [Prover] implementation that selects a [crate::ProverServer] by calling
@@ -57,8 +57,10 @@ impl Prover for LocalProver {
                 inner: InnerReceipt::Succinct(get_prover_server(opts)?.compress(&inner)?),
                 journal: receipt.journal.clone(),
             }),
+            // A fake receipt has no seal to compress, so it stays as it is.
+            InnerReceipt::Fake { .. } if is_dev_mode() => Ok(receipt.clone()),
             InnerReceipt::Fake { .. } => {
-                bail!("BonsaiProver does not support compress on a composite receipt")
+                bail!("fake receipts can only be compressed in dev mode")
             }
         }
     }
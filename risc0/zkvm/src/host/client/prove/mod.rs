@@ -24,7 +24,8 @@ use serde::{Deserialize, Serialize};
 
 use self::{bonsai::BonsaiProver, external::ExternalProver};
 use crate::{
-    host::prove_info::ProveInfo, is_dev_mode, ExecutorEnv, Receipt, SessionInfo, VerifierContext,
+    host::prove_info::ProveInfo, is_dev_mode, sha::Digestible, ExecutorEnv, Receipt, SessionInfo,
+    VerifierContext,
 };
 
 /// A Prover can execute a given ELF binary and produce a
@@ -95,6 +96,37 @@ pub trait Prover {
     ///
     /// If the receipt is succinct, this function will do nothing (i.e. it is idemopotent).
     fn compress(&self, opts: &ProverOpts, receipt: &Receipt) -> Result<Receipt>;
+
+    /// Aggregate unrelated receipts, possibly of different guests, into a single succinct
+    /// [Receipt].
+    ///
+    /// `aggregator_elf` must be a guest that runs [crate::guest::aggregate::run], such as
+    /// `AGGREGATE_ELF` from the `risc0-zkvm-aggregation` crate. It verifies each of `receipts`
+    /// with composition, and the resulting receipt is then compressed with `opts`, resolving
+    /// every assumption. The journal of the returned receipt is an
+    /// [Aggregation](crate::aggregation::Aggregation) of the image IDs and journals of
+    /// `receipts`, in order.
+    ///
+    /// Each of `receipts` must be unconditional and have exited with `Halted(0)` or `Paused(0)`.
+    fn aggregate(
+        &self,
+        opts: &ProverOpts,
+        aggregator_elf: &[u8],
+        receipts: &[Receipt],
+    ) -> Result<Receipt> {
+        let mut input = Vec::with_capacity(receipts.len());
+        let mut env = ExecutorEnv::builder();
+        for receipt in receipts {
+            let image_id = receipt.get_claim()?.pre.digest();
+            input.push((image_id, receipt.journal.bytes.clone()));
+            env.add_assumption(receipt.clone());
+        }
+        let env = env.write(&input)?.build()?;
+        let receipt = self
+            .prove_with_ctx(env, &VerifierContext::default(), aggregator_elf, opts)?
+            .receipt;
+        self.compress(opts, &receipt)
+    }
 }
 
 /// An Executor can execute a given ELF binary.
@@ -106,7 +138,11 @@ pub trait Executor {
 }
 
 /// Options to configure a [Prover].
+///
+/// Start from [ProverOpts::default] or [ProverOpts::fast] and adjust it with
+/// the `with_*` methods.
 #[derive(Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ProverOpts {
     /// The hash function to use.
     pub hashfn: String,
@@ -120,6 +156,23 @@ pub struct ProverOpts {
     // moment if there is a better place. At some point before 1.0, this option should be moved or
     // dropped.
     pub prove_guest_errors: bool,
+    /// When true, segments that are identical to an earlier segment of the
+    /// same session are proven only once and their receipt is reused.
+    ///
+    /// This only helps guests that return to exactly the same state at
+    /// segment boundaries with the same inputs, such as tight loops over a
+    /// fixed-size working set. Detection costs a hash of each segment.
+    #[serde(default)]
+    pub dedup_segments: bool,
+    /// When set, receipts are written to this directory as they are
+    /// produced, and receipts already in it are reused instead of proven
+    /// again.
+    ///
+    /// This lets a long proof resume after the prover is interrupted, e.g. on
+    /// a preemptible cloud instance, by proving the same session with the same
+    /// directory.
+    #[serde(default)]
+    pub checkpoint_dir: Option<PathBuf>,
 }
 
 impl Default for ProverOpts {
@@ -129,6 +182,8 @@ impl Default for ProverOpts {
         Self {
             hashfn: "poseidon2".to_string(),
             prove_guest_errors: false,
+            dedup_segments: false,
+            checkpoint_dir: None,
         }
     }
 }
@@ -139,6 +194,40 @@ impl ProverOpts {
         Self {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            dedup_segments: false,
+            checkpoint_dir: None,
+        }
+    }
+
+    /// Use the named hash function, see [ProverOpts::hashfn].
+    pub fn with_hashfn(self, hashfn: impl Into<String>) -> Self {
+        Self {
+            hashfn: hashfn.into(),
+            ..self
+        }
+    }
+
+    /// Set [ProverOpts::prove_guest_errors].
+    pub fn with_prove_guest_errors(self, prove_guest_errors: bool) -> Self {
+        Self {
+            prove_guest_errors,
+            ..self
+        }
+    }
+
+    /// Set [ProverOpts::dedup_segments].
+    pub fn with_dedup_segments(self, dedup_segments: bool) -> Self {
+        Self {
+            dedup_segments,
+            ..self
+        }
+    }
+
+    /// Set [ProverOpts::checkpoint_dir].
+    pub fn with_checkpoint_dir(self, checkpoint_dir: Option<PathBuf>) -> Self {
+        Self {
+            checkpoint_dir,
+            ..self
         }
     }
 }
@@ -0,0 +1,60 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records of guest executions that can be replayed deterministically.
+
+use risc0_binfmt::ExitCode;
+use risc0_zkp::core::digest::Digest;
+use serde::{Deserialize, Serialize};
+
+/// A single host syscall observed during execution, along with the data the
+/// host returned to the guest.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedSyscall {
+    /// The name of the syscall, e.g. `risc0_zkvm_platform::syscall::nr::SYS_READ`.
+    pub name: String,
+
+    /// The words written into the guest's buffer by the host.
+    pub to_guest: Vec<u32>,
+
+    /// The values returned in registers `a0` and `a1`.
+    pub regs: (u32, u32),
+}
+
+/// A record of everything the host provided to a guest during a [crate::Session].
+///
+/// This includes every syscall response, such as input data read from the
+/// host and randomness provided via `sys_rand`. Passing a [SessionRecord] to
+/// [crate::ExecutorEnvBuilder::replay] will re-execute the guest using the
+/// recorded responses, which reproduces the original execution exactly.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// The image ID of the initial memory image.
+    pub pre_state: Digest,
+
+    /// The input digest.
+    pub input: Digest,
+
+    /// The ordered list of syscalls made by the guest.
+    pub syscalls: Vec<RecordedSyscall>,
+
+    /// The [ExitCode] of the recorded session.
+    pub exit_code: ExitCode,
+
+    /// The journal committed by the guest, if any.
+    pub journal: Option<Vec<u8>>,
+
+    /// The digest of the system state at the end of execution.
+    pub post_state: Digest,
+}
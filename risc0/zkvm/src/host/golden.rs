@@ -0,0 +1,309 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden-file assertions for journals.
+//!
+//! Guest regression tests often check that a journal is unchanged from a
+//! known-good run. Comparing raw journal bytes makes failures hard to read, so
+//! the helpers here decode the journal into the type the guest committed,
+//! compare it field by field against a JSON golden file, and report only the
+//! fields that differ.
+//!
+//! Set `RISC0_UPDATE_GOLDEN=1` to write the decoded journal to the golden file
+//! instead of comparing against it.
+//!
+//! ```no_run
+//! # use risc0_zkvm::{assert_journal_eq, Receipt};
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Output {
+//!     balance: u64,
+//!     owner: String,
+//! }
+//!
+//! # let receipt: Receipt = todo!();
+//! assert_journal_eq!(receipt, "tests/golden/transfer.json", Output);
+//! ```
+
+use std::{fmt, path::Path};
+
+use anyhow::{Context as _, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::Journal;
+
+/// Environment variable which, when set to `1`, rewrites golden files with the
+/// decoded journal instead of comparing against them.
+pub const UPDATE_GOLDEN_ENV: &str = "RISC0_UPDATE_GOLDEN";
+
+/// A field whose value differs between the golden file and the journal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDiff {
+    /// Path to the field, such as `$.accounts[2].balance`.
+    pub path: String,
+
+    /// The value in the golden file, or `None` if the field is absent there.
+    pub expected: Option<Value>,
+
+    /// The value in the journal, or `None` if the field is absent there.
+    pub actual: Option<Value>,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => {
+                write!(f, "{}: expected {expected}, got {actual}", self.path)
+            }
+            (Some(expected), None) => write!(f, "{}: missing, expected {expected}", self.path),
+            (None, Some(actual)) => write!(f, "{}: unexpected {actual}", self.path),
+            (None, None) => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Compare the journal, decoded as `T`, against the golden file at `path`.
+///
+/// The golden file is decoded as `T` as well, so both sides are checked
+/// against the same schema. Returns the fields that differ, which is empty if
+/// the journal matches.
+pub fn diff_journal<T>(journal: &Journal, path: impl AsRef<Path>) -> Result<Vec<FieldDiff>>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let path = path.as_ref();
+    let actual: T = journal
+        .decode()
+        .context("failed to decode journal with the given schema")?;
+    let actual = serde_json::to_value(&actual)?;
+
+    let golden = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read golden file {}", path.display()))?;
+    let expected: T = serde_json::from_str(&golden).with_context(|| {
+        format!(
+            "failed to decode golden file {} with the given schema",
+            path.display()
+        )
+    })?;
+    let expected = serde_json::to_value(&expected)?;
+
+    let mut diffs = Vec::new();
+    diff_values("$".to_string(), Some(&expected), Some(&actual), &mut diffs);
+    Ok(diffs)
+}
+
+/// Write the journal, decoded as `T`, to the golden file at `path`.
+pub fn write_golden<T>(journal: &Journal, path: impl AsRef<Path>) -> Result<()>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let path = path.as_ref();
+    let value: T = journal
+        .decode()
+        .context("failed to decode journal with the given schema")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut json = serde_json::to_string_pretty(&value)?;
+    json.push('\n');
+    std::fs::write(path, json)
+        .with_context(|| format!("failed to write golden file {}", path.display()))
+}
+
+/// Assert that the journal, decoded as `T`, matches the golden file at `path`.
+///
+/// Panics with a field-level diff if it does not. Prefer the
+/// [assert_journal_eq](crate::assert_journal_eq) macro, which reports the
+/// caller's location.
+#[track_caller]
+pub fn assert_journal_golden<T>(journal: &Journal, path: impl AsRef<Path>)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let path = path.as_ref();
+    if std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|v| v == "1") {
+        if let Err(err) = write_golden::<T>(journal, path) {
+            panic!("{err:?}");
+        }
+        return;
+    }
+
+    let diffs = match diff_journal::<T>(journal, path) {
+        Ok(diffs) => diffs,
+        Err(err) => panic!("{err:?}"),
+    };
+    if !diffs.is_empty() {
+        let mut msg = format!(
+            "journal does not match golden file {} ({} fields differ):\n",
+            path.display(),
+            diffs.len()
+        );
+        for diff in &diffs {
+            msg += &format!("  {diff}\n");
+        }
+        msg += &format!("Set {UPDATE_GOLDEN_ENV}=1 to update the golden file.");
+        panic!("{msg}");
+    }
+}
+
+/// Assert that a receipt's journal matches a JSON golden file, decoding both
+/// with the given schema type.
+///
+/// On mismatch, panics with the paths and values of the fields that differ.
+/// See [golden](crate::golden) for details.
+#[macro_export]
+macro_rules! assert_journal_eq {
+    ($receipt:expr, $golden_path:expr, $schema:ty $(,)?) => {
+        $crate::golden::assert_journal_golden::<$schema>(&($receipt).journal, $golden_path)
+    };
+}
+
+fn diff_values(
+    path: String,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    diffs: &mut Vec<FieldDiff>,
+) {
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_values(
+                    format!("{path}.{key}"),
+                    expected.get(key),
+                    actual.get(key),
+                    diffs,
+                );
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for i in 0..expected.len().max(actual.len()) {
+                diff_values(
+                    format!("{path}[{i}]"),
+                    expected.get(i),
+                    actual.get(i),
+                    diffs,
+                );
+            }
+        }
+        (expected, actual) if expected != actual => diffs.push(FieldDiff {
+            path,
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::serde::to_vec;
+
+    #[derive(Serialize, Deserialize)]
+    struct Account {
+        owner: String,
+        balances: Vec<u64>,
+    }
+
+    fn journal(account: &Account) -> Journal {
+        Journal::new(bytemuck::cast_slice(&to_vec(account).unwrap()).to_vec())
+    }
+
+    fn golden(dir: &Path, value: Value) -> std::path::PathBuf {
+        let path = dir.join("golden.json");
+        std::fs::write(&path, value.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn matching_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = golden(dir.path(), json!({ "owner": "alice", "balances": [1, 2] }));
+        let account = Account {
+            owner: "alice".into(),
+            balances: vec![1, 2],
+        };
+        assert!(diff_journal::<Account>(&journal(&account), &path)
+            .unwrap()
+            .is_empty());
+        assert_journal_golden::<Account>(&journal(&account), &path);
+    }
+
+    #[test]
+    fn reports_changed_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = golden(dir.path(), json!({ "owner": "alice", "balances": [1, 2] }));
+        let account = Account {
+            owner: "bob".into(),
+            balances: vec![1, 3, 4],
+        };
+        let diffs: Vec<String> = diff_journal::<Account>(&journal(&account), &path)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diffs,
+            [
+                "$.balances[1]: expected 2, got 3",
+                "$.balances[2]: unexpected 4",
+                "$.owner: expected \"alice\", got \"bob\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn golden_must_match_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = golden(dir.path(), json!({ "owner": "alice" }));
+        let account = Account {
+            owner: "alice".into(),
+            balances: vec![],
+        };
+        let err = diff_journal::<Account>(&journal(&account), &path).unwrap_err();
+        assert!(err.to_string().contains("with the given schema"));
+    }
+
+    #[test]
+    fn write_then_compare() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/golden.json");
+        let account = Account {
+            owner: "carol".into(),
+            balances: vec![7],
+        };
+        write_golden::<Account>(&journal(&account), &path).unwrap();
+        assert!(diff_journal::<Account>(&journal(&account), &path)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "$.owner: expected \"alice\", got \"bob\"")]
+    fn assert_panics_with_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = golden(dir.path(), json!({ "owner": "alice", "balances": [] }));
+        let account = Account {
+            owner: "bob".into(),
+            balances: vec![],
+        };
+        assert_journal_golden::<Account>(&journal(&account), &path);
+    }
+}
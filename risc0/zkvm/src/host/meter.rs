@@ -0,0 +1,345 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pricing of guest executions.
+//!
+//! A [Meter] converts the resource [Usage] of a session into a cost using a
+//! pluggable [PricingPolicy], and issues a [SignedQuote] that can be handed to
+//! a client and later checked by the issuer.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, ensure, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{serde::to_vec, sha::Digest, SessionStats};
+
+/// Resources consumed by a session, used as the input to a [PricingPolicy].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Number of segments.
+    pub segments: u64,
+
+    /// Total cycles, including continuation overhead and padding.
+    pub total_cycles: u64,
+
+    /// Cycles spent executing the guest program.
+    pub user_cycles: u64,
+
+    /// Number of host syscalls made by the guest.
+    pub syscalls: u64,
+
+    /// Size of the journal in bytes.
+    pub journal_bytes: u64,
+}
+
+impl From<&SessionStats> for Usage {
+    fn from(stats: &SessionStats) -> Self {
+        Self {
+            segments: stats.segments as u64,
+            total_cycles: stats.total_cycles,
+            user_cycles: stats.user_cycles,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "prove")]
+impl From<&crate::Session> for Usage {
+    fn from(session: &crate::Session) -> Self {
+        Self {
+            syscalls: session.syscall_count,
+            journal_bytes: session
+                .journal
+                .as_ref()
+                .map_or(0, |journal| journal.bytes.len() as u64),
+            ..Self::from(&session.stats())
+        }
+    }
+}
+
+/// A policy for converting [Usage] into a cost.
+///
+/// Costs are expressed as an integer number of the smallest unit of whatever
+/// currency the policy uses.
+pub trait PricingPolicy {
+    /// A name identifying this policy, recorded in each [Quote].
+    fn name(&self) -> String;
+
+    /// Compute the cost of the given [Usage].
+    fn price(&self, usage: &Usage) -> Result<u64>;
+}
+
+/// A [PricingPolicy] that charges a fixed rate per unit of each resource.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LinearPricing {
+    /// Flat cost for every session.
+    pub base: u64,
+
+    /// Cost per segment.
+    pub per_segment: u64,
+
+    /// Cost per million total cycles, rounded up.
+    pub per_mcycle: u64,
+
+    /// Cost per host syscall.
+    pub per_syscall: u64,
+
+    /// Cost per journal byte.
+    pub per_journal_byte: u64,
+}
+
+impl PricingPolicy for LinearPricing {
+    fn name(&self) -> String {
+        "linear".to_string()
+    }
+
+    fn price(&self, usage: &Usage) -> Result<u64> {
+        let mcycles = usage.total_cycles.div_ceil(1_000_000);
+        [
+            Some(self.base),
+            self.per_segment.checked_mul(usage.segments),
+            self.per_mcycle.checked_mul(mcycles),
+            self.per_syscall.checked_mul(usage.syscalls),
+            self.per_journal_byte.checked_mul(usage.journal_bytes),
+        ]
+        .into_iter()
+        .try_fold(0u64, |total, cost| total.checked_add(cost?))
+        .ok_or_else(|| anyhow!("Price overflow"))
+    }
+}
+
+/// A price quote for a session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    /// The usage being priced.
+    pub usage: Usage,
+
+    /// The name of the [PricingPolicy] used.
+    pub policy: String,
+
+    /// The price of the usage.
+    pub cost: u64,
+
+    /// The digest of the [crate::ReceiptClaim] this quote is bound to, if any.
+    pub claim: Option<Digest>,
+
+    /// Time the quote was issued, in seconds since the Unix epoch.
+    pub issued_at: u64,
+}
+
+/// A [Quote] along with a signature from the [QuoteSigner] of the issuing
+/// [Meter].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedQuote {
+    /// The quote.
+    pub quote: Quote,
+
+    /// The signature over the encoded quote.
+    pub signature: Vec<u8>,
+}
+
+/// Signs and verifies [Quote]s.
+pub trait QuoteSigner {
+    /// Sign the given message.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verify a signature produced by [QuoteSigner::sign].
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()>;
+}
+
+/// A [QuoteSigner] using HMAC-SHA256 with a shared secret key.
+pub struct HmacSigner {
+    key: Vec<u8>,
+}
+
+impl HmacSigner {
+    /// Construct a new [HmacSigner] from a secret key.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self { key: key.into() }
+    }
+
+    fn mac(&self, message: &[u8]) -> Result<Hmac<Sha256>> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).map_err(|_| anyhow!("Invalid HMAC key"))?;
+        mac.update(message);
+        Ok(mac)
+    }
+}
+
+impl QuoteSigner for HmacSigner {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.mac(message)?.finalize().into_bytes().to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        self.mac(message)?
+            .verify_slice(signature)
+            .map_err(|_| anyhow!("Invalid quote signature"))
+    }
+}
+
+/// Issues signed price quotes for sessions.
+pub struct Meter {
+    policy: Box<dyn PricingPolicy>,
+    signer: Box<dyn QuoteSigner>,
+}
+
+impl Meter {
+    /// Construct a new [Meter] from a [PricingPolicy] and [QuoteSigner].
+    pub fn new(policy: impl PricingPolicy + 'static, signer: impl QuoteSigner + 'static) -> Self {
+        Self {
+            policy: Box::new(policy),
+            signer: Box::new(signer),
+        }
+    }
+
+    /// Price the given [Usage] and return a [SignedQuote].
+    ///
+    /// If `claim` is provided, the quote is bound to that claim digest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use risc0_zkvm::{HmacSigner, LinearPricing, Meter, Usage};
+    ///
+    /// let policy = LinearPricing {
+    ///     per_segment: 10,
+    ///     per_mcycle: 100,
+    ///     ..Default::default()
+    /// };
+    /// let meter = Meter::new(policy, HmacSigner::new(b"secret".to_vec()));
+    /// let usage = Usage {
+    ///     segments: 2,
+    ///     total_cycles: 2 << 20,
+    ///     ..Default::default()
+    /// };
+    /// let quote = meter.quote(usage, None).unwrap();
+    /// assert_eq!(quote.quote.cost, 2 * 10 + 3 * 100);
+    /// meter.verify(&quote).unwrap();
+    /// ```
+    pub fn quote(&self, usage: Usage, claim: Option<Digest>) -> Result<SignedQuote> {
+        let quote = Quote {
+            cost: self.policy.price(&usage)?,
+            usage,
+            policy: self.policy.name(),
+            claim,
+            issued_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        let signature = self.signer.sign(&encode(&quote)?)?;
+        Ok(SignedQuote { quote, signature })
+    }
+
+    /// Price the given [crate::Session] and return a [SignedQuote] bound to
+    /// its claim.
+    #[cfg(feature = "prove")]
+    pub fn quote_session(&self, session: &crate::Session) -> Result<SignedQuote> {
+        use crate::sha::Digestible;
+
+        self.quote(session.into(), Some(session.get_claim()?.digest()))
+    }
+
+    /// Verify that a [SignedQuote] was issued by this [Meter].
+    ///
+    /// This checks the signature and that the cost matches the current
+    /// [PricingPolicy].
+    pub fn verify(&self, quote: &SignedQuote) -> Result<()> {
+        self.signer
+            .verify(&encode(&quote.quote)?, &quote.signature)?;
+        ensure!(
+            quote.quote.policy == self.policy.name(),
+            "Quote was issued with pricing policy {}",
+            quote.quote.policy
+        );
+        ensure!(
+            quote.quote.cost == self.policy.price(&quote.quote.usage)?,
+            "Quote cost does not match the pricing policy"
+        );
+        Ok(())
+    }
+}
+
+/// Encode a [Quote] as the message to be signed.
+fn encode(quote: &Quote) -> Result<Vec<u8>> {
+    Ok(bytemuck::cast_slice(&to_vec(quote)?).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meter() -> Meter {
+        let policy = LinearPricing {
+            base: 1,
+            per_segment: 10,
+            per_mcycle: 100,
+            per_syscall: 2,
+            per_journal_byte: 3,
+        };
+        Meter::new(policy, HmacSigner::new(b"secret".to_vec()))
+    }
+
+    #[test]
+    fn linear_price() {
+        let usage = Usage {
+            segments: 2,
+            total_cycles: 1_000_001,
+            user_cycles: 900_000,
+            syscalls: 5,
+            journal_bytes: 32,
+        };
+        let quote = meter().quote(usage, None).unwrap();
+        assert_eq!(quote.quote.cost, 1 + 2 * 10 + 2 * 100 + 5 * 2 + 32 * 3);
+        assert_eq!(quote.quote.policy, "linear");
+    }
+
+    #[test]
+    fn price_overflow() {
+        let policy = LinearPricing {
+            per_segment: u64::MAX,
+            ..Default::default()
+        };
+        let usage = Usage {
+            segments: 2,
+            ..Default::default()
+        };
+        assert!(policy.price(&usage).is_err());
+    }
+
+    #[test]
+    fn verify_quote() {
+        let meter = meter();
+        let quote = meter
+            .quote(
+                Usage {
+                    segments: 1,
+                    ..Default::default()
+                },
+                Some(Digest::ZERO),
+            )
+            .unwrap();
+        meter.verify(&quote).unwrap();
+
+        // Tampering with the quote invalidates the signature.
+        let mut tampered = quote.clone();
+        tampered.quote.cost -= 1;
+        assert!(meter.verify(&tampered).is_err());
+
+        // A different key rejects the quote.
+        let other = Meter::new(LinearPricing::default(), HmacSigner::new(b"other".to_vec()));
+        assert!(other.verify(&quote).is_err());
+    }
+}
@@ -16,8 +16,14 @@
 pub(crate) mod api;
 #[cfg(feature = "client")]
 pub(crate) mod client;
+#[cfg(feature = "client")]
+pub(crate) mod golden;
+#[cfg(feature = "client")]
+pub(crate) mod meter;
 pub(crate) mod prove_info;
-pub(crate) mod receipt;
+pub(crate) use risc0_zkvm_verify::receipt;
+#[cfg(feature = "client")]
+pub(crate) mod receipt_store;
 pub(crate) mod recursion;
 #[cfg(feature = "prove")]
 pub(crate) mod server;
@@ -14,6 +14,10 @@
 
 //! Struct containing information about a prover's execution including the receipt.
 
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{Context as _, Result};
+
 use crate::Receipt;
 
 /// Information returned by the prover including receipt as well as other information useful for debugging
@@ -32,4 +36,87 @@ pub struct SessionStats {
     pub total_cycles: u64,
     /// User cycles run within guest
     pub user_cycles: u64,
+    /// Heap usage reported by the guest, if it was built with the `heap-stats` feature
+    pub heap: Option<HeapStats>,
+    /// Number of distinct guest memory pages touched, if reported by the prover
+    pub guest_pages: Option<u64>,
+    /// Instruction counts, if enabled with `ExecutorEnvBuilder::enable_insn_stats`
+    pub insns: Option<InsnStats>,
+}
+
+/// Struct containing information about the guest's heap usage
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeapStats {
+    /// Number of bytes of heap used by the guest
+    pub peak_heap: u64,
+    /// Peak number of bytes allocated and not yet deallocated
+    pub peak_live_bytes: u64,
+    /// Total number of allocations
+    pub total_allocs: u64,
+    /// Total number of deallocations
+    pub total_deallocs: u64,
+    /// Number of bytes of heap used by the guest at the end of each segment
+    pub segment_peaks: Vec<u64>,
+}
+
+/// Struct containing instruction counts gathered during execution
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InsnStats {
+    /// Number of instructions executed, keyed by lowercase mnemonic
+    pub opcodes: BTreeMap<String, u64>,
+    /// Number of user cycles spent on instructions, keyed by lowercase
+    /// mnemonic. Environment calls include the work done by the call, such as
+    /// SHA-256 compression rounds.
+    pub cycles: BTreeMap<String, u64>,
+    /// The most frequently executed instructions, hottest first
+    pub hotspots: Vec<Hotspot>,
+}
+
+/// A frequently executed instruction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hotspot {
+    /// Program counter of the instruction
+    pub pc: u32,
+    /// Number of times the instruction was executed
+    pub count: u64,
+    /// Function containing the instruction, as `name+0xoffset`, if known
+    pub symbol: Option<String>,
+}
+
+impl InsnStats {
+    /// Render these stats as CSV with the columns `kind,name,count,symbol`.
+    ///
+    /// Opcode rows have the kind `opcode` and the mnemonic as their name;
+    /// cycle rows have the kind `cycles`, the mnemonic as their name and the
+    /// cycle total as their count; hotspot rows have the kind `pc` and the hex
+    /// program counter as their name.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("kind,name,count,symbol\n");
+        for (name, count) in &self.opcodes {
+            out += &format!("opcode,{name},{count},\n");
+        }
+        for (name, cycles) in &self.cycles {
+            out += &format!("cycles,{name},{cycles},\n");
+        }
+        for hotspot in &self.hotspots {
+            let symbol = hotspot.symbol.as_deref().map(csv_field).unwrap_or_default();
+            out += &format!("pc,0x{:08x},{},{symbol}\n", hotspot.pc, hotspot.count);
+        }
+        out
+    }
+
+    /// Write these stats as CSV to `path`. See [InsnStats::to_csv].
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_csv())
+            .with_context(|| format!("failed to write instruction stats {}", path.display()))
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
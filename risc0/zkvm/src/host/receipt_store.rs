@@ -0,0 +1,358 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Archival storage for receipts.
+//!
+//! A [ReceiptStore] keeps receipts keyed by the digest of their
+//! [ReceiptClaim], and indexes them by image ID and journal digest so that
+//! applications can look up proofs without scanning everything they have
+//! archived. [MemoryReceiptStore] is suitable for tests and caches; enable the
+//! `receipt-store-sqlite` feature for a persistent [SqliteReceiptStore].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Read, Write},
+};
+
+use anyhow::{Context as _, Result};
+
+use crate::{
+    sha::{Digest, Digestible},
+    Receipt, ReceiptClaim,
+};
+
+/// The keys under which a receipt is indexed in a [ReceiptStore].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReceiptKeys {
+    /// The digest of the [ReceiptClaim], which identifies the receipt.
+    pub claim_digest: Digest,
+
+    /// The image ID of the guest that produced the receipt.
+    pub image_id: Digest,
+
+    /// The digest of the journal.
+    pub journal_digest: Digest,
+}
+
+impl ReceiptKeys {
+    /// Compute the keys of `receipt`.
+    pub fn new(receipt: &Receipt) -> Result<Self> {
+        let claim: ReceiptClaim = receipt.get_claim()?;
+        Ok(Self {
+            claim_digest: claim.digest(),
+            image_id: claim.pre.digest(),
+            journal_digest: receipt.journal.digest(),
+        })
+    }
+}
+
+/// A store of receipts, indexed by claim digest, image ID and journal digest.
+///
+/// Inserting a receipt with the same claim as an existing one replaces it.
+pub trait ReceiptStore {
+    /// Insert `receipt` and return its keys.
+    fn insert(&mut self, receipt: &Receipt) -> Result<ReceiptKeys>;
+
+    /// Get the receipt with the given claim digest.
+    fn get(&self, claim_digest: &Digest) -> Result<Option<Receipt>>;
+
+    /// Get all receipts produced by the guest with the given image ID.
+    fn find_by_image_id(&self, image_id: &Digest) -> Result<Vec<Receipt>>;
+
+    /// Get all receipts with the given journal digest.
+    fn find_by_journal_digest(&self, journal_digest: &Digest) -> Result<Vec<Receipt>>;
+
+    /// Get every receipt in the store.
+    fn all(&self) -> Result<Vec<Receipt>>;
+
+    /// Write every receipt in the store to `writer`, returning the number of
+    /// receipts written.
+    ///
+    /// The output can be loaded into any [ReceiptStore] with
+    /// [ReceiptStore::import].
+    fn export(&self, writer: &mut dyn Write) -> Result<usize> {
+        let receipts = self.all()?;
+        bincode::serialize_into(writer, &receipts)?;
+        Ok(receipts.len())
+    }
+
+    /// Insert every receipt from the output of [ReceiptStore::export],
+    /// returning the number of receipts read.
+    fn import(&mut self, reader: &mut dyn Read) -> Result<usize> {
+        let receipts: Vec<Receipt> =
+            bincode::deserialize_from(reader).context("failed to read receipt export")?;
+        for receipt in receipts.iter() {
+            self.insert(receipt)?;
+        }
+        Ok(receipts.len())
+    }
+}
+
+/// A [ReceiptStore] which keeps receipts in memory.
+#[derive(Default)]
+pub struct MemoryReceiptStore {
+    receipts: BTreeMap<Digest, (ReceiptKeys, Receipt)>,
+    by_image_id: BTreeMap<Digest, BTreeSet<Digest>>,
+    by_journal_digest: BTreeMap<Digest, BTreeSet<Digest>>,
+}
+
+impl MemoryReceiptStore {
+    /// Construct an empty [MemoryReceiptStore].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lookup(
+        &self,
+        index: &BTreeMap<Digest, BTreeSet<Digest>>,
+        key: &Digest,
+    ) -> Result<Vec<Receipt>> {
+        Ok(index
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|id| self.receipts[id].1.clone())
+            .collect())
+    }
+}
+
+impl ReceiptStore for MemoryReceiptStore {
+    fn insert(&mut self, receipt: &Receipt) -> Result<ReceiptKeys> {
+        let keys = ReceiptKeys::new(receipt)?;
+        let id = keys.claim_digest;
+        if let Some((old, _)) = self.receipts.insert(id, (keys, receipt.clone())) {
+            remove_entry(&mut self.by_image_id, &old.image_id, &id);
+            remove_entry(&mut self.by_journal_digest, &old.journal_digest, &id);
+        }
+        self.by_image_id
+            .entry(keys.image_id)
+            .or_default()
+            .insert(id);
+        self.by_journal_digest
+            .entry(keys.journal_digest)
+            .or_default()
+            .insert(id);
+        Ok(keys)
+    }
+
+    fn get(&self, claim_digest: &Digest) -> Result<Option<Receipt>> {
+        Ok(self
+            .receipts
+            .get(claim_digest)
+            .map(|(_, receipt)| receipt.clone()))
+    }
+
+    fn find_by_image_id(&self, image_id: &Digest) -> Result<Vec<Receipt>> {
+        self.lookup(&self.by_image_id, image_id)
+    }
+
+    fn find_by_journal_digest(&self, journal_digest: &Digest) -> Result<Vec<Receipt>> {
+        self.lookup(&self.by_journal_digest, journal_digest)
+    }
+
+    fn all(&self) -> Result<Vec<Receipt>> {
+        Ok(self
+            .receipts
+            .values()
+            .map(|(_, receipt)| receipt.clone())
+            .collect())
+    }
+}
+
+fn remove_entry(index: &mut BTreeMap<Digest, BTreeSet<Digest>>, key: &Digest, id: &Digest) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+#[cfg(feature = "receipt-store-sqlite")]
+pub use self::sqlite::SqliteReceiptStore;
+
+#[cfg(feature = "receipt-store-sqlite")]
+mod sqlite {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use rusqlite::{params, Connection, OptionalExtension};
+
+    use super::{ReceiptKeys, ReceiptStore};
+    use crate::{sha::Digest, Receipt};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS receipts (
+            claim_digest BLOB PRIMARY KEY NOT NULL,
+            image_id BLOB NOT NULL,
+            journal_digest BLOB NOT NULL,
+            receipt BLOB NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS receipts_image_id ON receipts (image_id);
+        CREATE INDEX IF NOT EXISTS receipts_journal_digest ON receipts (journal_digest);
+    ";
+
+    /// A [ReceiptStore] backed by a SQLite database.
+    pub struct SqliteReceiptStore {
+        conn: Connection,
+    }
+
+    impl SqliteReceiptStore {
+        /// Open the database at `path`, creating it if it does not exist.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            Self::with_connection(Connection::open(path)?)
+        }
+
+        /// Open a database which is kept in memory.
+        pub fn open_in_memory() -> Result<Self> {
+            Self::with_connection(Connection::open_in_memory()?)
+        }
+
+        fn with_connection(conn: Connection) -> Result<Self> {
+            conn.execute_batch(SCHEMA)?;
+            Ok(Self { conn })
+        }
+
+        fn query(&self, sql: &str, key: &Digest) -> Result<Vec<Receipt>> {
+            let mut stmt = self.conn.prepare_cached(sql)?;
+            let rows = stmt.query_map([key.as_bytes()], |row| row.get::<_, Vec<u8>>(0))?;
+            rows.map(|blob| Ok(bincode::deserialize(&blob?)?)).collect()
+        }
+    }
+
+    impl ReceiptStore for SqliteReceiptStore {
+        fn insert(&mut self, receipt: &Receipt) -> Result<ReceiptKeys> {
+            let keys = ReceiptKeys::new(receipt)?;
+            self.conn.execute(
+                "INSERT OR REPLACE INTO receipts (claim_digest, image_id, journal_digest, receipt)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    keys.claim_digest.as_bytes(),
+                    keys.image_id.as_bytes(),
+                    keys.journal_digest.as_bytes(),
+                    bincode::serialize(receipt)?,
+                ],
+            )?;
+            Ok(keys)
+        }
+
+        fn get(&self, claim_digest: &Digest) -> Result<Option<Receipt>> {
+            let blob: Option<Vec<u8>> = self
+                .conn
+                .query_row(
+                    "SELECT receipt FROM receipts WHERE claim_digest = ?1",
+                    [claim_digest.as_bytes()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(blob.map(|blob| bincode::deserialize(&blob)).transpose()?)
+        }
+
+        fn find_by_image_id(&self, image_id: &Digest) -> Result<Vec<Receipt>> {
+            self.query(
+                "SELECT receipt FROM receipts WHERE image_id = ?1 ORDER BY claim_digest",
+                image_id,
+            )
+        }
+
+        fn find_by_journal_digest(&self, journal_digest: &Digest) -> Result<Vec<Receipt>> {
+            self.query(
+                "SELECT receipt FROM receipts WHERE journal_digest = ?1 ORDER BY claim_digest",
+                journal_digest,
+            )
+        }
+
+        fn all(&self) -> Result<Vec<Receipt>> {
+            let mut stmt = self
+                .conn
+                .prepare_cached("SELECT receipt FROM receipts ORDER BY claim_digest")?;
+            let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?;
+            rows.map(|blob| Ok(bincode::deserialize(&blob?)?)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryReceiptStore, ReceiptStore};
+    use crate::{
+        sha::{Digest, Digestible},
+        ExitCode, InnerReceipt, MaybePruned, Receipt, ReceiptClaim,
+    };
+
+    fn receipt(image_id: u32, input: u32, journal: &[u8]) -> Receipt {
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::from([image_id; 8])),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::from([input; 8]),
+            output: MaybePruned::Pruned(Digest::ZERO),
+        };
+        Receipt::new(InnerReceipt::Fake { claim }, journal.to_vec())
+    }
+
+    fn check_store(store: &mut dyn ReceiptStore) {
+        let a = receipt(1, 1, b"a");
+        let b = receipt(1, 2, b"b");
+        let c = receipt(2, 3, b"a");
+        let keys = store.insert(&a).unwrap();
+        store.insert(&b).unwrap();
+        store.insert(&c).unwrap();
+
+        assert_eq!(keys.image_id, Digest::from([1; 8]));
+        assert_eq!(keys.journal_digest, a.journal.digest());
+        assert_eq!(store.get(&keys.claim_digest).unwrap(), Some(a.clone()));
+        assert_eq!(store.get(&Digest::ZERO).unwrap(), None);
+
+        let by_image = store.find_by_image_id(&Digest::from([1; 8])).unwrap();
+        assert_eq!(by_image.len(), 2);
+        assert!(by_image.contains(&a) && by_image.contains(&b));
+        let by_journal = store.find_by_journal_digest(&a.journal.digest()).unwrap();
+        assert_eq!(by_journal.len(), 2);
+        assert!(by_journal.contains(&a) && by_journal.contains(&c));
+        assert!(store
+            .find_by_image_id(&Digest::from([3; 8]))
+            .unwrap()
+            .is_empty());
+
+        // Inserting the same claim again replaces the receipt.
+        let a2 = receipt(1, 1, b"a2");
+        store.insert(&a2).unwrap();
+        assert_eq!(store.get(&keys.claim_digest).unwrap(), Some(a2.clone()));
+        assert_eq!(store.all().unwrap().len(), 3);
+        let by_journal = store.find_by_journal_digest(&a.journal.digest()).unwrap();
+        assert_eq!(by_journal, vec![c.clone()]);
+
+        let mut buf = Vec::new();
+        assert_eq!(store.export(&mut buf).unwrap(), 3);
+        let mut copy = MemoryReceiptStore::new();
+        assert_eq!(copy.import(&mut buf.as_slice()).unwrap(), 3);
+        let mut expected = store.all().unwrap();
+        let mut actual = copy.all().unwrap();
+        expected.sort_by_key(|r| r.journal.bytes.clone());
+        actual.sort_by_key(|r| r.journal.bytes.clone());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(&mut MemoryReceiptStore::new());
+    }
+
+    #[cfg(feature = "receipt-store-sqlite")]
+    #[test]
+    fn sqlite_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        check_store(&mut super::SqliteReceiptStore::open(tmp.path().join("receipts.db")).unwrap());
+    }
+}
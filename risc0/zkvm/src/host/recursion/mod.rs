@@ -19,18 +19,18 @@
 
 #[cfg(feature = "prove")]
 mod prove;
-mod receipt;
 #[cfg(test)]
 #[cfg(feature = "prove")]
 mod tests;
 
 pub use risc0_circuit_recursion::control_id::ALLOWED_IDS_ROOT;
+pub use risc0_zkvm_verify::recursion::{valid_control_ids, SuccinctReceipt};
 
 #[cfg(feature = "prove")]
 pub use self::prove::{
     identity_p254, join, lift, poseidon2_hal_pair, resolve, Program, Prover, ProverOpts,
     RECURSION_PO2,
 };
-pub use self::receipt::{valid_control_ids, SuccinctReceipt};
 
+#[cfg(feature = "prove")]
 const CIRCUIT: risc0_circuit_recursion::CircuitImpl = risc0_circuit_recursion::CircuitImpl::new();
@@ -18,7 +18,10 @@ use risc0_zkp::{
     core::digest::{Digest, DIGEST_WORDS},
     field::baby_bear::BabyBearElem,
 };
-use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+use risc0_zkvm_aggregation::{AGGREGATE_ELF, AGGREGATE_ID};
+use risc0_zkvm_methods::{
+    multi_test::MultiTestSpec, HELLO_COMMIT_ELF, HELLO_COMMIT_ID, MULTI_TEST_ELF, MULTI_TEST_ID,
+};
 use serial_test::serial;
 use test_log::test;
 
@@ -27,8 +30,9 @@ use super::{
     ProverOpts as RecursionProverOpts,
 };
 use crate::{
-    default_prover, get_prover_server, ExecutorEnv, ExecutorImpl, InnerReceipt, ProverOpts,
-    Receipt, SegmentReceipt, Session, VerifierContext, ALLOWED_IDS_ROOT,
+    aggregation::Aggregation, default_prover, get_prover_server, ExecutorEnv, ExecutorImpl,
+    InnerReceipt, Prover as _, ProverOpts, Receipt, SegmentReceipt, Session, VerifierContext,
+    ALLOWED_IDS_ROOT,
 };
 
 // Failure on older mac minis in the lab with Intel UHD 630 graphics:
@@ -142,6 +146,8 @@ fn generate_busy_loop_segments(hashfn: &str) -> (Session, Vec<SegmentReceipt>) {
     let opts = ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        dedup_segments: false,
+        checkpoint_dir: None,
     };
     let prover = get_prover_server(&opts).unwrap();
 
@@ -263,6 +269,42 @@ fn test_recursion_lift_resolve_e2e() {
     succinct_receipt.verify(MULTI_TEST_ID).unwrap();
 }
 
+#[cfg_attr(
+    not(all(feature = "metal", target_os = "macos", target_arch = "x86_64")),
+    test
+)]
+#[serial]
+fn test_recursion_aggregate_e2e() {
+    let prover = default_prover();
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Echo {
+            bytes: b"execution A".to_vec(),
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+    let receipt_a = prover.prove(env, MULTI_TEST_ELF).unwrap().receipt;
+    let env = ExecutorEnv::builder().build().unwrap();
+    let receipt_b = prover.prove(env, HELLO_COMMIT_ELF).unwrap().receipt;
+
+    let receipt = prover
+        .aggregate(
+            &ProverOpts::default(),
+            AGGREGATE_ELF,
+            &[receipt_a, receipt_b],
+        )
+        .unwrap();
+    assert!(matches!(receipt.inner, InnerReceipt::Succinct(_)));
+    receipt.verify(AGGREGATE_ID).unwrap();
+
+    let aggregation: Aggregation = receipt.journal.decode().unwrap();
+    assert_eq!(aggregation.claims.len(), 2);
+    assert!(aggregation.contains(MULTI_TEST_ID, b"execution A"));
+    assert!(aggregation.contains(HELLO_COMMIT_ID, b"hello world"));
+    assert!(!aggregation.contains(HELLO_COMMIT_ID, b"execution A"));
+}
+
 #[test]
 fn stable_root() {
     // This tests that none of the control IDs have changed unexpectedly.
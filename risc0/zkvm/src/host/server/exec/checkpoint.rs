@@ -0,0 +1,136 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Restartable snapshots of long-running executions.
+//!
+//! When [crate::ExecutorEnvBuilder::checkpoint_every] is set, the executor
+//! periodically stops at a segment split and writes a [Checkpoint] holding
+//! everything needed to continue the session from that split. After a crash,
+//! [crate::ExecutorImpl::from_checkpoint] loads it and continues from there.
+//!
+//! A checkpoint holds guest memory, including any input the guest has read,
+//! so it is encrypted with the env's cipher when
+//! [crate::ExecutorEnvBuilder::encrypt_at_rest] is set.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{bail, Context as _, Result};
+use risc0_binfmt::MemoryImage;
+use risc0_circuit_rv32im::prove::emu::exec::ExecutorProgress;
+use serde::{Deserialize, Serialize};
+
+use crate::{Assumption, ClockPolicy, ExecutorEnv};
+
+const MAGIC: &[u8; 8] = b"R0VMCKPT";
+const VERSION: u32 = 3;
+const FILE_NAME: &str = "checkpoint.bin";
+
+/// Marks a checkpoint body stored as is.
+const PLAIN: u8 = 0;
+
+/// Marks a checkpoint body sealed with the env's [crate::EnvelopeCipher].
+const SEALED: u8 = 1;
+
+/// The state of a session at a segment split.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// The memory image at the split.
+    pub(crate) image: MemoryImage,
+
+    /// Segment and cycle counts of the session so far.
+    pub(crate) progress: ExecutorProgress,
+
+    /// The data the guest has written to the journal so far.
+    pub(crate) journal: Vec<u8>,
+
+    /// The number of bytes the guest has read from each file descriptor.
+    pub(crate) read_offsets: BTreeMap<u32, u64>,
+
+    /// The assumptions the guest has accessed so far.
+    pub(crate) assumptions: Vec<Assumption>,
+
+    /// The clock the guest has seen so far.
+    pub(crate) clock: ClockPolicy,
+}
+
+impl Checkpoint {
+    /// Load the checkpoint stored in `dir`, decrypting it with the cipher of
+    /// `env` if it was sealed.
+    pub(crate) fn open(dir: &Path, env: &ExecutorEnv) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        let bytes = fs::read(&path)
+            .with_context(|| format!("Failed to read checkpoint: {}", path.display()))?;
+        Self::decode(&bytes, env)
+    }
+
+    /// Store this checkpoint in `dir`, replacing any earlier checkpoint.
+    ///
+    /// The checkpoint is sealed with the cipher of `env`, if any. It is written
+    /// to a temporary file first, so a crash while writing leaves the previous
+    /// checkpoint intact.
+    pub(crate) fn save(&self, dir: &Path, env: &ExecutorEnv) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(FILE_NAME);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, self.encode(env)?)
+            .with_context(|| format!("Failed to write checkpoint: {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write checkpoint: {}", path.display()))
+    }
+
+    fn encode(&self, env: &ExecutorEnv) -> Result<Vec<u8>> {
+        let body = bincode::serialize(self)?;
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = &env.cipher {
+            bytes.push(SEALED);
+            bytes.extend(cipher.seal(&body)?);
+            return Ok(bytes);
+        }
+        #[cfg(not(feature = "encryption"))]
+        let _ = env;
+        bytes.push(PLAIN);
+        bytes.extend(body);
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8], env: &ExecutorEnv) -> Result<Self> {
+        let header_len = MAGIC.len() + std::mem::size_of::<u32>() + 1;
+        if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+            bail!("Not an executor checkpoint");
+        }
+        let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len - 1].try_into()?);
+        if version != VERSION {
+            bail!("Unsupported checkpoint version: {version}");
+        }
+        let body = &bytes[header_len..];
+        match bytes[header_len - 1] {
+            PLAIN => Ok(bincode::deserialize(body)?),
+            #[cfg(feature = "encryption")]
+            SEALED => {
+                let Some(cipher) = &env.cipher else {
+                    bail!("Checkpoint is encrypted, but no cipher is configured");
+                };
+                Ok(bincode::deserialize(&cipher.open(body)?)?)
+            }
+            #[cfg(not(feature = "encryption"))]
+            SEALED => {
+                let _ = env;
+                bail!("Checkpoint is encrypted, which requires the `encryption` feature")
+            }
+            kind => bail!("Unknown checkpoint encoding: {kind}"),
+        }
+    }
+}
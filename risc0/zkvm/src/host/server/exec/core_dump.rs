@@ -0,0 +1,172 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshots of guest state for post-mortem analysis.
+//!
+//! When [crate::ExecutorEnvBuilder::core_dump] is set, the executor writes a
+//! [GuestCore] if the guest panics or faults. The core contains the registers
+//! and the guest pages touched in the segment where execution stopped, and can
+//! be loaded later with [GuestCore::open].
+
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+use anyhow::{bail, Context as _, Result};
+use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
+use risc0_circuit_rv32im::prove::emu::exec::ExecutorSnapshot;
+use risc0_zkvm_platform::{syscall::reg_abi::REG_MAX, PAGE_SIZE};
+use rustc_demangle::demangle;
+use serde::{Deserialize, Serialize};
+
+const MAGIC: &[u8; 8] = b"R0VMCORE";
+const VERSION: u32 = 1;
+
+const REG_NAMES: [&str; REG_MAX] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// A snapshot of the guest taken when execution stopped abnormally.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GuestCore {
+    /// A description of why execution stopped.
+    pub reason: String,
+
+    /// The program counter of the instruction that was being executed.
+    pub pc: u32,
+
+    /// The number of user cycles executed before execution stopped.
+    pub cycle: u64,
+
+    /// The general purpose registers, indexed by register number.
+    pub registers: [u32; REG_MAX],
+
+    /// The guest pages touched in the final segment, keyed by page index.
+    pub pages: BTreeMap<u32, Vec<u8>>,
+}
+
+/// A function symbol in the guest program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuestSymbol {
+    /// The demangled name of the function.
+    pub name: String,
+
+    /// The offset of the address from the start of the function.
+    pub offset: u32,
+}
+
+impl GuestCore {
+    pub(crate) fn new(reason: String, snapshot: ExecutorSnapshot) -> Self {
+        Self {
+            reason,
+            pc: snapshot.pc,
+            cycle: snapshot.cycle,
+            registers: snapshot.registers,
+            pages: snapshot.pages,
+        }
+    }
+
+    /// Load a [GuestCore] previously written by the executor.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read core dump: {}", path.display()))?;
+        Self::decode(&bytes)
+    }
+
+    /// Write this [GuestCore] to the specified path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.encode()?)
+            .with_context(|| format!("Failed to write core dump: {}", path.display()))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(self)?);
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let header_len = MAGIC.len() + std::mem::size_of::<u32>();
+        if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+            bail!("Not a guest core dump");
+        }
+        let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into()?);
+        if version != VERSION {
+            bail!("Unsupported core dump version: {version}");
+        }
+        Ok(bincode::deserialize(&bytes[header_len..])?)
+    }
+
+    /// Load a byte of guest memory, if the containing page is in the core.
+    pub fn load_u8(&self, addr: u32) -> Option<u8> {
+        let page = self.pages.get(&(addr / PAGE_SIZE as u32))?;
+        Some(page[addr as usize % PAGE_SIZE])
+    }
+
+    /// Load a region of guest memory, if all containing pages are in the core.
+    pub fn load_region(&self, addr: u32, size: u32) -> Option<Vec<u8>> {
+        (addr..addr.checked_add(size)?)
+            .map(|addr| self.load_u8(addr))
+            .collect()
+    }
+
+    /// Find the function containing `addr` in the guest program `elf`.
+    pub fn lookup_symbol(elf: &[u8], addr: u32) -> Result<Option<GuestSymbol>> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)?;
+        let Some((symtab, strtab)) = elf.symbol_table()? else {
+            return Ok(None);
+        };
+        for sym in symtab {
+            let start = sym.st_value;
+            let end = start + sym.st_size;
+            if sym.st_symtype() == STT_FUNC && (start..end).contains(&(addr as u64)) {
+                let name = strtab.get(sym.st_name as usize)?;
+                return Ok(Some(GuestSymbol {
+                    name: demangle(name).to_string(),
+                    offset: (addr as u64 - start) as u32,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Render a human readable summary of this core, resolving code addresses
+    /// against the guest program `elf`.
+    pub fn symbolize(&self, elf: &[u8]) -> Result<String> {
+        let describe = |addr: u32| -> Result<String> {
+            Ok(match Self::lookup_symbol(elf, addr)? {
+                Some(sym) => format!("0x{addr:08x} <{}+0x{:x}>", sym.name, sym.offset),
+                None => format!("0x{addr:08x}"),
+            })
+        };
+
+        let mut out = String::new();
+        writeln!(out, "reason: {}", self.reason)?;
+        writeln!(out, "cycle:  {}", self.cycle)?;
+        writeln!(out, "pc:     {}", describe(self.pc)?)?;
+        writeln!(out, "ra:     {}", describe(self.registers[1])?)?;
+        writeln!(out, "registers:")?;
+        for (name, value) in REG_NAMES.iter().zip(self.registers.iter()) {
+            writeln!(out, "  {name:>4}: 0x{value:08x}")?;
+        }
+        writeln!(out, "pages:")?;
+        for page_idx in self.pages.keys() {
+            writeln!(out, "  0x{:08x}", page_idx * PAGE_SIZE as u32)?;
+        }
+        Ok(out)
+    }
+}
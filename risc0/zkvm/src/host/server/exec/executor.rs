@@ -12,9 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{cell::RefCell, io::Write, mem, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    cell::{Cell, RefCell},
+    io::Write,
+    mem,
+    ops::ControlFlow,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 
-use anyhow::{Context as _, Result};
+use anyhow::{ensure, Context as _, Result};
 use human_repr::HumanDuration as _;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_circuit_rv32im::prove::emu::{
@@ -29,23 +38,46 @@ use risc0_zkvm_platform::{fileno, memory::GUEST_MAX_MEM, PAGE_SIZE};
 use tempfile::tempdir;
 
 use crate::{
-    host::client::env::SegmentPath, Assumption, Assumptions, ExecutorEnv, FileSegmentRef, Output,
-    Segment, SegmentRef, Session,
+    host::{
+        client::{env::SegmentPath, replay::RecordedSyscall},
+        server::session::{null_callback, SegmentWriter},
+    },
+    sha::Digestible,
+    Assumption, Assumptions, ClockPolicy, ExecutorEnv, ExitCode, ExitEvent, ExpectedOutputs,
+    FileSegmentRef, GuestPanic, HeapStats, Output, Segment, SegmentInfo, SegmentRef, SegmentStart,
+    Session, SessionObserver, SplitEvent, SuspendedState, SyscallEvent, TraceFormat,
 };
 
 use super::{
+    checkpoint::Checkpoint,
+    core_dump::GuestCore,
+    insn_stats::InsnCounter,
+    memory_audit::MemoryAudit,
     profiler::Profiler,
+    replay::Replayer,
     syscall::{SyscallContext, SyscallTable},
+    trace_file::TraceWriter,
 };
 
 // The Executor provides an implementation for the execution phase.
 ///
 /// The proving phase uses an execution trace generated by the Executor.
 pub struct ExecutorImpl<'a> {
-    env: ExecutorEnv<'a>,
+    pub(crate) env: ExecutorEnv<'a>,
     image: MemoryImage,
+    elf: Option<Vec<u8>>,
     pub(crate) syscall_table: SyscallTable<'a>,
     profiler: Option<Rc<RefCell<Profiler>>>,
+    memory_audit: Option<Rc<RefCell<MemoryAudit>>>,
+    insn_counter: Option<Rc<RefCell<InsnCounter>>>,
+    trace_writer: Option<Rc<RefCell<TraceWriter>>>,
+    syscalls: RefCell<Vec<RecordedSyscall>>,
+    syscall_count: Cell<u64>,
+    replay: Option<RefCell<Replayer>>,
+    restored: Option<Checkpoint>,
+    heap_stats: Rc<RefCell<Option<HeapStats>>>,
+    #[cfg(feature = "gdb-stub")]
+    gdb: Option<Rc<RefCell<super::gdb::GdbStub>>>,
 }
 
 impl<'a> ExecutorImpl<'a> {
@@ -80,7 +112,7 @@ impl<'a> ExecutorImpl<'a> {
         let program = Program::load_elf(elf, GUEST_MAX_MEM as u32)?;
         let image = MemoryImage::new(&program, PAGE_SIZE as u32)?;
 
-        let profiler = if env.pprof_out.is_some() {
+        let profiler = if env.pprof_out.is_some() || env.folded_out.is_some() {
             let profiler = Rc::new(RefCell::new(Profiler::new(elf, None)?));
             env.trace.push(profiler.clone());
             Some(profiler)
@@ -88,32 +120,265 @@ impl<'a> ExecutorImpl<'a> {
             None
         };
 
-        Self::with_details(env, image, profiler)
+        let mut exec = Self::with_details(env, image, profiler)?;
+        if let Some(trace_writer) = &exec.trace_writer {
+            trace_writer.borrow_mut().load_symbols(elf)?;
+        }
+        exec.elf = Some(elf.to_vec());
+        Ok(exec)
+    }
+
+    /// Construct a new [ExecutorImpl] that resumes a paused guest from a
+    /// [SuspendedState].
+    ///
+    /// The [SuspendedState] may come from a different process. `env` must
+    /// provide the same inputs as the original execution; input the guest
+    /// already read before it paused is skipped. Assumptions the guest
+    /// verifies after resuming must be added to `env` again.
+    ///
+    /// # Example
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, ExecutorImpl, ExitCode, SuspendedState};
+    /// use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+    ///
+    /// let env = || {
+    ///     ExecutorEnv::builder()
+    ///         .write(&MultiTestSpec::PauseResume(1))
+    ///         .unwrap()
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let session = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF).unwrap().run().unwrap();
+    /// let bytes = bincode::serialize(&session.suspend().unwrap()).unwrap();
+    ///
+    /// // Possibly in another process:
+    /// let state: SuspendedState = bincode::deserialize(&bytes).unwrap();
+    /// let session = ExecutorImpl::resume(state, env())
+    ///     .unwrap()
+    ///     .run()
+    ///     .unwrap();
+    /// assert_eq!(session.exit_code, ExitCode::Halted(0));
+    /// ```
+    pub fn resume(state: SuspendedState, mut env: ExecutorEnv<'a>) -> Result<Self> {
+        ensure!(
+            matches!(state.exit_code, ExitCode::Paused(_)),
+            "cannot resume from exit code {:?}",
+            state.exit_code
+        );
+        env.posix_io.borrow_mut().skip_reads(&state.read_offsets)?;
+        env.clock = state.clock;
+        Self::new(env, state.image)
+    }
+
+    /// Construct a new [ExecutorImpl] that continues a session from the last
+    /// checkpoint written to `dir` by [crate::ExecutorEnvBuilder::checkpoint_every].
+    ///
+    /// `env` must provide the same guest, inputs and assumptions as the
+    /// original execution; input the guest already read before the checkpoint
+    /// is skipped. [ExecutorImpl::run] includes the segments stored before the
+    /// checkpoint in the returned [Session], while
+    /// [ExecutorImpl::run_with_callback] only reports the segments executed
+    /// after it.
+    pub fn from_checkpoint<P: AsRef<Path>>(mut env: ExecutorEnv<'a>, dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let checkpoint = Checkpoint::open(dir, &env)?;
+        env.posix_io
+            .borrow_mut()
+            .skip_reads(&checkpoint.read_offsets)?;
+        env.assumptions.borrow_mut().accessed = checkpoint.assumptions.clone();
+        env.clock = checkpoint.clock;
+        if env.segment_path.is_none() {
+            env.segment_path = Some(SegmentPath::Path(dir.to_path_buf()));
+        }
+
+        let mut exec = Self::new(env, checkpoint.image.clone())?;
+        exec.restored = Some(checkpoint);
+        Ok(exec)
     }
 
     fn with_details(
-        env: ExecutorEnv<'a>,
+        mut env: ExecutorEnv<'a>,
         image: MemoryImage,
         profiler: Option<Rc<RefCell<Profiler>>>,
     ) -> Result<Self> {
-        let syscall_table = SyscallTable::new(&env);
+        // Capture the session start time once, so that it can be carried over
+        // to a resumed or restored session.
+        if env.clock == ClockPolicy::SessionStart {
+            env.clock = ClockPolicy::Fixed(SystemTime::now());
+        }
+        let heap_stats = Rc::new(RefCell::new(None));
+        let mut syscall_table = SyscallTable::new(&env);
+        syscall_table.with_heap_stats(heap_stats.clone());
+        let replay = env
+            .replay
+            .take()
+            .map(|record| RefCell::new(Replayer::new(record)));
+        let memory_audit = env.memory_audit.is_some().then(|| {
+            let memory_audit = Rc::new(RefCell::new(MemoryAudit::default()));
+            env.trace.push(memory_audit.clone());
+            memory_audit
+        });
+        let insn_counter = env.insn_stats.is_some().then(|| {
+            let insn_counter = Rc::new(RefCell::new(InsnCounter::default()));
+            env.trace.push(insn_counter.clone());
+            insn_counter
+        });
+        let trace_writer = env.trace_file.clone().map(|(path, format)| {
+            let trace_writer = match format {
+                TraceFormat::Perfetto => Rc::new(RefCell::new(TraceWriter::new(path))),
+            };
+            env.trace.push(trace_writer.clone());
+            env.observers.push(trace_writer.clone());
+            trace_writer
+        });
         Ok(Self {
             env,
             image,
+            elf: None,
             syscall_table,
             profiler,
+            memory_audit,
+            insn_counter,
+            trace_writer,
+            syscalls: RefCell::default(),
+            syscall_count: Cell::default(),
+            replay,
+            restored: None,
+            heap_stats,
+            #[cfg(feature = "gdb-stub")]
+            gdb: None,
         })
     }
 
+    /// Attach a GDB remote serial protocol debugger to this executor.
+    ///
+    /// Execution stops before the first instruction and waits for the
+    /// debugger connected on `stream` to resume it. The debugger can set
+    /// breakpoints, single-step, and inspect registers and guest memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::net::TcpListener;
+    ///
+    /// use risc0_zkvm::{ExecutorEnv, ExecutorImpl};
+    /// use risc0_zkvm_methods::HELLO_COMMIT_ELF;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:9000").unwrap();
+    /// let (stream, _) = listener.accept().unwrap();
+    ///
+    /// let mut exec = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF).unwrap();
+    /// exec.attach_gdb(stream).unwrap();
+    /// exec.run().unwrap();
+    /// ```
+    #[cfg(feature = "gdb-stub")]
+    pub fn attach_gdb(&mut self, stream: std::net::TcpStream) -> Result<()> {
+        stream.set_nodelay(true)?;
+        let gdb = Rc::new(RefCell::new(super::gdb::GdbStub::new(
+            stream,
+            self.image.clone(),
+        )));
+        self.env.trace.push(gdb.clone());
+        self.gdb = Some(gdb);
+        Ok(())
+    }
+
     /// This will run the executor to get a [Session] which contain the results
     /// of the execution.
     pub fn run(&mut self) -> Result<Session> {
+        if let Some(checkpoint) = &self.env.checkpoint {
+            std::fs::create_dir_all(&checkpoint.dir)?;
+        }
         if self.env.segment_path.is_none() {
             self.env.segment_path = Some(SegmentPath::TempDir(Arc::new(tempdir()?)));
         }
 
         let path = self.env.segment_path.clone().unwrap();
-        self.run_with_callback(|segment| Ok(Box::new(FileSegmentRef::new(&segment, &path)?)))
+
+        // Segments stored before a restored checkpoint are not executed again.
+        let restored = self
+            .restored
+            .as_ref()
+            .map_or(0, |checkpoint| checkpoint.progress.segments as u32);
+        let mut segments = (0..restored)
+            .map(|index| -> Result<Box<dyn SegmentRef>> {
+                let segment = FileSegmentRef::existing(index, &path)?;
+                #[cfg(feature = "encryption")]
+                let segment = segment.with_cipher(self.env.cipher.clone());
+                Ok(Box::new(segment))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut writer = SegmentWriter::new(path.clone());
+        #[cfg(feature = "encryption")]
+        writer.set_cipher(self.env.cipher.clone());
+        let mut session = self.run_with_callback(|segment| Ok(Box::new(writer.write(segment)?)))?;
+
+        segments.append(&mut session.segments);
+        session.segments = segments;
+        Ok(session)
+    }
+
+    /// Execute the guest and return the [ExpectedOutputs] that a receipt
+    /// would attest to, without proving.
+    ///
+    /// Segments are discarded as they are produced rather than written to
+    /// disk, so this is the cheapest way to learn the post-image ID and
+    /// journal digest of an execution.
+    pub fn expected_outputs(&mut self) -> Result<ExpectedOutputs> {
+        self.run_with_callback(null_callback)?.expected_outputs()
+    }
+
+    /// Run the executor twice and check that both runs produce the same
+    /// segments.
+    ///
+    /// The first run uses this executor's environment and is recorded. The
+    /// second run replays that record from the same initial image, so the
+    /// guest sees exactly the same host responses. If the runs differ in the
+    /// number of segments, or in the pre-state image ID, post-state image ID,
+    /// cycle count or exit code of any segment, this returns an error naming
+    /// the first divergent segment. This is meant as a one-call determinism
+    /// smoke test, e.g. in CI for host applications.
+    ///
+    /// Returns the [Session] of the first run. Executors restored from a
+    /// checkpoint, or configured to write checkpoints, are not supported.
+    pub fn run_twice_and_compare(&mut self) -> Result<Session> {
+        ensure!(
+            self.restored.is_none() && self.env.checkpoint.is_none(),
+            "Determinism audit does not support checkpoints"
+        );
+        let image = self.image.clone();
+
+        // Record the first run for the comparison only, leaving the env as
+        // the caller configured it.
+        let record = mem::replace(&mut self.env.record, true);
+        let first = self.run();
+        self.env.record = record;
+        let mut first = first?;
+        let replay = first.record()?;
+        if !record {
+            first.syscalls = None;
+        }
+
+        // Every setting that affects how execution proceeds or is split into
+        // segments is carried over; host I/O comes from the replayed record.
+        let env = ExecutorEnv {
+            clock: self.env.clock,
+            segment_limit_po2: self.env.segment_limit_po2,
+            session_limit: self.env.session_limit,
+            memory_limit: self.env.memory_limit,
+            input_digest: self.env.input_digest,
+            replay: Some(replay),
+            segment_plan: self.env.segment_plan.clone(),
+            ..Default::default()
+        };
+        env.assumptions.borrow_mut().cached = self.env.assumptions.borrow().cached.clone();
+        let second = ExecutorImpl::new(env, image)?
+            .run()
+            .context("Determinism audit failed: second run diverged from the first")?;
+
+        compare_sessions(&first, &second)?;
+        Ok(first)
     }
 
     /// Run the executor until [crate::ExitCode::Halted] or
@@ -122,7 +387,21 @@ impl<'a> ExecutorImpl<'a> {
     where
         F: FnMut(Segment) -> Result<Box<dyn SegmentRef>>,
     {
+        if let Some(replay) = &self.replay {
+            replay.borrow().check_image(self.image.compute_id())?;
+        }
+        self.syscalls.borrow_mut().clear();
+        self.syscall_count.set(0);
+        self.heap_stats.replace(None);
+
+        let restored = self.restored.take();
         let journal = Journal::default();
+        if let Some(restored) = &restored {
+            journal
+                .buf
+                .borrow_mut()
+                .extend_from_slice(&restored.journal);
+        }
         self.env
             .posix_io
             .borrow_mut()
@@ -134,59 +413,178 @@ impl<'a> ExecutorImpl<'a> {
             .unwrap_or(DEFAULT_SEGMENT_LIMIT_PO2 as u32) as usize;
 
         let mut refs = Vec::new();
-        let mut exec = Executor::new(
-            self.image.clone(),
-            self,
-            self.env.input_digest,
-            self.env.trace.clone(),
-        );
+        let mut heap_peaks = Vec::new();
+        let mut image = self.image.clone();
+        let mut progress = restored.map(|checkpoint| checkpoint.progress);
+        let checkpoint = self.env.checkpoint.clone();
+        let mut user_cycles = progress.as_ref().map_or(0, |progress| progress.user_cycles);
+        self.notify(|observer| {
+            observer.on_segment_start(&SegmentStart {
+                index: progress.as_ref().map_or(0, |progress| progress.segments) as u32,
+                user_cycles,
+            })
+        });
 
         let start_time = Instant::now();
-        let result = exec.run(segment_limit_po2, self.env.session_limit, |inner| {
-            let output = inner
-                .exit_code
-                .expects_output()
-                .then(|| -> Option<Result<_>> {
-                    inner
-                        .output_digest
-                        .and_then(|digest| {
-                            (digest != Digest::ZERO).then(|| journal.buf.borrow().clone())
-                        })
-                        .map(|journal| {
-                            Ok(Output {
-                                journal: journal.into(),
-                                assumptions: Assumptions(
-                                    self.env
-                                        .assumptions
-                                        .borrow()
-                                        .accessed
-                                        .iter()
-                                        .map(|a| {
-                                            Ok(match a {
-                                                Assumption::Proven(r) => r.get_claim()?.into(),
-                                                Assumption::Unresolved(r) => r.clone(),
+        let result = loop {
+            let mut exec =
+                Executor::new(image, self, self.env.input_digest, self.env.trace.clone());
+            if let Some(progress) = progress.take() {
+                exec = exec.with_progress(progress);
+            }
+            if let Some(plan) = &self.env.segment_plan {
+                exec = exec.with_split_points(plan.split_points().to_vec());
+            }
+            if let Some(limit) = self.env.memory_limit {
+                exec = exec.with_memory_limit(limit.try_into()?);
+            }
+
+            let mut checkpoint_due = false;
+            let result = exec.run_until(segment_limit_po2, self.env.session_limit, |inner| {
+                let split = inner.exit_code == ExitCode::SystemSplit;
+                // The bump allocator only grows, so the last report is the
+                // peak of this segment.
+                heap_peaks.push(
+                    self.heap_stats
+                        .borrow()
+                        .as_ref()
+                        .map_or(0, |stats| stats.peak_heap),
+                );
+                let info = SegmentInfo {
+                    po2: inner.po2 as u32,
+                    cycles: inner.insn_cycles as u32,
+                };
+                let output = inner
+                    .exit_code
+                    .expects_output()
+                    .then(|| -> Option<Result<_>> {
+                        inner
+                            .output_digest
+                            .and_then(|digest| {
+                                (digest != Digest::ZERO).then(|| journal.buf.borrow().clone())
+                            })
+                            .map(|journal| {
+                                Ok(Output {
+                                    journal: journal.into(),
+                                    assumptions: Assumptions(
+                                        self.env
+                                            .assumptions
+                                            .borrow()
+                                            .accessed
+                                            .iter()
+                                            .map(|a| {
+                                                Ok(match a {
+                                                    Assumption::Proven(r) => r.get_claim()?.into(),
+                                                    Assumption::Unresolved(r) => r.clone(),
+                                                })
                                             })
-                                        })
-                                        .collect::<Result<Vec<_>>>()?,
-                                )
-                                .into(),
+                                            .collect::<Result<Vec<_>>>()?,
+                                    )
+                                    .into(),
+                                })
                             })
+                    })
+                    .flatten()
+                    .transpose()?;
+
+                let segment = Segment {
+                    index: inner.index as u32,
+                    inner,
+                    output,
+                };
+                let index = segment.index;
+                let segment_ref = callback(segment.into())?;
+                refs.push(segment_ref);
+                user_cycles += info.cycles as u64;
+
+                let flow = match &self.env.segment_callback {
+                    Some(segment_callback) if split => segment_callback.borrow_mut()(&info),
+                    _ => ControlFlow::Continue(()),
+                };
+                if split {
+                    self.notify(|observer| {
+                        observer.on_split(&SplitEvent {
+                            index,
+                            po2: info.po2,
+                            cycles: info.cycles,
                         })
-                })
-                .flatten()
-                .transpose()?;
-
-            let segment = Segment {
-                index: inner.index as u32,
-                inner,
-                output,
+                    });
+                    if flow.is_continue() {
+                        self.notify(|observer| {
+                            observer.on_segment_start(&SegmentStart {
+                                index: index + 1,
+                                user_cycles,
+                            })
+                        });
+                    }
+                }
+                if let Some(checkpoint) = &checkpoint {
+                    if split && flow.is_continue() && (index + 1) % checkpoint.every == 0 {
+                        checkpoint_due = true;
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                Ok(flow)
+            });
+            let result = match result {
+                Ok(result) => result,
+                Err(mut err) => {
+                    self.dump_core(&exec, err.to_string());
+                    if let Err(audit_err) = self.write_memory_audit() {
+                        tracing::warn!("{audit_err:#}");
+                    }
+                    if let Err(trace_err) = self.finish_trace_file() {
+                        tracing::warn!("{trace_err:#}");
+                    }
+                    if let Some(panic) = err.downcast_mut::<GuestPanic>() {
+                        if let Some(elf) = self.elf.as_ref() {
+                            if let Err(symbolize_err) = panic.symbolize(elf) {
+                                tracing::warn!("failed to symbolize guest panic: {symbolize_err}");
+                            }
+                        }
+                        panic.heap = self.heap_stats.take();
+                    }
+                    return Err(err);
+                }
             };
-            let segment_ref = callback(segment.into())?;
-            refs.push(segment_ref);
-            Ok(())
-        })?;
+            if !checkpoint_due {
+                break result;
+            }
+
+            // Execution only stopped at this split to write a checkpoint, so
+            // continue from here with a new executor.
+            let current = exec.take_progress().context("missing executor progress")?;
+            let dir = &checkpoint.as_ref().unwrap().dir;
+            Checkpoint {
+                image: result.post_image.clone(),
+                progress: current.clone(),
+                journal: journal.buf.borrow().clone(),
+                read_offsets: self.env.posix_io.borrow().read_offsets.clone(),
+                assumptions: self.env.assumptions.borrow().accessed.clone(),
+                clock: self.env.clock,
+            }
+            .save(dir, &self.env)?;
+            tracing::debug!("wrote checkpoint after {} segments", current.segments);
+
+            image = result.post_image;
+            progress = Some(current);
+        };
         let elapsed = start_time.elapsed();
 
+        self.notify(|observer| {
+            observer.on_exit(&ExitEvent {
+                exit_code: result.exit_code,
+                segments: result.segments as u32,
+                user_cycles: result.user_cycles,
+                total_cycles: result.total_cycles,
+            })
+        });
+
+        #[cfg(feature = "gdb-stub")]
+        if let Some(gdb) = &self.gdb {
+            gdb.borrow_mut().on_exit(result.exit_code)?;
+        }
+
         // Set the session_journal to the committed data iff the the guest set a non-zero output.
         let session_journal = result
             .output_digest
@@ -204,13 +602,21 @@ impl<'a> ExecutorImpl<'a> {
         let assumptions = mem::take(&mut self.env.assumptions.borrow_mut().accessed);
 
         if let Some(profiler) = self.profiler.take() {
-            let report = profiler.borrow_mut().finalize_to_vec();
-            std::fs::write(self.env.pprof_out.as_ref().unwrap(), report)?;
+            let mut profiler = profiler.borrow_mut();
+            if let Some(pprof_out) = self.env.pprof_out.as_ref() {
+                std::fs::write(pprof_out, profiler.finalize_to_vec())?;
+            }
+            if let Some(folded_out) = self.env.folded_out.as_ref() {
+                std::fs::write(folded_out, profiler.finalize_to_folded())?;
+            }
         }
 
+        self.write_memory_audit()?;
+        self.finish_trace_file()?;
+
         self.image = result.post_image.clone();
 
-        let session = Session::new(
+        let mut session = Session::new(
             refs,
             self.env.input_digest.unwrap_or_default(),
             session_journal,
@@ -222,6 +628,32 @@ impl<'a> ExecutorImpl<'a> {
             result.pre_state,
             result.post_state,
         );
+        session.syscall_count = self.syscall_count.get();
+        session.syscalls = self.env.record.then(|| self.syscalls.take());
+        session.read_offsets = self.env.posix_io.borrow().read_offsets.clone();
+        session.clock = self.env.clock;
+        session.heap_stats = self.heap_stats.take().map(|stats| HeapStats {
+            segment_peaks: heap_peaks,
+            ..stats
+        });
+        session.guest_pages = result.guest_pages as u64;
+        if let (Some(insn_counter), Some(config)) = (&self.insn_counter, &self.env.insn_stats) {
+            let stats = insn_counter.borrow_mut().finalize(
+                config.hotspots,
+                self.elf.as_deref(),
+                result.user_cycles,
+            );
+            if let Some(path) = &config.csv {
+                stats.save_csv(path)?;
+            }
+            session.insn_stats = Some(stats);
+        }
+        session.sub_sessions = self.env.sub_sessions.take();
+
+        // A record only describes a single run, so replay is finished here.
+        if let Some(replay) = self.replay.take() {
+            replay.into_inner().finish(&session)?;
+        }
 
         tracing::info_span!("executor").in_scope(|| {
             tracing::info!("execution time: {}", elapsed.human_duration());
@@ -230,6 +662,53 @@ impl<'a> ExecutorImpl<'a> {
 
         Ok(session)
     }
+
+    fn notify(&self, mut f: impl FnMut(&mut dyn SessionObserver)) {
+        for observer in self.env.observers.iter() {
+            f(&mut *observer.borrow_mut());
+        }
+    }
+
+    /// Write the memory audit report if a report path is configured, warning
+    /// if the guest touched protected memory.
+    fn write_memory_audit(&self) -> Result<()> {
+        let (Some(memory_audit), Some(path)) = (&self.memory_audit, &self.env.memory_audit) else {
+            return Ok(());
+        };
+        let report = memory_audit.borrow_mut().take_report();
+        if !report.is_clean() {
+            tracing::warn!(
+                "guest made {} accesses to protected memory",
+                report.accesses.len()
+            );
+        }
+        report.save(path)
+    }
+
+    /// Finish writing the trace file, if one is configured.
+    fn finish_trace_file(&self) -> Result<()> {
+        match &self.trace_writer {
+            Some(trace_writer) => trace_writer.borrow_mut().finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a [GuestCore] if a core dump path is configured.
+    ///
+    /// Failures are logged rather than returned so that they do not mask the
+    /// error that stopped the guest.
+    fn dump_core(&self, exec: &Executor<'_, '_, Self>, reason: String) {
+        let Some(path) = self.env.core_dump.as_ref() else {
+            return;
+        };
+        tracing::info!("writing core dump to {}", path.display());
+        let result = exec
+            .snapshot()
+            .and_then(|snapshot| GuestCore::new(reason, snapshot).save(path));
+        if let Err(err) = result {
+            tracing::warn!("failed to write core dump: {err}");
+        }
+    }
 }
 
 struct ContextAdapter<'a> {
@@ -241,6 +720,14 @@ impl<'a> SyscallContext for ContextAdapter<'a> {
         self.ctx.get_cycle()
     }
 
+    fn get_segment_cycle(&self) -> usize {
+        self.ctx.get_segment_cycle()
+    }
+
+    fn get_pc(&self) -> u32 {
+        self.ctx.get_pc()
+    }
+
     fn load_register(&mut self, idx: usize) -> u32 {
         self.ctx.peek_register(idx).unwrap()
     }
@@ -250,6 +737,47 @@ impl<'a> SyscallContext for ContextAdapter<'a> {
     }
 }
 
+/// Report the first difference between the segments of two [Session]s.
+fn compare_sessions(first: &Session, second: &Session) -> Result<()> {
+    ensure!(
+        first.segments.len() == second.segments.len(),
+        "Determinism audit failed: first run produced {} segments, second run produced {}",
+        first.segments.len(),
+        second.segments.len()
+    );
+    for (idx, (lhs, rhs)) in first
+        .segments
+        .iter()
+        .zip(second.segments.iter())
+        .enumerate()
+    {
+        let (lhs, rhs) = (lhs.resolve()?.inner, rhs.resolve()?.inner);
+        let (lhs_pre, rhs_pre) = (lhs.pre_state.digest(), rhs.pre_state.digest());
+        ensure!(
+            lhs_pre == rhs_pre,
+            "Determinism audit failed at segment {idx}: pre-state image ID {lhs_pre} != {rhs_pre}"
+        );
+        let (lhs_post, rhs_post) = (lhs.post_state.digest(), rhs.post_state.digest());
+        ensure!(
+            lhs_post == rhs_post,
+            "Determinism audit failed at segment {idx}: post-state image ID {lhs_post} != {rhs_post}"
+        );
+        ensure!(
+            lhs.insn_cycles == rhs.insn_cycles,
+            "Determinism audit failed at segment {idx}: {} cycles != {} cycles",
+            lhs.insn_cycles,
+            rhs.insn_cycles
+        );
+        ensure!(
+            lhs.exit_code == rhs.exit_code,
+            "Determinism audit failed at segment {idx}: exit code {:?} != {:?}",
+            lhs.exit_code,
+            rhs.exit_code
+        );
+    }
+    Ok(())
+}
+
 impl<'a> NewSyscall for ExecutorImpl<'a> {
     fn syscall(
         &self,
@@ -257,12 +785,36 @@ impl<'a> NewSyscall for ExecutorImpl<'a> {
         ctx: &mut dyn NewSyscallContext,
         into_guest: &mut [u32],
     ) -> Result<(u32, u32)> {
+        let cycle = ctx.get_cycle() as u64;
         let mut ctx = ContextAdapter { ctx };
-        self.syscall_table
-            .get_syscall(&syscall)
-            .context(format!("Unknown syscall: {syscall:?}"))?
-            .borrow_mut()
-            .syscall(&syscall, &mut ctx, into_guest)
+        let mut dispatch = |into_guest: &mut [u32]| {
+            self.syscall_table
+                .get_syscall(syscall)
+                .context(format!("Unknown syscall: {syscall:?}"))?
+                .borrow_mut()
+                .syscall(syscall, &mut ctx, into_guest)
+        };
+        let regs = match &self.replay {
+            Some(replay) => replay.borrow_mut().syscall(syscall, into_guest, dispatch)?,
+            None => dispatch(into_guest)?,
+        };
+        self.notify(|observer| {
+            observer.on_syscall(&SyscallEvent {
+                name: syscall,
+                cycle,
+                to_guest: into_guest,
+                regs,
+            })
+        });
+        self.syscall_count.set(self.syscall_count.get() + 1);
+        if self.env.record {
+            self.syscalls.borrow_mut().push(RecordedSyscall {
+                name: syscall.to_string(),
+                to_guest: into_guest.to_vec(),
+                regs,
+            });
+        }
+        Ok(regs)
     }
 }
 
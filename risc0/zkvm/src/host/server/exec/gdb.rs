@@ -0,0 +1,290 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A GDB remote serial protocol server for debugging guests.
+//!
+//! The stub is driven by the [TraceEvent]s emitted by the executor. It keeps a
+//! shadow copy of the guest's registers and memory, and stops execution at
+//! breakpoints or after single steps by blocking inside the trace callback
+//! until the debugger resumes. Only inspection is supported; writes to
+//! registers or memory are rejected.
+//!
+//! To debug a guest, attach a connection with [crate::ExecutorImpl::attach_gdb]
+//! before calling [crate::ExecutorImpl::run], then connect with:
+//!
+//! ```text
+//! riscv32-unknown-elf-gdb path/to/guest.elf -ex "target remote localhost:9000"
+//! ```
+
+use std::{
+    collections::BTreeSet,
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+use anyhow::{anyhow, bail, Result};
+use risc0_binfmt::{ExitCode, MemoryImage};
+use risc0_zkvm_platform::memory::MEM_SIZE;
+
+use crate::{TraceCallback, TraceEvent};
+
+/// Index of the program counter in the RISC-V GDB register file.
+const REG_PC: usize = 32;
+
+/// Signal reported to the debugger when execution stops (SIGTRAP).
+const STOP_REPLY: &str = "S05";
+
+/// What the stub should do after handling a packet.
+enum Action {
+    Reply(String),
+    Resume,
+    Step,
+    Detach,
+    Kill,
+}
+
+pub(crate) struct GdbStub {
+    stream: TcpStream,
+    regs: [u32; 32],
+    pc: u32,
+    image: MemoryImage,
+    breakpoints: BTreeSet<u32>,
+    stepping: bool,
+    resumed: bool,
+    detached: bool,
+}
+
+impl GdbStub {
+    pub fn new(stream: TcpStream, image: MemoryImage) -> Self {
+        Self {
+            stream,
+            regs: [0; 32],
+            pc: image.pc,
+            image,
+            breakpoints: BTreeSet::new(),
+            // Stop before the first instruction so the debugger can set breakpoints.
+            stepping: true,
+            resumed: false,
+            detached: false,
+        }
+    }
+
+    /// Report the end of execution to the debugger.
+    ///
+    /// A halted guest is reported as an exited process. For any other
+    /// [ExitCode], the debugger may inspect the final state before resuming.
+    pub fn on_exit(&mut self, exit_code: ExitCode) -> Result<()> {
+        if self.detached {
+            return Ok(());
+        }
+        match exit_code {
+            ExitCode::Halted(code) => self.send(&format!("W{:02x}", code & 0xff)),
+            _ => self.stop(),
+        }
+    }
+
+    /// Report a stop to the debugger and process packets until it resumes.
+    ///
+    /// The stop reply answers the last `c` or `s` packet. On the initial stop
+    /// after attaching, nothing has been requested yet, so the stub only waits
+    /// for packets; the debugger learns why execution stopped by sending `?`.
+    fn stop(&mut self) -> Result<()> {
+        if self.resumed {
+            self.send(STOP_REPLY)?;
+        }
+        loop {
+            let packet = self.recv()?;
+            match self.handle(&packet) {
+                Action::Reply(reply) => self.send(&reply)?,
+                Action::Resume => {
+                    self.stepping = false;
+                    self.resumed = true;
+                    return Ok(());
+                }
+                Action::Step => {
+                    self.stepping = true;
+                    self.resumed = true;
+                    return Ok(());
+                }
+                Action::Detach => {
+                    self.send("OK")?;
+                    self.detached = true;
+                    self.stepping = false;
+                    return Ok(());
+                }
+                Action::Kill => bail!("Execution killed by debugger"),
+            }
+        }
+    }
+
+    fn handle(&mut self, packet: &str) -> Action {
+        let (cmd, args) = packet.split_at(packet.len().min(1));
+        match cmd {
+            "?" => Action::Reply(STOP_REPLY.to_string()),
+            "g" => Action::Reply(
+                self.regs
+                    .iter()
+                    .chain([&self.pc])
+                    .map(|reg| encode_hex(&reg.to_le_bytes()))
+                    .collect(),
+            ),
+            "p" => Action::Reply(
+                match usize::from_str_radix(args, 16) {
+                    Ok(idx) if idx < REG_PC => Some(self.regs[idx]),
+                    Ok(REG_PC) => Some(self.pc),
+                    _ => None,
+                }
+                .map_or_else(|| "E01".to_string(), |reg| encode_hex(&reg.to_le_bytes())),
+            ),
+            "m" => Action::Reply(
+                parse_addr_len(args)
+                    .and_then(|(addr, len)| self.read_memory(addr, len))
+                    .map_or_else(|| "E14".to_string(), |bytes| encode_hex(&bytes)),
+            ),
+            "Z" | "z" => Action::Reply(match parse_breakpoint(args) {
+                Some(addr) => {
+                    if cmd == "Z" {
+                        self.breakpoints.insert(addr);
+                    } else {
+                        self.breakpoints.remove(&addr);
+                    }
+                    "OK".to_string()
+                }
+                None => String::new(),
+            }),
+            "c" => Action::Resume,
+            "s" => Action::Step,
+            "D" => Action::Detach,
+            "k" => Action::Kill,
+            "H" | "T" => Action::Reply("OK".to_string()),
+            "G" | "P" | "M" | "X" => Action::Reply("E01".to_string()),
+            _ => Action::Reply(
+                match packet.split(':').next().unwrap_or_default() {
+                    "qSupported" => "PacketSize=4000",
+                    "qAttached" => "1",
+                    "qC" => "QC1",
+                    "qfThreadInfo" => "m1",
+                    "qsThreadInfo" => "l",
+                    _ => "",
+                }
+                .to_string(),
+            ),
+        }
+    }
+
+    fn read_memory(&self, addr: u32, len: u32) -> Option<Vec<u8>> {
+        let end = addr.checked_add(len)?;
+        if end as usize > MEM_SIZE {
+            return None;
+        }
+        (addr..end)
+            .map(|addr| {
+                let mut byte = [0u8];
+                self.image.load_region_in_page(addr, &mut byte).ok()?;
+                Some(byte[0])
+            })
+            .collect()
+    }
+
+    fn write_memory(&mut self, addr: u32, region: &[u8]) {
+        for (addr, byte) in (addr..).zip(region) {
+            self.image.store_region_in_page(addr, &[*byte]);
+        }
+    }
+
+    fn recv(&mut self) -> Result<String> {
+        // Acknowledgements and interrupts while stopped are ignored.
+        while self.read_byte()? != b'$' {}
+        let mut packet = Vec::new();
+        loop {
+            match self.read_byte()? {
+                b'#' => break,
+                byte => packet.push(byte),
+            }
+        }
+        let mut checksum = [0u8; 2];
+        self.stream.read_exact(&mut checksum)?;
+        let expected = u8::from_str_radix(std::str::from_utf8(&checksum)?, 16)?;
+        if expected != compute_checksum(&packet) {
+            self.stream.write_all(b"-")?;
+            return self.recv();
+        }
+        self.stream.write_all(b"+")?;
+        Ok(String::from_utf8(packet)?)
+    }
+
+    fn send(&mut self, data: &str) -> Result<()> {
+        let checksum = compute_checksum(data.as_bytes());
+        write!(self.stream, "${data}#{checksum:02x}")?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn read_byte(&mut self) -> Result<u8> {
+        let mut byte = [0u8];
+        self.stream
+            .read_exact(&mut byte)
+            .map_err(|err| anyhow!("Debugger connection lost: {err}"))?;
+        Ok(byte[0])
+    }
+}
+
+impl TraceCallback for GdbStub {
+    fn trace_callback(&mut self, event: TraceEvent) -> Result<()> {
+        match event {
+            // Events are emitted once an instruction has been executed, and are
+            // followed by the effects of that instruction. Stopping here
+            // presents the state from just before the instruction executed.
+            TraceEvent::InstructionStart { pc, .. } => {
+                self.pc = pc;
+                if !self.detached && (self.stepping || self.breakpoints.contains(&pc)) {
+                    self.stop()?;
+                }
+            }
+            TraceEvent::RegisterSet { idx, value } => self.regs[idx] = value,
+            TraceEvent::MemorySet { addr, region } => self.write_memory(addr, &region),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parse the `addr,length` arguments of an `m` packet.
+fn parse_addr_len(args: &str) -> Option<(u32, u32)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((
+        u32::from_str_radix(addr, 16).ok()?,
+        u32::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+/// Parse the `type,addr,kind` arguments of a `Z` or `z` packet.
+///
+/// Software and hardware breakpoints are treated the same; watchpoints are not
+/// supported.
+fn parse_breakpoint(args: &str) -> Option<u32> {
+    let mut parts = args.split(',');
+    match parts.next()? {
+        "0" | "1" => u32::from_str_radix(parts.next()?, 16).ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Structured reports for guest panics.
+//!
+//! When the guest calls `sys_panic`, the executor returns a [GuestPanic] error
+//! which can be recovered with [anyhow::Error::downcast_ref]. If the executor
+//! was constructed from an ELF, the report includes frames symbolized from the
+//! guest's debug info.
+
+use std::fmt;
+
+use addr2line::{fallible_iterator::FallibleIterator, object::File, LookupResult, ObjectContext};
+use anyhow::Result;
+
+use super::core_dump::GuestCore;
+use crate::{guest::checked::ArithError, HeapStats};
+
+/// A location in the guest program that was active when the guest panicked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestFrame {
+    /// The code address of this frame.
+    pub pc: u32,
+
+    /// The demangled name of the function, if known.
+    pub function: Option<String>,
+
+    /// The source file, if the guest was built with debug info.
+    pub file: Option<String>,
+
+    /// The source line, if the guest was built with debug info.
+    pub line: Option<u32>,
+}
+
+/// The error returned by the executor when the guest panics.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct GuestPanic {
+    /// The panic message, without the location prefix added by the panic
+    /// handler.
+    pub message: String,
+
+    /// The source file that panicked, if reported by the guest.
+    pub file: Option<String>,
+
+    /// The source line that panicked, if reported by the guest.
+    pub line: Option<u32>,
+
+    /// The frames at the `sys_panic` call site, innermost first.
+    ///
+    /// This contains the frames for the program counter of the `sys_panic`
+    /// call and for the return address in `ra`, including inlined functions.
+    /// It is not a full backtrace: the guest stack is not unwound, so callers
+    /// further up are not included. It is empty if the executor was not
+    /// constructed from an ELF.
+    pub call_site_frames: Vec<GuestFrame>,
+
+    /// Heap usage last reported by the guest, if it was built with the
+    /// `heap-stats` feature. A guest that runs out of memory reports its usage
+    /// just before it panics.
+    pub heap: Option<HeapStats>,
+
+    /// The typed failure, if the guest panicked in a
+    /// [checked](crate::guest::checked) arithmetic operation.
+    pub arith_error: Option<ArithError>,
+
+    raw: String,
+    pc: u32,
+    ra: u32,
+}
+
+impl GuestPanic {
+    pub(crate) fn new(raw: String, pc: u32, ra: u32) -> Self {
+        let (message, file, line) = parse_panic(&raw);
+        let arith_error = ArithError::decode(&message);
+        Self {
+            message,
+            file,
+            line,
+            call_site_frames: Vec::new(),
+            heap: None,
+            arith_error,
+            raw,
+            pc,
+            ra,
+        }
+    }
+
+    /// Resolve [GuestPanic::call_site_frames] against the guest program `elf`.
+    pub(crate) fn symbolize(&mut self, elf: &[u8]) -> Result<()> {
+        let file = File::parse(elf)?;
+        let ctx = ObjectContext::new(&file)?;
+        let mut frames = lookup_frames(&ctx, elf, self.pc)?;
+        // The return address points after the call, so look up the call itself.
+        if self.ra != 0 {
+            frames.extend(lookup_frames(&ctx, elf, self.ra.wrapping_sub(4))?);
+        }
+        self.call_site_frames = frames;
+        Ok(())
+    }
+}
+
+impl fmt::Display for GuestPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guest panicked: {}", self.raw)
+    }
+}
+
+impl std::error::Error for GuestPanic {}
+
+fn lookup_frames(ctx: &ObjectContext, elf: &[u8], pc: u32) -> Result<Vec<GuestFrame>> {
+    let frames: Vec<_> = match ctx.find_frames(pc as u64) {
+        LookupResult::Output(frames) => frames?
+            .map(|frame| {
+                let location = frame.location.as_ref();
+                Ok(GuestFrame {
+                    pc,
+                    function: frame
+                        .function
+                        .as_ref()
+                        .and_then(|name| name.demangle().ok())
+                        .map(|name| name.to_string()),
+                    file: location.and_then(|loc| loc.file).map(str::to_string),
+                    line: location.and_then(|loc| loc.line),
+                })
+            })
+            .collect()?,
+        // Split DWARF is not supported, fall back to the symbol table.
+        LookupResult::Load { .. } => Vec::new(),
+    };
+    if !frames.is_empty() {
+        return Ok(frames);
+    }
+    Ok(vec![GuestFrame {
+        pc,
+        function: GuestCore::lookup_symbol(elf, pc)?.map(|sym| sym.name),
+        file: None,
+        line: None,
+    }])
+}
+
+/// Split a message produced by the guest panic handler into the message and
+/// its location.
+///
+/// Both `panicked at FILE:LINE:COL:\nMSG` and the older
+/// `panicked at 'MSG', FILE:LINE:COL` formats are recognized. Anything else,
+/// such as a message passed to `risc0_zkvm::guest::abort`, is returned as is.
+fn parse_panic(raw: &str) -> (String, Option<String>, Option<u32>) {
+    let unparsed = || (raw.to_string(), None, None);
+    let Some(rest) = raw.strip_prefix("panicked at ") else {
+        return unparsed();
+    };
+
+    let (message, location) = if let Some((location, message)) = rest.split_once(":\n") {
+        (message, location)
+    } else if let Some((message, location)) = rest
+        .strip_prefix('\'')
+        .and_then(|rest| rest.rsplit_once("', "))
+    {
+        (message, location)
+    } else {
+        return unparsed();
+    };
+
+    // The location is FILE:LINE:COL, and FILE may itself contain colons.
+    let mut parts = location.rsplitn(3, ':');
+    let (Some(_col), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+        return unparsed();
+    };
+    let Ok(line) = line.parse() else {
+        return unparsed();
+    };
+    (message.to_string(), Some(file.to_string()), Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_panic;
+
+    #[test]
+    fn parse_location() {
+        assert_eq!(
+            parse_panic("panicked at src/main.rs:12:5:\nboom: 1"),
+            ("boom: 1".into(), Some("src/main.rs".into()), Some(12))
+        );
+        assert_eq!(
+            parse_panic("panicked at 'boom, again', src/lib.rs:3:9"),
+            ("boom, again".into(), Some("src/lib.rs".into()), Some(3))
+        );
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert_eq!(parse_panic("abort"), ("abort".into(), None, None));
+        assert_eq!(
+            parse_panic("panicked at somewhere:\nboom"),
+            ("panicked at somewhere:\nboom".into(), None, None)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Instruction counts gathered during execution.
+//!
+//! When [crate::ExecutorEnvBuilder::enable_insn_stats] is set, the executor
+//! counts how often each instruction is executed and summarizes the counts as
+//! an [InsnStats] on the [crate::Session]. This is much cheaper than running
+//! the profiler, and is often enough to find the loops worth optimizing.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use risc0_circuit_rv32im::prove::emu::rv32im::Emulator;
+
+use super::core_dump::GuestCore;
+use crate::{Hotspot, InsnStats, TraceCallback, TraceEvent};
+
+/// A [TraceCallback] that counts executed instructions by program counter.
+#[derive(Default)]
+pub(crate) struct InsnCounter {
+    /// Execution count, encoded instruction and user cycles, by program counter
+    counts: HashMap<u32, (u64, u32, u64)>,
+
+    /// Program counter and starting cycle of the last instruction seen, whose
+    /// cycles are only known once the next instruction starts
+    last: Option<(u32, u64)>,
+}
+
+impl InsnCounter {
+    /// Summarize the counts, keeping the `hotspots` hottest program counters
+    /// and resolving them to symbols in `elf` if it is provided.
+    ///
+    /// `user_cycles` is the cycle count at the end of execution, which
+    /// accounts for the cycles of the last instruction.
+    pub(crate) fn finalize(
+        &mut self,
+        hotspots: usize,
+        elf: Option<&[u8]>,
+        user_cycles: u64,
+    ) -> InsnStats {
+        self.end_insn(user_cycles);
+        let counts = std::mem::take(&mut self.counts);
+
+        let emu = Emulator::new();
+        let mut opcodes = BTreeMap::new();
+        let mut cycles = BTreeMap::new();
+        for (count, insn, insn_cycles) in counts.values() {
+            let name = format!("{:?}", emu.decode_kind(*insn)).to_lowercase();
+            *opcodes.entry(name.clone()).or_default() += count;
+            *cycles.entry(name).or_default() += insn_cycles;
+        }
+
+        let mut pcs: Vec<_> = counts
+            .into_iter()
+            .map(|(pc, (count, ..))| (pc, count))
+            .collect();
+        pcs.sort_by(|(pc_a, count_a), (pc_b, count_b)| count_b.cmp(count_a).then(pc_a.cmp(pc_b)));
+        let hotspots = pcs
+            .into_iter()
+            .take(hotspots)
+            .map(|(pc, count)| Hotspot {
+                pc,
+                count,
+                symbol: elf
+                    .and_then(|elf| GuestCore::lookup_symbol(elf, pc).ok().flatten())
+                    .map(|sym| format!("{}+0x{:x}", sym.name, sym.offset)),
+            })
+            .collect();
+
+        InsnStats {
+            opcodes,
+            cycles,
+            hotspots,
+        }
+    }
+
+    /// Charge the cycles up to `cycle` to the last instruction seen.
+    fn end_insn(&mut self, cycle: u64) {
+        if let Some((pc, start)) = self.last.take() {
+            if let Some(entry) = self.counts.get_mut(&pc) {
+                entry.2 += cycle.saturating_sub(start);
+            }
+        }
+    }
+}
+
+impl TraceCallback for InsnCounter {
+    fn trace_callback(&mut self, event: TraceEvent) -> Result<()> {
+        if let TraceEvent::InstructionStart { cycle, pc, insn } = event {
+            self.end_insn(cycle as u64);
+            let entry = self.counts.entry(pc).or_default();
+            entry.0 += 1;
+            entry.1 = insn;
+            self.last = Some((pc, cycle as u64));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An audit log of guest accesses to protected memory.
+//!
+//! When [crate::ExecutorEnvBuilder::enable_memory_audit] is set, the executor
+//! records every load and store instruction that touches memory outside of the
+//! guest address space, and writes a [MemoryAuditReport] when the session
+//! ends, whether or not execution succeeded. Such accesses fault, so a guest
+//! that passes review should produce an empty report.
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use risc0_zkvm_platform::memory::{GUEST_MAX_MEM, GUEST_MIN_MEM};
+use serde::{Deserialize, Serialize};
+
+use crate::{TraceCallback, TraceEvent};
+
+/// A region of memory that guest code may not access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtectedRegion {
+    /// The page starting at address zero, below the guest address space.
+    NullPage,
+
+    /// System memory, including the registers and the page table, above the
+    /// guest address space.
+    System,
+}
+
+impl ProtectedRegion {
+    /// Returns the protected region containing `addr`, if any.
+    pub fn of(addr: u32) -> Option<Self> {
+        let addr = addr as usize;
+        if addr < GUEST_MIN_MEM {
+            Some(Self::NullPage)
+        } else if addr >= GUEST_MAX_MEM {
+            Some(Self::System)
+        } else {
+            None
+        }
+    }
+}
+
+/// A load or store by the guest into a [ProtectedRegion].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectedAccess {
+    /// The number of user cycles executed before the access.
+    pub cycle: u32,
+
+    /// The program counter of the instruction that made the access.
+    pub pc: u32,
+
+    /// The address that was accessed.
+    pub addr: u32,
+
+    /// Whether the access was a store, rather than a load.
+    pub store: bool,
+
+    /// The protected region containing [ProtectedAccess::addr].
+    pub region: ProtectedRegion,
+}
+
+/// The accesses to protected memory made during a session.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAuditReport {
+    /// The protected accesses, in the order they occurred.
+    pub accesses: Vec<ProtectedAccess>,
+}
+
+impl MemoryAuditReport {
+    /// Returns true if the guest made no protected accesses.
+    pub fn is_clean(&self) -> bool {
+        self.accesses.is_empty()
+    }
+
+    /// Load a report written by the executor from `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read memory audit {}", path.display()))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write memory audit {}", path.display()))
+    }
+}
+
+/// A [TraceCallback] that collects a [MemoryAuditReport].
+#[derive(Default)]
+pub(crate) struct MemoryAudit {
+    report: MemoryAuditReport,
+}
+
+impl MemoryAudit {
+    pub(crate) fn take_report(&mut self) -> MemoryAuditReport {
+        std::mem::take(&mut self.report)
+    }
+}
+
+impl TraceCallback for MemoryAudit {
+    fn trace_callback(&mut self, event: TraceEvent) -> Result<()> {
+        if let TraceEvent::MemoryAccessFault {
+            cycle,
+            pc,
+            addr,
+            store,
+        } = event
+        {
+            if let Some(region) = ProtectedRegion::of(addr) {
+                self.report.accesses.push(ProtectedAccess {
+                    cycle,
+                    pc,
+                    addr,
+                    store,
+                    region,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -18,8 +18,18 @@
 //! [crate::Session] contains one or more [crate::Segment]s, each of which
 //! contains an execution trace of the specified program.
 
+pub(crate) mod checkpoint;
+pub(crate) mod core_dump;
 pub(crate) mod executor;
+#[cfg(feature = "gdb-stub")]
+pub(crate) mod gdb;
+pub(crate) mod guest_panic;
+pub(crate) mod insn_stats;
+pub(crate) mod memory_audit;
 pub(crate) mod profiler;
+pub(crate) mod replay;
+pub(crate) mod segment_plan;
 pub(crate) mod syscall;
 #[cfg(test)]
 mod tests;
+pub(crate) mod trace_file;
@@ -18,10 +18,15 @@
 //! guest.  It does not trace full stack traces, but only provides the
 //! top level stack frame.  (More than one stack frame may show up
 //! in the case of inlined functions).
+//!
+//! Cycles spent in the SHA-256 and BigInt accelerators are attributed to the
+//! `ecall` instruction that invoked them, and are additionally reported as a
+//! separate `accelerator_cycles` sample value. Results can be output either as
+//! a pprof protobuf or as folded stacks suitable for flamegraph tools.
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Write,
     hash::{Hash, Hasher},
     rc::Rc,
@@ -30,13 +35,17 @@ use std::{
 use addr2line::{
     fallible_iterator::FallibleIterator,
     gimli::{EndianRcSlice, RunTimeEndian},
-    object::{File, Object, ObjectSegment},
+    object::File,
     LookupResult, ObjectContext,
 };
 use anyhow::{anyhow, Result};
-use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
+use elf::{
+    abi::{PF_X, PT_LOAD, STT_FUNC},
+    endian::LittleEndian,
+    ElfBytes,
+};
 use prost::Message;
-use risc0_zkvm_platform::memory::TEXT_START;
+use risc0_zkvm_platform::syscall::{ecall, reg_abi::REG_T0};
 use rrs_lib::instruction_formats::{IType, JType, OPCODE_JAL, OPCODE_JALR};
 use rustc_demangle::demangle;
 
@@ -48,6 +57,9 @@ mod proto {
     include!(concat!(env!("OUT_DIR"), "/perftools.profiles.rs"));
 }
 
+/// The encoding of the `ecall` instruction.
+const ECALL_INSN: u32 = 0x0000_0073;
+
 /// Operations effecting the function call stack.
 #[derive(Debug)]
 enum CallStackOp {
@@ -101,6 +113,10 @@ struct CallNode {
     /// Counter by program counter with the current call stack.
     pub(crate) counts: HashMap<u32, usize>,
 
+    /// Counter of accelerator cycles by program counter with the current call
+    /// stack.
+    pub(crate) accel_counts: HashMap<u32, usize>,
+
     /// Nodes representing further calls from this context.
     pub(crate) calls: HashMap<u32, Rc<RefCell<CallNode>>>,
 }
@@ -159,6 +175,12 @@ pub struct Profiler {
     // Current CallNode key in the stack
     current_key: u32,
 
+    // Value of the t0 register, which selects the ecall being made
+    t0: u32,
+
+    // Whether the call tree has been added to the profile
+    finalized: bool,
+
     ctx: ObjectContext,
 
     profile: ProfileBuilder,
@@ -222,6 +244,8 @@ impl Profiler {
             root: Rc::clone(&root),
             current_node: Some(root),
             current_key: 0,
+            t0: 0,
+            finalized: false,
             call_stack_path: Vec::new(),
             ctx,
             profile: ProfileBuilder::new(),
@@ -229,24 +253,31 @@ impl Profiler {
 
         // Save the main binary name
         let bin_name = profiler.profile.get_string(filename.unwrap_or("unknown"));
-        for segment in file.segments() {
-            if segment.address() == TEXT_START as u64 {
-                profiler.profile.profile.mapping.push(proto::Mapping {
-                    id: 1,
-                    memory_start: segment.address(),
-                    memory_limit: segment.address() + segment.size(),
-                    file_offset: segment.file_range().0,
-                    filename: bin_name,
-                    has_functions: true,
-                    has_filenames: filename.is_some(),
-                    has_line_numbers: true,
-                    has_inline_frames: true,
-                    ..Default::default()
-                });
-            }
-        }
 
+        // The text mapping is the executable segment holding the entry point.
+        // This is read from the ELF, since guests may use a non-default layout.
         let elf = ElfBytes::<LittleEndian>::minimal_parse(elf_data)?;
+        let entry = elf.ehdr.e_entry;
+        let text = elf.segments().into_iter().flatten().find(|phdr| {
+            phdr.p_type == PT_LOAD
+                && phdr.p_flags & PF_X != 0
+                && (phdr.p_vaddr..phdr.p_vaddr + phdr.p_memsz).contains(&entry)
+        });
+        if let Some(phdr) = text {
+            profiler.profile.profile.mapping.push(proto::Mapping {
+                id: 1,
+                memory_start: phdr.p_vaddr,
+                memory_limit: phdr.p_vaddr + phdr.p_memsz,
+                file_offset: phdr.p_offset,
+                filename: bin_name,
+                has_functions: true,
+                has_filenames: filename.is_some(),
+                has_line_numbers: true,
+                has_inline_frames: true,
+                ..Default::default()
+            });
+        }
+
         if let Some((symtab, strtab)) = elf.symbol_table()? {
             for sym in symtab {
                 if sym.st_symtype() == STT_FUNC {
@@ -325,9 +356,10 @@ impl Profiler {
                     self.profile.get_location(loc)
                 })
                 .collect();
+            let accel_count = node.accel_counts.get(&pc).copied().unwrap_or_default();
             let sample = proto::Sample {
                 location_id: location_ids,
-                value: vec![*count as i64],
+                value: vec![*count as i64, accel_count as i64],
                 ..Default::default()
             };
 
@@ -341,23 +373,41 @@ impl Profiler {
         }
     }
 
-    /// Count and save the profiling samples, write the results to `output_path`.
-    #[cfg(test)]
-    pub(crate) fn finalize(mut self) -> ProfileBuilder {
+    /// Add all call stacks in the call tree to the profile, if not already done.
+    fn walk(&mut self) {
+        if self.finalized {
+            return;
+        }
         let root_ref = Rc::clone(&self.root);
         tracing::debug!("{}", self.root.borrow().fmt(0, &self));
         self.walk_stacks(root_ref, Vec::new());
+        self.finalized = true;
+    }
+
+    /// Count and save the profiling samples, write the results to `output_path`.
+    #[cfg(test)]
+    pub(crate) fn finalize(mut self) -> ProfileBuilder {
+        self.walk();
         self.profile
     }
 
     /// Count and save the profiling samples, consuming the profiler and
     /// returning the compiled profile protobuf, encoded as bytes.
     pub fn finalize_to_vec(&mut self) -> Vec<u8> {
-        let root_ref = Rc::clone(&self.root);
-        tracing::debug!("{}", self.root.borrow().fmt(0, &self));
-        self.walk_stacks(root_ref, Vec::new());
+        self.walk();
         self.profile.profile.encode_to_vec()
     }
+
+    /// Count and save the profiling samples, returning them as folded stacks.
+    ///
+    /// Each line contains a call stack, from the outermost function to the
+    /// innermost, separated by `;`, followed by the number of cycles spent in
+    /// that stack. This is the input format used by flamegraph tools such as
+    /// `inferno-flamegraph` and `flamegraph.pl`.
+    pub fn finalize_to_folded(&mut self) -> String {
+        self.walk();
+        self.profile.folded()
+    }
 }
 
 impl TraceCallback for Profiler {
@@ -380,6 +430,12 @@ impl TraceCallback for Profiler {
                         .entry(self.current_key)
                         .and_modify(|e| *e += cycles as usize)
                         .or_insert(cycles as usize);
+                    if orig_insn == ECALL_INSN && matches!(self.t0, ecall::SHA | ecall::BIGINT) {
+                        *current_node_borrowed
+                            .accel_counts
+                            .entry(self.current_key)
+                            .or_default() += cycles as usize;
+                    }
                 }
 
                 if let Some(op) = extract_call_stack_op(orig_insn) {
@@ -441,6 +497,7 @@ impl TraceCallback for Profiler {
                 self.insn = insn;
                 self.cycle = cycle;
             }
+            TraceEvent::RegisterSet { idx, value } if idx == REG_T0 => self.t0 = value,
             _ => (),
         }
         Ok(())
@@ -482,6 +539,12 @@ impl ProfileBuilder {
             ..Default::default()
         };
         builder.profile.sample_type.push(sample_type);
+        let sample_type = proto::ValueType {
+            r#type: builder.get_string("accelerator_cycles"),
+            unit: builder.get_string("count"),
+            ..Default::default()
+        };
+        builder.profile.sample_type.push(sample_type);
 
         builder
     }
@@ -538,6 +601,46 @@ impl ProfileBuilder {
         self.profile.sample.push(sample)
     }
 
+    /// Render the samples in the profile as folded stacks.
+    fn folded(&self) -> String {
+        let mut stacks: BTreeMap<String, i64> = BTreeMap::new();
+        for sample in &self.profile.sample {
+            // Locations are stored from the innermost frame outwards.
+            let stack: Vec<_> = sample
+                .location_id
+                .iter()
+                .rev()
+                .flat_map(|id| self.profile.location[*id as usize - 1].line.iter().rev())
+                .map(|line| {
+                    let func = &self.profile.function[line.function_id as usize - 1];
+                    self.profile.string_table[func.name as usize].replace(';', ":")
+                })
+                .collect();
+            *stacks.entry(stack.join(";")).or_default() += sample.value[0];
+        }
+
+        let mut output = String::new();
+        for (stack, cycles) in stacks {
+            writeln!(output, "{stack} {cycles}").unwrap();
+        }
+        output
+    }
+
+    /// Returns the sum of each sample value (cycles, accelerator cycles) over
+    /// all samples in the profile.
+    #[cfg(test)]
+    pub(crate) fn totals(&self) -> (usize, usize) {
+        self.profile
+            .sample
+            .iter()
+            .fold((0, 0), |(cycles, accel), sample| {
+                (
+                    cycles + sample.value[0] as usize,
+                    accel + sample.value[1] as usize,
+                )
+            })
+    }
+
     /// Dereferences strings, etc. in the protobuf for testing purposes.
     /// Returns a tuple of (frames, program counter, cycles)
     #[cfg(test)]
@@ -0,0 +1,141 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic replay of a recorded [crate::Session].
+
+use anyhow::{bail, ensure, Result};
+use risc0_zkvm_platform::syscall::nr::{
+    SYS_HEAP_STATS, SYS_LOG, SYS_VERIFY, SYS_VERIFY_INTEGRITY, SYS_WRITE,
+};
+
+use crate::{
+    host::client::replay::{RecordedSyscall, SessionRecord},
+    sha::{Digest, Digestible},
+    Session,
+};
+
+/// Returns true if the syscall is dispatched to the live host handler during
+/// replay.
+///
+/// These syscalls either only pass data from the guest to the host, or are
+/// resolved against the assumptions in the [crate::ExecutorEnv]. Their results
+/// are still checked against the record.
+fn is_live(name: &str) -> bool {
+    [
+        SYS_HEAP_STATS,
+        SYS_LOG,
+        SYS_VERIFY,
+        SYS_VERIFY_INTEGRITY,
+        SYS_WRITE,
+    ]
+    .iter()
+    .any(|nr| nr.as_str() == name)
+}
+
+pub(crate) struct Replayer {
+    record: SessionRecord,
+    next: usize,
+}
+
+impl Replayer {
+    pub fn new(record: SessionRecord) -> Self {
+        Self { record, next: 0 }
+    }
+
+    /// Check that execution is starting from the recorded image.
+    pub fn check_image(&self, image_id: Digest) -> Result<()> {
+        ensure!(
+            image_id == self.record.pre_state,
+            "Replay record was captured from image {}, but the executor is running image {image_id}",
+            self.record.pre_state
+        );
+        Ok(())
+    }
+
+    /// Answer a syscall from the record.
+    ///
+    /// The `live` handler is only invoked for syscalls that pass data from the
+    /// guest to the host; all other syscalls are answered from the record.
+    pub fn syscall<F>(&mut self, name: &str, into_guest: &mut [u32], live: F) -> Result<(u32, u32)>
+    where
+        F: FnOnce(&mut [u32]) -> Result<(u32, u32)>,
+    {
+        let idx = self.next;
+        let Some(expected) = self.record.syscalls.get(idx) else {
+            bail!("Replay diverged at syscall {idx}: guest called {name}, but the record is exhausted");
+        };
+        ensure!(
+            expected.name == name,
+            "Replay diverged at syscall {idx}: expected {}, guest called {name}",
+            expected.name
+        );
+        ensure!(
+            expected.to_guest.len() == into_guest.len(),
+            "Replay diverged at syscall {idx} ({name}): expected a {} word buffer, guest provided {}",
+            expected.to_guest.len(),
+            into_guest.len()
+        );
+        self.next += 1;
+
+        if is_live(name) {
+            let regs = live(into_guest)?;
+            let actual = RecordedSyscall {
+                name: name.to_string(),
+                to_guest: into_guest.to_vec(),
+                regs,
+            };
+            ensure!(
+                &actual == expected,
+                "Replay diverged at syscall {idx} ({name}): host returned {actual:?}, expected {expected:?}"
+            );
+            Ok(regs)
+        } else {
+            into_guest.copy_from_slice(&expected.to_guest);
+            Ok(expected.regs)
+        }
+    }
+
+    /// Check that the replayed [Session] matches the record.
+    pub fn finish(self, session: &Session) -> Result<()> {
+        let remaining = self.record.syscalls.len() - self.next;
+        ensure!(
+            remaining == 0,
+            "Replay ended with {remaining} recorded syscalls not made by the guest"
+        );
+        ensure!(
+            session.input == self.record.input,
+            "Replay diverged: expected input digest {}, got {}",
+            self.record.input,
+            session.input
+        );
+        ensure!(
+            session.exit_code == self.record.exit_code,
+            "Replay diverged: expected exit code {:?}, got {:?}",
+            self.record.exit_code,
+            session.exit_code
+        );
+        let journal = session.journal.as_ref().map(|journal| &journal.bytes);
+        ensure!(
+            journal == self.record.journal.as_ref(),
+            "Replay diverged: journal does not match the record"
+        );
+        let post_state = session.post_state.digest();
+        ensure!(
+            post_state == self.record.post_state,
+            "Replay diverged: expected post state {}, got {post_state}",
+            self.record.post_state
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Profile-guided placement of segment splits.
+
+use anyhow::{ensure, Result};
+use risc0_circuit_rv32im::prove::emu::exec::PAGE_CYCLES;
+use serde::{Deserialize, Serialize};
+
+use crate::Session;
+
+/// Planned split points for an execution.
+///
+/// By default the executor only ends a segment when it reaches the segment
+/// limit, so segments can differ a lot in proving time, e.g. when a short final
+/// segment follows full ones, or when some segments spend many cycles on
+/// paging. On a parallel prover the most expensive segment dominates latency.
+/// A [SegmentPlan] adds split points, expressed in user cycles, so segments can
+/// be sized from a profile of an earlier run of the same guest and input.
+///
+/// Segments are still split at the segment limit, so the limit set with
+/// [crate::ExecutorEnvBuilder::segment_limit_po2] must be large enough to hold
+/// the planned segments.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentPlan {
+    split_points: Vec<u64>,
+}
+
+impl SegmentPlan {
+    /// Construct a plan that ends a segment before each of the given user
+    /// cycle counts is exceeded.
+    pub fn new(mut split_points: Vec<u64>) -> Self {
+        split_points.sort_unstable();
+        split_points.dedup();
+        Self { split_points }
+    }
+
+    /// Plan `count` segments of roughly equal proving cost, using the segments
+    /// of `session` as a profile.
+    ///
+    /// The cost of each profiled segment is estimated as its user cycles plus
+    /// the cycles needed to page in and page out every page it touched, and is
+    /// assumed to be spread evenly over its user cycles. The plan is only
+    /// useful for runs that execute the same instructions as `session`, i.e.
+    /// the same guest with the same input.
+    pub fn balanced(session: &Session, count: usize) -> Result<Self> {
+        ensure!(count > 0, "segment count must be non-zero");
+
+        let mut profile = Vec::with_capacity(session.segments.len());
+        for segment in session.segments.iter() {
+            let segment = segment.resolve()?;
+            let user_cycles = segment.inner.insn_cycles as u64;
+            let pages = segment.inner.partial_image.pages.len() as u64;
+            profile.push((user_cycles, user_cycles + 2 * pages * PAGE_CYCLES as u64));
+        }
+        Ok(Self::from_profile(&profile, count))
+    }
+
+    /// Split a profile of `(user_cycles, cost)` pairs into `count` parts of
+    /// equal cost.
+    fn from_profile(profile: &[(u64, u64)], count: usize) -> Self {
+        let total_cost: u64 = profile.iter().map(|(_, cost)| cost).sum();
+        let target = total_cost.div_ceil(count as u64).max(1);
+
+        let mut split_points = Vec::new();
+        let mut user = 0u64;
+        let mut cost_so_far = 0u64;
+        let mut next = target;
+        for &(user_cycles, cost) in profile {
+            while next < total_cost && next < cost_so_far + cost {
+                let offset =
+                    (user_cycles as u128 * (next - cost_so_far) as u128 / cost as u128) as u64;
+                split_points.push(user + offset);
+                next += target;
+            }
+            user += user_cycles;
+            cost_so_far += cost;
+        }
+        split_points.retain(|&at| at > 0 && at < user);
+        Self::new(split_points)
+    }
+
+    /// The user cycle counts before which segments end.
+    pub fn split_points(&self) -> &[u64] {
+        &self.split_points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentPlan;
+
+    #[test]
+    fn balanced_uniform() {
+        // Two segments of equal cost split into four parts.
+        let plan = SegmentPlan::from_profile(&[(1000, 1000), (1000, 1000)], 4);
+        assert_eq!(plan.split_points(), &[500, 1000, 1500]);
+    }
+
+    #[test]
+    fn balanced_accounts_for_paging() {
+        // The first segment costs three times as much per user cycle, so it
+        // gets more of the splits.
+        let plan = SegmentPlan::from_profile(&[(1000, 3000), (1000, 1000)], 2);
+        assert_eq!(plan.split_points(), &[666]);
+    }
+
+    #[test]
+    fn new_sorts_and_dedups() {
+        let plan = SegmentPlan::new(vec![30, 10, 20, 10]);
+        assert_eq!(plan.split_points(), &[10, 20, 30]);
+    }
+}
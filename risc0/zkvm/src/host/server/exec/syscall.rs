@@ -14,18 +14,28 @@
 
 //! Handlers for two-way private I/O between host and guest.
 
-use std::{cell::RefCell, cmp::min, collections::HashMap, rc::Rc, str::from_utf8};
+use std::{
+    cell::RefCell,
+    cmp::min,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+    str::from_utf8,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
+use risc0_circuit_rv32im::prove::emu::exec::DEFAULT_SEGMENT_LIMIT_PO2;
 use risc0_zkvm_platform::{
     fileno,
     syscall::{
         nr::{
-            SYS_ARGC, SYS_ARGV, SYS_CYCLE_COUNT, SYS_GETENV, SYS_LOG, SYS_PANIC, SYS_RANDOM,
-            SYS_READ, SYS_VERIFY, SYS_VERIFY_INTEGRITY, SYS_WRITE,
+            SYS_ARGC, SYS_ARGV, SYS_CHANNEL, SYS_CLOCK, SYS_CYCLE_COUNT, SYS_GETENV,
+            SYS_HEAP_STATS, SYS_LOG, SYS_PANIC, SYS_RANDOM, SYS_READ, SYS_SEGMENT_CYCLE_COUNT,
+            SYS_SEGMENT_LIMIT_PO2, SYS_SESSION_CYCLE_COUNT, SYS_SESSION_LIMIT, SYS_VERIFY,
+            SYS_VERIFY_INTEGRITY, SYS_WRITE,
         },
-        reg_abi::{REG_A3, REG_A4, REG_A5},
+        reg_abi::{REG_A3, REG_A4, REG_A5, REG_A6, REG_RA},
         SyscallName, DIGEST_BYTES, DIGEST_WORDS,
     },
     WORD_SIZE,
@@ -33,12 +43,12 @@ use risc0_zkvm_platform::{
 
 use crate::{
     host::client::{
-        env::{Assumptions, ExecutorEnv},
+        env::{Assumptions, ClockPolicy, ExecutorEnv},
         posix_io::PosixIo,
         slice_io::SliceIo,
     },
     sha::{Digest, Digestible},
-    Assumption, MaybePruned, PrunedValueError, ReceiptClaim,
+    Assumption, GuestPanic, HeapStats, MaybePruned, PrunedValueError, ReceiptClaim,
 };
 
 /// A host-side implementation of a system call.
@@ -57,6 +67,24 @@ pub trait SyscallContext {
     /// Returns the current cycle being executed.
     fn get_cycle(&self) -> usize;
 
+    /// Returns the number of instruction cycles executed in the current
+    /// segment.
+    ///
+    /// The default implementation returns 0, for contexts that do not track
+    /// segments.
+    fn get_segment_cycle(&self) -> usize {
+        0
+    }
+
+    /// Returns the program counter of the `ecall` instruction that invoked
+    /// the syscall.
+    ///
+    /// The default implementation returns 0, for contexts that do not track
+    /// the program counter.
+    fn get_pc(&self) -> u32 {
+        0
+    }
+
     /// Loads the value of the given register, e.g. REG_A0.
     fn load_register(&mut self, idx: usize) -> u32;
 
@@ -87,11 +115,23 @@ impl<'a> SyscallTable<'a> {
         let sys_verify = SysVerify::new(env.assumptions.clone());
 
         let posix_io = env.posix_io.clone();
-        this.with_syscall(SYS_CYCLE_COUNT, SysCycleCount)
+        this.with_syscall(SYS_CLOCK, SysClock::new(env.clock))
+            .with_syscall(SYS_CYCLE_COUNT, SysCycleCount)
+            .with_syscall(SYS_SEGMENT_CYCLE_COUNT, SysSegmentCycleCount)
+            .with_syscall(SYS_SESSION_CYCLE_COUNT, SysSessionCycleCount)
+            .with_syscall(
+                SYS_SEGMENT_LIMIT_PO2,
+                SysSegmentLimitPo2(
+                    env.segment_limit_po2
+                        .unwrap_or(DEFAULT_SEGMENT_LIMIT_PO2 as u32),
+                ),
+            )
+            .with_syscall(SYS_SESSION_LIMIT, SysSessionLimit(env.session_limit))
             .with_syscall(SYS_LOG, posix_io.clone())
             .with_syscall(SYS_PANIC, SysPanic)
             .with_syscall(SYS_RANDOM, SysRandom)
             .with_syscall(SYS_GETENV, SysGetenv(env.env_vars.clone()))
+            .with_syscall(SYS_CHANNEL, SysChannel(env.channels.clone()))
             .with_syscall(SYS_READ, posix_io.clone())
             .with_syscall(SYS_WRITE, posix_io)
             .with_syscall(SYS_VERIFY, sys_verify.clone())
@@ -107,6 +147,13 @@ impl<'a> SyscallTable<'a> {
         this
     }
 
+    pub(crate) fn with_heap_stats(
+        &mut self,
+        heap_stats: Rc<RefCell<Option<HeapStats>>>,
+    ) -> &mut Self {
+        self.with_syscall(SYS_HEAP_STATS, SysHeapStats(heap_stats))
+    }
+
     pub(crate) fn with_syscall(
         &mut self,
         syscall: SyscallName,
@@ -134,6 +181,90 @@ impl Syscall for SysCycleCount {
     }
 }
 
+/// The time in nanoseconds since the UNIX epoch, captured when the syscall
+/// table is created, or [None] if the clock is disabled.
+pub(crate) struct SysClock(Option<u64>);
+
+impl SysClock {
+    fn new(policy: ClockPolicy) -> Self {
+        let time = match policy {
+            ClockPolicy::SessionStart => Some(SystemTime::now()),
+            ClockPolicy::Fixed(time) => Some(time),
+            ClockPolicy::Disabled => None,
+        };
+        // Times before the epoch are reported as the epoch.
+        Self(time.map(|time| {
+            time.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64
+        }))
+    }
+}
+
+impl Syscall for SysClock {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let nanos = self
+            .0
+            .ok_or_else(|| anyhow!("Guest read the clock, but the host clock is disabled"))?;
+        Ok((nanos as u32, (nanos >> 32) as u32))
+    }
+}
+
+pub(crate) struct SysSegmentCycleCount;
+impl Syscall for SysSegmentCycleCount {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        Ok((ctx.get_segment_cycle() as u32, 0))
+    }
+}
+
+pub(crate) struct SysSessionCycleCount;
+impl Syscall for SysSessionCycleCount {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let cycle = ctx.get_cycle() as u64;
+        Ok((cycle as u32, (cycle >> 32) as u32))
+    }
+}
+
+pub(crate) struct SysSegmentLimitPo2(pub u32);
+impl Syscall for SysSegmentLimitPo2 {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        Ok((self.0, 0))
+    }
+}
+
+pub(crate) struct SysSessionLimit(pub Option<u64>);
+impl Syscall for SysSessionLimit {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let limit = self.0.unwrap_or(u64::MAX);
+        Ok((limit as u32, (limit >> 32) as u32))
+    }
+}
+
 pub(crate) struct SysGetenv(pub HashMap<String, String>);
 impl Syscall for SysGetenv {
     fn syscall(
@@ -159,6 +290,45 @@ impl Syscall for SysGetenv {
     }
 }
 
+/// Resolves named input channels to the file descriptors carrying them.
+pub(crate) struct SysChannel(pub BTreeMap<String, u32>);
+impl Syscall for SysChannel {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let buf_ptr = ctx.load_register(REG_A3);
+        let buf_len = ctx.load_register(REG_A4);
+        let from_guest = ctx.load_region(buf_ptr, buf_len)?;
+        let name = from_utf8(&from_guest)?;
+        tracing::debug!("SYS_CHANNEL: {name}");
+        Ok((self.0.get(name).copied().unwrap_or(u32::MAX), 0))
+    }
+}
+
+pub(crate) struct SysHeapStats(pub Rc<RefCell<Option<HeapStats>>>);
+impl Syscall for SysHeapStats {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let stats = HeapStats {
+            peak_heap: ctx.load_register(REG_A3) as u64,
+            peak_live_bytes: ctx.load_register(REG_A4) as u64,
+            total_allocs: ctx.load_register(REG_A5) as u64,
+            total_deallocs: ctx.load_register(REG_A6) as u64,
+            ..Default::default()
+        };
+        tracing::debug!("SYS_HEAP_STATS: {stats:?}");
+        self.0.replace(Some(stats));
+        Ok((0, 0))
+    }
+}
+
 pub(crate) struct SysPanic;
 impl Syscall for SysPanic {
     fn syscall(
@@ -171,7 +341,8 @@ impl Syscall for SysPanic {
         let buf_len = ctx.load_register(REG_A4);
         let from_guest = ctx.load_region(buf_ptr, buf_len)?;
         let msg = from_utf8(&from_guest)?;
-        bail!("Guest panicked: {msg}");
+        let ra = ctx.load_register(REG_RA);
+        Err(GuestPanic::new(msg.to_string(), ctx.get_pc(), ra).into())
     }
 }
 
@@ -540,6 +711,7 @@ impl<'a> PosixIo<'a> {
         // Fill unaligned word out.
         let mut to_guest_end: [u8; WORD_SIZE] = [0; WORD_SIZE];
         let nread_end = read_all(&mut to_guest_end[0..unaligned_end])?;
+        *self.read_offsets.entry(fd).or_default() += (nread_main + nread_end) as u64;
 
         Ok((
             (nread_main + nread_end) as u32,
@@ -591,6 +763,12 @@ impl<'a> PosixIo<'a> {
 //     let buf_len = ctx.load_register(REG_A4);
 //     let from_guest = ctx.load_region(buf_ptr, buf_len)?;
 
+// SysHeapStats:
+//     let peak_heap = ctx.load_register(REG_A3);
+//     let peak_live_bytes = ctx.load_register(REG_A4);
+//     let total_allocs = ctx.load_register(REG_A5);
+//     let total_deallocs = ctx.load_register(REG_A6);
+
 // SysPanic:
 //     let buf_ptr = ctx.load_register(REG_A3);
 //     let buf_len = ctx.load_register(REG_A4);
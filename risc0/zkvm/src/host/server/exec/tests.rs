@@ -13,10 +13,14 @@
 // limitations under the License.
 
 use std::{
+    cell::RefCell,
     collections::{BTreeMap, HashSet},
     io::Cursor,
+    ops::ControlFlow,
+    rc::Rc,
     str::from_utf8,
     sync::Mutex,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::Result;
@@ -24,13 +28,22 @@ use bytes::Bytes;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkvm_methods::{
     multi_test::{MultiTestSpec, SYS_MULTI_TEST},
-    BLST_ELF, HELLO_COMMIT_ELF, MULTI_TEST_ELF, RAND_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
+    BLST_ELF, HELLO_COMMIT_ELF, HELLO_COMMIT_ID, MULTI_TEST_ELF, RAND_ELF, SLICE_IO_ELF,
+    STANDARD_LIB_ELF,
+};
+use risc0_zkvm_platform::{
+    fileno,
+    syscall::{
+        bigint,
+        nr::{SYS_HEAP_STATS, SYS_PANIC, SYS_RANDOM},
+    },
+    PAGE_SIZE, WORD_SIZE,
 };
-use risc0_zkvm_platform::{fileno, syscall::nr::SYS_RANDOM, PAGE_SIZE, WORD_SIZE};
 use sha2::{Digest as _, Sha256};
 use test_log::test;
 
 use crate::{
+    guest::checked::{ArithError, Op, Reason},
     host::server::{
         exec::{
             profiler::{Frame, Profiler},
@@ -40,7 +53,9 @@ use crate::{
     },
     serde::to_vec,
     sha::{Digest, Digestible},
-    ExecutorEnv, ExecutorImpl, ExitCode,
+    ClockPolicy, ExecutorEnv, ExecutorImpl, ExitCode, ExitEvent, GuestCore, GuestPanic, HeapStats,
+    MemoryAuditReport, MemoryLimitExceeded, ProtectedRegion, SegmentPlan, SegmentStart,
+    SessionObserver, SplitEvent, SuspendedState, SyscallEvent, TraceFormat,
 };
 
 fn run_test(spec: MultiTestSpec) {
@@ -110,6 +125,91 @@ fn basic() {
     assert_eq!(segment.index, 0);
 }
 
+/// Returns an image that reports heap stats to the host, then runs `tail`.
+fn heap_stats_image(tail: &[u32]) -> MemoryImage {
+    let mut image = BTreeMap::from([
+        (0x4000, 0x00008637), // lui x12, 0x8
+        (0x4004, 0x00200293), // addi x5, x0, 2
+        (0x4008, 0x10000693), // addi x13, x0, 0x100
+        (0x400c, 0x04000713), // addi x14, x0, 0x40
+        (0x4010, 0x00300793), // addi x15, x0, 3
+        (0x4014, 0x00100813), // addi x16, x0, 1
+        (0x4018, 0x00000073), // ecall(software)
+    ]);
+    for (i, insn) in tail.iter().enumerate() {
+        image.insert(0x401c + (i * WORD_SIZE) as u32, *insn);
+    }
+    // The syscall names are nul-terminated strings at 0x8000 and 0x9000.
+    for (addr, name) in [(0x8000, SYS_HEAP_STATS), (0x9000, SYS_PANIC)] {
+        let mut name = name.as_str().as_bytes().to_vec();
+        name.push(0);
+        for (i, chunk) in name.chunks(WORD_SIZE).enumerate() {
+            let mut word = [0u8; WORD_SIZE];
+            word[..chunk.len()].copy_from_slice(chunk);
+            image.insert(addr + (i * WORD_SIZE) as u32, u32::from_le_bytes(word));
+        }
+    }
+    let program = Program {
+        entry: 0x4000,
+        image,
+    };
+    MemoryImage::new(&program, PAGE_SIZE as u32).unwrap()
+}
+
+#[test]
+fn heap_stats() {
+    let image = heap_stats_image(&[
+        0x00000293, // addi x5, x0, 0
+        0x000055b7, // lui x11, 0x5
+        0x00000073, // ecall(halt)
+    ]);
+
+    let env = ExecutorEnv::default();
+    let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert_eq!(
+        session.stats().heap,
+        Some(HeapStats {
+            peak_heap: 0x100,
+            peak_live_bytes: 0x40,
+            total_allocs: 3,
+            total_deallocs: 1,
+            segment_peaks: vec![0x100],
+        })
+    );
+
+    // Guests built without the `heap-stats` feature do not report heap usage.
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::DoNothing)
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.stats().heap, None);
+}
+
+// A guest that runs out of memory reports its heap usage before it panics.
+#[test]
+fn heap_stats_on_panic() {
+    let image = heap_stats_image(&[
+        0x00009637, // lui x12, 0x9
+        0x000096b7, // lui x13, 0x9
+        0x00400713, // addi x14, x0, 4
+        0x00200293, // addi x5, x0, 2
+        0x00000073, // ecall(software)
+    ]);
+
+    let err = ExecutorImpl::new(ExecutorEnv::default(), image)
+        .unwrap()
+        .run()
+        .unwrap_err();
+    let panic = err.downcast_ref::<GuestPanic>().unwrap();
+    assert_eq!(panic.heap.as_ref().unwrap().peak_heap, 0x100);
+}
+
 #[test]
 fn system_split() {
     let entry = 0x4000;
@@ -155,6 +255,146 @@ fn system_split() {
     assert_eq!(segments[1].index, 1);
 }
 
+#[test]
+fn segment_callback_abort() {
+    let infos = Rc::new(RefCell::new(Vec::new()));
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 18 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .segment_callback({
+            let infos = infos.clone();
+            move |info| {
+                infos.borrow_mut().push(info.clone());
+                if infos.borrow().len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+        })
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(session.exit_code, ExitCode::Aborted);
+    assert!(session.journal.is_none());
+    assert_eq!(session.segments.len(), 2);
+    let infos = infos.borrow();
+    assert_eq!(infos.len(), 2);
+    let segments: Vec<_> = session
+        .segments
+        .iter()
+        .map(|x| x.resolve().unwrap())
+        .collect();
+    for (segment, info) in segments.iter().zip(infos.iter()) {
+        assert_eq!(segment.inner.exit_code, ExitCode::SystemSplit);
+        assert_eq!(segment.inner.po2 as u32, info.po2);
+        assert_eq!(segment.inner.insn_cycles as u32, info.cycles);
+    }
+    assert_eq!(
+        session.user_cycles,
+        infos.iter().map(|info| info.cycles as u64).sum::<u64>()
+    );
+    assert!(session.post_state == segments[1].inner.post_state);
+}
+
+#[test]
+fn segment_callback_continue() {
+    let count = Rc::new(RefCell::new(0));
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 18 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .segment_callback({
+            let count = count.clone();
+            move |_| {
+                *count.borrow_mut() += 1;
+                ControlFlow::Continue(())
+            }
+        })
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    // The callback is only invoked at splits, not for the final segment.
+    assert_eq!(*count.borrow(), session.segments.len() - 1);
+}
+
+#[test]
+fn session_observer() {
+    #[derive(Default)]
+    struct Events {
+        starts: Vec<SegmentStart>,
+        syscalls: Vec<String>,
+        splits: Vec<SplitEvent>,
+        exits: Vec<ExitEvent>,
+    }
+
+    struct Recorder(Rc<RefCell<Events>>);
+
+    impl SessionObserver for Recorder {
+        fn on_segment_start(&mut self, event: &SegmentStart) {
+            self.0.borrow_mut().starts.push(event.clone());
+        }
+
+        fn on_syscall(&mut self, event: &SyscallEvent<'_>) {
+            self.0.borrow_mut().syscalls.push(event.name.to_string());
+        }
+
+        fn on_split(&mut self, event: &SplitEvent) {
+            self.0.borrow_mut().splits.push(event.clone());
+        }
+
+        fn on_exit(&mut self, event: &ExitEvent) {
+            self.0.borrow_mut().exits.push(event.clone());
+        }
+    }
+
+    let events = Rc::new(RefCell::new(Events::default()));
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 18 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .observer(Recorder(events.clone()))
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    let events = events.borrow();
+    let segments = session.segments.len();
+    assert_eq!(events.starts.len(), segments);
+    assert_eq!(events.splits.len(), segments - 1);
+    for (idx, start) in events.starts.iter().enumerate() {
+        assert_eq!(start.index, idx as u32);
+    }
+    for (split, next) in events.splits.iter().zip(events.starts.iter().skip(1)) {
+        assert_eq!(split.index + 1, next.index);
+    }
+
+    // The guest reads its input through a syscall.
+    assert!(!events.syscalls.is_empty());
+    assert_eq!(events.syscalls.len() as u64, session.syscall_count);
+
+    assert_eq!(events.exits.len(), 1);
+    let exit = &events.exits[0];
+    assert_eq!(exit.exit_code, ExitCode::Halted(0));
+    assert_eq!(exit.segments as usize, segments);
+    assert_eq!(exit.user_cycles, session.user_cycles);
+    assert_eq!(exit.total_cycles, session.total_cycles);
+}
+
 #[test]
 fn libm_build() {
     run_test(MultiTestSpec::LibM);
@@ -192,6 +432,148 @@ fn host_syscall() {
     assert_eq!(*actual.lock().unwrap(), expected[..expected.len() - 1]);
 }
 
+#[test]
+fn sub_executor() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Syscall { count: 1 })
+        .unwrap()
+        .sub_executor(SYS_MULTI_TEST, HELLO_COMMIT_ELF)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert_eq!(session.sub_sessions.len(), 1);
+
+    let sub = &session.sub_sessions[0];
+    assert_eq!(sub.channel, SYS_MULTI_TEST.as_str());
+    assert_eq!(sub.journal, b"hello world");
+    assert_eq!(sub.image_id().unwrap(), HELLO_COMMIT_ID.into());
+    assert!(sub.user_cycles > 0);
+
+    // The recorded input reproduces the nested execution.
+    let env = ExecutorEnv::builder()
+        .write_slice(&sub.input)
+        .build()
+        .unwrap();
+    let rerun = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(rerun.get_claim().unwrap().digest(), sub.claim.digest());
+}
+
+#[test]
+fn expected_outputs() {
+    let expected = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+        .unwrap()
+        .expected_outputs()
+        .unwrap();
+    assert_eq!(expected.image_id, HELLO_COMMIT_ID.into());
+    assert_eq!(expected.exit_code, ExitCode::Halted(0));
+    assert_eq!(
+        expected.journal_digest,
+        Some(b"hello world".to_vec().digest())
+    );
+
+    let session = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(expected.post_image_id, session.post_state.digest());
+    assert_eq!(expected.claim_digest, session.get_claim().unwrap().digest());
+}
+
+#[test]
+fn run_twice_and_compare() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::DoRandom)
+        .unwrap()
+        .segment_limit_po2(14)
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run_twice_and_compare().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    // Recording is only enabled for the comparison.
+    assert!(!exec.env.record);
+    assert!(session.record().is_err());
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encrypt_segments_at_rest() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(15)
+        .segment_path(dir.path())
+        .encrypt_at_rest(crate::StaticKeyProvider::new([3; 32]))
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(session.segments.len() > 1);
+
+    for (idx, segment) in session.segments.iter().enumerate() {
+        let contents = std::fs::read(dir.path().join(format!("{idx}.bincode"))).unwrap();
+        assert!(contents.starts_with(b"R0E1"));
+        assert_eq!(segment.resolve().unwrap().index, idx as u32);
+    }
+}
+
+#[test]
+fn segment_file_deltas() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 1 << 18 })
+            .unwrap()
+            .segment_limit_po2(14)
+            .segment_path(dir.path())
+            .build()
+            .unwrap()
+    };
+    let session = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let expected = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run_with_callback(|segment| Ok(Box::new(crate::SimpleSegmentRef::new(segment))))
+        .unwrap();
+    assert!(session.segments.len() > 8);
+    assert_eq!(session.segments.len(), expected.segments.len());
+
+    for (idx, (segment, expected)) in session.segments.iter().zip(expected.segments).enumerate() {
+        let segment = segment.resolve().unwrap();
+        let expected = expected.resolve().unwrap();
+        assert_eq!(segment.index, idx as u32);
+        assert_eq!(
+            segment.inner.partial_image.pages,
+            expected.inner.partial_image.pages
+        );
+        assert_eq!(
+            segment.inner.partial_image.pc,
+            expected.inner.partial_image.pc
+        );
+
+        // Segments after the first are stored as deltas, which are smaller.
+        if idx == 1 {
+            let size = std::fs::metadata(dir.path().join("1.bincode"))
+                .unwrap()
+                .len();
+            assert!(size < bincode::serialize(&expected).unwrap().len() as u64);
+        }
+    }
+}
+
 // Make sure panics in the callback get propagated correctly.
 #[test]
 #[should_panic(expected = "I am panicking from here!")]
@@ -211,6 +593,324 @@ fn host_syscall_callback_panic() {
     assert_eq!(session.exit_code, ExitCode::Halted(0));
 }
 
+#[test]
+fn clock() {
+    let run = |policy: ClockPolicy| {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::Clock)
+            .unwrap()
+            .clock(policy)
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run_twice_and_compare()
+    };
+
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let session = run(ClockPolicy::Fixed(time)).unwrap();
+    let nanos: u64 = session.journal.unwrap().decode().unwrap();
+    assert_eq!(nanos, 1_700_000_000_000_000_000);
+
+    // The time is captured once, and replay reproduces it.
+    let before = SystemTime::now();
+    let session = run(ClockPolicy::SessionStart).unwrap();
+    let nanos: u64 = session.journal.unwrap().decode().unwrap();
+    let time = SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos);
+    assert!(time >= before && time <= SystemTime::now());
+
+    let err = run(ClockPolicy::Disabled).err().unwrap();
+    assert!(err.to_string().contains("clock is disabled"), "{err}");
+}
+
+#[test]
+fn segment_plan() {
+    let spec = MultiTestSpec::BusyLoop { cycles: 1 << 18 };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .segment_limit_po2(18)
+        .build()
+        .unwrap();
+    let profile = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let plan = SegmentPlan::balanced(&profile, 4).unwrap();
+    assert_eq!(plan.split_points().len(), 3);
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .segment_limit_po2(18)
+        .segment_plan(plan)
+        .build()
+        .unwrap();
+    // The replayed run must follow the same plan, or the segment counts differ.
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run_twice_and_compare()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert_eq!(session.segments.len(), 4);
+    assert_eq!(session.user_cycles, profile.user_cycles);
+
+    // The planned segments are smaller than the full segments of the profile.
+    let po2s: Vec<_> = session
+        .segments
+        .iter()
+        .map(|segment| segment.resolve().unwrap().po2())
+        .collect();
+    assert!(po2s.iter().all(|&po2| po2 < 18), "{po2s:?}");
+}
+
+#[test]
+fn checkpoint_resume() {
+    let dir = tempfile::tempdir().unwrap();
+    let spec = MultiTestSpec::BusyLoop { cycles: 1 << 18 };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .segment_limit_po2(16)
+        .checkpoint_every(2, dir.path())
+        .build()
+        .unwrap();
+    let full = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(full.exit_code, ExitCode::Halted(0));
+    assert!(full.segments.len() > 2);
+    assert!(dir.path().join("checkpoint.bin").is_file());
+
+    // Continue from the last checkpoint, as if the host had crashed after
+    // writing it.
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .segment_limit_po2(16)
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_checkpoint(env, dir.path()).unwrap();
+    // The restored session keeps the time captured when it first started.
+    assert_eq!(exec.env.clock, full.clock);
+    let resumed = exec.run().unwrap();
+    assert_eq!(resumed.exit_code, ExitCode::Halted(0));
+    assert_eq!(resumed.segments.len(), full.segments.len());
+    assert_eq!(resumed.user_cycles, full.user_cycles);
+    assert_eq!(
+        resumed.get_claim().unwrap().digest(),
+        full.get_claim().unwrap().digest()
+    );
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encrypt_checkpoint_at_rest() {
+    const SECRET: &[u8] = b"checkpoint-input";
+    let spec = MultiTestSpec::ShaDigestIter {
+        data: SECRET.to_vec(),
+        num_iter: 1 << 12,
+    };
+    let env = |dir: &std::path::Path, encrypt: bool| {
+        let mut env = ExecutorEnv::builder();
+        env.write(&spec).unwrap().segment_limit_po2(14);
+        if !dir.join("checkpoint.bin").exists() {
+            env.checkpoint_every(1, dir);
+        }
+        if encrypt {
+            env.encrypt_at_rest(crate::StaticKeyProvider::new([5; 32]));
+        }
+        env.build().unwrap()
+    };
+    let contains_secret = |contents: &[u8]| {
+        contents
+            .windows(SECRET.len())
+            .any(|window| window == SECRET)
+    };
+
+    let plain_dir = tempfile::tempdir().unwrap();
+    ExecutorImpl::from_elf(env(plain_dir.path(), false), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let plain = std::fs::read(plain_dir.path().join("checkpoint.bin")).unwrap();
+    assert!(contains_secret(&plain));
+
+    let dir = tempfile::tempdir().unwrap();
+    let full = ExecutorImpl::from_elf(env(dir.path(), true), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(full.segments.len() > 1);
+    let sealed = std::fs::read(dir.path().join("checkpoint.bin")).unwrap();
+    assert!(!contains_secret(&sealed));
+
+    // The sealed checkpoint can only be resumed with the cipher.
+    assert!(ExecutorImpl::from_checkpoint(env(dir.path(), false), dir.path()).is_err());
+    let resumed = ExecutorImpl::from_checkpoint(env(dir.path(), true), dir.path())
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(
+        resumed.get_claim().unwrap().digest(),
+        full.get_claim().unwrap().digest()
+    );
+}
+
+#[test]
+fn suspend_resume() {
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::PauseResume(3))
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+    let paused = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(paused.exit_code, ExitCode::Paused(3));
+
+    let bytes = bincode::serialize(&paused.suspend().unwrap()).unwrap();
+    let state: SuspendedState = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(state.image.pc, paused.post_image.pc);
+
+    let resumed = ExecutorImpl::resume(state, env()).unwrap().run().unwrap();
+    assert_eq!(resumed.exit_code, ExitCode::Halted(0));
+    assert_eq!(resumed.pre_state.digest(), paused.post_state.digest());
+
+    // Only paused sessions can be suspended.
+    assert!(resumed.suspend().is_err());
+}
+
+#[test]
+fn suspend_resume_between_reads() {
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::PauseBetweenReads)
+            .unwrap()
+            .write(&1u32)
+            .unwrap()
+            .write(&2u32)
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+    let paused = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(paused.exit_code, ExitCode::Paused(0));
+
+    // The resumed guest continues reading after the first `u32`.
+    let resumed = ExecutorImpl::resume(paused.suspend().unwrap(), env())
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(resumed.exit_code, ExitCode::Halted(0));
+    let (first, second): (u32, u32) = resumed.journal.unwrap().decode().unwrap();
+    assert_eq!((first, second), (1, 2));
+
+    // The skipped input must still be provided.
+    assert!(ExecutorImpl::resume(paused.suspend().unwrap(), ExecutorEnv::default()).is_err());
+}
+
+#[test]
+fn clock_across_pause() {
+    let env = || {
+        ExecutorEnv::builder()
+            .write(&MultiTestSpec::ClockAcrossPause)
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+    let paused = ExecutorImpl::from_elf(env(), MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(paused.exit_code, ExitCode::Paused(0));
+
+    std::thread::sleep(Duration::from_millis(10));
+    let resumed = ExecutorImpl::resume(paused.suspend().unwrap(), env())
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(resumed.exit_code, ExitCode::Halted(0));
+    let (first, second): (u64, u64) = resumed.journal.unwrap().decode().unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn cycle_counters() {
+    let cycles = 1 << 18;
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CycleCounters { cycles })
+        .unwrap()
+        .segment_limit_po2(16)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert!(session.segments.len() > 1);
+
+    let (session_cycles, segment_cycles): (u64, u32) = session.journal.unwrap().decode().unwrap();
+    assert!(session_cycles >= cycles);
+    assert!(session_cycles <= session.user_cycles);
+    assert!((segment_cycles as u64) < 1 << 16);
+    assert!((segment_cycles as u64) < session_cycles);
+}
+
+#[test]
+fn timebox() {
+    const LIMIT: u64 = 1 << 17;
+    let (steps, work) = (100, 200);
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::TimeBox { steps, work })
+        .unwrap()
+        .session_limit(Some(LIMIT))
+        .build()
+        .unwrap();
+    let mut session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let mut pauses = 0;
+    while session.exit_code == ExitCode::Paused(0) {
+        assert!(session.user_cycles < LIMIT);
+        pauses += 1;
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::TimeBox { steps, work })
+            .unwrap()
+            .session_limit(Some(LIMIT))
+            .build()
+            .unwrap();
+        session = ExecutorImpl::resume(session.suspend().unwrap(), env)
+            .unwrap()
+            .run()
+            .unwrap();
+    }
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert!(pauses > 1);
+
+    let mut expected = 0u64;
+    for _ in 0..steps {
+        for i in 0..work {
+            expected = expected
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(i as u64 | 1);
+        }
+    }
+    let (value, guest_pauses): (u64, u32) = session.journal.unwrap().decode().unwrap();
+    assert_eq!(value, expected);
+    assert_eq!(guest_pauses, pauses);
+}
+
 #[test]
 fn sha_accel() {
     run_test(MultiTestSpec::ShaConforms);
@@ -252,6 +952,35 @@ fn bigint_accel() {
     }
 }
 
+#[test]
+fn bigint_ops() {
+    let mut rng = rand::thread_rng();
+    let cases = (0..4).map(|_| testutils::BigIntTestCase::sample(&mut rng));
+    for mut case in cases {
+        // The modexp oracle uses Montgomery form, which needs an odd modulus.
+        case.modulus[0] |= 1;
+        println!("Running BigInt ops test case: {:x?}", case);
+        let input = MultiTestSpec::BigIntOps {
+            x: case.x,
+            y: case.y,
+            modulus: case.modulus,
+        };
+
+        let env = ExecutorEnv::builder()
+            .write(&input)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+        assert_eq!(
+            session.journal.unwrap().bytes.as_slice(),
+            bytemuck::cast_slice::<[u32; bigint::WIDTH_WORDS], u8>(case.expected_ops().as_slice())
+        );
+    }
+}
+
 #[test]
 fn env_stdio() {
     const MSG: &str = "Hello world!  This is a test of standard input and output.";
@@ -442,6 +1171,80 @@ fn unaligned_short_read() {
     assert_eq!(actual, expected, "pos and lens: {spec:?}");
 }
 
+#[test]
+fn read_frame() {
+    let spec = MultiTestSpec::ReadFrame { max_len: 16 };
+
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .write_frame(b"hello")
+        .unwrap()
+        .write_frame(&[0u8; 64])
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let (frame, err): (Vec<u8>, String) = session.journal.unwrap().decode().unwrap();
+    assert_eq!(frame, b"hello");
+    assert_eq!(err, "frame length 64 exceeds maximum of 16");
+
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .write_slice(&10u32.to_le_bytes())
+        .write_slice(b"abc")
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let err: String = session.journal.unwrap().decode().unwrap();
+    assert_eq!(err, "frame truncated: expected 10 bytes, got 3");
+}
+
+#[test]
+fn read_channels() {
+    let spec = MultiTestSpec::ReadChannels {
+        reads: vec![
+            ("b".to_string(), 3),
+            ("a".to_string(), 5),
+            ("b".to_string(), 3),
+        ],
+    };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .channel("a", &b"hello"[..])
+        .channel("b", &b"world!"[..])
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert_eq!(session.journal.unwrap().bytes, b"worhellold!");
+}
+
+#[test]
+fn missing_channel() {
+    let spec = MultiTestSpec::ReadChannels {
+        reads: vec![("missing".to_string(), 1)],
+    };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("input channel not found: missing"));
+}
+
 #[test]
 fn large_io_words() {
     const FD: u32 = 123;
@@ -502,7 +1305,8 @@ mod sys_verify {
     };
 
     fn exec_hello_commit() -> Session {
-        let session = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+        let env = ExecutorEnv::builder().record(true).build().unwrap();
+        let session = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
             .unwrap()
             .run()
             .unwrap();
@@ -875,46 +1679,287 @@ fn getrandom_panic() {
 }
 
 #[test]
-fn slice_io() {
-    let run = |slice: &[u8]| {
+fn slice_io() {
+    let run = |slice: &[u8]| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&[slice.len() as u32])
+            .write_slice(slice)
+            .build()
+            .unwrap();
+        let mut exec = ExecutorImpl::from_elf(env, SLICE_IO_ELF).unwrap();
+        let session = exec.run().unwrap();
+        assert_eq!(session.journal.unwrap().bytes, slice);
+    };
+
+    run(b"");
+    run(b"xyz");
+    run(b"0000");
+}
+
+// Check that a compliant host will return an error on panic.
+#[test]
+fn panic() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Panic)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let err = exec.run().err().unwrap();
+    assert!(err.to_string().contains("MultiTestSpec::Panic invoked"));
+
+    let panic = err.downcast_ref::<GuestPanic>().unwrap();
+    assert_eq!(panic.message, "MultiTestSpec::Panic invoked");
+    assert!(panic.file.as_ref().unwrap().ends_with("multi_test.rs"));
+    assert!(panic.line.is_some());
+    assert!(panic.call_site_frames[0].function.is_some());
+}
+
+#[test]
+fn checked_arith_panic() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CheckedAdd { a: u64::MAX, b: 1 })
+        .unwrap()
+        .build()
+        .unwrap();
+    let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap_err();
+
+    let panic = err.downcast_ref::<GuestPanic>().unwrap();
+    assert_eq!(
+        panic.arith_error,
+        Some(ArithError::new(Reason::Overflow, Op::Add, "u64"))
+    );
+    // The panic is reported at the call site, not inside `checked`.
+    assert!(panic.file.as_ref().unwrap().ends_with("multi_test.rs"));
+}
+
+#[test]
+fn fault() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Fault)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let err = exec.run().err().unwrap();
+    assert!(err.to_string().contains("StoreAccessFault"));
+}
+
+#[test]
+fn core_dump() {
+    let tmp = tempfile::tempdir().unwrap();
+    let run = |spec: MultiTestSpec, path: &std::path::Path| {
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .core_dump(path)
+            .build()
+            .unwrap();
+        let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let err = exec.run().err().unwrap();
+        let core = GuestCore::open(path).unwrap();
+        assert_eq!(core.reason, err.to_string());
+        core
+    };
+
+    let core = run(MultiTestSpec::Panic, &tmp.path().join("panic.core"));
+    assert!(core.reason.contains("MultiTestSpec::Panic invoked"));
+    assert!(core.cycle > 0);
+    // The stack was in use when the guest panicked.
+    let sp = core.registers[2];
+    assert!(core.load_u8(sp).is_some());
+    let symbol = GuestCore::lookup_symbol(MULTI_TEST_ELF, core.pc)
+        .unwrap()
+        .unwrap();
+    let summary = core.symbolize(MULTI_TEST_ELF).unwrap();
+    assert!(summary.contains(&symbol.name), "{summary}");
+
+    let core = run(MultiTestSpec::Fault, &tmp.path().join("fault.core"));
+    assert!(core.reason.contains("StoreAccessFault"));
+    assert!(GuestCore::lookup_symbol(MULTI_TEST_ELF, core.pc)
+        .unwrap()
+        .is_some());
+
+    // No core is written when execution succeeds.
+    let path = tmp.path().join("ok.core");
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::DoNothing)
+        .unwrap()
+        .core_dump(&path)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn memory_limit() {
+    let run = |limit: Option<u64>| {
         let env = ExecutorEnv::builder()
-            .write_slice(&[slice.len() as u32])
-            .write_slice(slice)
+            .write(&MultiTestSpec::DoNothing)
+            .unwrap()
+            .memory_limit(limit)
             .build()
             .unwrap();
-        let mut exec = ExecutorImpl::from_elf(env, SLICE_IO_ELF).unwrap();
-        let session = exec.run().unwrap();
-        assert_eq!(session.journal.unwrap().bytes, slice);
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run()
     };
 
-    run(b"");
-    run(b"xyz");
-    run(b"0000");
+    let session = run(None).unwrap();
+    let pages = session.stats().guest_pages.unwrap();
+    assert!(pages > 0);
+    assert_eq!(session.guest_pages, pages);
+
+    // The limit is inclusive.
+    run(Some(pages)).unwrap();
+
+    let err = run(Some(pages - 1)).err().unwrap();
+    let err = err.downcast_ref::<MemoryLimitExceeded>().unwrap();
+    assert_eq!(err.limit, pages as usize - 1);
+    assert_eq!(err.pages, pages as usize);
 }
 
-// Check that a compliant host will return an error on panic.
 #[test]
-fn panic() {
+fn memory_audit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let run = |spec: MultiTestSpec, path: &std::path::Path| {
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .enable_memory_audit(path)
+            .build()
+            .unwrap();
+        let result = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run();
+        (result, MemoryAuditReport::open(path).unwrap())
+    };
+
+    // A report is written, and is clean, when execution succeeds.
+    let (result, report) = run(MultiTestSpec::DoNothing, &tmp.path().join("ok.json"));
+    result.unwrap();
+    assert!(report.is_clean());
+
+    let (result, report) = run(MultiTestSpec::Fault, &tmp.path().join("fault.json"));
+    assert!(result.is_err());
+    assert_eq!(report.accesses.len(), 1);
+    let access = &report.accesses[0];
+    assert_eq!(access.addr, 1);
+    assert!(access.store);
+    assert_eq!(access.region, ProtectedRegion::NullPage);
+    assert!(GuestCore::lookup_symbol(MULTI_TEST_ELF, access.pc)
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn insn_stats() {
+    let tmp = tempfile::tempdir().unwrap();
+    let csv = tmp.path().join("insns.csv");
     let env = ExecutorEnv::builder()
-        .write(&MultiTestSpec::Panic)
+        .write(&MultiTestSpec::Profiler)
         .unwrap()
+        .enable_insn_stats(5)
+        .insn_stats_csv(&csv)
         .build()
         .unwrap();
-    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
-    let err = exec.run().err().unwrap();
-    assert!(err.to_string().contains("MultiTestSpec::Panic invoked"));
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let stats = session.stats().insns.unwrap();
+    let executed: u64 = stats.opcodes.values().sum();
+    assert!(executed > 0 && executed <= session.user_cycles);
+    assert!(stats.opcodes["addi"] > 0);
+    assert!(!stats.opcodes.contains_key("invalid"));
+    assert_eq!(
+        stats.cycles.keys().collect::<Vec<_>>(),
+        stats.opcodes.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(stats.cycles.values().sum::<u64>(), session.user_cycles);
+
+    assert_eq!(stats.hotspots.len(), 5);
+    assert!(stats
+        .hotspots
+        .windows(2)
+        .all(|pair| pair[0].count >= pair[1].count));
+    assert!(stats
+        .hotspots
+        .iter()
+        .all(|hotspot| hotspot.symbol.is_some()));
+
+    let csv = std::fs::read_to_string(csv).unwrap();
+    assert!(csv.starts_with("kind,name,count,symbol\nopcode,"));
+    assert!(csv.contains("\ncycles,addi,"));
+    assert_eq!(
+        csv.lines().filter(|line| line.starts_with("pc,")).count(),
+        5
+    );
 }
 
 #[test]
-fn fault() {
+fn insn_stats_ecall_cycles() {
     let env = ExecutorEnv::builder()
-        .write(&MultiTestSpec::Fault)
+        .write(&MultiTestSpec::ShaDigest {
+            data: vec![0u8; 1024],
+        })
         .unwrap()
+        .enable_insn_stats(0)
         .build()
         .unwrap();
-    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
-    let err = exec.run().err().unwrap();
-    assert!(err.to_string().contains("StoreAccessFault"));
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    // The SHA-256 compression rounds are charged to the ecall.
+    let stats = session.stats().insns.unwrap();
+    assert!(stats.cycles["eany"] > stats.opcodes["eany"]);
+    assert_eq!(stats.cycles.values().sum::<u64>(), session.user_cycles);
+}
+
+#[test]
+fn trace_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("trace.json");
+    let env = ExecutorEnv::builder()
+        .trace_file(&path, TraceFormat::Perfetto)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let events: Vec<serde_json::Value> =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let in_category = |cat: &str| {
+        events
+            .iter()
+            .filter(|event| event["cat"] == cat)
+            .collect::<Vec<_>>()
+    };
+
+    let spans = in_category("instructions");
+    assert!(spans
+        .iter()
+        .any(|span| span["name"].as_str().unwrap().contains("main")));
+    assert!(spans.windows(2).all(|pair| pair[0]["ts"].as_u64().unwrap()
+        + pair[0]["dur"].as_u64().unwrap()
+        == pair[1]["ts"].as_u64().unwrap()));
+
+    assert!(in_category("syscalls")
+        .iter()
+        .any(|event| event["name"] == "SYS_WRITE"));
+
+    let paging = in_category("paging");
+    assert!(paging.iter().any(|event| event["name"] == "page-in"));
+    assert!(paging.iter().any(|event| event["name"] == "page-out"));
 }
 
 #[test]
@@ -1004,6 +2049,53 @@ fn profiler() {
     assert!(check(&fr, addr), "{fr:#?} {addr}");
 }
 
+#[test]
+fn profiler_folded() {
+    let mut profiler = Profiler::new(MULTI_TEST_ELF, Some("multi_test.elf")).unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::Profiler)
+        .unwrap()
+        .trace_callback(&mut profiler)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let folded = profiler.finalize_to_folded();
+    let line = folded
+        .lines()
+        .find(|line| line.contains("profile_test_func2"))
+        .unwrap_or_else(|| panic!("{folded}"));
+    let (stack, cycles) = line.rsplit_once(' ').unwrap();
+    assert!(cycles.parse::<u64>().unwrap() > 0);
+    let func1 = stack.find("profile_test_func1").unwrap();
+    let func2 = stack.find("profile_test_func2").unwrap();
+    assert!(func1 < func2, "{stack}");
+}
+
+#[test]
+fn profiler_accelerator_cycles() {
+    let mut profiler = Profiler::new(MULTI_TEST_ELF, Some("multi_test.elf")).unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::ShaDigest {
+            data: vec![0u8; 1024],
+        })
+        .unwrap()
+        .trace_callback(&mut profiler)
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let (total_cycles, accel_cycles) = profiler.finalize().totals();
+    assert!(accel_cycles > 0);
+    assert!(accel_cycles < total_cycles);
+}
+
 #[test]
 fn oom() {
     let env = ExecutorEnv::builder()
@@ -1132,6 +2224,325 @@ fn out_of_bounds_ecall() {
     run_test(MultiTestSpec::OutOfBoundsEcall);
 }
 
+mod replay {
+    use risc0_zkvm_methods::{
+        multi_test::{MultiTestSpec, SYS_MULTI_TEST},
+        HELLO_COMMIT_ELF, MULTI_TEST_ELF,
+    };
+    use test_log::test;
+
+    use crate::{ExecutorEnv, ExecutorImpl};
+
+    #[test]
+    fn replay_matches_record() {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::Syscall { count: 3 })
+            .unwrap()
+            .io_callback(SYS_MULTI_TEST, |_| Ok("hello".into()))
+            .record(true)
+            .build()
+            .unwrap();
+        let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        let record = session.record().unwrap();
+        assert!(!record.syscalls.is_empty());
+
+        // The replayed environment provides neither the input nor the io_callback.
+        let env = ExecutorEnv::builder()
+            .replay(record)
+            .record(true)
+            .build()
+            .unwrap();
+        let replayed = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert!(session.post_state == replayed.post_state);
+        assert_eq!(
+            session.record().unwrap().syscalls,
+            replayed.record().unwrap().syscalls
+        );
+    }
+
+    #[test]
+    fn record_disabled() {
+        let session = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert!(session.syscall_count > 0);
+        let err = session.record().err().unwrap();
+        assert!(err.to_string().contains("not recorded"), "{err}");
+    }
+
+    #[test]
+    fn replay_reproduces_randomness() {
+        let env = ExecutorEnv::builder().record(true).build().unwrap();
+        let session = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+
+        // Post state digests are randomized, so this only matches if the
+        // randomness is replayed.
+        let env = ExecutorEnv::builder()
+            .replay(session.record().unwrap())
+            .build()
+            .unwrap();
+        let replayed = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert!(session.post_state == replayed.post_state);
+        assert_eq!(session.journal, replayed.journal);
+    }
+
+    #[test]
+    fn replay_wrong_image() {
+        let env = ExecutorEnv::builder().record(true).build().unwrap();
+        let session = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+        let env = ExecutorEnv::builder()
+            .replay(session.record().unwrap())
+            .build()
+            .unwrap();
+        let err = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("Replay record was captured from image"));
+    }
+
+    #[test]
+    fn replay_divergence() {
+        let session = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+
+        let mut record = session.record().unwrap();
+        record.syscalls.pop();
+        let env = ExecutorEnv::builder().replay(record).build().unwrap();
+        let err = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Replay diverged"), "{err:?}");
+
+        let mut record = session.record().unwrap();
+        record.syscalls[0].name = "unknown".to_string();
+        let env = ExecutorEnv::builder().replay(record).build().unwrap();
+        let err = ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+            .unwrap()
+            .run()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Replay diverged"), "{err:?}");
+    }
+}
+
+#[test]
+fn manifest_round_trip() {
+    let spec = MultiTestSpec::Syscall { count: 3 };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .env_var("VAR1", "SOME_VALUE")
+        .segment_limit_po2(16)
+        .io_callback(SYS_MULTI_TEST, |_| Ok("hello".into()))
+        .record(true)
+        .build()
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = env.to_manifest(dir.path()).unwrap();
+    assert_eq!(manifest.segment_limit_po2, Some(16));
+    assert_eq!(manifest.env_vars["VAR1"], "SOME_VALUE");
+    assert_eq!(manifest.slice_io_channels, vec![SYS_MULTI_TEST.as_str()]);
+    assert_eq!(
+        manifest.input.unwrap().len,
+        to_vec(&spec).unwrap().len() as u64 * 4
+    );
+
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    // Bundle the session record so the io_callback does not need to be re-attached.
+    let dir = tempfile::tempdir().unwrap();
+    ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .segment_limit_po2(16)
+        .replay(session.record().unwrap())
+        .build()
+        .unwrap()
+        .to_manifest(dir.path())
+        .unwrap();
+    let env = ExecutorEnv::from_manifest(dir.path())
+        .unwrap()
+        .build()
+        .unwrap();
+    let replayed = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(session.post_state == replayed.post_state);
+
+    // Corrupted blobs are rejected.
+    for entry in std::fs::read_dir(dir.path().join("blobs")).unwrap() {
+        std::fs::write(entry.unwrap().path(), b"corrupt").unwrap();
+    }
+    let err = ExecutorEnv::from_manifest(dir.path()).err().unwrap();
+    assert!(err.to_string().contains("does not match its digest"));
+}
+
+#[test]
+fn manifest_execution_settings() {
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let env = ExecutorEnv::builder()
+        .memory_limit(Some(1024))
+        .clock(ClockPolicy::Fixed(time))
+        .segment_plan(SegmentPlan::new(vec![1 << 14, 1 << 15]))
+        .channel("prices", &[1u8, 2, 3][..])
+        .build()
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = env.to_manifest(dir.path()).unwrap();
+    assert_eq!(manifest.channels["prices"], env.channels["prices"]);
+
+    let restored = ExecutorEnv::from_manifest(dir.path())
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(restored.memory_limit, Some(1024));
+    assert_eq!(restored.clock, ClockPolicy::Fixed(time));
+    assert_eq!(restored.segment_plan, env.segment_plan);
+    assert_eq!(restored.channels, env.channels);
+}
+
+#[cfg(feature = "gdb-stub")]
+mod gdb {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    use risc0_zkvm_methods::HELLO_COMMIT_ELF;
+    use test_log::test;
+
+    use crate::{ExecutorEnv, ExecutorImpl, ExitCode, TraceEvent};
+
+    struct Client(TcpStream);
+
+    impl Client {
+        fn request(&mut self, packet: &str) -> String {
+            let checksum = packet.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+            write!(self.0, "${packet}#{checksum:02x}").unwrap();
+            self.reply()
+        }
+
+        fn reply(&mut self) -> String {
+            let mut byte = [0u8];
+            loop {
+                self.0.read_exact(&mut byte).unwrap();
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+            let mut reply = Vec::new();
+            loop {
+                self.0.read_exact(&mut byte).unwrap();
+                if byte[0] == b'#' {
+                    break;
+                }
+                reply.push(byte[0]);
+            }
+            let mut checksum = [0u8; 2];
+            self.0.read_exact(&mut checksum).unwrap();
+            self.0.write_all(b"+").unwrap();
+            String::from_utf8(reply).unwrap()
+        }
+
+        fn pc(&mut self) -> u32 {
+            let reply = self.request("p20");
+            u32::from_le_bytes(hex::decode(reply).unwrap().try_into().unwrap())
+        }
+    }
+
+    #[test]
+    fn breakpoint_and_step() {
+        // Find an instruction that is executed later on to use as a breakpoint.
+        let mut pcs = Vec::new();
+        {
+            let env = ExecutorEnv::builder()
+                .trace_callback(|event| {
+                    if let TraceEvent::InstructionStart { pc, .. } = event {
+                        pcs.push(pc);
+                    }
+                    Ok(())
+                })
+                .build()
+                .unwrap();
+            ExecutorImpl::from_elf(env, HELLO_COMMIT_ELF)
+                .unwrap()
+                .run()
+                .unwrap();
+        }
+        let bp = pcs[100..]
+            .iter()
+            .copied()
+            .find(|pc| !pcs[..100].contains(pc))
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = Client(TcpStream::connect(addr).unwrap());
+            // Nothing is sent until the debugger asks, so replies stay in step.
+            assert_eq!(
+                client.request("qSupported:multiprocess+;swbreak+"),
+                "PacketSize=4000"
+            );
+            assert_eq!(client.request("?"), "S05");
+
+            // Execution is stopped before the first instruction.
+            let entry = client.pc();
+            assert_eq!(client.request("g").len(), 33 * 8);
+            assert_eq!(client.request(&format!("m{entry:x},4")).len(), 8);
+
+            assert_eq!(client.request("s"), "S05");
+            assert_ne!(client.pc(), entry);
+
+            assert_eq!(client.request(&format!("Z0,{bp:x},4")), "OK");
+            assert_eq!(client.request("c"), "S05");
+            assert_eq!(client.pc(), bp);
+            assert_eq!(client.request(&format!("z0,{bp:x},4")), "OK");
+
+            assert_eq!(client.request("c"), "W00");
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut exec = ExecutorImpl::from_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF).unwrap();
+        exec.attach_gdb(stream).unwrap();
+        let session = exec.run().unwrap();
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+        client.join().unwrap();
+    }
+}
+
 #[cfg(feature = "docker")]
 mod docker {
     use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
@@ -0,0 +1,258 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timelines of execution in the Chrome trace event format.
+//!
+//! When [crate::ExecutorEnvBuilder::trace_file] is set, the executor writes a
+//! JSON timeline of the session that can be opened in Perfetto
+//! (<https://ui.perfetto.dev>) or `chrome://tracing`. Timestamps are user
+//! cycles, displayed as microseconds. The timeline has three tracks:
+//!
+//! * `instructions`: consecutive instructions in the same function are merged
+//!   into one span, named after the function when the guest is run from its
+//!   ELF.
+//! * `syscalls`: an instant event for each syscall handled by the host.
+//! * `paging`: a span for each page-in and page-out, lasting as many cycles as
+//!   the segment is charged for it.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context as _, Result};
+use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
+use rustc_demangle::demangle;
+use serde_json::{json, Value};
+
+use crate::{SessionObserver, SplitEvent, SyscallEvent, TraceCallback, TraceEvent};
+
+const PID: u32 = 0;
+const INSN_TID: u32 = 0;
+const SYSCALL_TID: u32 = 1;
+const PAGING_TID: u32 = 2;
+
+/// A function in the guest program.
+struct FuncSymbol {
+    start: u32,
+    end: u32,
+    name: String,
+}
+
+/// A run of consecutive instructions in the same function.
+struct Span {
+    func: Option<usize>,
+    start_cycle: u32,
+}
+
+/// A [TraceCallback] and [SessionObserver] that writes a Chrome trace.
+pub(crate) struct TraceWriter {
+    path: PathBuf,
+    out: Option<BufWriter<File>>,
+    symbols: Vec<FuncSymbol>,
+    span: Option<Span>,
+    last_cycle: u32,
+    error: Option<anyhow::Error>,
+}
+
+impl TraceWriter {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            out: None,
+            symbols: Vec::new(),
+            span: None,
+            last_cycle: 0,
+            error: None,
+        }
+    }
+
+    /// Name instruction spans after the functions in the guest program `elf`.
+    pub(crate) fn load_symbols(&mut self, elf: &[u8]) -> Result<()> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(elf)?;
+        let Some((symtab, strtab)) = elf.symbol_table()? else {
+            return Ok(());
+        };
+        let mut symbols = Vec::new();
+        for sym in symtab {
+            if sym.st_symtype() == STT_FUNC && sym.st_size > 0 {
+                symbols.push(FuncSymbol {
+                    start: sym.st_value.try_into()?,
+                    end: (sym.st_value + sym.st_size).try_into()?,
+                    name: demangle(strtab.get(sym.st_name as usize)?).to_string(),
+                });
+            }
+        }
+        symbols.sort_by_key(|sym| sym.start);
+        self.symbols = symbols;
+        Ok(())
+    }
+
+    /// Close any open span and finish the trace file.
+    ///
+    /// Returns the first error encountered while writing the trace.
+    pub(crate) fn finish(&mut self) -> Result<()> {
+        self.close_span(self.last_cycle + 1);
+        if let Some(mut out) = self.out.take() {
+            let result = out.write_all(b"\n]\n").and_then(|_| out.flush());
+            self.record(result.map_err(Into::into));
+        }
+        match self.error.take() {
+            Some(err) => Err(err.context(format!("failed to write trace {}", self.path.display()))),
+            None => Ok(()),
+        }
+    }
+
+    fn lookup(&self, pc: u32) -> Option<usize> {
+        let idx = self.symbols.partition_point(|sym| sym.start <= pc);
+        let idx = idx.checked_sub(1)?;
+        (pc < self.symbols[idx].end).then_some(idx)
+    }
+
+    fn close_span(&mut self, end_cycle: u32) {
+        let Some(span) = self.span.take() else {
+            return;
+        };
+        let name = match span.func {
+            Some(idx) => self.symbols[idx].name.clone(),
+            None => "unknown".to_string(),
+        };
+        self.write(json!({
+            "name": name,
+            "cat": "instructions",
+            "ph": "X",
+            "ts": span.start_cycle,
+            "dur": end_cycle - span.start_cycle,
+            "pid": PID,
+            "tid": INSN_TID,
+        }));
+    }
+
+    fn write(&mut self, event: Value) {
+        let result = self.write_event(event);
+        self.record(result);
+    }
+
+    fn write_event(&mut self, event: Value) -> Result<()> {
+        if let Some(out) = &mut self.out {
+            out.write_all(b",\n")?;
+        } else {
+            self.out = Some(self.create()?);
+        }
+        serde_json::to_writer(self.out.as_mut().unwrap(), &event)?;
+        Ok(())
+    }
+
+    /// Create the trace file, starting with the names of the tracks.
+    fn create(&self) -> Result<BufWriter<File>> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        out.write_all(b"[\n")?;
+        for (tid, name) in [
+            (INSN_TID, "instructions"),
+            (SYSCALL_TID, "syscalls"),
+            (PAGING_TID, "paging"),
+        ] {
+            serde_json::to_writer(
+                &mut out,
+                &json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": PID,
+                    "tid": tid,
+                    "args": { "name": name },
+                }),
+            )?;
+            out.write_all(b",\n")?;
+        }
+        Ok(out)
+    }
+
+    fn write_page(&mut self, name: &str, cycle: u32, page_idx: u32, cycles: u32) {
+        self.write(json!({
+            "name": name,
+            "cat": "paging",
+            "ph": "X",
+            "ts": cycle,
+            "dur": cycles,
+            "pid": PID,
+            "tid": PAGING_TID,
+            "args": { "page": format!("0x{page_idx:05x}") },
+        }));
+    }
+
+    fn record(&mut self, result: Result<()>) {
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+    }
+}
+
+impl TraceCallback for TraceWriter {
+    fn trace_callback(&mut self, event: TraceEvent) -> Result<()> {
+        match event {
+            TraceEvent::InstructionStart { cycle, pc, .. } => {
+                let func = self.lookup(pc);
+                if self.span.as_ref().map(|span| span.func) != Some(func) {
+                    self.close_span(cycle);
+                    self.span = Some(Span {
+                        func,
+                        start_cycle: cycle,
+                    });
+                }
+                self.last_cycle = cycle;
+            }
+            TraceEvent::PageIn {
+                cycle,
+                page_idx,
+                cycles,
+            } => self.write_page("page-in", cycle, page_idx, cycles),
+            TraceEvent::PageOut {
+                cycle,
+                page_idx,
+                cycles,
+            } => self.write_page("page-out", cycle, page_idx, cycles),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl SessionObserver for TraceWriter {
+    fn on_syscall(&mut self, event: &SyscallEvent<'_>) {
+        let name = event.name.rsplit("::").next().unwrap_or(event.name);
+        self.write(json!({
+            "name": name,
+            "cat": "syscalls",
+            "ph": "i",
+            "s": "t",
+            "ts": event.cycle,
+            "pid": PID,
+            "tid": SYSCALL_TID,
+        }));
+    }
+
+    fn on_split(&mut self, event: &SplitEvent) {
+        self.write(json!({
+            "name": format!("segment {} split", event.index),
+            "cat": "segments",
+            "ph": "i",
+            "s": "g",
+            "ts": self.last_cycle,
+            "pid": PID,
+            "tid": INSN_TID,
+            "args": { "po2": event.po2, "cycles": event.cycles },
+        }));
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Restartable proving of long sessions.
+//!
+//! When [crate::ProverOpts::checkpoint_dir] is set, every segment receipt and
+//! every lift and join produced while proving is written to that directory.
+//! Proving the same session again, e.g. after the machine was preempted, loads
+//! these receipts instead of proving them again, so only the work in flight at
+//! the time of the interruption is lost.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::sha::Digest;
+
+const MAGIC: &[u8; 8] = b"R0VMPRCK";
+const VERSION: u32 = 1;
+
+/// A directory of receipts produced while proving.
+pub(crate) struct ProofCheckpoint {
+    dir: PathBuf,
+}
+
+impl ProofCheckpoint {
+    pub(crate) fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Load the receipt of `kind` stored under `key`, if any.
+    ///
+    /// Unreadable entries are ignored, since they can always be proven again.
+    pub(crate) fn load<T: DeserializeOwned>(&self, kind: &str, key: &Digest) -> Option<T> {
+        let path = self.path(kind, key);
+        let bytes = fs::read(&path).ok()?;
+        match decode(&bytes) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                tracing::warn!("ignoring proof checkpoint {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Store a receipt of `kind` under `key`.
+    ///
+    /// The receipt is written to a temporary file first, so an interruption
+    /// while writing never leaves a partial entry behind.
+    pub(crate) fn store<T: Serialize>(&self, kind: &str, key: &Digest, value: &T) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(kind, key);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, encode(value)?)
+            .with_context(|| format!("Failed to write proof checkpoint: {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write proof checkpoint: {}", path.display()))
+    }
+
+    fn path(&self, kind: &str, key: &Digest) -> PathBuf {
+        self.dir.join(format!("{kind}-{key}.bin"))
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend(bincode::serialize(value)?);
+    Ok(bytes)
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let header_len = MAGIC.len() + std::mem::size_of::<u32>();
+    if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+        bail!("Not a proof checkpoint");
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into()?);
+    if version != VERSION {
+        bail!("Unsupported proof checkpoint version: {version}");
+    }
+    Ok(bincode::deserialize(&bytes[header_len..])?)
+}
@@ -30,6 +30,9 @@ use crate::{
 /// accurate journal contents but no cryptographic information.
 /// Because the receipt is fake, a verifier can only "verify" this receipt
 /// if dev mode is turned on; verification will otherwise fail.
+/// [crate::Prover::compress] returns fake receipts unchanged in dev mode.
+/// Proving individual segments and the recursion operations are not
+/// supported and return an error.
 ///
 /// CONVENIENT, BUT NOT MEANT FOR PRODUCTION
 /// Dev mode supports rapid development by allowing the developer to quickly
@@ -82,7 +85,7 @@ impl ProverServer for DevModeProver {
     }
 
     fn prove_segment(&self, _ctx: &VerifierContext, _segment: &Segment) -> Result<SegmentReceipt> {
-        unimplemented!("This is unsupported for dev mode.")
+        bail!("This is unsupported for dev mode.")
     }
 
     fn get_peak_memory_usage(&self) -> usize {
@@ -90,11 +93,11 @@ impl ProverServer for DevModeProver {
     }
 
     fn lift(&self, _receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
-        unimplemented!("This is unsupported for dev mode.")
+        bail!("This is unsupported for dev mode.")
     }
 
     fn join(&self, _a: &SuccinctReceipt, _b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-        unimplemented!("This is unsupported for dev mode.")
+        bail!("This is unsupported for dev mode.")
     }
 
     fn resolve(
@@ -102,10 +105,10 @@ impl ProverServer for DevModeProver {
         _conditional: &SuccinctReceipt,
         _assumption: &SuccinctReceipt,
     ) -> Result<SuccinctReceipt> {
-        unimplemented!("This is unsupported for dev mode.")
+        bail!("This is unsupported for dev mode.")
     }
 
     fn identity_p254(&self, _a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-        unimplemented!("This is unsupported for dev mode.")
+        bail!("This is unsupported for dev mode.")
     }
 }
@@ -0,0 +1,158 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, ensure, Result};
+use risc0_binfmt::MemoryImage;
+
+use super::{finish_session, ProverServer};
+use crate::{
+    host::{
+        api::{client::Client, Asset, AssetRequest},
+        prove_info::ProveInfo,
+        receipt::{SegmentReceipt, SuccinctReceipt},
+    },
+    sha::Digestible,
+    ExecutorEnv, ExitCode, ProverOpts, Segment, Session, VerifierContext,
+};
+
+/// A [ProverServer] that proves in a separate `r0vm` server process.
+///
+/// Requests are sent over the versioned protobuf protocol of the zkVM API, so
+/// the server may be a local child process started with
+/// [Client::new_sub_process] or a remote process reached through a custom
+/// [Connector](crate::Connector). Every receipt returned by the server is
+/// verified locally before it is used.
+///
+/// [ProverServer::prove_with_ctx] runs both execution and proving in the
+/// server. [ProverServer::prove_session] proves the segments of a session
+/// executed in this process one at a time, sending each segment to the
+/// server.
+pub struct ExternalProverServer {
+    client: Client,
+    opts: ProverOpts,
+}
+
+impl ExternalProverServer {
+    /// Construct an [ExternalProverServer] that sends requests through
+    /// `client`, asking the server to prove with `opts`.
+    pub fn new(client: Client, opts: ProverOpts) -> Self {
+        Self { client, opts }
+    }
+
+    /// Construct an [ExternalProverServer] that starts the `r0vm` found with
+    /// the `RISC0_SERVER_PATH` environment variable.
+    pub fn from_env(opts: ProverOpts) -> Result<Self> {
+        Ok(Self::new(Client::from_env()?, opts))
+    }
+}
+
+impl ProverServer for ExternalProverServer {
+    fn prove_with_ctx(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        elf: &[u8],
+    ) -> Result<ProveInfo> {
+        let image_id = MemoryImage::compute_id_cached(elf)?;
+        let binary = Asset::Inline(elf.to_vec().into());
+        let prove_info = self.client.prove(&env, &self.opts, binary)?;
+        if self.opts.prove_guest_errors {
+            prove_info.receipt.verify_integrity_with_context(ctx)?;
+            let pre = prove_info.receipt.get_claim()?.pre.digest();
+            ensure!(
+                pre == image_id,
+                "received unexpected image ID: expected {}, found {}",
+                hex::encode(image_id),
+                hex::encode(pre)
+            );
+        } else {
+            prove_info.receipt.verify_with_context(ctx, image_id)?;
+        }
+        Ok(prove_info)
+    }
+
+    fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
+        if session.exit_code == ExitCode::Aborted {
+            bail!("cannot prove a session that was aborted by the host");
+        }
+        let mut segments = Vec::new();
+        for segment_ref in session.segments.iter() {
+            let segment = segment_ref.resolve()?;
+            for hook in &session.hooks {
+                hook.on_pre_prove_segment(&segment);
+            }
+            segments.push(self.prove_segment(ctx, &segment)?);
+            for hook in &session.hooks {
+                hook.on_post_prove_segment(&segment);
+            }
+        }
+        finish_session(ctx, session, segments)
+    }
+
+    fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
+        let segment_bytes = bincode::serialize(segment)?;
+        let receipt = self.client.prove_segment(
+            &self.opts,
+            Asset::Inline(segment_bytes.into()),
+            AssetRequest::Inline,
+        )?;
+        receipt.verify_integrity_with_context(ctx)?;
+        Ok(receipt)
+    }
+
+    fn get_peak_memory_usage(&self) -> usize {
+        0
+    }
+
+    fn lift(&self, receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
+        let lifted = self.client.lift(
+            &self.opts,
+            receipt.clone().try_into()?,
+            AssetRequest::Inline,
+        )?;
+        lifted.verify_integrity()?;
+        Ok(lifted)
+    }
+
+    fn join(&self, a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
+        let joined = self.client.join(
+            &self.opts,
+            a.clone().try_into()?,
+            b.clone().try_into()?,
+            AssetRequest::Inline,
+        )?;
+        joined.verify_integrity()?;
+        Ok(joined)
+    }
+
+    fn resolve(
+        &self,
+        conditional: &SuccinctReceipt,
+        assumption: &SuccinctReceipt,
+    ) -> Result<SuccinctReceipt> {
+        let resolved = self.client.resolve(
+            &self.opts,
+            conditional.clone().try_into()?,
+            assumption.clone().try_into()?,
+            AssetRequest::Inline,
+        )?;
+        resolved.verify_integrity()?;
+        Ok(resolved)
+    }
+
+    fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
+        self.client
+            .identity_p254(&self.opts, a.clone().try_into()?, AssetRequest::Inline)
+    }
+}
@@ -14,8 +14,11 @@
 
 //! Run the zkVM guest and prove its results.
 
+mod checkpoint;
 mod dev_mode;
+mod external;
 mod prover_impl;
+mod telemetry;
 #[cfg(test)]
 mod tests;
 
@@ -27,12 +30,18 @@ use risc0_core::field::baby_bear::{BabyBear, Elem, ExtElem};
 use risc0_zkp::hal::{CircuitHal, Hal};
 
 use self::{dev_mode::DevModeProver, prover_impl::ProverImpl};
+pub use self::{
+    external::ExternalProverServer,
+    telemetry::{ProofKind, ProofTimings, ProverTelemetry},
+};
 use crate::{
     host::{
         prove_info::ProveInfo,
         receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
     },
-    is_dev_mode, ExecutorEnv, ExecutorImpl, ProverOpts, Segment, Session, VerifierContext,
+    is_dev_mode,
+    sha::Digestible,
+    Assumption, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, Session, VerifierContext,
 };
 
 /// A ProverServer can execute a given ELF binary and produce a [ProveInfo] which contains a [crate::Receipt]
@@ -81,6 +90,22 @@ pub trait ProverServer {
     /// Convert a [SuccinctReceipt] with a Poseidon hash function that uses a 254-bit field
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt>;
 
+    /// Compact a [SuccinctReceipt] into a Groth16 [CompactReceipt](crate::CompactReceipt).
+    ///
+    /// This runs the identity_p254 recursion program and passes the resulting seal to the Groth16
+    /// prover, which produces a receipt that can be verified on-chain. Requires running Docker on
+    /// an x86 architecture.
+    #[cfg(feature = "groth16-prove")]
+    fn compact(&self, receipt: &SuccinctReceipt) -> Result<crate::CompactReceipt> {
+        let ident_receipt = self.identity_p254(receipt)?;
+        let seal_bytes = ident_receipt.get_seal_bytes();
+        let seal = risc0_groth16::docker::stark_to_snark(&seal_bytes)?.to_vec();
+        Ok(crate::CompactReceipt {
+            seal,
+            claim: receipt.claim.clone(),
+        })
+    }
+
     /// Compress a [CompositeReceipt] into a single [SuccinctReceipt].
     ///
     /// A [CompositeReceipt] may contain an arbitrary number of receipts assembled into
@@ -140,6 +165,70 @@ where
     pub circuit_hal: Rc<C>,
 }
 
+/// Assemble the segment receipts of a [Session] into a [Receipt], checking
+/// that it proves the claim of the session.
+pub(crate) fn finish_session(
+    ctx: &VerifierContext,
+    session: &Session,
+    segments: Vec<SegmentReceipt>,
+) -> Result<ProveInfo> {
+    // TODO(#982): Support unresolved assumptions here.
+    let assumptions = session
+        .assumptions
+        .iter()
+        .map(|x| match x {
+            Assumption::Proven(receipt) => Ok(receipt.inner.clone()),
+            Assumption::Unresolved(_) => {
+                bail!("no receipt available for unresolved assumption")
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let composite_receipt = CompositeReceipt {
+        segments,
+        assumptions,
+        journal_digest: session.journal.as_ref().map(|journal| journal.digest()),
+    };
+
+    // Verify the receipt to catch if something is broken in the proving process.
+    composite_receipt.verify_integrity_with_context(ctx)?;
+    if composite_receipt.get_claim()?.digest() != session.get_claim()?.digest() {
+        tracing::debug!("composite receipt and session claim do not match");
+        tracing::debug!(
+            "composite receipt claim: {:#?}",
+            composite_receipt.get_claim()?
+        );
+        tracing::debug!("session claim: {:#?}", session.get_claim()?);
+        bail!(
+            "session and composite receipt claim do not match: session {}, receipt {}",
+            hex::encode(&session.get_claim()?.digest()),
+            hex::encode(&composite_receipt.get_claim()?.digest())
+        );
+    }
+
+    let receipt = Receipt::new(
+        InnerReceipt::Composite(composite_receipt),
+        session.journal.clone().unwrap_or_default().bytes,
+    );
+
+    // Verify the receipt to catch if something is broken in the proving process.
+    receipt.verify_integrity_with_context(ctx)?;
+    if receipt.get_claim()?.digest() != session.get_claim()?.digest() {
+        tracing::debug!("receipt and session claim do not match");
+        tracing::debug!("receipt claim: {:#?}", receipt.get_claim()?);
+        tracing::debug!("session claim: {:#?}", session.get_claim()?);
+        bail!(
+            "session and receipt claim do not match: session {}, receipt {}",
+            hex::encode(&session.get_claim()?.digest()),
+            hex::encode(&receipt.get_claim()?.digest())
+        );
+    }
+
+    Ok(ProveInfo {
+        receipt,
+        stats: session.stats(),
+    })
+}
+
 impl Session {
     /// For each segment, call [ProverServer::prove_session] and collect the
     /// receipts.
@@ -165,18 +254,20 @@ mod cuda {
             "sha-256" => {
                 let hal = Rc::new(CudaHalSha256::new());
                 let circuit_hal = Rc::new(CudaCircuitHalSha256::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("cuda", HalPair { hal, circuit_hal })
+                        .with_segment_dedup(opts.dedup_segments)
+                        .with_checkpoint_dir(opts.checkpoint_dir.clone()),
+                ))
             }
             "poseidon2" => {
                 let hal = Rc::new(CudaHalPoseidon2::new());
                 let circuit_hal = Rc::new(CudaCircuitHalPoseidon2::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("cuda", HalPair { hal, circuit_hal })
+                        .with_segment_dedup(opts.dedup_segments)
+                        .with_checkpoint_dir(opts.checkpoint_dir.clone()),
+                ))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
         }
@@ -201,18 +292,20 @@ mod metal {
             "sha-256" => {
                 let hal = Rc::new(MetalHalSha256::new());
                 let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("metal", HalPair { hal, circuit_hal })
+                        .with_segment_dedup(opts.dedup_segments)
+                        .with_checkpoint_dir(opts.checkpoint_dir.clone()),
+                ))
             }
             "poseidon2" => {
                 let hal = Rc::new(MetalHalPoseidon2::new());
                 let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashPoseidon2>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("metal", HalPair { hal, circuit_hal })
+                        .with_segment_dedup(opts.dedup_segments)
+                        .with_checkpoint_dir(opts.checkpoint_dir.clone()),
+                ))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
         }
@@ -242,7 +335,11 @@ mod cpu {
         let hal = Rc::new(CpuHal::new(suite));
         let circuit_hal = Rc::new(CpuCircuitHal::new());
         let hal_pair = HalPair { hal, circuit_hal };
-        Ok(Rc::new(ProverImpl::new("cpu", hal_pair)))
+        Ok(Rc::new(
+            ProverImpl::new("cpu", hal_pair)
+                .with_segment_dedup(opts.dedup_segments)
+                .with_checkpoint_dir(opts.checkpoint_dir.clone()),
+        ))
     }
 }
 
@@ -12,19 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{collections::HashMap, path::PathBuf};
+
 use anyhow::{bail, Result};
 use risc0_core::field::baby_bear::{BabyBear, Elem, ExtElem};
 use risc0_zkp::hal::{CircuitHal, Hal};
+use serde::{de::DeserializeOwned, Serialize};
 
-use super::{HalPair, ProverServer};
+use super::{checkpoint::ProofCheckpoint, finish_session, HalPair, ProverServer};
 use crate::{
     host::{
         prove_info::ProveInfo,
-        receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
+        receipt::{SegmentReceipt, SuccinctReceipt},
         recursion::{identity_p254, join, lift, resolve},
     },
-    sha::Digestible,
-    Receipt, Segment, Session, VerifierContext,
+    sha::{Digest, Digestible, Sha256},
+    ExitCode, Segment, Session, VerifierContext,
 };
 
 /// An implementation of a Prover that runs locally.
@@ -35,6 +38,8 @@ where
 {
     name: String,
     hal_pair: HalPair<H, C>,
+    dedup_segments: bool,
+    checkpoint: Option<ProofCheckpoint>,
 }
 
 impl<H, C> ProverImpl<H, C>
@@ -47,84 +52,143 @@ where
         Self {
             name: name.to_string(),
             hal_pair,
+            dedup_segments: false,
+            checkpoint: None,
         }
     }
-}
 
-impl<H, C> ProverServer for ProverImpl<H, C>
-where
-    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
-    C: CircuitHal<H>,
-{
-    fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
-        tracing::debug!(
-            "prove_session: {}, exit_code = {:?}, journal = {:?}, segments: {}",
-            self.name,
-            session.exit_code,
-            session.journal.as_ref().map(|x| hex::encode(x)),
-            session.segments.len()
-        );
+    /// Prove segments identical to an earlier segment of the session only
+    /// once, reusing the earlier receipt. See [crate::ProverOpts::dedup_segments].
+    pub fn with_segment_dedup(mut self, enabled: bool) -> Self {
+        self.dedup_segments = enabled;
+        self
+    }
+
+    /// Store receipts in `dir` as they are produced, and reuse receipts
+    /// already stored there. See [crate::ProverOpts::checkpoint_dir].
+    pub fn with_checkpoint_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.checkpoint = dir.map(|dir| ProofCheckpoint::new(&dir));
+        self
+    }
+
+    /// Prove each segment of `session` in order, calling the session hooks
+    /// around every segment that is proven rather than reused.
+    pub(crate) fn prove_segments(
+        &self,
+        ctx: &VerifierContext,
+        session: &Session,
+    ) -> Result<Vec<SegmentReceipt>> {
         let mut segments = Vec::new();
+        let mut proven: HashMap<Digest, SegmentReceipt> = HashMap::new();
         for segment_ref in session.segments.iter() {
             let segment = segment_ref.resolve()?;
+            let key = match self.dedup_segments {
+                true => Some(segment_key(&segment)?),
+                false => None,
+            };
+            if let Some(receipt) = key.as_ref().and_then(|key| proven.get(key)) {
+                tracing::debug!(
+                    "reusing receipt {} for segment {}",
+                    receipt.index,
+                    segment.index
+                );
+                segments.push(SegmentReceipt {
+                    index: segment.index,
+                    ..receipt.clone()
+                });
+                continue;
+            }
             for hook in &session.hooks {
                 hook.on_pre_prove_segment(&segment);
             }
-            segments.push(self.prove_segment(ctx, &segment)?);
+            let receipt = self.checkpointed(
+                "segment",
+                || segment_key(&segment),
+                |receipt: &SegmentReceipt| Ok(receipt.verify_integrity_with_context(ctx)?),
+                || self.prove_segment(ctx, &segment),
+            )?;
+            let receipt = SegmentReceipt {
+                index: segment.index,
+                ..receipt
+            };
             for hook in &session.hooks {
                 hook.on_post_prove_segment(&segment);
             }
+            if let Some(key) = key {
+                proven.insert(key, receipt.clone());
+            }
+            segments.push(receipt);
         }
-        // TODO(#982): Support unresolved assumptions here.
-        let assumptions = session
-            .assumptions
-            .iter()
-            .map(|x| Ok(x.as_receipt()?.inner.clone()))
-            .collect::<Result<Vec<_>>>()?;
-        let composite_receipt = CompositeReceipt {
-            segments,
-            assumptions,
-            journal_digest: session.journal.as_ref().map(|journal| journal.digest()),
-        };
+        Ok(segments)
+    }
 
-        // Verify the receipt to catch if something is broken in the proving process.
-        composite_receipt.verify_integrity_with_context(ctx)?;
-        if composite_receipt.get_claim()?.digest() != session.get_claim()?.digest() {
-            tracing::debug!("composite receipt and session claim do not match");
-            tracing::debug!(
-                "composite receipt claim: {:#?}",
-                composite_receipt.get_claim()?
-            );
-            tracing::debug!("session claim: {:#?}", session.get_claim()?);
-            bail!(
-                "session and composite receipt claim do not match: session {}, receipt {}",
-                hex::encode(&session.get_claim()?.digest()),
-                hex::encode(&composite_receipt.get_claim()?.digest())
-            );
+    /// Return the receipt of `kind` for `key` from the checkpoint directory if
+    /// one is stored there and passes `verify`. Otherwise, produce it with
+    /// `prove` and store it.
+    fn checkpointed<T: Serialize + DeserializeOwned>(
+        &self,
+        kind: &str,
+        key: impl FnOnce() -> Result<Digest>,
+        verify: impl FnOnce(&T) -> Result<()>,
+        prove: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let Some(checkpoint) = &self.checkpoint else {
+            return prove();
+        };
+        let kind = format!("{kind}-{}", self.hal_pair.hal.get_hash_suite().name);
+        let key = key()?;
+        if let Some(value) = checkpoint.load(&kind, &key) {
+            match verify(&value) {
+                Ok(()) => {
+                    tracing::debug!("reusing checkpointed {kind} {key}");
+                    return Ok(value);
+                }
+                Err(err) => tracing::warn!("ignoring checkpointed {kind} {key}: {err}"),
+            }
         }
+        let value = prove()?;
+        checkpoint.store(&kind, &key, &value)?;
+        Ok(value)
+    }
+}
 
-        let receipt = Receipt::new(
-            InnerReceipt::Composite(composite_receipt),
-            session.journal.clone().unwrap_or_default().bytes,
-        );
+/// A digest of everything that determines the claim proven for a [Segment],
+/// i.e. all of it except its index within the [Session].
+pub(crate) fn segment_key(segment: &Segment) -> Result<Digest> {
+    let inner = &segment.inner;
+    let words = crate::serde::to_vec(&(
+        &inner.partial_image,
+        &inner.pre_state,
+        &inner.post_state,
+        &inner.syscalls,
+        inner.insn_cycles,
+        inner.po2,
+        inner.exit_code,
+        &inner.input_digest,
+        &inner.output_digest,
+        &segment.output,
+    ))?;
+    Ok(*crate::sha::Impl::hash_words(&words))
+}
 
-        // Verify the receipt to catch if something is broken in the proving process.
-        receipt.verify_integrity_with_context(ctx)?;
-        if receipt.get_claim()?.digest() != session.get_claim()?.digest() {
-            tracing::debug!("receipt and session claim do not match");
-            tracing::debug!("receipt claim: {:#?}", receipt.get_claim()?);
-            tracing::debug!("session claim: {:#?}", session.get_claim()?);
-            bail!(
-                "session and receipt claim do not match: session {}, receipt {}",
-                hex::encode(&session.get_claim()?.digest()),
-                hex::encode(&receipt.get_claim()?.digest())
-            );
+impl<H, C> ProverServer for ProverImpl<H, C>
+where
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    C: CircuitHal<H>,
+{
+    fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
+        tracing::debug!(
+            "prove_session: {}, exit_code = {:?}, journal = {:?}, segments: {}",
+            self.name,
+            session.exit_code,
+            session.journal.as_ref().map(|x| hex::encode(x)),
+            session.segments.len()
+        );
+        if session.exit_code == ExitCode::Aborted {
+            bail!("cannot prove a session that was aborted by the host");
         }
-
-        Ok(ProveInfo {
-            receipt,
-            stats: session.stats(),
-        })
+        let segments = self.prove_segments(ctx, session)?;
+        finish_session(ctx, session, segments)
     }
 
     fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
@@ -157,11 +221,34 @@ where
     }
 
     fn lift(&self, receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
-        lift(receipt)
+        self.checkpointed(
+            "lift",
+            || Ok(receipt.claim.digest()),
+            |lifted: &SuccinctReceipt| {
+                lifted.verify_integrity_with_context(&VerifierContext::default())?;
+                if lifted.claim.digest() != receipt.claim.digest() {
+                    bail!("lifted receipt claim does not match");
+                }
+                Ok(())
+            },
+            || lift(receipt),
+        )
     }
 
     fn join(&self, a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-        join(a, b)
+        self.checkpointed(
+            "join",
+            || {
+                Ok(*crate::sha::Impl::hash_pair(
+                    &a.claim.digest(),
+                    &b.claim.digest(),
+                ))
+            },
+            |joined: &SuccinctReceipt| {
+                Ok(joined.verify_integrity_with_context(&VerifierContext::default())?)
+            },
+            || join(a, b),
+        )
     }
 
     fn resolve(
@@ -0,0 +1,251 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-proof timing breakdowns for proving fleet telemetry.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The kind of proof described by [ProofTimings].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ProofKind {
+    /// A proof of a segment with the rv32im circuit.
+    #[default]
+    Segment,
+    /// A proof with the recursion circuit, such as lift, join or resolve.
+    Recursion,
+}
+
+/// Time spent in each phase of a single proof.
+///
+/// Phases nest: the time to commit a group includes the NTTs and hashing done
+/// for it, so the phases do not add up to [ProofTimings::total].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ProofTimings {
+    /// The kind of proof.
+    pub kind: ProofKind,
+    /// Wall-clock time of the whole proof.
+    pub total: Duration,
+    /// Time spent executing the circuit to generate the witness.
+    pub execute: Duration,
+    /// Time spent committing each group of the witness, keyed by group name.
+    pub commit: BTreeMap<String, Duration>,
+    /// Time spent in number theoretic transforms.
+    pub ntt: Duration,
+    /// Time spent building Merkle trees.
+    pub hash: Duration,
+    /// Time spent evaluating the constraints of the circuit.
+    pub eval_check: Duration,
+    /// Time spent in the FRI protocol.
+    pub fri: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Phase {
+    Execute,
+    Commit(String),
+    Ntt,
+    Hash,
+    EvalCheck,
+    Fri,
+}
+
+impl Phase {
+    fn add(self, timings: &mut ProofTimings, elapsed: Duration) {
+        let slot = match self {
+            Phase::Execute => &mut timings.execute,
+            Phase::Commit(group) => timings.commit.entry(group).or_default(),
+            Phase::Ntt => &mut timings.ntt,
+            Phase::Hash => &mut timings.hash,
+            Phase::EvalCheck => &mut timings.eval_check,
+            Phase::Fri => &mut timings.fri,
+        };
+        *slot += elapsed;
+    }
+}
+
+/// Classify a span emitted by the provers by its name.
+fn classify(attrs: &Attributes<'_>) -> Option<Phase> {
+    Some(match attrs.metadata().name() {
+        "compute_execute" | "step_exec" => Phase::Execute,
+        "PolyGroup" => {
+            let mut visitor = NameVisitor(None);
+            attrs.record(&mut visitor);
+            Phase::Commit(visitor.0.unwrap_or_default())
+        }
+        "batch_expand_into_evaluate_ntt" | "batch_interpolate_ntt" => Phase::Ntt,
+        "MerkleTreeProver" => Phase::Hash,
+        "eval_check" => Phase::EvalCheck,
+        "fri_prove" => Phase::Fri,
+        _ => return None,
+    })
+}
+
+/// Returns the kind of proof started by a span, if any.
+fn proof_kind(name: &str) -> Option<ProofKind> {
+    match name {
+        "prove_segment" => Some(ProofKind::Segment),
+        "run_with_hal" => Some(ProofKind::Recursion),
+        _ => None,
+    }
+}
+
+struct NameVisitor(Option<String>);
+
+impl Visit for NameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "name" {
+            self.0 = Some(format!("{value:?}").trim_matches('"').to_string());
+        }
+    }
+}
+
+#[derive(Default)]
+struct Timer {
+    entered: Option<Instant>,
+    elapsed: Duration,
+}
+
+type Callback = dyn Fn(&ProofTimings) + Send + Sync;
+
+/// A [tracing_subscriber::Layer] that reports the [ProofTimings] of every
+/// proof made on the local machine.
+///
+/// The timings are collected from the spans already emitted by the provers, so
+/// the layer must be installed in the subscriber of the proving threads.
+/// `callback` is invoked on the proving thread when each proof finishes, and
+/// should hand the timings off to a telemetry system without blocking.
+///
+/// ```no_run
+/// use risc0_zkvm::ProverTelemetry;
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry()
+///     .with(ProverTelemetry::new(|timings| {
+///         println!("{:?} proof took {:?}", timings.kind, timings.total);
+///     }))
+///     .init();
+/// ```
+#[derive(Clone)]
+pub struct ProverTelemetry {
+    callback: Arc<Callback>,
+}
+
+impl ProverTelemetry {
+    /// Construct a layer that calls `callback` with the timings of each
+    /// proof.
+    pub fn new(callback: impl Fn(&ProofTimings) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<S> Layer<S> for ProverTelemetry
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(kind) = proof_kind(attrs.metadata().name()) {
+            let mut extensions = span.extensions_mut();
+            extensions.insert(Timer::default());
+            extensions.insert(ProofTimings {
+                kind,
+                ..Default::default()
+            });
+            return;
+        }
+        let Some(phase) = classify(attrs) else {
+            return;
+        };
+        // Only count the outermost span of each phase, e.g. `step_exec` within
+        // `compute_execute`.
+        let nested = span
+            .scope()
+            .skip(1)
+            .any(|parent| parent.extensions().get::<Phase>() == Some(&phase));
+        if !nested {
+            let mut extensions = span.extensions_mut();
+            extensions.insert(Timer::default());
+            extensions.insert(phase);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timer) = span.extensions_mut().get_mut::<Timer>() {
+                timer.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timer) = span.extensions_mut().get_mut::<Timer>() {
+                if let Some(entered) = timer.entered.take() {
+                    timer.elapsed += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(elapsed) = extensions.get_mut::<Timer>().map(|timer| timer.elapsed) else {
+            return;
+        };
+        if let Some(mut timings) = extensions.remove::<ProofTimings>() {
+            timings.total = elapsed;
+            (self.callback)(&timings);
+            return;
+        }
+        let Some(phase) = extensions.remove::<Phase>() else {
+            return;
+        };
+        drop(extensions);
+        if let Some(proof) = span
+            .scope()
+            .skip(1)
+            .find(|parent| parent.extensions().get::<ProofTimings>().is_some())
+        {
+            if let Some(timings) = proof.extensions_mut().get_mut::<ProofTimings>() {
+                phase.add(timings, elapsed);
+            }
+        }
+    }
+}
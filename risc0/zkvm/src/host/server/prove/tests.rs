@@ -12,13 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::rc::Rc;
+use std::{
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::Result;
 use risc0_binfmt::MemoryImage;
 use risc0_circuit_rv32im::prove::{emu::testutil, hal::cpu::CpuCircuitHal};
 use risc0_zkp::{
-    core::{digest::Digest, hash::blake2b::Blake2bCpuHashSuite},
+    core::{
+        digest::Digest,
+        hash::{blake2b::Blake2bCpuHashSuite, sha::Sha256HashSuite},
+    },
     hal::cpu::CpuHal,
     verify::VerificationError,
 };
@@ -26,8 +33,11 @@ use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_I
 use risc0_zkvm_platform::{memory, PAGE_SIZE, WORD_SIZE};
 use serial_test::serial;
 use test_log::test;
+use tracing_subscriber::layer::SubscriberExt;
 
-use super::{get_prover_server, HalPair, ProverImpl};
+use super::{
+    get_prover_server, prover_impl::segment_key, HalPair, ProofKind, ProverImpl, ProverTelemetry,
+};
 use crate::{
     host::server::testutils,
     serde::{from_slice, to_vec},
@@ -39,6 +49,8 @@ fn prover_opts_fast() -> ProverOpts {
     ProverOpts {
         hashfn: "sha-256".to_string(),
         prove_guest_errors: false,
+        dedup_segments: false,
+        checkpoint_dir: None,
     }
 }
 
@@ -59,6 +71,8 @@ fn prove_nothing(hashfn: &str) -> Result<ProveInfo> {
     let opts = ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        dedup_segments: false,
+        checkpoint_dir: None,
     };
     get_prover_server(&opts).unwrap().prove(env, MULTI_TEST_ELF)
 }
@@ -84,6 +98,30 @@ fn hashfn_blake2b() {
     prover.prove(env, MULTI_TEST_ELF).unwrap();
 }
 
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn telemetry() {
+    let timings = Arc::new(Mutex::new(Vec::new()));
+    let layer = {
+        let timings = timings.clone();
+        ProverTelemetry::new(move |proof| timings.lock().unwrap().push(proof.clone()))
+    };
+    let subscriber = tracing_subscriber::registry().with(layer);
+    tracing::subscriber::with_default(subscriber, || prove_nothing("sha-256").unwrap());
+
+    let timings = timings.lock().unwrap();
+    assert_eq!(timings.len(), 1);
+    let proof = &timings[0];
+    assert_eq!(proof.kind, ProofKind::Segment);
+    assert!(proof.execute > Duration::ZERO);
+    assert!(proof.ntt > Duration::ZERO);
+    assert!(proof.hash > Duration::ZERO);
+    assert!(proof.eval_check > Duration::ZERO);
+    assert!(proof.fri > Duration::ZERO);
+    assert!(!proof.commit.is_empty());
+    assert!(proof.total >= proof.execute + proof.eval_check + proof.fri);
+}
+
 #[test]
 #[cfg_attr(feature = "cuda", serial)]
 fn receipt_serde() {
@@ -464,6 +502,121 @@ fn continuation() {
     }
 }
 
+#[test]
+fn segment_key_ignores_index() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let env = ExecutorEnv::builder()
+        .segment_limit_po2(14) // 16k cycles
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+    let mut segments: Vec<_> = session
+        .segments
+        .iter()
+        .map(|x| x.resolve().unwrap())
+        .collect();
+    assert_eq!(segments.len(), 2);
+
+    let first = segment_key(&segments[0]).unwrap();
+    assert_ne!(first, segment_key(&segments[1]).unwrap());
+
+    segments[0].index = 7;
+    segments[0].inner.index = 7;
+    assert_eq!(first, segment_key(&segments[0]).unwrap());
+}
+
+#[test]
+fn dedup_segments() {
+    use std::cell::Cell;
+
+    use crate::{Segment, SessionEvents, SimpleSegmentRef};
+
+    struct Counter(Rc<Cell<usize>>);
+
+    impl SessionEvents for Counter {
+        fn on_pre_prove_segment(&self, _: &Segment) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(15)
+        .build()
+        .unwrap();
+    let mut session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let distinct = session.segments.len();
+
+    // Real sessions rarely repeat a segment, so append a copy of the first.
+    let mut repeat = session.segments[0].resolve().unwrap();
+    repeat.index = distinct as u32;
+    repeat.inner.index = distinct;
+    session
+        .segments
+        .push(Box::new(SimpleSegmentRef::new(repeat)));
+
+    let proven = Rc::new(Cell::new(0));
+    session.add_hook(Counter(proven.clone()));
+    let hal_pair = HalPair {
+        hal: Rc::new(CpuHal::new(Sha256HashSuite::new_suite())),
+        circuit_hal: Rc::new(CpuCircuitHal::new()),
+    };
+    let prover = ProverImpl::new("cpu", hal_pair).with_segment_dedup(true);
+    let receipts = prover
+        .prove_segments(&VerifierContext::default(), &session)
+        .unwrap();
+
+    assert_eq!(proven.get(), distinct);
+    assert_eq!(receipts.len(), distinct + 1);
+    for (idx, receipt) in receipts.iter().enumerate() {
+        assert_eq!(receipt.index, idx as u32);
+    }
+    assert_eq!(receipts[distinct].seal, receipts[0].seal);
+}
+
+#[test]
+fn checkpoint_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(15)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let opts = ProverOpts {
+        checkpoint_dir: Some(dir.path().to_path_buf()),
+        ..prover_opts_fast()
+    };
+    let prove = || {
+        get_prover_server(&opts)
+            .unwrap()
+            .prove_session(&VerifierContext::default(), &session)
+            .unwrap()
+            .receipt
+    };
+
+    let receipt = prove();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+    let segments = &receipt.inner.composite().unwrap().segments;
+    assert!(std::fs::read_dir(dir.path()).unwrap().count() > 0);
+
+    // Proving again reuses the stored segment receipts.
+    let resumed = prove();
+    resumed.verify(MULTI_TEST_ID).unwrap();
+    let resumed_segments = &resumed.inner.composite().unwrap().segments;
+    assert_eq!(resumed_segments, segments);
+}
+
 #[test]
 fn sys_input() {
     use hex::FromHex;
@@ -530,6 +683,39 @@ mod docker {
 
         receipt.verify(MULTI_TEST_ID).unwrap();
     }
+
+    #[cfg(feature = "groth16-prove")]
+    #[test]
+    fn compact() {
+        use crate::{
+            get_prover_server, ExecutorEnv, ExecutorImpl, InnerReceipt, ProverOpts, Receipt,
+            VerifierContext,
+        };
+        use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::BusyLoop { cycles: 0 })
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+
+        let opts = ProverOpts::default();
+        let ctx = VerifierContext::default();
+        let prover = get_prover_server(&opts).unwrap();
+        let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
+        let composite_receipt = receipt.inner.composite().unwrap();
+        let succinct_receipt = prover.compress(composite_receipt).unwrap();
+        let compact_receipt = prover.compact(&succinct_receipt).unwrap();
+        assert_eq!(compact_receipt.claim, succinct_receipt.claim);
+
+        let receipt = Receipt::new(
+            InnerReceipt::Compact(compact_receipt),
+            receipt.journal.bytes,
+        );
+        receipt.verify(MULTI_TEST_ID).unwrap();
+    }
 }
 
 mod sys_verify {
@@ -560,6 +746,8 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: true,
+            dedup_segments: false,
+            checkpoint_dir: None,
         };
 
         let env = ExecutorEnvBuilder::default()
@@ -16,19 +16,26 @@
 //! execution traces between the execution phase and the proving phase.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     fs,
     path::{Path, PathBuf},
 };
 
-use anyhow::{ensure, Result};
-use risc0_binfmt::{MemoryImage, SystemState};
+use anyhow::{ensure, Context as _, Result};
+use risc0_binfmt::{ImageDelta, MemoryImage, SystemState};
 use risc0_circuit_rv32im::prove::segment::Segment as CircuitSegment;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    host::{client::env::SegmentPath, prove_info::SessionStats},
-    sha::Digest,
+    host::{
+        client::{
+            env::SegmentPath,
+            replay::{RecordedSyscall, SessionRecord},
+        },
+        env::ClockPolicy,
+        prove_info::{HeapStats, InsnStats, SessionStats},
+    },
+    sha::{Digest, Digestible},
     Assumption, Assumptions, ExitCode, Journal, Output, ReceiptClaim,
 };
 
@@ -82,6 +89,111 @@ pub struct Session {
 
     /// The system state of the final [MemoryImage] at the end of execution.
     pub post_state: SystemState,
+
+    /// Heap usage reported by the guest, if it was built with the `heap-stats`
+    /// feature.
+    pub heap_stats: Option<HeapStats>,
+
+    /// The number of distinct guest memory pages touched during execution.
+    pub guest_pages: u64,
+
+    /// Instruction counts, if enabled with
+    /// [crate::ExecutorEnvBuilder::enable_insn_stats].
+    pub insn_stats: Option<InsnStats>,
+
+    /// Nested executions run by host handlers registered with
+    /// [crate::ExecutorEnvBuilder::sub_executor], in the order they ran.
+    pub sub_sessions: Vec<SubSession>,
+
+    pub(crate) syscall_count: u64,
+
+    pub(crate) syscalls: Option<Vec<RecordedSyscall>>,
+
+    pub(crate) read_offsets: BTreeMap<u32, u64>,
+
+    pub(crate) clock: ClockPolicy,
+}
+
+/// A summary of a nested execution run on behalf of the guest.
+///
+/// The claim of each [SubSession] is added to the outer environment as an
+/// unresolved assumption, so the outer guest can call `env::verify` on the
+/// returned journal. Running the nested guest again with [SubSession::input]
+/// as its stdin, proving it and resolving that assumption yields a single
+/// receipt covering both executions.
+#[derive(Clone, Debug)]
+pub struct SubSession {
+    /// The channel the guest used to request this execution.
+    pub channel: String,
+
+    /// The bytes sent by the guest, which were the stdin of the nested guest.
+    pub input: Vec<u8>,
+
+    /// The [ReceiptClaim] that proving this execution would produce.
+    pub claim: ReceiptClaim,
+
+    /// The data committed by the nested guest.
+    pub journal: Vec<u8>,
+
+    /// The number of user cycles used by the nested execution.
+    pub user_cycles: u64,
+
+    /// The total number of cycles used by the nested execution.
+    pub total_cycles: u64,
+}
+
+impl SubSession {
+    /// The image ID of the nested guest.
+    pub fn image_id(&self) -> Result<Digest> {
+        Ok(self.claim.pre.as_value()?.digest())
+    }
+}
+
+/// The public outputs that proving a [Session] would attest to.
+///
+/// This can be computed by execution alone, with
+/// [crate::ExecutorImpl::expected_outputs], so that parties can agree on the
+/// expected result of a guest before any proof exists.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ExpectedOutputs {
+    /// The image ID of the initial [MemoryImage].
+    pub image_id: Digest,
+
+    /// The image ID of the final [MemoryImage].
+    pub post_image_id: Digest,
+
+    /// The [ExitCode] of the session.
+    pub exit_code: ExitCode,
+
+    /// The SHA-256 digest of the journal, if the session produced one.
+    pub journal_digest: Option<Digest>,
+
+    /// The digest of the [ReceiptClaim] that a receipt for the session would
+    /// prove.
+    pub claim_digest: Digest,
+}
+
+/// The state of a paused [Session], from which execution can be resumed.
+///
+/// Unlike a [Session], this is serializable, so a paused guest can be resumed
+/// in a different process, e.g. after a host restart. Use [Session::suspend]
+/// to create it and [crate::ExecutorImpl::resume] to continue execution.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SuspendedState {
+    /// The [MemoryImage] at the point execution was paused.
+    pub image: MemoryImage,
+
+    /// The [ExitCode::Paused] code the guest paused with.
+    pub exit_code: ExitCode,
+
+    /// The number of bytes the guest read from each file descriptor before it
+    /// paused. These are skipped when the guest is resumed.
+    pub read_offsets: BTreeMap<u32, u64>,
+
+    /// The clock the guest saw before it paused, so that it sees the same
+    /// time after it is resumed.
+    pub clock: ClockPolicy,
 }
 
 /// The execution trace of a portion of a program.
@@ -157,6 +269,14 @@ impl Session {
             total_cycles,
             pre_state,
             post_state,
+            heap_stats: None,
+            guest_pages: 0,
+            insn_stats: None,
+            sub_sessions: Vec::new(),
+            syscall_count: 0,
+            syscalls: None,
+            read_offsets: BTreeMap::new(),
+            clock: ClockPolicy::default(),
         }
     }
 
@@ -214,6 +334,55 @@ impl Session {
         })
     }
 
+    /// Returns the [ExpectedOutputs] that a receipt for this [Session] would
+    /// attest to.
+    pub fn expected_outputs(&self) -> Result<ExpectedOutputs> {
+        Ok(ExpectedOutputs {
+            image_id: self.pre_state.digest(),
+            post_image_id: self.post_state.digest(),
+            exit_code: self.exit_code,
+            journal_digest: self.journal.as_ref().map(|journal| journal.digest()),
+            claim_digest: self.get_claim()?.digest(),
+        })
+    }
+
+    /// Returns the [SuspendedState] needed to resume this paused [Session].
+    ///
+    /// Fails unless the [Session] ended with [ExitCode::Paused].
+    pub fn suspend(&self) -> Result<SuspendedState> {
+        ensure!(
+            matches!(self.exit_code, ExitCode::Paused(_)),
+            "Session with exit code {:?} cannot be resumed",
+            self.exit_code
+        );
+        Ok(SuspendedState {
+            image: self.post_image.clone(),
+            exit_code: self.exit_code,
+            read_offsets: self.read_offsets.clone(),
+            clock: self.clock,
+        })
+    }
+
+    /// Returns a [SessionRecord] of everything the host provided to the guest
+    /// during this [Session].
+    ///
+    /// The record can be passed to [crate::ExecutorEnvBuilder::replay] to
+    /// re-execute the guest exactly as it ran in this [Session]. This fails
+    /// unless recording was enabled with [crate::ExecutorEnvBuilder::record].
+    pub fn record(&self) -> Result<SessionRecord> {
+        let syscalls = self.syscalls.clone().context(
+            "Syscalls were not recorded; enable recording with ExecutorEnvBuilder::record",
+        )?;
+        Ok(SessionRecord {
+            pre_state: self.pre_state.digest(),
+            input: self.input,
+            syscalls,
+            exit_code: self.exit_code,
+            journal: self.journal.as_ref().map(|journal| journal.bytes.clone()),
+            post_state: self.post_state.digest(),
+        })
+    }
+
     /// Log cycle information for this [Session].
     ///
     /// This logs the total and user cycles for this [Session] at the INFO level.
@@ -224,6 +393,10 @@ impl Session {
         tracing::info!("total cycles: {}", self.total_cycles);
         tracing::info!("user cycles: {}", self.user_cycles);
         tracing::info!("cycle efficiency: {}%", cycle_efficiency as u32);
+        if let Some(heap_stats) = &self.heap_stats {
+            tracing::info!("peak heap: {} bytes", heap_stats.peak_heap);
+        }
+        tracing::info!("guest pages touched: {}", self.guest_pages);
     }
 
     /// Returns stats for the session
@@ -234,6 +407,9 @@ impl Session {
             segments: self.segments.len(),
             total_cycles: self.total_cycles,
             user_cycles: self.user_cycles,
+            heap: self.heap_stats.clone(),
+            guest_pages: Some(self.guest_pages),
+            insns: self.insn_stats.clone(),
         }
     }
 }
@@ -275,6 +451,25 @@ impl SimpleSegmentRef {
     }
 }
 
+/// Number of segments between full segment files written by [SegmentWriter].
+///
+/// Segments in between store their partial image as a delta against the
+/// previous segment, so resolving a segment reads at most this many files.
+const SEGMENT_KEYFRAME_INTERVAL: u32 = 8;
+
+/// The contents of a segment file.
+///
+/// Generic so that segments can be written by reference.
+#[derive(Serialize, Deserialize)]
+enum SegmentFile<S = Segment, D = ImageDelta> {
+    /// A segment including its full partial image.
+    Full(S),
+
+    /// A segment whose partial image is stored as a delta against the partial
+    /// image of the segment at index `base`, stored in the same directory.
+    Delta { base: u32, delta: D, segment: S },
+}
+
 /// A basic implementation of a [SegmentRef] that saves the segment to a file
 ///
 /// The [Segment] is stored in a user-specified file in this implementation,
@@ -285,14 +480,14 @@ impl SimpleSegmentRef {
 /// [1]: https://github.com/risc0/risc0/blob/main/examples/zkevm-demo/src/main.rs
 pub struct FileSegmentRef {
     path: PathBuf,
-    _dir: SegmentPath,
+    dir: SegmentPath,
+    #[cfg(feature = "encryption")]
+    cipher: Option<crate::EnvelopeCipher>,
 }
 
 impl SegmentRef for FileSegmentRef {
     fn resolve(&self) -> Result<Segment> {
-        let contents = fs::read(&self.path)?;
-        let segment = bincode::deserialize(&contents)?;
-        Ok(segment)
+        self.load(&self.path)
     }
 }
 
@@ -310,11 +505,132 @@ impl FileSegmentRef {
     ///
     /// This builds a FileSegmentRef that stores `segment` in a file at `path`.
     pub fn new(segment: &Segment, dir: &SegmentPath) -> Result<Self> {
-        let path = dir.path().join(format!("{}.bincode", segment.index));
-        fs::write(&path, bincode::serialize(&segment)?)?;
-        Ok(Self {
-            path,
-            _dir: dir.clone(),
-        })
+        Self::create(segment.index, dir).store(SegmentFile::Full(segment))
+    }
+
+    /// Construct a [FileSegmentRef] for a segment that is already stored in
+    /// `dir`, e.g. by an execution that was restored from a checkpoint.
+    pub(crate) fn existing(index: u32, dir: &SegmentPath) -> Result<Self> {
+        let segment = Self::create(index, dir);
+        ensure!(
+            segment.path.is_file(),
+            "Segment not found: {}",
+            segment.path.display()
+        );
+        Ok(segment)
+    }
+
+    #[cfg(feature = "encryption")]
+    pub(crate) fn with_cipher(mut self, cipher: Option<crate::EnvelopeCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Construct a [FileSegmentRef] that stores `segment` encrypted with
+    /// `cipher`.
+    #[cfg(feature = "encryption")]
+    pub fn new_encrypted(
+        segment: &Segment,
+        dir: &SegmentPath,
+        cipher: crate::EnvelopeCipher,
+    ) -> Result<Self> {
+        Self::create(segment.index, dir)
+            .with_cipher(Some(cipher))
+            .store(SegmentFile::Full(segment))
+    }
+
+    fn create(index: u32, dir: &SegmentPath) -> Self {
+        Self {
+            path: dir.path().join(format!("{index}.bincode")),
+            dir: dir.clone(),
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    fn store(self, file: SegmentFile<&Segment, &ImageDelta>) -> Result<Self> {
+        let contents = bincode::serialize(&file)?;
+        #[cfg(feature = "encryption")]
+        let contents = match &self.cipher {
+            Some(cipher) => cipher.seal(&contents)?,
+            None => contents,
+        };
+        fs::write(&self.path, contents)?;
+        Ok(self)
+    }
+
+    fn load(&self, path: &Path) -> Result<Segment> {
+        let contents = fs::read(path)?;
+        #[cfg(feature = "encryption")]
+        let contents = match &self.cipher {
+            Some(cipher) => cipher.open(&contents)?,
+            None => contents,
+        };
+        match bincode::deserialize(&contents)? {
+            SegmentFile::Full(segment) => Ok(segment),
+            SegmentFile::Delta {
+                base,
+                delta,
+                mut segment,
+            } => {
+                let base = Self::create(base, &self.dir).path;
+                let mut image = self.load(&base)?.inner.partial_image;
+                image.apply_delta(delta)?;
+                segment.inner.partial_image = image;
+                Ok(segment)
+            }
+        }
+    }
+}
+
+/// Writes the segments of an execution to [FileSegmentRef]s.
+///
+/// The partial image of each segment is stored as a delta against the previous
+/// segment, with a full segment every [SEGMENT_KEYFRAME_INTERVAL] segments.
+pub(crate) struct SegmentWriter {
+    dir: SegmentPath,
+    last: Option<(u32, MemoryImage)>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<crate::EnvelopeCipher>,
+}
+
+impl SegmentWriter {
+    pub(crate) fn new(dir: SegmentPath) -> Self {
+        Self {
+            dir,
+            last: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    pub(crate) fn set_cipher(&mut self, cipher: Option<crate::EnvelopeCipher>) {
+        self.cipher = cipher;
+    }
+
+    pub(crate) fn write(&mut self, segment: Segment) -> Result<FileSegmentRef> {
+        let index = segment.index;
+        let image = segment.inner.partial_image.clone();
+        let segment_ref = FileSegmentRef::create(index, &self.dir);
+        #[cfg(feature = "encryption")]
+        let segment_ref = segment_ref.with_cipher(self.cipher.clone());
+        let segment_ref = match self.last.take() {
+            Some((base, base_image))
+                if base + 1 == index && index % SEGMENT_KEYFRAME_INTERVAL != 0 =>
+            {
+                let delta = base_image.diff(&image);
+                let mut segment = segment;
+                segment.inner.partial_image.pages.clear();
+                segment_ref.store(SegmentFile::Delta {
+                    base,
+                    delta: &delta,
+                    segment: &segment,
+                })?
+            }
+            _ => segment_ref.store(SegmentFile::Full(&segment))?,
+        };
+        self.last = Some((index, image));
+        Ok(segment_ref)
     }
 }
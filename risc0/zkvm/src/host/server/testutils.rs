@@ -15,7 +15,9 @@
 use core::mem;
 
 use crypto_bigint::{
-    rand_core::CryptoRngCore, CheckedMul, Encoding, NonZero, Random, RandomMod, U256, U512,
+    modular::runtime_mod::{DynResidue, DynResidueParams},
+    rand_core::CryptoRngCore,
+    CheckedMul, Encoding, NonZero, Random, RandomMod, U256, U512,
 };
 use risc0_zkvm_platform::syscall::bigint;
 
@@ -63,11 +65,32 @@ impl BigIntTestCase {
         bigint_to_arr(&z)
     }
 
+    /// Expected results of addmod, submod, and modexp, as committed by
+    /// `MultiTestSpec::BigIntOps`. The modulus must be odd.
+    ///
+    /// These are computed with crypto-bigint rather than by repeating the
+    /// guest's square-and-multiply, so that the two are checked independently.
+    pub fn expected_ops(&self) -> [[u32; bigint::WIDTH_WORDS]; 3] {
+        let n = NonZero::new(arr_to_bigint(self.modulus)).unwrap();
+        let x = arr_to_bigint(self.x).rem(&n);
+        let y = arr_to_bigint(self.y).rem(&n);
+        let exp = arr_to_bigint(self.y);
+
+        let params = DynResidueParams::new(n.as_ref());
+        let pow = DynResidue::new(&x, params).pow(&exp).retrieve();
+
+        [
+            bigint_to_arr(&x.add_mod(&y, &n)),
+            bigint_to_arr(&x.sub_mod(&y, &n)),
+            bigint_to_arr(&pow),
+        ]
+    }
+
     // NOTE: Testing here could be significantly improved by creating a less uniform
     // test case generator. It is likely more important to test inputs of different
     // byte-lengths, with zero and 0xff bytes, and other boundary values than
     // testing values in the middle.
-    fn sample(rng: &mut impl CryptoRngCore) -> BigIntTestCase {
+    pub fn sample(rng: &mut impl CryptoRngCore) -> BigIntTestCase {
         let modulus = NonZero::<U256>::random(rng);
         let mut x = U256::random(rng);
         let mut y = U256::random_mod(rng, &modulus);
@@ -50,9 +50,15 @@
 //!
 //! | Feature          | Target(s)         | Implies    | Description                                                                                                                                                  |
 //! | ---------------- | ----------------- | ---------- | ------------------------------------------------------------------------------------------------------------------------------------------------------------ |
+//! | canonical-json   | all               |            | Enables a canonical JSON serializer, following RFC 8785, that gives the same bytes for a JSON payload in the guest and on the host.                          |
 //! | client           | all except rv32im | std        | Enables the client API.                                                                                                                                      |
 //! | cuda             |                   | prove, std | Enables CUDA GPU acceleration for the prover. Requires CUDA toolkit to be installed.                                                                         |
 //! | disable-dev-mode | all except rv32im |            | Disables dev mode so that proving and verifying may not be faked. Used to prevent a misplaced `RISC0_DEV_MODE` from breaking security in production systems. |
+//! | encryption       | all except rv32im | client     | Enables envelope encryption of segments and other data persisted by the host, with a pluggable key provider.                                                 |
+//! | gdb-stub         | all except rv32im | prove, std | Enables a GDB remote serial protocol server for debugging guests in the executor.                                                                            |
+//! | groth16-prove    | all except rv32im | prove, std | Enables producing Groth16 receipts on the host with `ProverServer::compact`. Requires Docker on x86.                                                         |
+//! | heap-stats       | rv32im            |            | Enables a guest allocator which tracks heap usage and reports it to the host.                                                                                |
+//! | json             | all except rv32im | std        | Enables canonical JSON encodings of receipts, with byte fields encoded as hex strings.                                                                       |
 //! | metal            | macos             | prove, std | Enables Metal GPU acceleration for the prover.                                                                                                               |
 //! | prove            | all except rv32im | std        | Enables the prover, incompatible within the zkvm guest.                                                                                                      |
 //! | std              | all               |            | Support for the Rust stdlib.                                                                                                                                 |
@@ -60,8 +66,8 @@
 //! [`cargo risczero` tool]: https://crates.io/crates/cargo-risczero
 //! [dev-docs]: https://dev.risczero.com
 //! [examples]: https://dev.risczero.com/api/zkvm/examples
-//! [receipt]: crate::host::receipt::Receipt
-//! [receipt-verify]: crate::host::receipt::Receipt::verify
+//! [receipt]: crate::Receipt
+//! [receipt-verify]: crate::Receipt::verify
 //! [rust guest workarounds]:
 //!     https://github.com/risc0/risc0/issues?q=is%3Aissue+is%3Aopen+label%3A%22rust+guest+workarounds%22
 //! [YouTube]: https://www.youtube.com/@risczero
@@ -74,9 +80,12 @@ extern crate alloc;
 pub mod guest;
 #[cfg(not(target_os = "zkvm"))]
 mod host;
-mod receipt_claim;
-pub mod serde;
-pub mod sha;
+
+/// Golden-file assertions for journals
+#[cfg(all(not(target_os = "zkvm"), feature = "client"))]
+pub mod golden {
+    pub use super::host::golden::*;
+}
 
 /// Re-exports for recursion
 #[cfg(all(not(target_os = "zkvm"), feature = "prove"))]
@@ -90,8 +99,19 @@ pub use anyhow::Result;
 pub use bytes::Bytes;
 pub use risc0_binfmt::{ExitCode, InvalidExitCodeError, SystemState};
 pub use risc0_zkvm_platform::{align_up, declare_syscall, memory::GUEST_MAX_MEM, PAGE_SIZE};
+#[cfg(feature = "canonical-json")]
+pub use risc0_zkvm_verify::canonical_json;
+#[cfg(feature = "std")]
+pub use risc0_zkvm_verify::is_dev_mode;
+pub use risc0_zkvm_verify::{aggregation, redact, serde, sha};
 
-pub use self::receipt_claim::{Assumptions, MaybePruned, Output, PrunedValueError, ReceiptClaim};
+#[cfg(all(not(target_os = "zkvm"), feature = "encryption"))]
+pub use self::host::client::encryption::{EnvelopeCipher, KeyProvider, StaticKeyProvider};
+#[cfg(all(not(target_os = "zkvm"), feature = "receipt-store-sqlite"))]
+pub use self::host::receipt_store::SqliteReceiptStore;
+pub use self::receipt_claim::{
+    Assumptions, MaybePruned, Output, PrunedValueError, ReceiptClaim, ReceiptClaimPreimage,
+};
 #[cfg(all(not(target_os = "zkvm"), feature = "prove",))]
 pub use {
     self::host::{
@@ -99,14 +119,24 @@ pub use {
         client::prove::local::LocalProver,
         recursion::RECURSION_PO2,
         server::{
-            exec::executor::ExecutorImpl,
-            prove::{get_prover_server, HalPair, ProverServer},
+            exec::{
+                core_dump::{GuestCore, GuestSymbol},
+                executor::ExecutorImpl,
+                guest_panic::{GuestFrame, GuestPanic},
+                memory_audit::{MemoryAuditReport, ProtectedAccess, ProtectedRegion},
+                segment_plan::SegmentPlan,
+            },
+            prove::{
+                get_prover_server, ExternalProverServer, HalPair, ProofKind, ProofTimings,
+                ProverServer, ProverTelemetry,
+            },
             session::{
-                FileSegmentRef, Segment, SegmentRef, Session, SessionEvents, SimpleSegmentRef,
+                ExpectedOutputs, FileSegmentRef, Segment, SegmentRef, Session, SessionEvents,
+                SimpleSegmentRef, SubSession, SuspendedState,
             },
         },
     },
-    risc0_circuit_rv32im::prove::engine::loader::Loader,
+    risc0_circuit_rv32im::prove::{emu::exec::MemoryLimitExceeded, engine::loader::Loader},
     risc0_groth16::{
         docker::stark_to_snark, to_json as seal_to_json, ProofJson as Groth16ProofJson,
     },
@@ -118,30 +148,43 @@ pub use {
             client::Client as ApiClient, Asset, AssetRequest, Connector, SegmentInfo, SessionInfo,
         },
         client::{
-            env::{ExecutorEnv, ExecutorEnvBuilder},
+            env::{ClockPolicy, ExecutorEnv, ExecutorEnvBuilder, TraceFormat},
+            manifest::{BlobRef, EnvManifest, MANIFEST_VERSION},
+            observer::{ExitEvent, SegmentStart, SessionObserver, SplitEvent, SyscallEvent},
             prove::{
-                bonsai::BonsaiProver, default_executor, default_prover, external::ExternalProver,
+                bonsai::{BonsaiProver, BonsaiReceiptVerifier},
+                default_executor, default_prover,
+                external::ExternalProver,
                 Executor, Prover, ProverOpts,
             },
+            replay::{RecordedSyscall, SessionRecord},
         },
+        meter::{
+            HmacSigner, LinearPricing, Meter, PricingPolicy, Quote, QuoteSigner, SignedQuote, Usage,
+        },
+        receipt_store::{MemoryReceiptStore, ReceiptKeys, ReceiptStore},
+    },
+    risc0_circuit_rv32im::trace::{
+        FilteredTrace, TraceCallback, TraceEvent, TraceEventKind, TraceFilter,
     },
-    risc0_circuit_rv32im::trace::{TraceCallback, TraceEvent},
 };
 #[cfg(not(target_os = "zkvm"))]
 pub use {
     self::host::{
-        prove_info::{ProveInfo, SessionStats},
+        prove_info::{HeapStats, Hotspot, InsnStats, ProveInfo, SessionStats},
         receipt::{
-            Assumption, CompactReceipt, CompositeReceipt, InnerReceipt, Journal, Receipt,
-            SegmentReceipt, SuccinctReceipt, VerifierContext,
+            Assumption, CompactReceipt, CompositeReceipt, InnerReceipt, Journal, OnchainArgs,
+            Receipt, SegmentReceipt, SuccinctReceipt, VerifierContext, ONCHAIN_VERIFY_SELECTOR,
         },
         recursion::ALLOWED_IDS_ROOT,
     },
     risc0_binfmt::compute_image_id,
     risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID,
     risc0_groth16::Seal as Groth16Seal,
+    risc0_zkvm_verify::{ImageIdPolicy, SealError},
 };
 
+use risc0_zkvm_verify::receipt_claim;
 use semver::Version;
 
 /// Reports the current version of this crate.
@@ -152,20 +195,3 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn get_version() -> Result<Version, semver::Error> {
     Version::parse(VERSION)
 }
-
-/// Returns `true` if dev mode is enabled.
-#[cfg(feature = "std")]
-pub fn is_dev_mode() -> bool {
-    let is_env_set = std::env::var("RISC0_DEV_MODE")
-        .ok()
-        .map(|x| x.to_lowercase())
-        .filter(|x| x == "1" || x == "true" || x == "yes")
-        .is_some();
-
-    if cfg!(feature = "disable-dev-mode") && is_env_set {
-        panic!("zkVM: Inconsistent settings -- please resolve. \
-            The RISC0_DEV_MODE environment variable is set but dev mode has been disabled by feature flag.");
-    }
-
-    cfg!(not(feature = "disable-dev-mode")) && is_env_set
-}
@@ -0,0 +1,66 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The journal of a guest that aggregates the receipts of other guests.
+//!
+//! Aggregation proves many unrelated executions, possibly of different guests,
+//! with a single receipt. An aggregation guest verifies each receipt with
+//! composition and commits an [Aggregation] listing the image ID and journal
+//! digest of every execution it verified. Once the aggregation receipt is
+//! compressed, all the assumptions are resolved and it is a single succinct
+//! receipt that can be verified on-chain.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sha::{Digest, Impl, Sha256};
+
+/// An execution verified by an aggregation guest.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AggregatedClaim {
+    /// Image ID of the guest.
+    pub image_id: Digest,
+    /// Digest of the journal committed by the guest.
+    pub journal_digest: Digest,
+}
+
+impl AggregatedClaim {
+    /// Construct the claim for an execution of `image_id` with `journal`.
+    pub fn new(image_id: impl Into<Digest>, journal: &[u8]) -> Self {
+        Self {
+            image_id: image_id.into(),
+            journal_digest: *Impl::hash_bytes(journal),
+        }
+    }
+}
+
+/// The journal of an aggregation guest.
+///
+/// After verifying the aggregation receipt against the image ID of the
+/// aggregation guest, decode its journal to check which executions it covers.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Aggregation {
+    /// The executions verified by the aggregation guest, in order.
+    pub claims: Vec<AggregatedClaim>,
+}
+
+impl Aggregation {
+    /// Returns true if the aggregation covers an execution of `image_id` with
+    /// `journal`.
+    pub fn contains(&self, image_id: impl Into<Digest>, journal: &[u8]) -> bool {
+        self.claims
+            .contains(&AggregatedClaim::new(image_id, journal))
+    }
+}
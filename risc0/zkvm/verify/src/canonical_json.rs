@@ -0,0 +1,312 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical JSON serialization for commitments over JSON payloads.
+//!
+//! Hashing JSON text directly is fragile: the same document can be written
+//! with different key orders, whitespace, number formats and string escapes,
+//! so a digest computed in the guest often fails to match one computed on the
+//! host. The functions in this module write JSON following the JSON
+//! Canonicalization Scheme ([RFC 8785]), which gives every document a single
+//! byte representation. They are available in both the guest and the host.
+//!
+//! ```rust
+//! use risc0_zkvm_verify::canonical_json;
+//!
+//! let a = canonical_json::canonicalize(r#"{ "b": [1.0, 2], "a": "x" }"#).unwrap();
+//! let b = canonical_json::canonicalize(r#"{"a":"x","b":[1,2.00]}"#).unwrap();
+//! assert_eq!(a, r#"{"a":"x","b":[1,2]}"#);
+//! assert_eq!(a, b);
+//! ```
+//!
+//! [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter, Write};
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use crate::sha::{Digest, Impl, Sha256};
+
+/// The largest integer magnitude that can be represented exactly by an IEEE
+/// 754 double, which is the number model used by canonical JSON.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Errors produced while writing canonical JSON.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The input could not be parsed or serialized as JSON.
+    Json(String),
+    /// A number was NaN or infinite.
+    NonFiniteNumber,
+    /// An integer was outside the range that a double can represent exactly.
+    UnsafeInteger(String),
+}
+
+/// A Result type for `risc0_zkvm_verify::canonical_json` operations that can
+/// fail.
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        match self {
+            Self::Json(msg) => write!(formatter, "Invalid JSON: {msg}"),
+            Self::NonFiniteNumber => formatter.write_str("Found a NaN or infinite number"),
+            Self::UnsafeInteger(value) => {
+                write!(formatter, "Integer cannot be represented exactly: {value}")
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err.to_string())
+    }
+}
+
+/// Serialize the given value as canonical JSON.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, &serde_json::to_value(value)?)?;
+    Ok(out)
+}
+
+/// Serialize the given value as canonical JSON, returning the UTF-8 bytes.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    Ok(to_string(value)?.into_bytes())
+}
+
+/// Rewrite the given JSON text in canonical form.
+pub fn canonicalize(json: &str) -> Result<String> {
+    let value: Value = serde_json::from_str(json)?;
+    to_string(&value)
+}
+
+/// Compute the SHA-256 digest of the canonical JSON encoding of the given
+/// value.
+pub fn digest<T: Serialize + ?Sized>(value: &T) -> Result<Digest> {
+    Ok(*Impl::hash_bytes(&to_vec(value)?))
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(true) => out.push_str("true"),
+        Value::Bool(false) => out.push_str("false"),
+        Value::Number(number) => write_number(out, number)?,
+        Value::String(string) => write_string(out, string),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, value)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // Members are sorted by the UTF-16 code units of their keys.
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", ch as u32).unwrap();
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(out: &mut String, number: &Number) -> Result<()> {
+    let value = if let Some(value) = number.as_u64() {
+        if value > MAX_SAFE_INTEGER {
+            return Err(Error::UnsafeInteger(number.to_string()));
+        }
+        value as f64
+    } else if let Some(value) = number.as_i64() {
+        if value.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(Error::UnsafeInteger(number.to_string()));
+        }
+        value as f64
+    } else {
+        number.as_f64().ok_or(Error::NonFiniteNumber)?
+    };
+    write_f64(out, value)
+}
+
+/// Write a double the way ECMAScript's `Number.prototype.toString` does, as
+/// required by RFC 8785.
+fn write_f64(out: &mut String, value: f64) -> Result<()> {
+    if !value.is_finite() {
+        return Err(Error::NonFiniteNumber);
+    }
+    if value == 0.0 {
+        out.push('0');
+        return Ok(());
+    }
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    // The `{:e}` formatter yields the shortest digit string that round-trips,
+    // e.g. `1.25e-7`, which is the same digit string ECMAScript picks.
+    let formatted = format!("{:e}", value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|ch| *ch != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    // With the value written as 0.DIGITS * 10^n.
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        write!(out, "e{}{}", if n > 1 { '+' } else { '-' }, (n - 1).abs()).unwrap();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use serde::Serialize;
+
+    use super::{canonicalize, digest, to_string, write_f64, Error};
+
+    #[test]
+    fn key_order_and_whitespace() {
+        let a =
+            canonicalize("{\n  \"b\": 1,\n  \"a\": [true, null, {\"d\": 2, \"c\": 3}]\n}").unwrap();
+        let b = canonicalize(r#"{"a":[true,null,{"c":3,"d":2}],"b":1}"#).unwrap();
+        assert_eq!(a, r#"{"a":[true,null,{"c":3,"d":2}],"b":1}"#);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn keys_sorted_by_utf16() {
+        // U+1F600 sorts after U+FB01 in UTF-8, but before it in UTF-16.
+        let json = canonicalize("{\"\u{1F600}\":1,\"\u{FB01}\":2,\"a\":3}").unwrap();
+        assert_eq!(json, "{\"a\":3,\"\u{1F600}\":1,\"\u{FB01}\":2}");
+    }
+
+    #[test]
+    fn strings() {
+        let json = to_string("\"\\\u{08}\u{0c}\n\r\t\u{1f}\u{7f}\u{e9}\u{2028}").unwrap();
+        assert_eq!(
+            json,
+            "\"\\\"\\\\\\b\\f\\n\\r\\t\\u001f\u{7f}\u{e9}\u{2028}\""
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let cases = [
+            ("0", "0"),
+            ("-0.0", "0"),
+            ("1.0", "1"),
+            ("-1.5", "-1.5"),
+            ("100", "100"),
+            ("1e2", "100"),
+            ("0.000001", "0.000001"),
+            ("0.0000001", "1e-7"),
+            ("123456789012345680000", "123456789012345680000"),
+            ("1e21", "1e+21"),
+            ("1.5e300", "1.5e+300"),
+            ("333333333.33333329", "333333333.3333333"),
+            ("9007199254740991", "9007199254740991"),
+            ("-9007199254740991", "-9007199254740991"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(canonicalize(input).unwrap(), expected, "{input}");
+        }
+        assert!(matches!(
+            canonicalize("9007199254740993"),
+            Err(Error::UnsafeInteger(_))
+        ));
+        assert_eq!(
+            write_f64(&mut String::new(), f64::NAN),
+            Err(Error::NonFiniteNumber)
+        );
+    }
+
+    #[test]
+    fn serialize_matches_text() {
+        #[derive(Serialize)]
+        struct Payload {
+            name: String,
+            amounts: BTreeMap<String, u32>,
+            ratio: f64,
+        }
+
+        let payload = Payload {
+            name: "alice".into(),
+            amounts: BTreeMap::from([("usd".into(), 5), ("eur".into(), 7)]),
+            ratio: 0.5,
+        };
+        let text = r#"{ "ratio": 0.50, "name": "alice", "amounts": { "usd": 5, "eur": 7 } }"#;
+        assert_eq!(to_string(&payload).unwrap(), canonicalize(text).unwrap());
+        assert_eq!(
+            digest(&payload).unwrap(),
+            digest(&serde_json::from_str::<serde_json::Value>(text).unwrap()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical JSON encodings of receipts.
+//!
+//! The serde implementations of the receipt types are tuned for binary formats
+//! such as bincode, and encode byte strings and digests as arrays of numbers.
+//! The encodings in this module are intended for web verifiers and other
+//! tooling that consume receipts as text: every byte string, seal, and digest
+//! is written as a `0x`-prefixed hex string.
+
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sha::Digest, Assumptions, CompactReceipt, CompositeReceipt, ExitCode, InnerReceipt, Journal,
+    MaybePruned, Output, Receipt, ReceiptClaim, SegmentReceipt, SuccinctReceipt, SystemState,
+};
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>> {
+    let value = value
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("hex string is missing 0x prefix: {value}"))?;
+    Ok(hex::decode(value)?)
+}
+
+fn words_to_hex(words: &[u32]) -> String {
+    to_hex(bytemuck::cast_slice(words))
+}
+
+fn words_from_hex(value: &str) -> Result<Vec<u32>> {
+    let bytes = from_hex(value)?;
+    ensure!(
+        bytes.len() % 4 == 0,
+        "seal length is not a multiple of the word size"
+    );
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+fn digest_from_hex(value: &str) -> Result<Digest> {
+    Ok(Digest::try_from(from_hex(value)?.as_slice())?)
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReceiptJson {
+    inner: InnerReceiptJson,
+    journal: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InnerReceiptJson {
+    Composite {
+        segments: Vec<SegmentReceiptJson>,
+        assumptions: Vec<InnerReceiptJson>,
+        journal_digest: Option<String>,
+    },
+    Succinct(SuccinctReceiptJson),
+    Compact {
+        seal: String,
+        claim: ReceiptClaimJson,
+    },
+    Fake {
+        claim: ReceiptClaimJson,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct SegmentReceiptJson {
+    seal: String,
+    index: u32,
+    hashfn: String,
+    claim: ReceiptClaimJson,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccinctReceiptJson {
+    seal: String,
+    control_id: String,
+    claim: ReceiptClaimJson,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReceiptClaimJson {
+    pre: MaybePrunedJson<SystemStateJson>,
+    post: MaybePrunedJson<SystemStateJson>,
+    exit_code: ExitCode,
+    input: String,
+    output: MaybePrunedJson<Option<OutputJson>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SystemStateJson {
+    pc: u32,
+    merkle_root: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OutputJson {
+    journal: MaybePrunedJson<String>,
+    assumptions: MaybePrunedJson<Vec<MaybePrunedJson<ReceiptClaimJson>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MaybePrunedJson<T> {
+    Value(T),
+    Pruned(String),
+}
+
+impl<T> MaybePrunedJson<T> {
+    fn encode<U>(value: &MaybePruned<U>, f: impl FnOnce(&U) -> T) -> Self
+    where
+        U: Clone + Serialize,
+    {
+        match value {
+            MaybePruned::Value(value) => Self::Value(f(value)),
+            MaybePruned::Pruned(digest) => Self::Pruned(to_hex(digest.as_bytes())),
+        }
+    }
+
+    fn decode<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<MaybePruned<U>>
+    where
+        U: Clone + Serialize,
+    {
+        Ok(match self {
+            Self::Value(value) => MaybePruned::Value(f(value)?),
+            Self::Pruned(digest) => MaybePruned::Pruned(digest_from_hex(&digest)?),
+        })
+    }
+}
+
+impl From<&SystemState> for SystemStateJson {
+    fn from(value: &SystemState) -> Self {
+        Self {
+            pc: value.pc,
+            merkle_root: to_hex(value.merkle_root.as_bytes()),
+        }
+    }
+}
+
+impl TryFrom<SystemStateJson> for SystemState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SystemStateJson) -> Result<Self> {
+        Ok(Self {
+            pc: value.pc,
+            merkle_root: digest_from_hex(&value.merkle_root)?,
+        })
+    }
+}
+
+impl From<&Output> for OutputJson {
+    fn from(value: &Output) -> Self {
+        Self {
+            journal: MaybePrunedJson::encode(&value.journal, |journal| to_hex(journal)),
+            assumptions: MaybePrunedJson::encode(&value.assumptions, |assumptions| {
+                assumptions
+                    .0
+                    .iter()
+                    .map(|claim| MaybePrunedJson::encode(claim, ReceiptClaimJson::from))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl TryFrom<OutputJson> for Output {
+    type Error = anyhow::Error;
+
+    fn try_from(value: OutputJson) -> Result<Self> {
+        Ok(Self {
+            journal: value.journal.decode(|journal| from_hex(&journal))?,
+            assumptions: value.assumptions.decode(|assumptions| {
+                Ok(Assumptions(
+                    assumptions
+                        .into_iter()
+                        .map(|claim| claim.decode(ReceiptClaim::try_from))
+                        .collect::<Result<_>>()?,
+                ))
+            })?,
+        })
+    }
+}
+
+impl From<&ReceiptClaim> for ReceiptClaimJson {
+    fn from(value: &ReceiptClaim) -> Self {
+        Self {
+            pre: MaybePrunedJson::encode(&value.pre, SystemStateJson::from),
+            post: MaybePrunedJson::encode(&value.post, SystemStateJson::from),
+            exit_code: value.exit_code,
+            input: to_hex(value.input.as_bytes()),
+            output: MaybePrunedJson::encode(&value.output, |output| {
+                output.as_ref().map(OutputJson::from)
+            }),
+        }
+    }
+}
+
+impl TryFrom<ReceiptClaimJson> for ReceiptClaim {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ReceiptClaimJson) -> Result<Self> {
+        Ok(Self {
+            pre: value.pre.decode(SystemState::try_from)?,
+            post: value.post.decode(SystemState::try_from)?,
+            exit_code: value.exit_code,
+            input: digest_from_hex(&value.input)?,
+            output: value
+                .output
+                .decode(|output| output.map(Output::try_from).transpose())?,
+        })
+    }
+}
+
+impl From<&SegmentReceipt> for SegmentReceiptJson {
+    fn from(value: &SegmentReceipt) -> Self {
+        Self {
+            seal: words_to_hex(&value.seal),
+            index: value.index,
+            hashfn: value.hashfn.clone(),
+            claim: (&value.claim).into(),
+        }
+    }
+}
+
+impl TryFrom<SegmentReceiptJson> for SegmentReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SegmentReceiptJson) -> Result<Self> {
+        Ok(Self {
+            seal: words_from_hex(&value.seal)?,
+            index: value.index,
+            hashfn: value.hashfn,
+            claim: value.claim.try_into()?,
+        })
+    }
+}
+
+impl From<&SuccinctReceipt> for SuccinctReceiptJson {
+    fn from(value: &SuccinctReceipt) -> Self {
+        Self {
+            seal: words_to_hex(&value.seal),
+            control_id: to_hex(value.control_id.as_bytes()),
+            claim: (&value.claim).into(),
+        }
+    }
+}
+
+impl TryFrom<SuccinctReceiptJson> for SuccinctReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SuccinctReceiptJson) -> Result<Self> {
+        Ok(Self {
+            seal: words_from_hex(&value.seal)?,
+            control_id: digest_from_hex(&value.control_id)?,
+            claim: value.claim.try_into()?,
+        })
+    }
+}
+
+impl From<&InnerReceipt> for InnerReceiptJson {
+    fn from(value: &InnerReceipt) -> Self {
+        match value {
+            InnerReceipt::Composite(inner) => Self::Composite {
+                segments: inner.segments.iter().map(Into::into).collect(),
+                assumptions: inner.assumptions.iter().map(Into::into).collect(),
+                journal_digest: inner.journal_digest.map(|digest| to_hex(digest.as_bytes())),
+            },
+            InnerReceipt::Succinct(inner) => Self::Succinct(inner.into()),
+            InnerReceipt::Compact(inner) => Self::Compact {
+                seal: to_hex(&inner.seal),
+                claim: (&inner.claim).into(),
+            },
+            InnerReceipt::Fake { claim } => Self::Fake {
+                claim: claim.into(),
+            },
+        }
+    }
+}
+
+impl TryFrom<InnerReceiptJson> for InnerReceipt {
+    type Error = anyhow::Error;
+
+    fn try_from(value: InnerReceiptJson) -> Result<Self> {
+        Ok(match value {
+            InnerReceiptJson::Composite {
+                segments,
+                assumptions,
+                journal_digest,
+            } => InnerReceipt::Composite(CompositeReceipt {
+                segments: segments
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_>>()?,
+                assumptions: assumptions
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_>>()?,
+                journal_digest: journal_digest
+                    .map(|digest| digest_from_hex(&digest))
+                    .transpose()?,
+            }),
+            InnerReceiptJson::Succinct(inner) => InnerReceipt::Succinct(inner.try_into()?),
+            InnerReceiptJson::Compact { seal, claim } => InnerReceipt::Compact(CompactReceipt {
+                seal: from_hex(&seal)?,
+                claim: claim.try_into()?,
+            }),
+            InnerReceiptJson::Fake { claim } => InnerReceipt::Fake {
+                claim: claim.try_into()?,
+            },
+        })
+    }
+}
+
+impl Receipt {
+    /// Encode this [Receipt] as canonical JSON.
+    ///
+    /// Byte strings, seals, and digests are encoded as `0x`-prefixed hex
+    /// strings. Use [Receipt::from_json] to decode it.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&ReceiptJson {
+            inner: (&self.inner).into(),
+            journal: to_hex(&self.journal.bytes),
+        })?)
+    }
+
+    /// Decode a [Receipt] from the JSON produced by [Receipt::to_json].
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: ReceiptJson = serde_json::from_str(json)?;
+        Ok(Self {
+            inner: value.inner.try_into()?,
+            journal: Journal::new(from_hex(&value.journal)?),
+        })
+    }
+}
+
+impl SegmentReceipt {
+    /// Encode this [SegmentReceipt] as canonical JSON.
+    ///
+    /// See [Receipt::to_json] for details of the encoding.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&SegmentReceiptJson::from(self))?)
+    }
+
+    /// Decode a [SegmentReceipt] from the JSON produced by
+    /// [SegmentReceipt::to_json].
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str::<SegmentReceiptJson>(json)?.try_into()
+    }
+}
+
+impl SuccinctReceipt {
+    /// Encode this [SuccinctReceipt] as canonical JSON.
+    ///
+    /// See [Receipt::to_json] for details of the encoding.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&SuccinctReceiptJson::from(self))?)
+    }
+
+    /// Decode a [SuccinctReceipt] from the JSON produced by
+    /// [SuccinctReceipt::to_json].
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str::<SuccinctReceiptJson>(json)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim() -> ReceiptClaim {
+        ReceiptClaim {
+            pre: SystemState {
+                pc: 0x1000,
+                merkle_root: Digest::from([1u32; 8]),
+            }
+            .into(),
+            post: MaybePruned::Pruned(Digest::from([2u32; 8])),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: vec![0xde, 0xad, 0xbe, 0xef].into(),
+                assumptions: Assumptions(vec![MaybePruned::Pruned(Digest::from([3u32; 8]))]).into(),
+            })
+            .into(),
+        }
+    }
+
+    #[test]
+    fn receipt_round_trip() {
+        let segment = SegmentReceipt {
+            seal: vec![1, 2, 0xffff_ffff],
+            index: 0,
+            hashfn: "poseidon2".to_string(),
+            claim: claim(),
+        };
+        let receipt = Receipt::new(
+            InnerReceipt::Composite(CompositeReceipt {
+                segments: vec![segment],
+                assumptions: vec![InnerReceipt::Fake { claim: claim() }],
+                journal_digest: Some(Digest::from([4u32; 8])),
+            }),
+            vec![0xde, 0xad, 0xbe, 0xef],
+        );
+
+        let json = receipt.to_json().unwrap();
+        assert!(json.contains("\"journal\":\"0xdeadbeef\""));
+        assert!(json.contains("\"seal\":\"0x0100000002000000ffffffff\""));
+
+        assert_eq!(Receipt::from_json(&json).unwrap(), receipt);
+    }
+
+    #[test]
+    fn succinct_round_trip() {
+        let receipt = SuccinctReceipt {
+            seal: vec![7, 8, 9],
+            control_id: Digest::from([5u32; 8]),
+            claim: claim(),
+        };
+        let json = receipt.to_json().unwrap();
+        assert_eq!(SuccinctReceipt::from_json(&json).unwrap(), receipt);
+    }
+
+    #[test]
+    fn reject_unaligned_seal() {
+        let json = SegmentReceipt {
+            seal: vec![1],
+            index: 0,
+            hashfn: "sha-256".to_string(),
+            claim: claim(),
+        }
+        .to_json()
+        .unwrap()
+        .replace("0x01000000", "0x010000");
+        assert!(SegmentReceipt::from_json(&json).is_err());
+    }
+}
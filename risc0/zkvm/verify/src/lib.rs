@@ -0,0 +1,99 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Receipt types and verification for the RISC Zero zkVM.
+//!
+//! This crate contains the verification path of [risc0-zkvm]: the [Receipt]
+//! types, [VerifierContext], and the control IDs accepted by the verifier. It
+//! is `no_std` with `alloc`, making it suitable for embedded and on-chain
+//! verifiers that only need [Receipt::verify]. The circuit crates are linked
+//! for their verifiers only, without the executor or prover. All items are
+//! re-exported by `risc0-zkvm`.
+//!
+//! # Crate Feature Flags
+//!
+//! | Feature          | Target(s)         | Implies | Description                                                                                                                                                  |
+//! | ---------------- | ----------------- | ------- | ------------------------------------------------------------------------------------------------------------------------------------------------------------ |
+//! | canonical-json   | all               |         | Enables a canonical JSON serializer, following RFC 8785, that gives the same bytes for a JSON payload in the guest and on the host.                          |
+//! | disable-dev-mode | all except rv32im |         | Disables dev mode so that proving and verifying may not be faked. Used to prevent a misplaced `RISC0_DEV_MODE` from breaking security in production systems. |
+//! | json             | all except rv32im | std     | Enables canonical JSON encodings of receipts, with byte fields encoded as hex strings.                                                                       |
+//! | std              | all               |         | Support for the Rust stdlib.                                                                                                                                 |
+//! | wasm             | wasm32            |         | Exports receipt verification to JavaScript with `wasm-bindgen`.                                                                                              |
+//!
+//! [risc0-zkvm]: https://docs.rs/risc0-zkvm
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+pub mod aggregation;
+#[cfg(feature = "canonical-json")]
+pub mod canonical_json;
+#[cfg(all(not(target_os = "zkvm"), feature = "json"))]
+mod json;
+#[cfg(not(target_os = "zkvm"))]
+mod policy;
+#[cfg(not(target_os = "zkvm"))]
+pub mod receipt;
+pub mod receipt_claim;
+#[cfg(not(target_os = "zkvm"))]
+pub mod recursion;
+pub mod redact;
+#[cfg(not(target_os = "zkvm"))]
+mod seal_check;
+pub mod serde;
+pub mod sha;
+#[cfg(all(not(target_os = "zkvm"), feature = "wasm"))]
+mod wasm;
+
+pub use risc0_binfmt::{ExitCode, InvalidExitCodeError, SystemState};
+
+pub use self::receipt_claim::{
+    Assumptions, MaybePruned, Output, PrunedValueError, ReceiptClaim, ReceiptClaimPreimage,
+};
+#[cfg(not(target_os = "zkvm"))]
+pub use {
+    self::{
+        policy::ImageIdPolicy,
+        receipt::{
+            Assumption, CompactReceipt, CompositeReceipt, InnerReceipt, Journal, OnchainArgs,
+            Receipt, SegmentReceipt, SuccinctReceipt, VerifierContext, ONCHAIN_VERIFY_SELECTOR,
+        },
+        recursion::{valid_control_ids, ALLOWED_IDS_ROOT},
+        seal_check::SealError,
+    },
+    risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID,
+    risc0_groth16::Seal as Groth16Seal,
+};
+#[cfg(all(not(target_os = "zkvm"), feature = "wasm"))]
+pub use self::wasm::verify_receipt;
+
+/// Returns `true` if dev mode is enabled.
+#[cfg(feature = "std")]
+pub fn is_dev_mode() -> bool {
+    let is_env_set = std::env::var("RISC0_DEV_MODE")
+        .ok()
+        .map(|x| x.to_lowercase())
+        .filter(|x| x == "1" || x == "true" || x == "yes")
+        .is_some();
+
+    if cfg!(feature = "disable-dev-mode") && is_env_set {
+        panic!("zkVM: Inconsistent settings -- please resolve. \
+            The RISC0_DEV_MODE environment variable is set but dev mode has been disabled by feature flag.");
+    }
+
+    cfg!(not(feature = "disable-dev-mode")) && is_env_set
+}
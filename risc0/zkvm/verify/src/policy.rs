@@ -0,0 +1,194 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policies for accepting receipts from several guest programs.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use risc0_binfmt::ExitCode;
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+use serde::{Deserialize, Serialize};
+
+use crate::{receipt::Journal, sha::Digestible, ReceiptClaim};
+
+/// A policy describing the receipts an application accepts, for use with
+/// [Receipt::verify_with_policy](crate::Receipt::verify_with_policy).
+///
+/// Applications that verify receipts from many guest programs can list the
+/// allowed image IDs, each with a name for the guest, in one place. The policy
+/// also constrains the exit code of the guest and the size of the journal.
+///
+/// ```rust
+/// use risc0_zkvm_verify::{ExitCode, ImageIdPolicy};
+/// # use risc0_zkvm_verify::sha::Digest;
+/// # const TRANSFER_ID: [u32; 8] = [1; 8];
+/// # const SWAP_ID: [u32; 8] = [2; 8];
+///
+/// let policy = ImageIdPolicy::new()
+///     .allow("transfer", TRANSFER_ID)
+///     .allow("swap", SWAP_ID)
+///     .max_journal_size(1024)
+///     .require_exit_codes([ExitCode::Halted(0)]);
+/// assert_eq!(policy.name(Digest::from(SWAP_ID)), Some("swap"));
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct ImageIdPolicy {
+    image_ids: BTreeMap<Digest, String>,
+    max_journal_size: Option<usize>,
+    exit_codes: Option<Vec<ExitCode>>,
+}
+
+impl ImageIdPolicy {
+    /// Construct a policy that does not allow any image ID.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow receipts for the guest with the given name and image ID.
+    pub fn allow(mut self, name: impl Into<String>, image_id: impl Into<Digest>) -> Self {
+        self.image_ids.insert(image_id.into(), name.into());
+        self
+    }
+
+    /// Reject receipts with a journal larger than `bytes`.
+    pub fn max_journal_size(mut self, bytes: usize) -> Self {
+        self.max_journal_size = Some(bytes);
+        self
+    }
+
+    /// Only accept receipts where the guest exited with one of the given exit
+    /// codes.
+    ///
+    /// By default, only `Halted(0)` and `Paused(0)` are accepted, as in
+    /// [Receipt::verify](crate::Receipt::verify).
+    pub fn require_exit_codes(mut self, exit_codes: impl IntoIterator<Item = ExitCode>) -> Self {
+        self.exit_codes = Some(exit_codes.into_iter().collect());
+        self
+    }
+
+    /// Returns the name of the guest with the given image ID, if it is allowed.
+    pub fn name(&self, image_id: impl Into<Digest>) -> Option<&str> {
+        self.image_ids.get(&image_id.into()).map(String::as_str)
+    }
+
+    /// Check the given claim and journal against this policy, returning the
+    /// name of the guest that produced them.
+    ///
+    /// This does not verify that the claim is attested to by a seal. Use
+    /// [Receipt::verify_with_policy](crate::Receipt::verify_with_policy) to
+    /// verify a receipt.
+    pub fn check(
+        &self,
+        claim: &ReceiptClaim,
+        journal: &Journal,
+    ) -> Result<&str, VerificationError> {
+        let name = self
+            .name(claim.pre.digest())
+            .ok_or(VerificationError::ImageVerificationError)?;
+
+        let exit_code_allowed = match self.exit_codes {
+            Some(ref exit_codes) => exit_codes.contains(&claim.exit_code),
+            None => claim.exit_code.is_ok(),
+        };
+        if !exit_code_allowed {
+            return Err(VerificationError::UnexpectedExitCode);
+        }
+
+        if let Some(limit) = self.max_journal_size {
+            let size = journal.bytes.len();
+            if size > limit {
+                return Err(VerificationError::JournalTooLarge { size, limit });
+            }
+        }
+
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use risc0_binfmt::ExitCode;
+    use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+    use super::ImageIdPolicy;
+    use crate::{
+        receipt::Journal, sha::Digestible, Assumptions, MaybePruned, Output, ReceiptClaim,
+    };
+
+    fn claim(image_id: Digest, exit_code: ExitCode, journal: &Journal) -> ReceiptClaim {
+        ReceiptClaim {
+            pre: MaybePruned::Pruned(image_id),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code,
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: MaybePruned::Pruned(journal.digest()),
+                assumptions: Assumptions(vec![]).into(),
+            })
+            .into(),
+        }
+    }
+
+    #[test]
+    fn check() {
+        let transfer_id = Digest::from([1u32; 8]);
+        let swap_id = Digest::from([2u32; 8]);
+        let policy = ImageIdPolicy::new()
+            .allow("transfer", transfer_id)
+            .allow("swap", swap_id)
+            .max_journal_size(4);
+
+        let journal = Journal::new(vec![1, 2, 3, 4]);
+        let ok = |id| claim(id, ExitCode::Halted(0), &journal);
+        assert_eq!(policy.check(&ok(transfer_id), &journal), Ok("transfer"));
+        assert_eq!(policy.check(&ok(swap_id), &journal), Ok("swap"));
+        assert_eq!(
+            policy.check(&ok(Digest::ZERO), &journal),
+            Err(VerificationError::ImageVerificationError)
+        );
+        assert_eq!(
+            policy.check(&claim(swap_id, ExitCode::Halted(1), &journal), &journal),
+            Err(VerificationError::UnexpectedExitCode)
+        );
+
+        let large = Journal::new(vec![0; 5]);
+        assert_eq!(
+            policy.check(&claim(swap_id, ExitCode::Halted(0), &large), &large),
+            Err(VerificationError::JournalTooLarge { size: 5, limit: 4 })
+        );
+    }
+
+    #[test]
+    fn check_exit_codes() {
+        let image_id = Digest::from([1u32; 8]);
+        let journal = Journal::new(vec![]);
+        let policy = ImageIdPolicy::new()
+            .allow("guest", image_id)
+            .require_exit_codes([ExitCode::Halted(0), ExitCode::Halted(1)]);
+
+        for (exit_code, expected) in [
+            (ExitCode::Halted(0), Ok("guest")),
+            (ExitCode::Halted(1), Ok("guest")),
+            (
+                ExitCode::Paused(0),
+                Err(VerificationError::UnexpectedExitCode),
+            ),
+        ] {
+            let claim = claim(image_id, exit_code, &journal);
+            assert_eq!(policy.check(&claim, &journal), expected);
+        }
+    }
+}
@@ -42,11 +42,11 @@ use risc0_zkp::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 // Make succinct receipt available through this `receipt` module.
-pub use super::recursion::SuccinctReceipt;
+pub use crate::recursion::SuccinctReceipt;
 use crate::{
     serde::{from_slice, Error},
     sha::{Digestible, Sha256},
-    Assumptions, MaybePruned, Output, ReceiptClaim,
+    Assumptions, ImageIdPolicy, MaybePruned, Output, ReceiptClaim,
 };
 
 /// A receipt attesting to the execution of a guest program.
@@ -67,19 +67,14 @@ use crate::{
 /// # Example
 ///
 /// To create a [Receipt] attesting to the faithful execution of your code, run
-/// one of the `prove` functions from a [crate::Prover].
+/// one of the `prove` functions from a `risc0_zkvm::Prover`.
 ///
-/// ```rust
-/// # #[cfg(feature = "prove")]
+/// ```rust,ignore
 /// use risc0_zkvm::{default_prover, ExecutorEnv};
 /// use risc0_zkvm_methods::FIB_ELF;
 ///
-/// # #[cfg(not(feature = "cuda"))]
-/// # #[cfg(feature = "prove")]
-/// # {
 /// let env = ExecutorEnv::builder().write_slice(&[20]).build().unwrap();
 /// let receipt = default_prover().prove(env, FIB_ELF).unwrap();
-/// # }
 /// ```
 ///
 /// To confirm that a [Receipt] was honestly generated, use [Receipt::verify]
@@ -88,26 +83,20 @@ use crate::{
 /// [risc0_build](https://docs.rs/risc0-build/latest/risc0_build/) for more
 /// information about how ImageIDs are generated.)
 ///
-/// ```rust
+/// ```rust,ignore
 /// use risc0_zkvm::Receipt;
-/// # #[cfg(feature = "prove")]
 /// # use risc0_zkvm::{default_prover, ExecutorEnv};
 /// # use risc0_zkvm_methods::{FIB_ELF, FIB_ID};
 ///
-/// # #[cfg(not(feature = "cuda"))]
-/// # #[cfg(feature = "prove")]
-/// # {
 /// # let env = ExecutorEnv::builder().write_slice(&[20]).build().unwrap();
 /// # let receipt = default_prover().prove(env, FIB_ELF).unwrap().receipt;
 /// receipt.verify(FIB_ID).unwrap();
-/// # }
 /// ```
 ///
 /// The public outputs of the [Receipt] are contained in the [Receipt::journal].
 /// You can use [Journal::decode] to deserialize the journal as typed and
 /// structured data, or access the [Journal::bytes] directly.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Receipt {
     /// The polymorphic [InnerReceipt].
     pub inner: InnerReceipt,
@@ -193,6 +182,37 @@ impl Receipt {
         Ok(())
     }
 
+    /// Verify that this receipt proves an execution of one of the guests
+    /// allowed by the given [ImageIdPolicy], returning the name of that guest.
+    ///
+    /// In addition to the integrity of the receipt, this checks that the image
+    /// ID is allowed, that the exit code and journal size satisfy the policy,
+    /// and that the journal has not been tampered with.
+    pub fn verify_with_policy<'a>(
+        &self,
+        policy: &'a ImageIdPolicy,
+    ) -> Result<&'a str, VerificationError> {
+        self.verify_with_policy_and_context(&VerifierContext::default(), policy)
+    }
+
+    /// Verify that this receipt proves an execution of one of the guests
+    /// allowed by the given [ImageIdPolicy], returning the name of that guest.
+    ///
+    /// In addition to the integrity of the receipt, this checks that the image
+    /// ID is allowed, that the exit code and journal size satisfy the policy,
+    /// and that the journal has not been tampered with.
+    pub fn verify_with_policy_and_context<'a>(
+        &self,
+        ctx: &VerifierContext,
+        policy: &'a ImageIdPolicy,
+    ) -> Result<&'a str, VerificationError> {
+        tracing::debug!("Receipt::verify_with_policy_and_context");
+        // Check the policy first, as it is much cheaper than verifying the seal.
+        let name = policy.check(&self.inner.get_claim()?, &self.journal)?;
+        self.verify_integrity_with_context(ctx)?;
+        Ok(name)
+    }
+
     /// Verify the integrity of this receipt, ensuring the claim and journal
     /// are attested to by the seal.
     ///
@@ -244,6 +264,115 @@ impl Receipt {
     pub fn get_claim(&self) -> Result<ReceiptClaim, VerificationError> {
         self.inner.get_claim()
     }
+
+    /// Encode the seal of this receipt as the `seal` argument of
+    /// `IRiscZeroVerifier.verify`.
+    ///
+    /// The receipt must be a [CompactReceipt]. The remaining arguments of the
+    /// on-chain call are the image ID, the post state digest (available from
+    /// [Receipt::get_claim]), and the digest of the [Receipt::journal].
+    pub fn to_onchain_seal(&self) -> Result<Vec<u8>, VerificationError> {
+        Ok(self.inner.compact()?.seal.clone())
+    }
+
+    /// Returns the SHA-256 digest of the [Receipt::journal], as passed to
+    /// `IRiscZeroVerifier.verify`.
+    pub fn journal_digest(&self) -> Digest {
+        self.journal.digest()
+    }
+
+    /// Collect the arguments of an `IRiscZeroVerifier.verify` call for this
+    /// receipt.
+    ///
+    /// Only a [CompactReceipt] can be verified on-chain. Composite and
+    /// succinct receipts must first be compressed to a Groth16 proof, with
+    /// `ProverServer::compact` from `risc0-zkvm`.
+    pub fn to_onchain_args(&self) -> Result<OnchainArgs, VerificationError> {
+        let claim = self.get_claim()?;
+        Ok(OnchainArgs {
+            seal: self.to_onchain_seal()?,
+            image_id: claim.pre.digest(),
+            post_state_digest: claim.post.digest(),
+            journal_digest: self.journal_digest(),
+        })
+    }
+
+    /// Construct a [Receipt] from the arguments of an `IRiscZeroVerifier.verify`
+    /// call, along with the journal whose digest was passed to the verifier.
+    ///
+    /// This is the inverse of [Receipt::to_onchain_seal]. The on-chain verifier
+    /// only accepts successful executions with no unresolved assumptions, so the
+    /// resulting claim has exit code `Halted(0)` and an empty assumptions list.
+    pub fn from_onchain_parts(
+        seal: Vec<u8>,
+        image_id: impl Into<Digest>,
+        post_state_digest: impl Into<Digest>,
+        journal: Vec<u8>,
+    ) -> Result<Self, VerificationError> {
+        Seal::from_vec(&seal).map_err(|_| VerificationError::ReceiptFormatError)?;
+        let journal = Journal::new(journal);
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(image_id.into()),
+            post: MaybePruned::Pruned(post_state_digest.into()),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: MaybePruned::Pruned(journal.digest()),
+                assumptions: Assumptions(vec![]).into(),
+            })
+            .into(),
+        };
+        Ok(Self {
+            inner: InnerReceipt::Compact(CompactReceipt { seal, claim }),
+            journal,
+        })
+    }
+}
+
+/// The selector of `IRiscZeroVerifier.verify(bytes,bytes32,bytes32,bytes32)`.
+pub const ONCHAIN_VERIFY_SELECTOR: [u8; 4] = [0x6e, 0xfe, 0xf0, 0x09];
+
+/// The arguments of an `IRiscZeroVerifier.verify` call, from
+/// [Receipt::to_onchain_args].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnchainArgs {
+    /// The Groth16 seal.
+    pub seal: Vec<u8>,
+
+    /// The image ID of the guest program.
+    pub image_id: Digest,
+
+    /// The digest of the [SystemState] after execution.
+    pub post_state_digest: Digest,
+
+    /// The SHA-256 digest of the journal.
+    pub journal_digest: Digest,
+}
+
+impl OnchainArgs {
+    /// ABI-encode a call to `IRiscZeroVerifier.verify` with these arguments,
+    /// including the function selector.
+    pub fn to_calldata(&self) -> Vec<u8> {
+        fn abi_word(value: usize) -> [u8; 32] {
+            let mut word = [0u8; 32];
+            word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+            word
+        }
+
+        // The seal is dynamic, so the head holds its offset and the tail holds
+        // its length followed by the bytes, padded to a multiple of 32.
+        let len = 4 + 32 * 5 + self.seal.len().next_multiple_of(32);
+        let mut calldata = Vec::with_capacity(len);
+        calldata.extend_from_slice(&ONCHAIN_VERIFY_SELECTOR);
+        calldata.extend_from_slice(&abi_word(32 * 4));
+        calldata.extend_from_slice(self.image_id.as_bytes());
+        calldata.extend_from_slice(self.post_state_digest.as_bytes());
+        calldata.extend_from_slice(self.journal_digest.as_bytes());
+        calldata.extend_from_slice(&abi_word(self.seal.len()));
+        calldata.extend_from_slice(&self.seal);
+        calldata.resize(len, 0);
+        calldata
+    }
 }
 
 /// A journal is a record of all public commitments for a given proof session.
@@ -279,8 +408,7 @@ impl AsRef<[u8]> for Journal {
 
 /// An inner receipt can take the form of a [CompositeReceipt] or a
 /// [SuccinctReceipt].
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum InnerReceipt {
     /// A non-succinct [CompositeReceipt].
     Composite(CompositeReceipt),
@@ -368,8 +496,7 @@ impl InnerReceipt {
 }
 
 /// A receipt composed of a Groth16 over the BN_254 curve
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct CompactReceipt {
     /// A Groth16 proof of a zkVM execution with the associated claim.
     pub seal: Vec<u8>,
@@ -410,8 +537,7 @@ impl CompactReceipt {
 /// A receipt composed of one or more [SegmentReceipt] structs proving a single
 /// execution with continuations, and zero or more [Receipt] structs proving any
 /// assumptions.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct CompositeReceipt {
     /// Segment receipts forming the proof of an execution with continuations.
     pub segments: Vec<SegmentReceipt>,
@@ -610,8 +736,7 @@ impl CompositeReceipt {
 ///
 /// A SegmentReceipt attests that a Segment was executed in a manner
 /// consistent with the [ReceiptClaim] included in the receipt.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct SegmentReceipt {
     /// The cryptographic data attesting to the validity of the code execution.
     ///
@@ -707,16 +832,6 @@ impl Assumption {
             Self::Unresolved(claim) => Ok(claim.clone()),
         }
     }
-
-    #[cfg(feature = "prove")]
-    pub(crate) fn as_receipt(&self) -> Result<&Receipt> {
-        match self {
-            Self::Proven(receipt) => Ok(receipt),
-            Self::Unresolved(_) => Err(anyhow::anyhow!(
-                "no receipt available for unresolved assumption"
-            )),
-        }
-    }
 }
 
 impl From<Receipt> for Assumption {
@@ -762,9 +877,11 @@ fn decode_system_state_from_io(
     Ok(SystemState { pc, merkle_root })
 }
 
-pub(crate) fn decode_receipt_claim_from_seal(
-    seal: &[u32],
-) -> Result<ReceiptClaim, VerificationError> {
+/// Decode the [ReceiptClaim] from the globals encoded on an rv32im segment seal.
+///
+/// The seal itself is not verified; use [SegmentReceipt::verify_integrity_with_context] to check
+/// that the decoded claim is attested to by the seal.
+pub fn decode_receipt_claim_from_seal(seal: &[u32]) -> Result<ReceiptClaim, VerificationError> {
     let elems = bytemuck::cast_slice(seal);
     let io = layout::OutBuffer(elems);
     let body = layout::LAYOUT.mux.body;
@@ -808,3 +925,86 @@ impl Default for VerifierContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn onchain_round_trip() {
+        let seal: Vec<u8> = (0..=255).collect();
+        let image_id = Digest::from([1u32; 8]);
+        let post_state_digest = Digest::from([2u32; 8]);
+        let journal = b"journal".to_vec();
+
+        let receipt =
+            Receipt::from_onchain_parts(seal.clone(), image_id, post_state_digest, journal.clone())
+                .unwrap();
+        assert_eq!(receipt.to_onchain_seal().unwrap(), seal);
+        assert_eq!(receipt.journal.bytes, journal);
+
+        let claim = receipt.get_claim().unwrap();
+        assert_eq!(claim.pre.digest(), image_id);
+        assert_eq!(claim.post.digest(), post_state_digest);
+        assert_eq!(claim.exit_code, ExitCode::Halted(0));
+        assert_eq!(
+            claim.output.digest(),
+            Output {
+                journal: MaybePruned::Pruned(Journal::new(journal).digest()),
+                assumptions: Assumptions(vec![]).into(),
+            }
+            .digest()
+        );
+    }
+
+    #[test]
+    fn onchain_rejects_malformed() {
+        assert!(
+            Receipt::from_onchain_parts(vec![0; 64], Digest::ZERO, Digest::ZERO, vec![]).is_err()
+        );
+
+        let receipt = Receipt::new(
+            InnerReceipt::Fake {
+                claim: Receipt::from_onchain_parts(
+                    vec![0; 256],
+                    Digest::ZERO,
+                    Digest::ZERO,
+                    vec![],
+                )
+                .unwrap()
+                .get_claim()
+                .unwrap(),
+            },
+            vec![],
+        );
+        assert!(receipt.to_onchain_seal().is_err());
+        assert!(receipt.to_onchain_args().is_err());
+    }
+
+    #[test]
+    fn onchain_calldata() {
+        let seal: Vec<u8> = (0..=255).collect();
+        let receipt = Receipt::from_onchain_parts(
+            seal.clone(),
+            Digest::from([1u32; 8]),
+            Digest::from([2u32; 8]),
+            b"journal".to_vec(),
+        )
+        .unwrap();
+
+        let args = receipt.to_onchain_args().unwrap();
+        assert_eq!(args.seal, seal);
+        assert_eq!(args.image_id, Digest::from([1u32; 8]));
+        assert_eq!(args.post_state_digest, Digest::from([2u32; 8]));
+        assert_eq!(args.journal_digest, receipt.journal_digest());
+
+        let calldata = args.to_calldata();
+        assert_eq!(calldata.len(), 4 + 32 * 5 + 256);
+        assert_eq!(calldata[..4], ONCHAIN_VERIFY_SELECTOR);
+        assert_eq!(calldata[4 + 31], 0x80);
+        assert_eq!(&calldata[36..68], args.image_id.as_bytes());
+        assert_eq!(&calldata[100..132], args.journal_digest.as_bytes());
+        assert_eq!(calldata[132 + 30..132 + 32], [0x01, 0x00]);
+        assert_eq!(&calldata[164..], seal.as_slice());
+    }
+}
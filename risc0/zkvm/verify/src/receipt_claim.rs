@@ -44,8 +44,7 @@ use crate::{
 /// state (i.e. the state of memory). [ReceiptClaim] is a "Merkle-ized struct" supporting
 /// partial openings of the underlying fields from a hash commitment to the full structure. Also
 /// see [MaybePruned].
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ReceiptClaim {
     /// The [SystemState] just before execution has begun.
     pub pre: MaybePruned<SystemState>,
@@ -98,6 +97,88 @@ impl ReceiptClaim {
         write_sha_halfs(flat, &self.output.digest::<sha::Impl>());
         Ok(())
     }
+
+    /// Return the preimage hashed by [ReceiptClaim::digest], split into its fields.
+    ///
+    /// This allows a verifier that only has the hash function, such as a smart contract, to
+    /// reconstruct the claim digest from the image ID, journal digest and exit code instead of
+    /// trusting a digest assembled off-chain.
+    pub fn preimage<S: Sha256>(&self) -> ReceiptClaimPreimage {
+        let (sys_exit, user_exit) = self.exit_code.into_pair();
+        ReceiptClaimPreimage {
+            tag_digest: *S::hash_bytes(ReceiptClaimPreimage::TAG.as_bytes()),
+            input: self.input,
+            pre: self.pre.digest::<S>(),
+            post: self.post.digest::<S>(),
+            output: self.output.digest::<S>(),
+            sys_exit,
+            user_exit,
+        }
+    }
+}
+
+/// The fields hashed to produce the digest of a [ReceiptClaim].
+///
+/// The digest is the SHA-256 hash of [ReceiptClaimPreimage::to_bytes], which is the
+/// [tagged_struct] encoding of the claim:
+///
+/// | Bytes    | Field                                             |
+/// |----------|---------------------------------------------------|
+/// | 0..32    | `tag_digest`, i.e. `sha256("risc0.ReceiptClaim")` |
+/// | 32..64   | `input`                                           |
+/// | 64..96   | `pre`                                             |
+/// | 96..128  | `post`                                            |
+/// | 128..160 | `output`                                          |
+/// | 160..164 | `sys_exit` as a little-endian `u32`               |
+/// | 164..168 | `user_exit` as a little-endian `u32`              |
+/// | 168..170 | the digest count, 4, as a little-endian `u16`     |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptClaimPreimage {
+    /// Digest of the struct tag.
+    pub tag_digest: Digest,
+    /// Input to the guest.
+    pub input: Digest,
+    /// Digest of the [SystemState] before execution.
+    pub pre: Digest,
+    /// Digest of the [SystemState] after execution.
+    pub post: Digest,
+    /// Digest of the [Output], or zero if there is no output.
+    pub output: Digest,
+    /// System part of the exit code.
+    pub sys_exit: u32,
+    /// User part of the exit code.
+    pub user_exit: u32,
+}
+
+impl ReceiptClaimPreimage {
+    /// Tag of the [ReceiptClaim] tagged struct.
+    pub const TAG: &'static str = "risc0.ReceiptClaim";
+
+    /// Length in bytes of the encoded preimage.
+    pub const LEN: usize = 5 * sha::DIGEST_BYTES + 2 * 4 + 2;
+
+    /// Encode the preimage into the bytes hashed by [ReceiptClaim::digest].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        for digest in [
+            &self.tag_digest,
+            &self.input,
+            &self.pre,
+            &self.post,
+            &self.output,
+        ] {
+            bytes.extend_from_slice(digest.as_bytes());
+        }
+        bytes.extend_from_slice(&self.sys_exit.to_le_bytes());
+        bytes.extend_from_slice(&self.user_exit.to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes
+    }
+
+    /// Hash the encoded preimage, producing the digest of the [ReceiptClaim].
+    pub fn digest<S: Sha256>(&self) -> Digest {
+        *S::hash_bytes(&self.to_bytes())
+    }
 }
 
 impl Digestible for ReceiptClaim {
@@ -105,7 +186,7 @@ impl Digestible for ReceiptClaim {
     fn digest<S: Sha256>(&self) -> Digest {
         let (sys_exit, user_exit) = self.exit_code.into_pair();
         tagged_struct::<S>(
-            "risc0.ReceiptClaim",
+            ReceiptClaimPreimage::TAG,
             &[
                 self.input,
                 self.pre.digest::<S>(),
@@ -151,8 +232,7 @@ impl From<InvalidExitCodeError> for DecodeError {
 impl std::error::Error for DecodeError {}
 
 /// Output field in the [ReceiptClaim], committing to a claimed journal and assumptions list.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Output {
     /// The journal committed to by the guest execution.
     pub journal: MaybePruned<Vec<u8>>,
@@ -180,8 +260,7 @@ impl Digestible for Output {
 }
 
 /// A list of assumptions, each a [Digest] of a [ReceiptClaim].
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Assumptions(pub Vec<MaybePruned<ReceiptClaim>>);
 
 impl Assumptions {
@@ -382,7 +461,6 @@ where
     }
 }
 
-#[cfg(test)]
 impl<T> PartialEq for MaybePruned<T>
 where
     T: Clone + Serialize + PartialEq,
@@ -432,14 +510,14 @@ impl std::error::Error for PrunedValueError {}
 ///
 /// Viewing the two structs as Merkle trees, in which subtrees may be pruned, the result of this
 /// operation is a tree with a set of nodes equal to the union of the set of nodes for each input.
-pub(crate) trait Merge: Digestible + Sized {
+pub trait Merge: Digestible + Sized {
     /// Merge two structs to produce an output with a union of the fields populated in the inputs.
     fn merge(&self, other: &Self) -> Result<Self, MergeInequalityError>;
 }
 
 /// Error returned when a merge it attempted with two values with unequal digests.
 #[derive(Debug, Clone)]
-pub(crate) struct MergeInequalityError(pub Digest, pub Digest);
+pub struct MergeInequalityError(pub Digest, pub Digest);
 
 impl fmt::Display for MergeInequalityError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -572,8 +650,11 @@ impl Merge for ReceiptClaim {
 mod tests {
     use hex::FromHex;
 
-    use super::{Assumptions, ExitCode, MaybePruned, Merge, Output, ReceiptClaim, SystemState};
-    use crate::sha::{Digest, Digestible};
+    use super::{
+        Assumptions, ExitCode, MaybePruned, Merge, Output, ReceiptClaim, ReceiptClaimPreimage,
+        SystemState,
+    };
+    use crate::sha::{self, Digest, Digestible};
 
     /// Testing utility for randomly pruning structs.
     trait RandPrune {
@@ -685,4 +766,32 @@ mod tests {
             assert_eq!(left.merge(&right).unwrap().digest(), claim.digest());
         }
     }
+
+    #[test]
+    fn receipt_claim_preimage() {
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::from([1, 2, 3, 4, 5, 6, 7, 8])),
+            post: SystemState {
+                pc: 0,
+                merkle_root: Digest::ZERO,
+            }
+            .into(),
+            exit_code: ExitCode::Halted(7),
+            input: Digest::ZERO,
+            output: MaybePruned::Value(Some(Output {
+                journal: MaybePruned::Value(b"hello world".to_vec()),
+                assumptions: MaybePruned::Value(Assumptions(vec![])),
+            })),
+        };
+
+        let preimage = claim.preimage::<sha::Impl>();
+        assert_eq!(preimage.pre, claim.pre.digest());
+        assert_eq!(preimage.output, claim.output.digest());
+        assert_eq!((preimage.sys_exit, preimage.user_exit), (0, 7));
+
+        let bytes = preimage.to_bytes();
+        assert_eq!(bytes.len(), ReceiptClaimPreimage::LEN);
+        assert_eq!(&bytes[160..170], &[0, 0, 0, 0, 7, 0, 0, 0, 4, 0]);
+        assert_eq!(preimage.digest::<sha::Impl>(), claim.digest());
+    }
 }
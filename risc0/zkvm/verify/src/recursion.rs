@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Receipts produced by the recursion circuit and their verification.
+
 use alloc::{collections::VecDeque, vec::Vec};
 
 use risc0_binfmt::read_sha_halfs;
@@ -21,8 +23,11 @@ use risc0_core::field::baby_bear::BabyBearElem;
 use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest, verify::VerificationError};
 use serde::{Deserialize, Serialize};
 
-use super::CIRCUIT;
-use crate::{host::receipt::VerifierContext, sha::Digestible, ReceiptClaim};
+pub use risc0_circuit_recursion::control_id::ALLOWED_IDS_ROOT;
+
+use crate::{receipt::VerifierContext, sha::Digestible, ReceiptClaim};
+
+const CIRCUIT: CircuitImpl = CircuitImpl::new();
 
 /// This function gets valid control IDs from the Poseidon2 and recursion
 /// circuits
@@ -44,8 +49,7 @@ pub fn valid_control_ids() -> Vec<Digest> {
 /// Using recursion, a [crate::CompositeReceipt] can be compressed to form a [SuccinctReceipt]. In this
 /// way, a constant sized proof can be generated for arbitrarily long computations, and with an
 /// arbitrary number of segments linked via composition.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SuccinctReceipt {
     /// The cryptographic seal of this receipt. This seal is a STARK proving an execution of the
     /// recursion circuit.
@@ -0,0 +1,204 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Salted commitments for redacting fields of a journal.
+//!
+//! A guest that proves a statement about sensitive data, such as the date of
+//! birth in an identity document, often needs to bind the journal to that data
+//! without publishing it. The guest commits a [SaltedCommitment] to each
+//! sensitive field alongside the public fields of its journal. Later, the
+//! holder of the data can reveal any one field to a verifier by handing over
+//! its [Salted] opening, which the verifier checks against the commitment in
+//! the journal with [SaltedCommitment::verify]. Fields that are not revealed
+//! stay hidden, as long as each salt is 32 uniformly random bytes that are
+//! never reused.
+//!
+//! ```rust
+//! use risc0_zkvm_verify::redact::{Salted, SaltedCommitment};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Journal {
+//!     over_18: bool,
+//!     name: SaltedCommitment<String>,
+//! }
+//!
+//! // In the guest, with the name and a random salt read from the host.
+//! let name = Salted::new(String::from("Alice"), [7u8; 32]);
+//! let journal = Journal {
+//!     over_18: true,
+//!     name: name.commit(),
+//! };
+//!
+//! // The verifier checks the name revealed to it against the journal.
+//! assert_eq!(journal.name.verify(&name).unwrap(), "Alice");
+//! ```
+
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
+
+use risc0_binfmt::tagged_struct;
+use serde::{Deserialize, Serialize};
+
+use crate::sha::{Digest, Impl, Sha256};
+
+/// Size in bytes of the salt of a [Salted] value.
+pub const SALT_BYTES: usize = 32;
+
+/// A value together with the salt used to commit to it.
+///
+/// This is the opening of a [SaltedCommitment]: revealing it reveals the
+/// value.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Salted<T> {
+    /// The committed value.
+    pub value: T,
+    /// The salt hiding the value in the commitment.
+    pub salt: [u8; SALT_BYTES],
+}
+
+impl<T: Serialize> Salted<T> {
+    /// Construct a salted value.
+    ///
+    /// `salt` must be chosen uniformly at random, and never reused.
+    pub fn new(value: T, salt: [u8; SALT_BYTES]) -> Self {
+        Self { value, salt }
+    }
+
+    /// Commit to the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be encoded with [crate::serde].
+    pub fn commit(&self) -> SaltedCommitment<T> {
+        SaltedCommitment {
+            digest: self.digest(),
+            phantom: PhantomData,
+        }
+    }
+
+    fn digest(&self) -> Digest {
+        let value: Vec<u32> =
+            crate::serde::to_vec(&self.value).expect("failed to encode salted value");
+        tagged_struct::<Impl>(
+            "risc0.SaltedCommitment",
+            &[*Impl::hash_bytes(&self.salt), *Impl::hash_words(&value)],
+            &[],
+        )
+    }
+}
+
+/// A commitment to a value of type `T` that hides the value.
+///
+/// Commitments are encoded as their [Digest], so they can be part of a
+/// journal.
+#[derive(Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct SaltedCommitment<T> {
+    digest: Digest,
+    #[serde(skip)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize> SaltedCommitment<T> {
+    /// Check that `opening` is the value committed to, and return the value.
+    pub fn verify<'a>(&self, opening: &'a Salted<T>) -> Result<&'a T, RevealError> {
+        if opening.digest() != self.digest {
+            return Err(RevealError);
+        }
+        Ok(&opening.value)
+    }
+}
+
+impl<T> SaltedCommitment<T> {
+    /// Construct a commitment from its digest, e.g. as read from a journal.
+    pub fn from_digest(digest: Digest) -> Self {
+        Self {
+            digest,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the digest of this commitment.
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+}
+
+impl<T> Clone for SaltedCommitment<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SaltedCommitment<T> {}
+
+impl<T> PartialEq for SaltedCommitment<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest == other.digest
+    }
+}
+
+impl<T> Eq for SaltedCommitment<T> {}
+
+impl<T> fmt::Debug for SaltedCommitment<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SaltedCommitment")
+            .field(&self.digest)
+            .finish()
+    }
+}
+
+/// Error returned when a revealed value does not match its commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevealError;
+
+impl fmt::Display for RevealError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "revealed value does not match the commitment")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RevealError {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::{RevealError, Salted, SaltedCommitment};
+    use crate::serde::{from_slice, to_vec};
+
+    #[test]
+    fn reveal() {
+        let name = Salted::new(String::from("Alice"), [1; 32]);
+        let commitment = name.commit();
+        assert_eq!(commitment.verify(&name), Ok(&String::from("Alice")));
+
+        let other_value = Salted::new(String::from("Bob"), [1; 32]);
+        assert_eq!(commitment.verify(&other_value), Err(RevealError));
+        let other_salt = Salted::new(String::from("Alice"), [2; 32]);
+        assert_eq!(commitment.verify(&other_salt), Err(RevealError));
+        assert_ne!(other_salt.commit(), commitment);
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let commitment = Salted::new(42u32, [3; 32]).commit();
+        let encoded = to_vec(&commitment).unwrap();
+        assert_eq!(encoded, to_vec(&commitment.digest()).unwrap());
+        let decoded: SaltedCommitment<u32> = from_slice(&encoded).unwrap();
+        assert_eq!(decoded, commitment);
+    }
+}
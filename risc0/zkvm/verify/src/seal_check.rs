@@ -0,0 +1,393 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural checks on receipt seals, run ahead of full verification.
+//!
+//! Verifying a STARK seal is expensive, and a seal that was truncated or
+//! corrupted in transit fails verification the same way a forged one does. The
+//! checks here only look at the shape of a seal: its length, the range of the
+//! field elements in its header, the hash function it names, and the numbering
+//! of the segments in a [CompositeReceipt]. A [SealError] means the receipt
+//! was damaged before it reached the verifier, and fetching it again may help.
+//! A receipt that passes these checks and then fails [Receipt::verify] is
+//! genuinely invalid.
+
+use alloc::{boxed::Box, string::String};
+use core::fmt;
+
+use risc0_core::field::baby_bear::{Elem, P};
+use risc0_zkp::{adapter::CircuitInfo, MAX_CYCLES_PO2, MIN_PO2};
+
+use crate::{
+    receipt::VerifierContext, CompactReceipt, CompositeReceipt, InnerReceipt, Receipt,
+    SegmentReceipt, SuccinctReceipt,
+};
+
+/// Size in bytes of an encoded Groth16 seal.
+const GROTH16_SEAL_SIZE: usize = 256;
+
+/// A structural defect found in a receipt seal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SealError {
+    /// The seal is empty.
+    Empty,
+
+    /// The seal is shorter than its header.
+    TooShort {
+        /// Length of the seal in words.
+        len: usize,
+        /// Minimum length for a seal of this kind.
+        min: usize,
+    },
+
+    /// A word in the seal header is not a valid BabyBear field element.
+    ElemOutOfRange {
+        /// Offset of the word within the seal.
+        offset: usize,
+        /// The word as found in the seal.
+        value: u32,
+    },
+
+    /// The cycle count encoded in the seal is outside the range supported by
+    /// the prover.
+    Po2OutOfRange {
+        /// The log2 of the cycle count encoded in the seal.
+        po2: u32,
+    },
+
+    /// The seal names a hash function that the verifier does not support.
+    UnknownHashFn(String),
+
+    /// A Groth16 seal does not have the expected length.
+    Groth16Length {
+        /// Length of the seal in bytes.
+        len: usize,
+        /// Expected length in bytes.
+        expected: usize,
+    },
+
+    /// A composite receipt has no segments.
+    NoSegments,
+
+    /// The segments of a composite receipt are not numbered consecutively
+    /// from zero.
+    SegmentIndex {
+        /// Position of the segment within the receipt.
+        position: usize,
+        /// Index recorded in the segment receipt.
+        index: u32,
+    },
+
+    /// A segment of a composite receipt has a defective seal.
+    Segment {
+        /// Position of the segment within the receipt.
+        position: usize,
+        /// The defect in the segment seal.
+        source: Box<SealError>,
+    },
+
+    /// An assumption of a composite receipt has a defective seal.
+    Assumption {
+        /// Position of the assumption within the receipt.
+        position: usize,
+        /// The defect in the assumption seal.
+        source: Box<SealError>,
+    },
+}
+
+impl fmt::Display for SealError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SealError::Empty => write!(f, "seal is empty"),
+            SealError::TooShort { len, min } => {
+                write!(f, "seal has {len} words, expected at least {min}")
+            }
+            SealError::ElemOutOfRange { offset, value } => {
+                write!(
+                    f,
+                    "seal word {offset} is not a field element: {value:#010x}"
+                )
+            }
+            SealError::Po2OutOfRange { po2 } => write!(f, "seal po2 {po2} is out of range"),
+            SealError::UnknownHashFn(hashfn) => write!(f, "unknown hash function: {hashfn}"),
+            SealError::Groth16Length { len, expected } => {
+                write!(f, "groth16 seal has {len} bytes, expected {expected}")
+            }
+            SealError::NoSegments => write!(f, "composite receipt has no segments"),
+            SealError::SegmentIndex { position, index } => {
+                write!(f, "segment {position} has index {index}")
+            }
+            SealError::Segment { position, source } => write!(f, "segment {position}: {source}"),
+            SealError::Assumption { position, source } => {
+                write!(f, "assumption {position}: {source}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SealError {}
+
+/// Check the header of a STARK seal: the globals of the circuit followed by
+/// the po2 of the trace, each of which must be a field element.
+fn check_stark_header<C: CircuitInfo>(seal: &[u32]) -> Result<(), SealError> {
+    if seal.is_empty() {
+        return Err(SealError::Empty);
+    }
+    // The header must be followed by at least the code Merkle root.
+    let min = C::OUTPUT_SIZE + 1 + 8;
+    if seal.len() < min {
+        return Err(SealError::TooShort {
+            len: seal.len(),
+            min,
+        });
+    }
+    // Field elements are stored in Montgomery form, which is always below P.
+    for (offset, &value) in seal[..=C::OUTPUT_SIZE].iter().enumerate() {
+        if value >= P {
+            return Err(SealError::ElemOutOfRange { offset, value });
+        }
+    }
+    let po2 = Elem::new_raw(seal[C::OUTPUT_SIZE]).as_u32();
+    if !(MIN_PO2..=MAX_CYCLES_PO2).contains(&(po2 as usize)) {
+        return Err(SealError::Po2OutOfRange { po2 });
+    }
+    Ok(())
+}
+
+impl Receipt {
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal(&self) -> Result<(), SealError> {
+        self.inner.check_seal()
+    }
+
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal_with_context(&self, ctx: &VerifierContext) -> Result<(), SealError> {
+        self.inner.check_seal_with_context(ctx)
+    }
+}
+
+impl InnerReceipt {
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal(&self) -> Result<(), SealError> {
+        self.check_seal_with_context(&VerifierContext::default())
+    }
+
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal_with_context(&self, ctx: &VerifierContext) -> Result<(), SealError> {
+        match self {
+            InnerReceipt::Composite(x) => x.check_seal_with_context(ctx),
+            InnerReceipt::Succinct(x) => x.check_seal(),
+            InnerReceipt::Compact(x) => x.check_seal(),
+            // A fake receipt has no seal to check.
+            InnerReceipt::Fake { .. } => Ok(()),
+        }
+    }
+}
+
+impl CompositeReceipt {
+    /// Check the structure of the seals in this receipt, without verifying
+    /// them.
+    ///
+    /// In addition to checking each segment seal, this checks that the
+    /// segments are numbered consecutively from zero, which catches receipts
+    /// that lost segments in transit.
+    pub fn check_seal_with_context(&self, ctx: &VerifierContext) -> Result<(), SealError> {
+        if self.segments.is_empty() {
+            return Err(SealError::NoSegments);
+        }
+        for (position, segment) in self.segments.iter().enumerate() {
+            if segment.index as usize != position {
+                return Err(SealError::SegmentIndex {
+                    position,
+                    index: segment.index,
+                });
+            }
+            segment
+                .check_seal_with_context(ctx)
+                .map_err(|err| SealError::Segment {
+                    position,
+                    source: Box::new(err),
+                })?;
+        }
+        for (position, assumption) in self.assumptions.iter().enumerate() {
+            assumption
+                .check_seal_with_context(ctx)
+                .map_err(|err| SealError::Assumption {
+                    position,
+                    source: Box::new(err),
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl SegmentReceipt {
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal_with_context(&self, ctx: &VerifierContext) -> Result<(), SealError> {
+        if !ctx.suites.contains_key(&self.hashfn) {
+            return Err(SealError::UnknownHashFn(self.hashfn.clone()));
+        }
+        check_stark_header::<risc0_circuit_rv32im::CircuitImpl>(&self.seal)
+    }
+}
+
+impl SuccinctReceipt {
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal(&self) -> Result<(), SealError> {
+        check_stark_header::<risc0_circuit_recursion::CircuitImpl>(&self.seal)
+    }
+}
+
+impl CompactReceipt {
+    /// Check the structure of the seal in this receipt, without verifying it.
+    pub fn check_seal(&self) -> Result<(), SealError> {
+        if self.seal.len() != GROTH16_SEAL_SIZE {
+            return Err(SealError::Groth16Length {
+                len: self.seal.len(),
+                expected: GROTH16_SEAL_SIZE,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    use risc0_binfmt::ExitCode;
+    use risc0_core::field::baby_bear::{Elem, P};
+    use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest};
+
+    use super::SealError;
+    use crate::{
+        receipt::VerifierContext, CompactReceipt, CompositeReceipt, MaybePruned, ReceiptClaim,
+        SegmentReceipt,
+    };
+
+    const OUTPUT_SIZE: usize = risc0_circuit_rv32im::CircuitImpl::OUTPUT_SIZE;
+
+    fn claim() -> ReceiptClaim {
+        ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::ZERO),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: MaybePruned::Value(None),
+        }
+    }
+
+    fn seal(po2: u32) -> Vec<u32> {
+        let mut seal = vec![0; OUTPUT_SIZE + 64];
+        seal[OUTPUT_SIZE] = Elem::new(po2).as_u32_montgomery();
+        seal
+    }
+
+    fn segment(index: u32, seal: Vec<u32>) -> SegmentReceipt {
+        SegmentReceipt {
+            seal,
+            index,
+            hashfn: "poseidon2".into(),
+            claim: claim(),
+        }
+    }
+
+    #[test]
+    fn segment_seal() {
+        let ctx = VerifierContext::default();
+        assert_eq!(segment(0, seal(20)).check_seal_with_context(&ctx), Ok(()));
+        assert_eq!(
+            segment(0, vec![]).check_seal_with_context(&ctx),
+            Err(SealError::Empty)
+        );
+        assert_eq!(
+            segment(0, vec![0; 10]).check_seal_with_context(&ctx),
+            Err(SealError::TooShort {
+                len: 10,
+                min: OUTPUT_SIZE + 9
+            })
+        );
+
+        let mut corrupt = seal(20);
+        corrupt[3] = P;
+        assert_eq!(
+            segment(0, corrupt).check_seal_with_context(&ctx),
+            Err(SealError::ElemOutOfRange {
+                offset: 3,
+                value: P
+            })
+        );
+        assert_eq!(
+            segment(0, seal(30)).check_seal_with_context(&ctx),
+            Err(SealError::Po2OutOfRange { po2: 30 })
+        );
+
+        let mut receipt = segment(0, seal(20));
+        receipt.hashfn = "md5".into();
+        assert_eq!(
+            receipt.check_seal_with_context(&ctx),
+            Err(SealError::UnknownHashFn("md5".into()))
+        );
+    }
+
+    #[test]
+    fn composite_segments() {
+        let ctx = VerifierContext::default();
+        let composite = |segments| CompositeReceipt {
+            segments,
+            assumptions: vec![],
+            journal_digest: None,
+        };
+
+        let receipt = composite(vec![segment(0, seal(20)), segment(1, seal(20))]);
+        assert_eq!(receipt.check_seal_with_context(&ctx), Ok(()));
+        assert_eq!(
+            composite(vec![]).check_seal_with_context(&ctx),
+            Err(SealError::NoSegments)
+        );
+        assert_eq!(
+            composite(vec![segment(0, seal(20)), segment(2, seal(20))])
+                .check_seal_with_context(&ctx),
+            Err(SealError::SegmentIndex {
+                position: 1,
+                index: 2
+            })
+        );
+        assert_eq!(
+            composite(vec![segment(0, seal(20)), segment(1, vec![])]).check_seal_with_context(&ctx),
+            Err(SealError::Segment {
+                position: 1,
+                source: Box::new(SealError::Empty)
+            })
+        );
+    }
+
+    #[test]
+    fn compact_seal() {
+        let receipt = |seal| CompactReceipt {
+            seal,
+            claim: claim(),
+        };
+        assert_eq!(receipt(vec![0; 256]).check_seal(), Ok(()));
+        assert_eq!(
+            receipt(vec![0; 255]).check_seal(),
+            Err(SealError::Groth16Length {
+                len: 255,
+                expected: 256
+            })
+        );
+    }
+}
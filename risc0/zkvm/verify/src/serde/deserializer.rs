@@ -15,11 +15,10 @@
 use alloc::{string::String, vec};
 
 use bytemuck::Pod;
-use risc0_zkvm_platform::WORD_SIZE;
+use risc0_zkvm_platform::{align_up, WORD_SIZE};
 use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
 
 use super::err::{Error, Result};
-use crate::align_up;
 
 /// A reader for reading streams with serialized word-based data
 pub trait WordRead {
@@ -36,7 +36,7 @@ pub enum Error {
     SerializeBufferFull,
 }
 
-/// A Result type for `risc0_zkvm::serde` operations that can fail
+/// A Result type for `risc0_zkvm_verify::serde` operations that can fail
 pub type Result<T> = core::result::Result<T, Error>;
 
 impl Display for Error {
@@ -22,7 +22,7 @@
 //! when transmitting data to the guest. Similarly, the deserialization function
 //! [from_slice] should be used when reading data from the guest. For example:
 //! ```rust
-//! use risc0_zkvm::serde::{from_slice, to_vec};
+//! use risc0_zkvm_verify::serde::{from_slice, to_vec};
 //! let input = 42_u32;
 //! let encoded = to_vec(&[input]).unwrap();
 //! let output: u32 = from_slice(&encoded).unwrap();
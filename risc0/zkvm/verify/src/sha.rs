@@ -28,7 +28,7 @@
 //! The other interface is to directly use an implementation of the [Sha256]
 //! trait defined in this module:
 //! ```rust
-//! use risc0_zkvm::sha::{Impl, Sha256};
+//! use risc0_zkvm_verify::sha::{Impl, Sha256};
 //!
 //! // Hash a u8 array
 //! let data = [1_u8, 2, 5, 14];
@@ -46,16 +46,18 @@
 //! assert_eq!(hash_hash, hash_hash_words);
 //! ```
 
+pub mod guest;
+
 pub use risc0_zkp::core::{
     digest::{Digest, DIGEST_BYTES, DIGEST_WORDS},
     hash::sha::{Block, Sha256, BLOCK_BYTES, BLOCK_WORDS, SHA256_INIT, WORD_SIZE},
 };
 
 // Pick the appropriate implementation of SHA-256 depending on whether we are
-// in the zkVM guest. Users can simply `use risc0_zkvm::sha::Impl`.
+// in the zkVM guest. Users can simply `use risc0_zkvm_verify::sha::Impl`.
 cfg_if::cfg_if! {
     if #[cfg(target_os = "zkvm")] {
-        pub use crate::guest::sha::Impl;
+        pub use self::guest::Impl;
     } else {
         pub use risc0_zkp::core::hash::sha::cpu::Impl;
     }
@@ -82,7 +84,7 @@ pub mod rust_crypto {
     //! # Usage
     //!
     //! ```rust
-    //! use risc0_zkvm::sha::rust_crypto::{Sha256, Digest as _};
+    //! use risc0_zkvm_verify::sha::rust_crypto::{Sha256, Digest as _};
     //!
     //! // create a Sha256 object
     //! let mut hasher = Sha256::new();
@@ -21,12 +21,11 @@ use risc0_zkp::core::{
     hash::sha::{Block, BLOCK_WORDS, SHA256_INIT},
 };
 use risc0_zkvm_platform::{
+    align_up,
     syscall::{sys_sha_buffer, sys_sha_compress},
     WORD_SIZE,
 };
 
-use crate::align_up;
-
 // FIP 180-4 specifies that the bit-string being hashed should have a `1`
 // appended to it before padding.
 const END_MARKER: u8 = 0x80;
@@ -0,0 +1,44 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Receipt verification exported to JavaScript with `wasm-bindgen`.
+//!
+//! This module does not require the `std` feature. Building for
+//! `wasm32-unknown-unknown` without `std` avoids pulling in `getrandom` through
+//! the Groth16 verifier dependencies, which is not available in the browser
+//! without additional configuration. The verifier is single threaded and does
+//! not use any randomness.
+
+use alloc::{string::ToString, vec::Vec};
+
+use risc0_zkp::core::digest::Digest;
+use wasm_bindgen::prelude::*;
+
+use crate::Receipt;
+
+/// Verify a bincode-encoded [Receipt] against the given image ID.
+///
+/// `image_id` must be the 32 byte image ID of the expected guest. On success,
+/// returns the bytes of the receipt's journal.
+#[wasm_bindgen]
+pub fn verify_receipt(receipt_bytes: &[u8], image_id: &[u8]) -> Result<Vec<u8>, JsError> {
+    let image_id =
+        Digest::try_from(image_id).map_err(|_| JsError::new("image ID must be 32 bytes"))?;
+    let receipt: Receipt =
+        bincode::deserialize(receipt_bytes).map_err(|err| JsError::new(&err.to_string()))?;
+    receipt
+        .verify(image_id)
+        .map_err(|err| JsError::new(&err.to_string()))?;
+    Ok(receipt.journal.bytes)
+}
@@ -12,12 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File, io::BufReader, path::PathBuf};
+use std::{fs::File, io::BufReader, path::PathBuf, time::Duration};
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 use colored::Colorize;
-use risc0_crates_validator::{types::repo::Repo, ProfileConfig, RunStatus, ValidatorBuilder};
+use risc0_crates_validator::{
+    report::{load_results, Report, ReportFormat},
+    types::repo::Repo,
+    ProfileConfig, RunStatus, ValidatorBuilder,
+};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -44,6 +48,30 @@ struct Args {
     #[arg(short = 'o', long)]
     output: Option<PathBuf>,
 
+    /// Write a report of the results for publication
+    ///
+    /// Rendered as HTML if the path ends in `.html`, markdown otherwise.
+    #[arg(short = 'r', long)]
+    report: Option<PathBuf>,
+
+    /// Results of a previous run, written with `--output`
+    ///
+    /// Regressions and newly passing crates are highlighted in the report.
+    #[arg(long, requires = "report")]
+    previous: Option<PathBuf>,
+
+    /// Number of crate profiles to validate concurrently
+    ///
+    /// Each job builds in its own project under the output location.
+    #[arg(short = 'j', long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Maximum number of seconds to spend on each crate profile
+    ///
+    /// Profiles that take longer are stopped and reported as `Timeout`.
+    #[arg(long)]
+    timeout: Option<u64>,
+
     // TODO: Maybe it'd be possible to use `clap_serde_derive` as a way to simplify argument parsing?
     // (Doing this for each variant of the `Repo` enum - `path`, `branch` and `tag`)
     /// Specify the RISC Zero repository path to use as source for templates and
@@ -85,7 +113,10 @@ fn main() -> Result<()> {
     let file = File::open(&args.profiles_path).context("Failed to open profiles_path file")?;
     let reader = BufReader::new(file);
     let profile_configs: ProfileConfig = serde_yaml::from_reader(reader)?;
-    let validator = ValidatorBuilder::new(profile_configs, args.repo()?, args.out_dir).build()?;
+    let validator = ValidatorBuilder::new(profile_configs, args.repo()?, args.out_dir)
+        .jobs(args.jobs)
+        .timeout(args.timeout.map(Duration::from_secs))
+        .build()?;
     let profiles = validator.context().profiles();
     let profiles_num = validator.context().profiles().len();
 
@@ -96,7 +127,7 @@ fn main() -> Result<()> {
             validator.run_single(&crate_name, profiles)?
         }
         None => {
-            info!("Running all crates");
+            info!("Running all crates with {} jobs", args.jobs);
             validator.run_all(profiles)?
         }
     };
@@ -117,6 +148,7 @@ fn main() -> Result<()> {
                 RunStatus::Skipped => result_str.yellow(),
                 RunStatus::BuildFail => result_str.red(),
                 RunStatus::RunFail => result_str.red(),
+                RunStatus::Timeout => result_str.red(),
             }
         );
     }
@@ -132,5 +164,25 @@ fn main() -> Result<()> {
         .context("Failed to write output json file")?;
     }
 
+    if let Some(report_path) = args.report {
+        let previous = args.previous.as_deref().map(load_results).transpose()?;
+        let mut report = Report::new(&results);
+        if let Some(previous) = &previous {
+            report = report.with_previous(previous);
+        }
+        if let Some(diff) = report.diff() {
+            info!(
+                "{} regressions, {} newly passing since previous run",
+                diff.regressions.len(),
+                diff.newly_passing.len()
+            );
+        }
+        std::fs::write(
+            &report_path,
+            report.render(ReportFormat::from_path(&report_path)),
+        )
+        .context("Failed to write report file")?;
+    }
+
     Ok(())
 }
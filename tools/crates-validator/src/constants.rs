@@ -15,6 +15,8 @@
 pub const RISC_ZERO_REPO_GIT: &str = "https://github.com/risc0/risc0.git";
 pub const RISC_ZERO_REPO_PATH_BUILD: &str = "risc0/build";
 pub const RISC_ZERO_REPO_PATH_ZKVM: &str = "risc0/zkvm";
+pub const CRATES_IO_API: &str = "https://crates.io/api/v1";
+pub const CRATES_IO_USER_AGENT: &str = "risc0-crates-validator (https://github.com/risc0/risc0)";
 
 #[cfg(test)]
 pub(crate) const PATH_YAML_CONFIG: &str = "profiles/config.yaml";
@@ -0,0 +1,45 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[derive(clap::Parser)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Sets the output location of the yaml [ProfileConfig]
+    #[arg(short, long)]
+    pub(crate) output_path: std::path::PathBuf,
+
+    /// Sets the number of crates, sorted by downloads, to profile
+    #[arg(short, long, default_value = "20")]
+    pub(crate) crate_count: usize,
+
+    /// Only include crates in one of the given crates.io categories
+    #[arg(short = 'C', long, value_parser, value_delimiter = ' ')]
+    pub(crate) categories: Vec<String>,
+
+    /// Only include crates in the `no-std` crates.io category
+    #[arg(long)]
+    pub(crate) no_std: bool,
+
+    /// Maximum number of result pages to fetch per crates.io query
+    ///
+    /// Bounds the crawl when filters are combined and few of the most
+    /// downloaded crates match all of them.
+    #[arg(long, default_value = "10")]
+    pub(crate) max_pages: usize,
+
+    /// Specify the path for the configuration file containing custom
+    /// instructions on how to generate the profile for crates.
+    #[arg(short = 'P', long)]
+    pub(crate) profiles_file: Option<String>,
+}
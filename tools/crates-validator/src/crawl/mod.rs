@@ -0,0 +1,349 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates a [ProfileConfig] from the most downloaded crates on crates.io.
+//!
+//! Unlike [crate::gen_profiles], which processes a full database dump, this
+//! queries the crates.io API directly and only fetches as many pages of
+//! results as are needed.
+
+pub mod args;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Write,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::{
+    constants::{CRATES_IO_API, CRATES_IO_USER_AGENT},
+    parser::Parser,
+    types::{profile_settings::ProfileSettings, version::Version},
+    Profile, ProfileConfig, Profiles,
+};
+
+pub use args::Args;
+
+/// Category used by crates.io for crates that support `#![no_std]`
+const NO_STD_CATEGORY: &str = "no-std";
+
+/// Maximum page size accepted by the crates.io API
+const PER_PAGE: usize = 100;
+
+/// Delay between requests, as asked of crawlers by the crates.io data access
+/// policy
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A crate as listed by the crates.io API
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CrateSummary {
+    pub name: String,
+    pub downloads: u64,
+    pub max_stable_version: Option<semver::Version>,
+    pub max_version: semver::Version,
+}
+
+impl CrateSummary {
+    /// Newest non-pre-release version, if the crate has one.
+    fn version(&self) -> semver::Version {
+        self.max_stable_version
+            .clone()
+            .unwrap_or_else(|| self.max_version.clone())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CratesPage {
+    crates: Vec<CrateSummary>,
+}
+
+/// Client for the crates.io API
+pub struct CratesIo {
+    client: reqwest::Client,
+    requested: bool,
+}
+
+impl CratesIo {
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(CRATES_IO_USER_AGENT)
+            .build()?;
+        Ok(Self {
+            client,
+            requested: false,
+        })
+    }
+
+    /// Fetch up to `limit` crates, most downloaded first, optionally restricted
+    /// to a category, without fetching more than `max_pages` pages.
+    pub async fn top_crates(
+        &mut self,
+        category: Option<&str>,
+        limit: usize,
+        max_pages: usize,
+    ) -> Result<Vec<CrateSummary>> {
+        let mut crates = Vec::new();
+        for page in 1..=max_pages {
+            if crates.len() >= limit {
+                break;
+            }
+            let results = self.fetch_page(category, page).await?;
+            let done = results.len() < PER_PAGE;
+            crates.extend(results);
+            if done {
+                break;
+            }
+        }
+        crates.truncate(limit);
+        Ok(crates)
+    }
+
+    async fn fetch_page(
+        &mut self,
+        category: Option<&str>,
+        page: usize,
+    ) -> Result<Vec<CrateSummary>> {
+        if self.requested {
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+        self.requested = true;
+
+        let mut query = vec![
+            ("sort", "downloads".to_string()),
+            ("per_page", PER_PAGE.to_string()),
+            ("page", page.to_string()),
+        ];
+        if let Some(category) = category {
+            query.push(("category", category.to_string()));
+        }
+        debug!("Fetching crates: {query:?}");
+
+        let body = self
+            .client
+            .get(format!("{CRATES_IO_API}/crates"))
+            .query(&query)
+            .send()
+            .await
+            .context("Failed to query crates.io")?
+            .error_for_status()
+            .context("crates.io returned an error")?
+            .text()
+            .await?;
+        Ok(parse_page(&body)?.crates)
+    }
+}
+
+fn parse_page(body: &str) -> Result<CratesPage> {
+    serde_json::from_str(body).context("Failed to parse crates.io response")
+}
+
+/// Crawls crates.io according to `args` and writes the resulting profile.
+pub async fn crawl(args: Args) -> Result<ProfileConfig> {
+    let custom: ProfileConfig = match &args.profiles_file {
+        Some(path) => Parser::new(path)?,
+        None => Parser::default(),
+    }
+    .try_into()?;
+
+    let mut crates_io = CratesIo::new()?;
+    let candidates = fetch_candidates(&mut crates_io, &args).await?;
+    let selected = select_crates(candidates, args.crate_count);
+    info!("Selected {} crates", selected.len());
+
+    let profile_config = build_profiles(&custom, &selected)?;
+    let mut output = File::create(&args.output_path)?;
+    output.write_all(serde_yaml::to_string(&profile_config)?.as_bytes())?;
+    info!("Wrote profile to: {}", args.output_path.to_string_lossy());
+
+    Ok(profile_config)
+}
+
+/// Fetches crates matching the filters in `args`.
+///
+/// crates.io can only filter by a single category per query, so each category
+/// is queried separately. When `--no-std` is combined with other categories,
+/// the results are intersected with the `no-std` category.
+async fn fetch_candidates(crates_io: &mut CratesIo, args: &Args) -> Result<Vec<CrateSummary>> {
+    let count = args.crate_count;
+    let intersect = args.no_std && !args.categories.is_empty();
+    // When intersecting, fetch as much as allowed so that enough crates
+    // survive the intersection.
+    let limit = if intersect {
+        args.max_pages * PER_PAGE
+    } else {
+        count
+    };
+
+    let categories: Vec<Option<&str>> = match (args.categories.is_empty(), args.no_std) {
+        (true, false) => vec![None],
+        (true, true) => vec![Some(NO_STD_CATEGORY)],
+        (false, _) => args.categories.iter().map(|c| Some(c.as_str())).collect(),
+    };
+
+    let mut candidates = Vec::new();
+    for category in categories {
+        let crates = crates_io
+            .top_crates(category, limit, args.max_pages)
+            .await?;
+        if crates.is_empty() {
+            warn!(
+                "No crates found in category '{}'",
+                category.unwrap_or_default()
+            );
+        }
+        candidates.extend(crates);
+    }
+
+    if intersect {
+        let no_std: BTreeSet<String> = crates_io
+            .top_crates(Some(NO_STD_CATEGORY), limit, args.max_pages)
+            .await?
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        candidates.retain(|c| no_std.contains(&c.name));
+    }
+
+    Ok(candidates)
+}
+
+/// Deduplicates `candidates` and keeps the `count` most downloaded.
+fn select_crates(candidates: Vec<CrateSummary>, count: usize) -> Vec<CrateSummary> {
+    let unique: BTreeMap<String, CrateSummary> = candidates
+        .into_iter()
+        .map(|c| (c.name.clone(), c))
+        .collect();
+    let mut crates: Vec<_> = unique.into_values().collect();
+    crates.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(a.name.cmp(&b.name)));
+    crates.truncate(count);
+    crates
+}
+
+/// Builds profiles for `selected` crates, pinned to their latest stable
+/// version, alongside the custom profiles.
+///
+/// Custom profiles take precedence over crawled ones with the same name, and
+/// are pinned to the crawled version if they don't specify any.
+fn build_profiles(custom: &ProfileConfig, selected: &[CrateSummary]) -> Result<ProfileConfig> {
+    let versions: BTreeMap<&str, semver::Version> = selected
+        .iter()
+        .map(|c| (c.name.as_str(), c.version()))
+        .collect();
+
+    let mut profiles = Profiles::new();
+    for profile in custom.profiles() {
+        let profile = match (profile.versions.is_empty(), versions.get(profile.name())) {
+            (true, Some(version)) => Profile::new(
+                profile.name().into(),
+                profile.settings.clone(),
+                Some(Version::Specific(version.clone()).into()),
+            )?,
+            _ => profile.clone(),
+        };
+        profiles.insert(profile);
+    }
+    for (name, version) in versions {
+        if profiles.iter().any(|p| p.name() == name) {
+            continue;
+        }
+        profiles.insert(Profile::new(
+            name.into(),
+            ProfileSettings::default(),
+            Some(Version::Specific(version).into()),
+        )?);
+    }
+
+    Ok(custom.replace_profiles(profiles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(name: &str, downloads: u64, stable: Option<&str>) -> CrateSummary {
+        CrateSummary {
+            name: name.into(),
+            downloads,
+            max_stable_version: stable.map(|v| v.parse().unwrap()),
+            max_version: "9.9.9-alpha".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn parse_crates_page() {
+        let body = r#"{
+            "crates": [
+                {
+                    "name": "serde",
+                    "downloads": 300,
+                    "max_stable_version": "1.0.200",
+                    "max_version": "1.0.200",
+                    "description": "A serialization framework"
+                },
+                {
+                    "name": "unstable",
+                    "downloads": 10,
+                    "max_stable_version": null,
+                    "max_version": "0.1.0-rc.1"
+                }
+            ],
+            "meta": { "total": 2 }
+        }"#;
+        let page = parse_page(body).unwrap();
+        assert_eq!(page.crates.len(), 2);
+        assert_eq!(page.crates[0].version(), semver::Version::new(1, 0, 200));
+        assert_eq!(page.crates[1].version().to_string(), "0.1.0-rc.1");
+    }
+
+    #[test]
+    fn select_dedups_and_sorts_by_downloads() {
+        let candidates = vec![
+            summary("b", 20, Some("1.0.0")),
+            summary("a", 30, Some("1.0.0")),
+            summary("b", 20, Some("1.0.0")),
+            summary("c", 10, Some("1.0.0")),
+        ];
+        let names: Vec<_> = select_crates(candidates, 2)
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn custom_profiles_take_precedence() {
+        let settings = ProfileSettings {
+            std: true,
+            ..Default::default()
+        };
+        let custom = ProfileConfig {
+            profiles: [Profile::new("a".into(), settings.clone(), None).unwrap()].into(),
+        };
+        let selected = [summary("a", 30, Some("1.2.3")), summary("b", 20, None)];
+        let config = build_profiles(&custom, &selected).unwrap();
+
+        let profiles: Vec<_> = config.profiles().iter().collect();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].name(), "a");
+        assert_eq!(profiles[0].settings(), &settings);
+        assert_eq!(profiles[0].versions(), [semver::Version::new(1, 2, 3)]);
+        assert_eq!(profiles[1].name(), "b");
+        assert_eq!(profiles[1].versions()[0].to_string(), "9.9.9-alpha");
+    }
+}
@@ -13,16 +13,24 @@
 // limitations under the License.
 
 pub mod constants;
+pub mod crawl;
 pub mod parser;
+pub mod report;
 pub mod types;
 
 use std::{
     collections::BTreeMap,
     fs::File,
-    io::{BufRead, BufReader, Write},
+    io::{BufRead, BufReader, Read, Write},
     ops::Not,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
     vec,
 };
 
@@ -201,6 +209,8 @@ pub struct Validator {
     context: ProfileConfig,
     proj_out_dir: PathBuf,
     repo: Repo,
+    jobs: usize,
+    timeout: Option<Duration>,
 }
 
 impl Validator {
@@ -223,12 +233,16 @@ pub enum RunStatus {
     RunFail,
     Skipped,
     Success,
+    Timeout,
 }
 
 impl Validator {
     /// Uses [cargo-risczero] to generate a new base project
-    fn gen_initial_project(&self, profile: &Profile) -> Result<PathBuf> {
-        let project_name = "template_project";
+    ///
+    /// Each worker gets its own project, so that concurrent builds do not
+    /// contend for the same target directory.
+    fn gen_initial_project(&self, profile: &Profile, worker: usize) -> Result<PathBuf> {
+        let project_name = format!("template_project_{worker}");
         let output_path = self.proj_out_dir.join(project_name);
 
         if output_path.exists() {
@@ -252,7 +266,7 @@ impl Validator {
         let mut cmd = std::process::Command::new("cargo");
         cmd.arg("risczero");
         cmd.arg("new");
-        cmd.arg(project_name);
+        cmd.arg(&project_name);
         cmd.arg("--no-git");
         cmd.arg("--dest");
         cmd.arg(&self.proj_out_dir);
@@ -410,7 +424,14 @@ impl Validator {
     }
 
     // Builds the template project
-    fn build_project(&self, profile: &Profile, working_dir: &Path) -> Result<(bool, String)> {
+    //
+    // Returns `None` if the build did not finish before the deadline.
+    fn build_project(
+        &self,
+        profile: &Profile,
+        working_dir: &Path,
+        deadline: Option<Instant>,
+    ) -> Result<Option<(bool, String)>> {
         debug!(
             "building {} - {}",
             profile.name(),
@@ -439,12 +460,15 @@ impl Validator {
             guest_log_file.path().to_string_lossy().to_string(),
         );
 
-        let output = Command::new("cargo")
-            .args(["build"])
+        let mut cmd = Command::new("cargo");
+        cmd.args(["build"])
             .env_clear()
             .envs(&filtered_env)
-            .current_dir(working_dir)
-            .output()?;
+            .current_dir(working_dir);
+        let Some(output) = output_until(&mut cmd, deadline)? else {
+            error!("{} - build - TIMEOUT", profile.name());
+            return Ok(None);
+        };
 
         let status = output.status;
         let res = if status.success() || (!status.success() && profile.settings.should_fail) {
@@ -472,13 +496,19 @@ impl Validator {
             (false, build_log_trimmed)
         };
 
-        Ok(res)
+        Ok(Some(res))
     }
 
     /// Run the prover a given [CrateProfile]
     ///
-    /// Requires that the build step has been run before hand
-    fn run_prover(&self, profile: &Profile, working_dir: &Path) -> Result<bool> {
+    /// Requires that the build step has been run before hand. Returns `None`
+    /// if the prover did not finish before the deadline.
+    fn run_prover(
+        &self,
+        profile: &Profile,
+        working_dir: &Path,
+        deadline: Option<Instant>,
+    ) -> Result<Option<bool>> {
         let cmd = working_dir.join("target/debug/host");
         if !cmd.exists() {
             bail!("Could not find 'host' binary in working_dir build dir, did the build run?");
@@ -491,7 +521,10 @@ impl Validator {
             cmd = cmd.env("RISC0_EXPERIMENTAL_PREFLIGHT", "1")
         }
 
-        let output = cmd.output()?;
+        let Some(output) = output_until(cmd, deadline)? else {
+            error!("{} - run - TIMEOUT", profile.name());
+            return Ok(None);
+        };
 
         let status = output.status;
         let res = if (status.success() && !profile.settings.should_fail)
@@ -507,11 +540,12 @@ impl Validator {
             false
         };
 
-        Ok(res)
+        Ok(Some(res))
     }
 
-    /// Run a given profile through the set of tests
-    fn run(&self, profile: &Profile) -> Result<Vec<ValidationResults>> {
+    /// Run a given profile through the set of tests, using the project of the
+    /// given worker
+    fn run(&self, profile: &Profile, worker: usize) -> Result<Vec<ValidationResults>> {
         if profile.should_skip() {
             warn!("Skipping {}", profile.name());
             return Ok(vec![ValidationResults::new(
@@ -523,11 +557,12 @@ impl Validator {
             )]);
         }
 
-        let working_dir = self.gen_initial_project(profile)?;
+        let working_dir = self.gen_initial_project(profile, worker)?;
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
         let mut results = Vec::new();
         for version in profile.versions() {
             self.customize_guest(profile, &version, &working_dir)?;
-            let (build_success, build_errors) = self.build_project(profile, &working_dir)?;
+            let build = self.build_project(profile, &working_dir, deadline)?;
             let create_validation_results = |status: RunStatus, build_errors: Option<String>| {
                 ValidationResults::new(
                     profile.name(),
@@ -537,6 +572,10 @@ impl Validator {
                     build_errors,
                 )
             };
+            let Some((build_success, build_errors)) = build else {
+                results.push(create_validation_results(RunStatus::Timeout, None));
+                break;
+            };
             if !build_success {
                 results.push(create_validation_results(
                     RunStatus::BuildFail,
@@ -544,9 +583,18 @@ impl Validator {
                 ));
                 continue;
             }
-            if profile.settings.run_prover && !self.run_prover(profile, &working_dir)? {
-                results.push(create_validation_results(RunStatus::RunFail, None));
-                continue;
+            if profile.settings.run_prover {
+                match self.run_prover(profile, &working_dir, deadline)? {
+                    Some(true) => {}
+                    Some(false) => {
+                        results.push(create_validation_results(RunStatus::RunFail, None));
+                        continue;
+                    }
+                    None => {
+                        results.push(create_validation_results(RunStatus::Timeout, None));
+                        break;
+                    }
+                }
             }
             results.push(create_validation_results(RunStatus::Success, None));
         }
@@ -558,7 +606,7 @@ impl Validator {
     pub fn run_single(&self, name: &str, profiles: &Profiles) -> Result<Vec<ValidationResults>> {
         for profile in profiles {
             if profile.name() == name {
-                return self.run(profile);
+                return self.run(profile, 0);
             }
         }
 
@@ -566,16 +614,76 @@ impl Validator {
     }
 
     // Run all profiles in config
+    //
+    // Up to `jobs` profiles are validated concurrently. Results are returned in
+    // the order of `profiles` regardless of which finish first.
     pub fn run_all(&self, profiles: &Profiles) -> Result<Vec<ValidationResults>> {
-        let mut results = vec![];
-        for profile in profiles {
-            results.push(self.run(profile)?);
+        let profiles: Vec<&Profile> = profiles.iter().collect();
+        let next = AtomicUsize::new(0);
+        let results: Vec<_> = profiles.iter().map(|_| Mutex::new(None)).collect();
+        thread::scope(|scope| {
+            for worker in 0..self.jobs.clamp(1, profiles.len().max(1)) {
+                let (profiles, next, results) = (&profiles, &next, &results);
+                scope.spawn(move || loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(profile) = profiles.get(idx) else {
+                        break;
+                    };
+                    let result = self.run(profile, worker);
+                    *results[idx].lock().unwrap() = Some(result);
+                });
+            }
+        });
+
+        let mut all = vec![];
+        for result in results {
+            all.extend(result.into_inner().unwrap().unwrap()?);
         }
 
-        Ok(results.into_iter().flatten().collect())
+        Ok(all)
     }
 }
 
+/// Runs `cmd` to completion and collects its output, killing it if it is still
+/// running at `deadline`.
+///
+/// Returns `None` if the command was killed.
+fn output_until(cmd: &mut Command, deadline: Option<Instant>) -> Result<Option<Output>> {
+    let Some(deadline) = deadline else {
+        return Ok(Some(cmd.output()?));
+    };
+
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let read_all = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            pipe.read_to_end(&mut buf).map(|_| buf)
+        })
+    };
+    let stdout = read_all(Box::new(child.stdout.take().unwrap()));
+    let stderr = read_all(Box::new(child.stderr.take().unwrap()));
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            // Grandchildren may still hold the pipes open, so the readers are
+            // left to finish on their own.
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap()?,
+        stderr: stderr.join().unwrap()?,
+    }))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidatorBuilder {
     #[serde(flatten)]
@@ -583,6 +691,10 @@ pub struct ValidatorBuilder {
     out_dir: Option<PathBuf>,
     #[serde(skip)]
     repo: Repo,
+    #[serde(skip)]
+    jobs: Option<usize>,
+    #[serde(skip)]
+    timeout: Option<Duration>,
 }
 
 impl ValidatorBuilder {
@@ -591,9 +703,23 @@ impl ValidatorBuilder {
             context,
             repo,
             out_dir,
+            jobs: None,
+            timeout: None,
         }
     }
 
+    /// Number of profiles to validate concurrently. Defaults to 1.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Maximum time to spend building and running each crate profile.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     pub fn out_dir_val(self, out_dir: PathBuf) -> Self {
         self.out_dir(Some(out_dir))
     }
@@ -613,6 +739,8 @@ impl ValidatorBuilder {
             context: self.context,
             repo: self.repo,
             proj_out_dir,
+            jobs: self.jobs.unwrap_or(1),
+            timeout: self.timeout,
         })
     }
 }
@@ -629,3 +757,27 @@ fn filter_flags(flags: &[&str]) -> BTreeMap<String, String> {
         .filter(|(k, _)| flags.contains(&k.as_str()))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_until_collects_output() {
+        let deadline = Some(Instant::now() + Duration::from_secs(30));
+        let output = output_until(Command::new("echo").arg("hello"), deadline)
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hello\n");
+    }
+
+    #[test]
+    fn output_until_kills_at_deadline() {
+        let start = Instant::now();
+        let deadline = Some(start + Duration::from_millis(200));
+        let output = output_until(Command::new("sleep").arg("30"), deadline).unwrap();
+        assert!(output.is_none());
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+}
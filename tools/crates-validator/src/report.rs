@@ -0,0 +1,401 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering of validation results for publication.
+//!
+//! A [Report] renders the results of a run as markdown or HTML, including the
+//! build logs of failed crates. Given the results of a previous run, it also
+//! highlights regressions and newly passing crates.
+
+use std::{collections::BTreeMap, fmt::Write as _, fs::File, io::BufReader, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::{RunStatus, ValidationResults};
+
+/// Output format of a [Report].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Picks the format from the extension of `path`, defaulting to markdown.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// Loads results previously written with `--output`.
+pub fn load_results(path: &Path) -> Result<Vec<ValidationResults>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open results file: {}", path.display()))?;
+    // JSON is a subset of YAML, so this accepts either.
+    serde_yaml::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse results file: {}", path.display()))
+}
+
+/// A crate version whose status changed since the previous run.
+#[derive(Debug)]
+pub struct StatusChange<'a> {
+    pub result: &'a ValidationResults,
+    pub previous: &'a RunStatus,
+}
+
+/// Differences between two runs.
+///
+/// Results are matched by crate name and version. Crate versions that are
+/// missing from either run, or skipped in either run, are not compared.
+#[derive(Debug, Default)]
+pub struct Diff<'a> {
+    /// Crate versions that passed previously but no longer do
+    pub regressions: Vec<StatusChange<'a>>,
+
+    /// Crate versions that pass now but did not previously
+    pub newly_passing: Vec<StatusChange<'a>>,
+}
+
+impl<'a> Diff<'a> {
+    pub fn new(previous: &'a [ValidationResults], current: &'a [ValidationResults]) -> Self {
+        let previous: BTreeMap<_, _> = previous.iter().map(|r| (key(r), &r.status)).collect();
+        let mut diff = Self::default();
+        for result in current {
+            let Some(&previous) = previous.get(&key(result)) else {
+                continue;
+            };
+            if matches!(previous, RunStatus::Skipped) || matches!(result.status, RunStatus::Skipped)
+            {
+                continue;
+            }
+            let change = StatusChange { result, previous };
+            match (is_success(previous), is_success(&result.status)) {
+                (true, false) => diff.regressions.push(change),
+                (false, true) => diff.newly_passing.push(change),
+                _ => {}
+            }
+        }
+        diff
+    }
+}
+
+fn key(result: &ValidationResults) -> (&str, Option<&semver::Version>) {
+    (&result.name, result.version.as_ref())
+}
+
+fn is_success(status: &RunStatus) -> bool {
+    matches!(status, RunStatus::Success)
+}
+
+fn version(result: &ValidationResults) -> String {
+    result
+        .version
+        .as_ref()
+        .map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+/// Renders the results of a run for publication.
+pub struct Report<'a> {
+    results: &'a [ValidationResults],
+    diff: Option<Diff<'a>>,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(results: &'a [ValidationResults]) -> Self {
+        Self {
+            results,
+            diff: None,
+        }
+    }
+
+    /// Highlight changes relative to the results of a previous run.
+    pub fn with_previous(mut self, previous: &'a [ValidationResults]) -> Self {
+        self.diff = Some(Diff::new(previous, self.results));
+        self
+    }
+
+    pub fn diff(&self) -> Option<&Diff<'a>> {
+        self.diff.as_ref()
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Html => self.render_html(),
+        }
+    }
+
+    fn successful(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| is_success(&r.status))
+            .count()
+    }
+
+    fn failures(&self) -> impl Iterator<Item = (&'a ValidationResults, &'a str)> {
+        self.results
+            .iter()
+            .filter_map(|r| r.build_errors.as_deref().map(|log| (r, log)))
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Crates compatibility report\n").unwrap();
+        writeln!(
+            out,
+            "**{}/{}** crate versions successful.\n",
+            self.successful(),
+            self.results.len()
+        )
+        .unwrap();
+
+        if let Some(diff) = &self.diff {
+            writeln!(out, "## Changes since previous run\n").unwrap();
+            for (title, changes) in [
+                ("Regressions", &diff.regressions),
+                ("Newly passing", &diff.newly_passing),
+            ] {
+                writeln!(out, "### {title}\n").unwrap();
+                if changes.is_empty() {
+                    writeln!(out, "None.\n").unwrap();
+                    continue;
+                }
+                writeln!(out, "| Crate | Version | Previous | Current |").unwrap();
+                writeln!(out, "| --- | --- | --- | --- |").unwrap();
+                for change in changes {
+                    writeln!(
+                        out,
+                        "| {} | {} | {} | {} |",
+                        change.result.name,
+                        version(change.result),
+                        change.previous.as_ref(),
+                        change.result.status.as_ref()
+                    )
+                    .unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        }
+
+        writeln!(out, "## Results\n").unwrap();
+        writeln!(out, "| Crate | Version | Status |").unwrap();
+        writeln!(out, "| --- | --- | --- |").unwrap();
+        for result in self.results {
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                result.name,
+                version(result),
+                result.status.as_ref()
+            )
+            .unwrap();
+        }
+
+        let mut failures = self.failures().peekable();
+        if failures.peek().is_some() {
+            writeln!(out, "\n## Failure logs").unwrap();
+            for (result, log) in failures {
+                writeln!(out, "\n### {} {}\n", result.name, version(result)).unwrap();
+                writeln!(out, "````text\n{}\n````", log.trim_end()).unwrap();
+            }
+        }
+
+        out
+    }
+
+    pub fn render_html(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "<!DOCTYPE html>\n<html>\n<head>").unwrap();
+        writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+        writeln!(out, "<title>Crates compatibility report</title>").unwrap();
+        writeln!(out, "</head>\n<body>").unwrap();
+        writeln!(out, "<h1>Crates compatibility report</h1>").unwrap();
+        writeln!(
+            out,
+            "<p><strong>{}/{}</strong> crate versions successful.</p>",
+            self.successful(),
+            self.results.len()
+        )
+        .unwrap();
+
+        if let Some(diff) = &self.diff {
+            writeln!(out, "<h2>Changes since previous run</h2>").unwrap();
+            for (title, changes) in [
+                ("Regressions", &diff.regressions),
+                ("Newly passing", &diff.newly_passing),
+            ] {
+                writeln!(out, "<h3>{title}</h3>").unwrap();
+                if changes.is_empty() {
+                    writeln!(out, "<p>None.</p>").unwrap();
+                    continue;
+                }
+                writeln!(out, "<table>").unwrap();
+                writeln!(
+                    out,
+                    "<tr><th>Crate</th><th>Version</th><th>Previous</th><th>Current</th></tr>"
+                )
+                .unwrap();
+                for change in changes {
+                    writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(&change.result.name),
+                        escape_html(&version(change.result)),
+                        change.previous.as_ref(),
+                        change.result.status.as_ref()
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "</table>").unwrap();
+            }
+        }
+
+        writeln!(out, "<h2>Results</h2>\n<table>").unwrap();
+        writeln!(
+            out,
+            "<tr><th>Crate</th><th>Version</th><th>Status</th></tr>"
+        )
+        .unwrap();
+        for result in self.results {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td class=\"{status}\">{status}</td></tr>",
+                escape_html(&result.name),
+                escape_html(&version(result)),
+                status = result.status.as_ref()
+            )
+            .unwrap();
+        }
+        writeln!(out, "</table>").unwrap();
+
+        let mut failures = self.failures().peekable();
+        if failures.peek().is_some() {
+            writeln!(out, "<h2>Failure logs</h2>").unwrap();
+            for (result, log) in failures {
+                writeln!(
+                    out,
+                    "<details>\n<summary>{} {}</summary>\n<pre>{}</pre>\n</details>",
+                    escape_html(&result.name),
+                    escape_html(&version(result)),
+                    escape_html(log.trim_end())
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(out, "</body>\n</html>").unwrap();
+        out
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::profile_settings::ProfileSettings;
+
+    fn result(name: &str, status: RunStatus, build_errors: Option<&str>) -> ValidationResults {
+        ValidationResults::new(
+            name,
+            ProfileSettings::default(),
+            Some(semver::Version::new(1, 0, 0)),
+            status,
+            build_errors.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn diff_finds_regressions_and_newly_passing() {
+        let previous = vec![
+            result("regressed", RunStatus::Success, None),
+            result("fixed", RunStatus::BuildFail, None),
+            result("still_failing", RunStatus::BuildFail, None),
+            result("was_skipped", RunStatus::Skipped, None),
+        ];
+        let current = vec![
+            result("regressed", RunStatus::RunFail, None),
+            result("fixed", RunStatus::Success, None),
+            result("still_failing", RunStatus::RunFail, None),
+            result("was_skipped", RunStatus::Success, None),
+            result("added", RunStatus::BuildFail, None),
+        ];
+        let diff = Diff::new(&previous, &current);
+        let names = |changes: &[StatusChange]| -> Vec<String> {
+            changes.iter().map(|c| c.result.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.regressions), ["regressed"]);
+        assert_eq!(names(&diff.newly_passing), ["fixed"]);
+    }
+
+    #[test]
+    fn renders_failure_logs() {
+        let results = vec![
+            result("good", RunStatus::Success, None),
+            result("bad", RunStatus::BuildFail, Some("error: <oops>\n")),
+        ];
+        let report = Report::new(&results);
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("**1/2** crate versions successful."));
+        assert!(markdown.contains("| bad | 1.0.0 | BuildFail |"));
+        assert!(markdown.contains("### bad 1.0.0\n\n````text\nerror: <oops>\n````"));
+        assert!(!markdown.contains("Changes since previous run"));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("<pre>error: &lt;oops&gt;</pre>"));
+    }
+
+    #[test]
+    fn renders_changes() {
+        let previous = vec![result("regressed", RunStatus::Success, None)];
+        let current = vec![result("regressed", RunStatus::BuildFail, None)];
+        let markdown = Report::new(&current)
+            .with_previous(&previous)
+            .render(ReportFormat::Markdown);
+        assert!(markdown.contains(
+            "### Regressions\n\n| Crate | Version | Previous | Current |\n\
+             | --- | --- | --- | --- |\n| regressed | 1.0.0 | Success | BuildFail |"
+        ));
+        assert!(markdown.contains("### Newly passing\n\nNone."));
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.html")),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.md")),
+            ReportFormat::Markdown
+        );
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks which receipts produced by prior releases the current verifier
+//! accepts.
+//!
+//! The corpus lives in `risc0/zkvm/receipts/corpus`. Its `index.json` lists
+//! each receipt along with the release that produced it and whether the
+//! current verifier is expected to accept it. New receipts are added with
+//! `cargo xtask gen-receipt --corpus` when cutting a release.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use hex::FromHex;
+use risc0_zkvm::{sha::Digest, Receipt, VERSION};
+use serde::{Deserialize, Serialize};
+
+pub const CORPUS_DIR: &str = "risc0/zkvm/receipts/corpus";
+
+const INDEX_FILE: &str = "index.json";
+
+/// A receipt in the corpus.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    /// Release of risc0-zkvm that produced the receipt.
+    pub version: String,
+    /// Name of the receipt, unique within a release.
+    pub name: String,
+    /// Hex-encoded image ID the receipt is verified against.
+    pub image_id: String,
+    /// Path of the bincode-encoded receipt, relative to the corpus directory.
+    pub file: String,
+    /// Whether the current verifier should accept the receipt.
+    ///
+    /// Set this to false when a release intentionally drops support for older
+    /// receipts, so that the change is recorded here.
+    #[serde(default = "default_expect_verified")]
+    pub expect_verified: bool,
+}
+
+fn default_expect_verified() -> bool {
+    true
+}
+
+pub fn load_index(corpus: &Path) -> Vec<CorpusEntry> {
+    let index = fs::read_to_string(corpus.join(INDEX_FILE)).unwrap();
+    serde_json::from_str(&index).unwrap()
+}
+
+pub fn save_index(corpus: &Path, entries: &[CorpusEntry]) {
+    let index = serde_json::to_string_pretty(entries).unwrap();
+    fs::write(corpus.join(INDEX_FILE), index + "\n").unwrap();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Verified,
+    DecodeFailed,
+    Rejected,
+}
+
+#[derive(Serialize)]
+struct CompatResult {
+    version: String,
+    name: String,
+    status: Status,
+    expect_verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl CompatResult {
+    fn is_expected(&self) -> bool {
+        (self.status == Status::Verified) == self.expect_verified
+    }
+}
+
+/// The compatibility matrix of the current verifier against the corpus.
+#[derive(Serialize)]
+struct Matrix {
+    verifier_version: String,
+    results: Vec<CompatResult>,
+}
+
+fn check_entry(corpus: &Path, entry: &CorpusEntry) -> CompatResult {
+    let result = |status, error| CompatResult {
+        version: entry.version.clone(),
+        name: entry.name.clone(),
+        status,
+        expect_verified: entry.expect_verified,
+        error,
+    };
+
+    let receipt = fs::read(corpus.join(&entry.file))
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| bincode::deserialize::<Receipt>(&bytes).map_err(|err| err.to_string()));
+    let receipt = match receipt {
+        Ok(receipt) => receipt,
+        Err(err) => return result(Status::DecodeFailed, Some(err)),
+    };
+    let image_id = match Digest::from_hex(&entry.image_id) {
+        Ok(image_id) => image_id,
+        Err(err) => return result(Status::DecodeFailed, Some(err.to_string())),
+    };
+    match receipt.verify(image_id) {
+        Ok(()) => result(Status::Verified, None),
+        Err(err) => result(Status::Rejected, Some(err.to_string())),
+    }
+}
+
+fn check_corpus(corpus: &Path) -> Matrix {
+    let results = load_index(corpus)
+        .iter()
+        .map(|entry| check_entry(corpus, entry))
+        .collect();
+    Matrix {
+        verifier_version: VERSION.to_string(),
+        results,
+    }
+}
+
+/// Verify every receipt in the corpus and report the compatibility matrix.
+#[derive(Parser)]
+pub struct CompatCheck {
+    /// Directory containing the receipt corpus.
+    #[arg(long, default_value = CORPUS_DIR)]
+    corpus: PathBuf,
+
+    /// Write the compatibility matrix as JSON to this path.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl CompatCheck {
+    pub fn run(&self) {
+        let matrix = check_corpus(&self.corpus);
+
+        println!("verifier {}", matrix.verifier_version);
+        for result in matrix.results.iter() {
+            let marker = if result.is_expected() {
+                ""
+            } else {
+                " (UNEXPECTED)"
+            };
+            println!(
+                "{:<16} {:<24} {:?}{marker}",
+                result.version, result.name, result.status
+            );
+            if let Some(err) = &result.error {
+                println!("    {err}");
+            }
+        }
+
+        if let Some(output) = &self.output {
+            fs::write(output, serde_json::to_string_pretty(&matrix).unwrap()).unwrap();
+        }
+
+        if !matrix.results.iter().all(CompatResult::is_expected) {
+            eprintln!(
+                "compatibility with prior receipts changed; update the corpus index if intended"
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_matches_expectations() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(CORPUS_DIR);
+        for result in check_corpus(&corpus).results {
+            assert!(
+                result.is_expected(),
+                "{} {}: {:?} {:?}",
+                result.version,
+                result.name,
+                result.status,
+                result.error
+            );
+        }
+    }
+
+    #[test]
+    fn reports_undecodable_receipt() {
+        let corpus = tempfile::tempdir().unwrap();
+        fs::write(corpus.path().join("bad.bin"), b"not a receipt").unwrap();
+        save_index(
+            corpus.path(),
+            &[CorpusEntry {
+                version: "0.0.0".to_string(),
+                name: "bad".to_string(),
+                image_id: hex::encode(Digest::ZERO),
+                file: "bad.bin".to_string(),
+                expect_verified: false,
+            }],
+        );
+
+        let matrix = check_corpus(corpus.path());
+        assert_eq!(matrix.results.len(), 1);
+        assert_eq!(matrix.results[0].status, Status::DecodeFailed);
+        assert!(matrix.results[0].is_expected());
+    }
+}
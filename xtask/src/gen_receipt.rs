@@ -12,12 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{fs, path::Path};
+
 use clap::Parser;
-use risc0_zkvm::{get_prover_server, ExecutorEnv, ProverOpts};
+use risc0_zkvm::{
+    get_prover_server, sha::Digest, ExecutorEnv, InnerReceipt, ProverOpts, Receipt, VERSION,
+};
 use risc0_zkvm_methods::{FIB_ELF, FIB_ID};
 
+use crate::compat_check::{load_index, save_index, CorpusEntry, CORPUS_DIR};
+
 #[derive(Parser)]
-pub struct GenReceipt;
+pub struct GenReceipt {
+    /// Also add receipts for the current version to the compatibility corpus.
+    #[arg(long)]
+    corpus: bool,
+}
 
 impl GenReceipt {
     pub fn run(&self) {
@@ -42,5 +52,36 @@ pub const FIB_RECEIPT: &[u8] = &{receipt_bytes:?};
         );
 
         std::fs::write("risc0/zkvm/receipts/src/receipts.rs", rust_code).unwrap();
+
+        if self.corpus {
+            let succinct = get_prover_server(&opts)
+                .unwrap()
+                .compress(receipt.inner.composite().unwrap())
+                .unwrap();
+            let succinct = Receipt::new(
+                InnerReceipt::Succinct(succinct),
+                receipt.journal.bytes.clone(),
+            );
+            add_to_corpus("fib-composite", &receipt);
+            add_to_corpus("fib-succinct", &succinct);
+        }
     }
 }
+
+fn add_to_corpus(name: &str, receipt: &Receipt) {
+    let corpus = Path::new(CORPUS_DIR);
+    let file = format!("{VERSION}/{name}.bin");
+    fs::create_dir_all(corpus.join(VERSION)).unwrap();
+    fs::write(corpus.join(&file), bincode::serialize(receipt).unwrap()).unwrap();
+
+    let mut entries = load_index(corpus);
+    entries.retain(|entry| !(entry.version == VERSION && entry.name == name));
+    entries.push(CorpusEntry {
+        version: VERSION.to_string(),
+        name: name.to_string(),
+        image_id: hex::encode(Digest::from(FIB_ID)),
+        file,
+        expect_verified: true,
+    });
+    save_index(corpus, &entries);
+}
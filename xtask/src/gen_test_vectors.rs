@@ -0,0 +1,152 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use risc0_circuit_recursion::control_id::{ALLOWED_IDS_ROOT, BN254_CONTROL_ID};
+use risc0_zkvm::{
+    get_prover_server, recursion::identity_p254, sha::Digestible, stark_to_snark, CompactReceipt,
+    ExecutorEnv, InnerReceipt, ProverOpts, Receipt,
+};
+use risc0_zkvm_methods::{HELLO_COMMIT_ELF, HELLO_COMMIT_ID};
+use serde::Serialize;
+
+/// Generate known-good receipt test vectors for the on-chain verifier.
+///
+/// The guest is proven once per hash suite, and each receipt is verified
+/// before it is written out, so the resulting JSON can be consumed by the
+/// Solidity tests to check that the contracts accept exactly what the Rust
+/// prover produces.
+#[derive(Parser)]
+pub struct GenTestVectors {
+    /// Path of the JSON file to write.
+    #[arg(long, default_value = "test-vectors.json")]
+    out: PathBuf,
+
+    /// Hash functions to generate segment receipts for.
+    #[arg(long, value_delimiter = ',', default_value = "sha-256,poseidon2")]
+    hashfn: Vec<String>,
+
+    /// Also generate a Groth16 receipt. Requires docker.
+    #[arg(long)]
+    groth16: bool,
+}
+
+#[derive(Serialize)]
+struct TestVectors {
+    version: String,
+    control_root: String,
+    bn254_control_id: String,
+    image_id: String,
+    journal: String,
+    journal_digest: String,
+    post_state_digest: String,
+    claim_digest: String,
+    receipts: Vec<TestVector>,
+}
+
+#[derive(Serialize)]
+struct TestVector {
+    hashfn: String,
+    kind: String,
+    seal: String,
+}
+
+impl GenTestVectors {
+    pub fn run(&self) {
+        assert!(
+            !self.groth16 || self.hashfn.iter().any(|x| x == "poseidon2"),
+            "a Groth16 receipt requires the poseidon2 hash function"
+        );
+
+        let mut receipts = Vec::new();
+        let mut last_receipt = None;
+        for hashfn in self.hashfn.iter() {
+            tracing::info!("prove: {hashfn}");
+            let opts = ProverOpts::default().with_hashfn(hashfn.clone());
+            let prover = get_prover_server(&opts).unwrap();
+            let receipt = prover
+                .prove(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+                .unwrap()
+                .receipt;
+            receipt.verify(HELLO_COMMIT_ID).unwrap();
+
+            let composite = receipt.inner.composite().unwrap();
+            assert_eq!(
+                composite.segments.len(),
+                1,
+                "test vectors expect a single segment"
+            );
+            receipts.push(TestVector {
+                hashfn: hashfn.clone(),
+                kind: "segment".to_string(),
+                seal: hex::encode(composite.segments[0].get_seal_bytes()),
+            });
+
+            // Recursion is only supported over poseidon2 segment receipts.
+            if hashfn == "poseidon2" {
+                tracing::info!("compress");
+                let succinct = prover.compress(composite).unwrap();
+                receipts.push(TestVector {
+                    hashfn: hashfn.clone(),
+                    kind: "succinct".to_string(),
+                    seal: hex::encode(succinct.get_seal_bytes()),
+                });
+
+                if self.groth16 {
+                    tracing::info!("stark-to-snark");
+                    let ident_receipt = identity_p254(&succinct).unwrap();
+                    let seal = stark_to_snark(&ident_receipt.get_seal_bytes())
+                        .unwrap()
+                        .to_vec();
+                    let compact = Receipt::new(
+                        InnerReceipt::Compact(CompactReceipt {
+                            seal: seal.clone(),
+                            claim: receipt.get_claim().unwrap(),
+                        }),
+                        receipt.journal.bytes.clone(),
+                    );
+                    compact.verify(HELLO_COMMIT_ID).unwrap();
+                    receipts.push(TestVector {
+                        hashfn: "groth16".to_string(),
+                        kind: "compact".to_string(),
+                        seal: hex::encode(seal),
+                    });
+                }
+            }
+
+            last_receipt = Some(receipt);
+        }
+
+        let receipt = last_receipt.expect("at least one hash function is required");
+
+        let claim = receipt.get_claim().unwrap();
+        let vectors = TestVectors {
+            version: risc0_zkvm::VERSION.to_string(),
+            control_root: ALLOWED_IDS_ROOT.to_string(),
+            bn254_control_id: BN254_CONTROL_ID.to_string(),
+            image_id: hex::encode(risc0_zkvm::sha::Digest::from(HELLO_COMMIT_ID)),
+            journal: hex::encode(&receipt.journal.bytes),
+            journal_digest: hex::encode(receipt.journal.digest()),
+            post_state_digest: hex::encode(claim.post.digest()),
+            claim_digest: hex::encode(claim.digest()),
+            receipts,
+        };
+
+        let json = serde_json::to_string_pretty(&vectors).unwrap();
+        std::fs::write(&self.out, json).unwrap();
+        tracing::info!("wrote {}", self.out.display());
+    }
+}
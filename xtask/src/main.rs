@@ -15,15 +15,19 @@
 mod bootstrap;
 mod bootstrap_groth16;
 mod bootstrap_poseidon;
+mod compat_check;
 mod gen_receipt;
+mod gen_test_vectors;
 mod install;
+mod release;
 
 use clap::{Parser, Subcommand};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 use self::{
     bootstrap::Bootstrap, bootstrap_groth16::BootstrapGroth16,
-    bootstrap_poseidon::BootstrapPoseidon, gen_receipt::GenReceipt, install::Install,
+    bootstrap_poseidon::BootstrapPoseidon, compat_check::CompatCheck, gen_receipt::GenReceipt,
+    gen_test_vectors::GenTestVectors, install::Install, release::ReleaseArtifacts,
 };
 
 #[derive(Parser)]
@@ -37,8 +41,11 @@ enum Commands {
     Bootstrap(Bootstrap),
     BootstrapGroth16(BootstrapGroth16),
     BootstrapPoseidon(BootstrapPoseidon),
+    CompatCheck(CompatCheck),
     GenReceipt(GenReceipt),
+    GenTestVectors(GenTestVectors),
     Install(Install),
+    ReleaseArtifacts(ReleaseArtifacts),
 }
 
 impl Commands {
@@ -47,8 +54,11 @@ impl Commands {
             Commands::Bootstrap(cmd) => cmd.run(),
             Commands::BootstrapGroth16(cmd) => cmd.run(),
             Commands::BootstrapPoseidon(cmd) => cmd.run(),
+            Commands::CompatCheck(cmd) => cmd.run(),
             Commands::Install(cmd) => cmd.run(),
             Commands::GenReceipt(cmd) => cmd.run(),
+            Commands::GenTestVectors(cmd) => cmd.run(),
+            Commands::ReleaseArtifacts(cmd) => cmd.run(),
         }
     }
 }
@@ -0,0 +1,170 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+use xshell::{cmd, Shell};
+
+/// Build and package prebuilt r0vm binaries.
+///
+/// Each archive contains the `r0vm` binary and a `metadata.json` describing
+/// how it was built. A `<archive>.sha256` file is written next to each archive
+/// and all checksums are listed in `SHA256SUMS` in the output directory.
+#[derive(Parser)]
+pub struct ReleaseArtifacts {
+    /// Platforms to build; defaults to all platforms buildable on this host.
+    #[arg(long, value_enum)]
+    platform: Vec<Platform>,
+
+    /// Directory to write the archives to.
+    #[arg(long, default_value = "dist")]
+    out_dir: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Platform {
+    LinuxX86_64Cpu,
+    LinuxX86_64Cuda,
+    MacosAarch64Metal,
+}
+
+impl Platform {
+    fn all_for_host() -> Vec<Self> {
+        if cfg!(target_os = "macos") {
+            vec![Self::MacosAarch64Metal]
+        } else {
+            vec![Self::LinuxX86_64Cpu, Self::LinuxX86_64Cuda]
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::LinuxX86_64Cpu => "linux-x86_64-cpu",
+            Self::LinuxX86_64Cuda => "linux-x86_64-cuda",
+            Self::MacosAarch64Metal => "macos-aarch64-metal",
+        }
+    }
+
+    fn target(&self) -> &'static str {
+        match self {
+            Self::LinuxX86_64Cpu | Self::LinuxX86_64Cuda => "x86_64-unknown-linux-gnu",
+            Self::MacosAarch64Metal => "aarch64-apple-darwin",
+        }
+    }
+
+    fn features(&self) -> &'static [&'static str] {
+        match self {
+            Self::LinuxX86_64Cpu => &[],
+            Self::LinuxX86_64Cuda => &["cuda"],
+            Self::MacosAarch64Metal => &["metal"],
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    name: &'a str,
+    version: &'a str,
+    platform: &'a str,
+    target: &'a str,
+    features: &'a [&'a str],
+    commit: String,
+    rustc: String,
+}
+
+impl ReleaseArtifacts {
+    pub fn run(&self) {
+        let sh = Shell::new().unwrap();
+        sh.create_dir(&self.out_dir).unwrap();
+        let out_dir = fs::canonicalize(&self.out_dir).unwrap();
+
+        let platforms = if self.platform.is_empty() {
+            Platform::all_for_host()
+        } else {
+            self.platform.clone()
+        };
+
+        let mut sums = String::new();
+        for platform in platforms {
+            let archive = build_archive(&sh, platform, &out_dir);
+            let line = format!("{}  {}\n", sha256_hex(&archive), file_name(&archive));
+            fs::write(archive.with_extension("gz.sha256"), &line).unwrap();
+            sums.push_str(&line);
+            println!("{}", archive.display());
+        }
+        fs::write(out_dir.join("SHA256SUMS"), sums).unwrap();
+    }
+}
+
+fn build_archive(sh: &Shell, platform: Platform, out_dir: &Path) -> PathBuf {
+    let target = platform.target();
+    let features = platform.features().join(",");
+    let features = (!features.is_empty()).then(|| format!("--features={features}"));
+    cmd!(
+        sh,
+        "cargo build --release -p risc0-r0vm --bin r0vm --target {target} {features...}"
+    )
+    .run()
+    .unwrap();
+
+    let version = risc0_zkvm::VERSION;
+    let stem = format!("r0vm-{version}-{}", platform.name());
+    let staging = out_dir.join(&stem);
+    if staging.exists() {
+        fs::remove_dir_all(&staging).unwrap();
+    }
+    fs::create_dir_all(&staging).unwrap();
+
+    let bin = Path::new("target")
+        .join(target)
+        .join("release")
+        .join("r0vm");
+    fs::copy(&bin, staging.join("r0vm")).unwrap();
+
+    let metadata = Metadata {
+        name: "r0vm",
+        version,
+        platform: platform.name(),
+        target,
+        features: platform.features(),
+        commit: cmd!(sh, "git rev-parse HEAD").read().unwrap(),
+        rustc: cmd!(sh, "rustc --version").read().unwrap(),
+    };
+    fs::write(
+        staging.join("metadata.json"),
+        serde_json::to_string_pretty(&metadata).unwrap(),
+    )
+    .unwrap();
+
+    let archive = out_dir.join(format!("{stem}.tar.gz"));
+    cmd!(sh, "tar -czf {archive} -C {out_dir} {stem}")
+        .run()
+        .unwrap();
+    fs::remove_dir_all(&staging).unwrap();
+    archive
+}
+
+fn sha256_hex(path: &Path) -> String {
+    hex::encode(Sha256::digest(fs::read(path).unwrap()))
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().unwrap().to_str().unwrap()
+}